@@ -0,0 +1,25 @@
+use anyhow::{Error, Result};
+use async_openai::{config::{AzureConfig, Config, OpenAIConfig}, Client};
+use dim_rs::llm::{instantiate_azure_client, instantiate_client};
+
+/// Builds clients for both providers from the environment, without sending requests.
+///
+/// OpenAI or a compatible server:
+///     OPENAI_API_KEY=... OPENAI_API_BASE=http://localhost:11434/v1 cargo run --example instantiate_clients
+///
+/// Azure OpenAI, additionally:
+///     AZURE_OPENAI_ENDPOINT=https://my-resource.openai.azure.com AZURE_OPENAI_DEPLOYMENT=gpt-4o-mini AZURE_OPENAI_API_KEY=...
+fn main() -> Result<(), Error> {
+    // OpenAI-compatible servers; local ones like Ollama need no key
+    let client: Client<OpenAIConfig> = instantiate_client(None, true)?;
+    println!("OpenAI-compatible API base: {}", client.config().api_base());
+
+    // Azure OpenAI, only when configured; `instantiate_client::<AzureConfig>` is equivalent
+    let azure: Result<Client<AzureConfig>, Error> = instantiate_azure_client();
+    match azure {
+        Ok(client) => println!("Azure request URL: {}", client.config().url("/chat/completions")),
+        Err(e) => println!("Azure OpenAI is not configured: {}", e),
+    }
+
+    Ok(())
+}
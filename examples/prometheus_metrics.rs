@@ -0,0 +1,52 @@
+//! Exposes the request, retry, latency and token metrics of dim to Prometheus.
+//!
+//! Run it with `cargo run --example prometheus_metrics --features metrics` and
+//! scrape `http://localhost:9000/metrics`.
+
+use std::net::SocketAddr;
+
+use dim_rs::prelude::*;
+use dim_rs::telemetry::describe_metrics;
+use anyhow::{Error, Result};
+use metrics_exporter_prometheus::PrometheusBuilder;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // Serve every metric recorded from now on at /metrics
+    let address: SocketAddr = SocketAddr::from(([0, 0, 0, 0], 9000));
+    PrometheusBuilder::new()
+        .with_http_listener(address)
+        .install()?;
+    describe_metrics();
+
+    // Initialize client
+    let client: async_openai::Client<async_openai::config::OpenAIConfig> = async_openai::Client::with_config(
+        async_openai::config::OpenAIConfig::new()
+            .with_api_base("http://192.168.0.101:11434/v1") // comment this out if you use OpenAI instead of Ollama
+            .with_api_key("your_api_key")
+    );
+    let model_parameters: ModelParameters = ModelParameters::builder()
+        .model("minicpm-v")
+        .build()?;
+    let prompts: Vec<String> = vec![
+        "Score the sentiment intensity of the text from 1 (extremely negative) to 9 (extremely positive). Format your response exactly like this example: {'sentiment_score': 7}".to_string(),
+        "Rate how urgent or time-sensitive the text feels from 1 (no urgency) to 9 (immediate action required). Format your response exactly like this example: {'urgency_score': 2}".to_string(),
+    ];
+
+    // Vectorize a few texts to fill the metrics
+    let texts: Vec<&str> = vec![
+        "The package arrived two days early, thank you!",
+        "My order is missing and nobody answers the phone.",
+        "Please reset my password before the meeting at noon.",
+    ];
+    for text in texts {
+        let mut vector: Vector<String> = Vector::from_text(text.to_string());
+        vectorize_string_concurrently(prompts.clone(), &mut vector, client.clone(), model_parameters.clone()).await?;
+        println!("Vector: {:?}", vector.get_vector());
+    }
+
+    println!("Metrics are served at http://localhost:9000/metrics, press Ctrl-C to stop");
+    tokio::signal::ctrl_c().await?;
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use anyhow::{Error, Result};
+use dim_rs::collection::search::{search_text, SearchHit};
+use dim_rs::collection::VectorCollection;
+use dim_rs::prelude::*;
+use dim_rs::vector::metrics::Metric;
+
+/// Vectorizes a few texts with the sample prompts, then finds the closest to a query.
+///
+///     OPENAI_API_BASE=http://localhost:11434/v1 DIM_MODEL=minicpm-v cargo run --example search_text
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // Local servers like Ollama need no key
+    let client: OpenAIClient = instantiate_client(None, true)?;
+    let vectorizer: Vectorizer<OpenAIClient> = Vectorizer::builder()
+        .client(client)
+        .prompts(load_prompts("./examples/prompts/text_prompts.json")?)
+        .model_parameters(ModelParameters::from_env()?)
+        .build()?;
+
+    // Vectorize the documents to search
+    let documents: [&str; 4] = [
+        "This is the worst service I have ever had, never again!!!",
+        "We hereby acknowledge receipt of your application.",
+        "lol that party was sick, best night ever",
+        "The meeting is scheduled for 10 am on Tuesday.",
+    ];
+    let mut vectors: Vec<Vector<String>> = documents.iter().map(|text| Vector::from_text(text.to_string())).collect();
+    for result in vectorizer.vectorize_batch(&mut vectors).await {
+        result?;
+    }
+    let collection: VectorCollection<String> = VectorCollection::from_vectors(vectors)?;
+
+    // Vectorize the query with the same prompts and rank the documents
+    let query: &str = "Thank you kindly for your prompt reply.";
+    let hits: Vec<SearchHit<String>> = search_text(query, &collection, &vectorizer, 2, Metric::Cosine).await?;
+    println!("Closest to {:?}:", query);
+    for hit in hits {
+        println!("  {:.3}  {}", hit.score, hit.data);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use anyhow::{Error, Result};
+use dim_rs::prelude::*;
+use dim_rs::server::{router_with_options, ServerOptions};
+
+/// Serves vectorization over HTTP on port 8080.
+///
+///     OPENAI_API_BASE=http://localhost:11434/v1 DIM_MODEL=minicpm-v cargo run --example server --features server
+///
+/// Then, for example:
+///     curl -X POST localhost:8080/vectorize/text -H 'content-type: application/json' -d '{"text": "Arrived quickly."}'
+///     curl -X POST localhost:8080/vectorize/image -F image=@examples/images/54e2c8ea-58ef-4871-ae3f-75eabd9a2c6c.jpg
+///     curl localhost:8080/healthz
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // Local servers like Ollama need no key
+    let client: OpenAIClient = instantiate_client(None, true)?;
+
+    let vectorizer: Vectorizer<OpenAIClient> = Vectorizer::builder()
+        .client(client)
+        .prompts(load_prompts("./examples/prompts/text_prompts.json")?)
+        .model_parameters(ModelParameters::from_env()?)
+        .max_concurrency(8)
+        .build()?;
+
+    // Requests may ask for these prompts with "prompt_set": "offensiveness"
+    let options: ServerOptions = ServerOptions::new()
+        .with_max_concurrent_requests(8)
+        .with_max_body_bytes(20 * 1024 * 1024)
+        .with_prompt_set(
+            "offensiveness",
+            PromptSet::new(vec!["output in json. Rate the offensiveness from 0.0 to 10.0. {'offensiveness': your score}"]),
+        );
+
+    let listener: tokio::net::TcpListener = tokio::net::TcpListener::bind("0.0.0.0:8080").await?;
+    println!("Listening on {}", listener.local_addr()?);
+    axum::serve(listener, router_with_options(vectorizer, options)?).await?;
+
+    Ok(())
+}
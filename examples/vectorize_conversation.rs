@@ -0,0 +1,54 @@
+use async_openai::config::OpenAIConfig;
+use async_openai::Client;
+use dim_rs::prelude::*;
+use dim_rs::raw_data::ChatTurn;
+use dim_rs::vectorization::conversation::vectorize_conversation_concurrently;
+use anyhow::{Error, Result};
+
+/// Scores a short support conversation as a whole
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // A three-turn support dialogue
+    let turns: Vec<ChatTurn> = vec![
+        ChatTurn::new("customer", "Hi, my order #1042 never arrived and it has been two weeks."),
+        ChatTurn::new("agent", "I'm sorry to hear that! I've sent a replacement with express shipping."),
+        ChatTurn::new("customer", "Thank you, that was quick."),
+    ];
+
+    // Create a Vector object from the conversation
+    let mut vector: Vector<Vec<ChatTurn>> = Vector::from_conversation(turns);
+
+    // Initialize client
+    let client: Client<OpenAIConfig> = Client::with_config(
+        OpenAIConfig::new()
+            .with_api_base("http://192.168.0.101:11434/v1") // comment this out if you use OpenAI instead of Ollama
+            .with_api_key("your_api_key")
+    );
+
+    // Initialize prompts
+    let prompts: Vec<String> = vec![
+        "output in json. Rate the agent's politeness from 0.0 to 10.0. {'politeness': your score}".to_string(),
+        "output in json. Rate how well the customer's issue was resolved from 0.0 to 10.0. {'resolution': your score}".to_string(),
+    ];
+
+    // Initialize model parameters
+    let model_parameters: ModelParameters = ModelParameters::builder()
+        .model("minicpm-v")
+        .temperature(0.7)
+        .build()?;
+
+    // Vectorize the conversation
+    vectorize_conversation_concurrently(
+        prompts,
+        &mut vector,
+        client,
+        model_parameters
+    ).await?;
+
+    // Print vectorized result
+    println!("Vector: {:?}", vector.get_vector());
+    println!("Labels: {:?}", vector.get_labels());
+
+    Ok(())
+}
@@ -1,8 +1,8 @@
-use dim_rs::{prelude::*, vectorization::ModelParameters};
+use async_openai::config::OpenAIConfig;
+use async_openai::Client;
+use dim_rs::prelude::*;
 use image::DynamicImage;
-use tokio;
 use anyhow::{Error, Result};
-use async_openai::{Client, config::OpenAIConfig};
 
 /// 1. provide examples on how to vectorize image and text
 /// 2. provide a real use case of why this method is useful
@@ -35,11 +35,10 @@ async fn main() -> Result<(), Error> {
     ];
 
     // Initialize model parameters
-    let model_parameters = ModelParameters::new(
-        "minicpm-v".to_string(), 
-        Some(0.7), 
-        None
-    );
+    let model_parameters: ModelParameters = ModelParameters::builder()
+        .model("minicpm-v")
+        .temperature(0.7)
+        .build()?;
 
     // Vectorize image
     vectorize_image_concurrently(
@@ -0,0 +1,55 @@
+use async_openai::config::OpenAIConfig;
+use async_openai::Client;
+use dim_rs::prelude::*;
+use dim_rs::vectorization::vectorize_images_concurrently;
+use image::DynamicImage;
+use anyhow::{Error, Result};
+
+/// Vectorizes several photos of one item in one call
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // Load the photos of one item; the same photo twice stands in for several angles
+    let image_path: &str = "./examples/images/54e2c8ea-58ef-4871-ae3f-75eabd9a2c6c.jpg";
+    let test_images: Vec<DynamicImage> = vec![
+        image::open(image_path).unwrap(),
+        image::open(image_path).unwrap().fliph(),
+    ];
+
+    // Create a Vector object from the images
+    let mut vector: Vector<Vec<DynamicImage>> = Vector::from_images(test_images);
+
+    // Initialize client
+    let client: Client<OpenAIConfig> = Client::with_config(
+        OpenAIConfig::new()
+            .with_api_base("http://192.168.0.101:11434/v1") // comment this out if you use OpenAI instead of Ollama
+            .with_api_key("your_api_key")
+    );
+
+    // Initialize prompts
+    let prompts: Vec<String> = vec![
+        "output in json. Rate the images' offensiveness from 0.0 to 10.0. {'offensiveness': your score}".to_string(),
+        "output in json. Rate the images' friendliness from 0.0 to 10.0. {'friendliness': your score}".to_string(),
+    ];
+
+    // Initialize model parameters; every request carries up to 4 images
+    let model_parameters: ModelParameters = ModelParameters::builder()
+        .model("minicpm-v")
+        .temperature(0.7)
+        .max_images_per_request(4)
+        .build()?;
+
+    // Vectorize the images together
+    vectorize_images_concurrently(
+        prompts,
+        &mut vector, 
+        client,
+        model_parameters
+    ).await?;
+
+    // Print vectorized result; one dimension per prompt, as for a single image
+    println!("Vector: {:?}", vector.get_vector());
+    println!("Vector Length: {:?}", vector.get_vector().len());
+
+    Ok(())
+}
@@ -1,7 +1,5 @@
-use dim_rs::{prelude::*, vectorization::ModelParameters};
-use tokio;
+use dim_rs::prelude::*;
 use anyhow::{Error, Result};
-use async_openai;
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
@@ -29,11 +27,6 @@ async fn main() -> Result<(), Error> {
         "Learning to play a musical instrument can be very fulfilling.".to_string(),
     ];
     
-    // Create Vector objects from the texts
-    let mut vectors: Vec<Vector<String>> = texts.into_iter()
-        .map(Vector::from_text)
-        .collect();
-    
     // Initialize client
     let client: async_openai::Client<async_openai::config::OpenAIConfig> = async_openai::Client::with_config(
         async_openai::config::OpenAIConfig::new()
@@ -56,14 +49,18 @@ async fn main() -> Result<(), Error> {
     ];
 
     // Vectorize all texts
-    for vector in &mut vectors {
-        let model_parameters = ModelParameters::new("mistral".to_string(), None, None);
-        vectorize_string_concurrently(
-            prompts.clone(),
-            vector,
+    let model_parameters: ModelParameters = ModelParameters::builder()
+        .model("mistral")
+        .build()?;
+    // Each call hands back a fully vectorized Vector, or an error
+    let mut vectors: Vec<Vector<String>> = Vec::new();
+    for text in texts {
+        vectors.push(vectorize_text(
+            text,
+            &prompts,
             client.clone(),
-            model_parameters
-        ).await?;
+            model_parameters.clone()
+        ).await?);
     }
 
     // Print statistics and validate vectors
@@ -0,0 +1,62 @@
+use async_openai::config::OpenAIConfig;
+use async_openai::Client;
+use dim_rs::prelude::*;
+use dim_rs::raw_data::record::RecordRenderer;
+use dim_rs::vectorization::record::vectorize_record_concurrently;
+use anyhow::{Error, Result};
+use serde_json::{json, Value};
+
+/// Scores JSON product rows, ignoring fields that should not affect the scores
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // Two rows that differ only in their SKU and stock count
+    let records: Vec<Value> = vec![
+        json!({"sku": "CH-001", "brand": "Oakly", "price": 120, "stock": 4, "description": "Solid oak dining chair with a woven seat."}),
+        json!({"sku": "CH-002", "brand": "Oakly", "price": 120, "stock": 31, "description": "Solid oak dining chair with a woven seat."}),
+    ];
+
+    // Leave out fields that identify rows rather than describe them
+    let renderer: RecordRenderer = RecordRenderer::new().exclude_fields(&["sku", "stock"]);
+    println!("Rendered record:\n{}", renderer.render(&records[0]));
+
+    // Initialize client
+    let client: Client<OpenAIConfig> = Client::with_config(
+        OpenAIConfig::new()
+            .with_api_base("http://192.168.0.101:11434/v1") // comment this out if you use OpenAI instead of Ollama
+            .with_api_key("your_api_key")
+    );
+
+    // Initialize prompts
+    let prompts: Vec<String> = vec![
+        "output in json. Rate the product's perceived quality from 0.0 to 10.0. {'quality': your score}".to_string(),
+        "output in json. Rate how affordable the product is from 0.0 to 10.0. {'affordability': your score}".to_string(),
+    ];
+
+    // A fixed seed and zero temperature make identical renderings score the same
+    let model_parameters: ModelParameters = ModelParameters::builder()
+        .model("minicpm-v")
+        .temperature(0.0)
+        .seed(42)
+        .build()?;
+
+    // Vectorize every record
+    let mut vectors: Vec<Vector<Value>> = Vec::new();
+    for record in records {
+        let mut vector: Vector<Value> = Vector::from_record(record);
+        vectorize_record_concurrently(
+            prompts.clone(),
+            &mut vector,
+            &renderer,
+            client.clone(),
+            model_parameters.clone()
+        ).await?;
+        println!("Vector: {:?}", vector.get_vector());
+        vectors.push(vector);
+    }
+
+    // The rows differ only in ignored fields, so their vectors match
+    assert_eq!(vectors[0].get_vector(), vectors[1].get_vector());
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use async_openai::config::OpenAIConfig;
+use async_openai::Client;
+use dim_rs::prelude::*;
+use dim_rs::raw_data::{read_texts_jsonl, TextDatasetOptions};
+use dim_rs::vector::io::save_jsonl;
+use anyhow::{Error, Result};
+
+/// Reads reviews from a JSONL file, scores them and writes the vectors to another JSONL file
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // Each line looks like {"review_id": 17, "body": "Arrived quickly and works as described."}
+    let options: TextDatasetOptions = TextDatasetOptions::new().with_skip_malformed(true);
+    let reviews = read_texts_jsonl("./examples/reviews.jsonl", "body", Some("review_id"), &options)?;
+
+    // Initialize client
+    let client: Client<OpenAIConfig> = Client::with_config(
+        OpenAIConfig::new()
+            .with_api_base("http://192.168.0.101:11434/v1") // comment this out if you use OpenAI instead of Ollama
+            .with_api_key("your_api_key")
+    );
+
+    // Initialize prompts
+    let prompts: Vec<String> = vec![
+        "output in json. Rate how satisfied the reviewer is from 0.0 to 10.0. {'satisfaction': your score}".to_string(),
+        "output in json. Rate how detailed the review is from 0.0 to 10.0. {'detail': your score}".to_string(),
+    ];
+    let model_parameters: ModelParameters = ModelParameters::builder()
+        .model("mistral")
+        .build()?;
+
+    // Vectorize the reviews as they are read
+    let mut vectors: Vec<Vector<String>> = Vec::new();
+    for review in reviews {
+        let mut vector: Vector<String> = review?;
+        vectorize_string_concurrently(
+            prompts.clone(),
+            &mut vector,
+            client.clone(),
+            model_parameters.clone()
+        ).await?;
+        vectors.push(vector);
+    }
+
+    save_jsonl("./examples/review_vectors.jsonl", &vectors)?;
+    println!("Wrote {} vectors", vectors.len());
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! Vectorizes texts from a browser or an edge worker such as a Cloudflare Worker.
+//!
+//! Build it with
+//! `cargo build --example vectorize_text_wasm --target wasm32-unknown-unknown --no-default-features --features wasm`
+//! and generate the JavaScript bindings with `wasm-bindgen`. The client sends
+//! its requests with `fetch`, so the endpoint has to allow the page's origin.
+
+#[cfg(target_arch = "wasm32")]
+mod worker {
+    use async_openai::config::OpenAIConfig;
+    use async_openai::Client;
+    use dim_rs::prelude::*;
+    use wasm_bindgen::prelude::*;
+
+    /// Vectorizes a text with one prompt per instruction, returning the vector
+    #[wasm_bindgen]
+    pub async fn vectorize_text(
+        text: String,
+        prompts: Vec<String>,
+        model: String,
+        api_base: String,
+        api_key: String,
+    ) -> Result<Vec<f32>, JsError> {
+        let client: Client<OpenAIConfig> = Client::with_config(
+            OpenAIConfig::new()
+                .with_api_base(api_base)
+                .with_api_key(api_key)
+        );
+        let model_parameters: ModelParameters = ModelParameters::builder()
+            .model(model)
+            .build()
+            .map_err(|e| JsError::new(&e.to_string()))?;
+
+        let mut vector: Vector<String> = Vector::from_text(text);
+        vectorize_string_concurrently(prompts, &mut vector, client, model_parameters)
+            .await
+            .map_err(|e| JsError::new(&e.to_string()))?;
+
+        Ok(vector.get_vector())
+    }
+}
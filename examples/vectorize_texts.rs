@@ -1,7 +1,5 @@
-use dim_rs::{prelude::*, vectorization::ModelParameters};
-use tokio;
+use dim_rs::prelude::*;
 use anyhow::{Error, Result};
-use async_openai;
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
@@ -36,8 +34,10 @@ async fn main() -> Result<(), Error> {
     ];
 
     // Vectorize image
-    let model_parameters = ModelParameters::new("minicpm-v".to_string(), None, None);
-    vectorize_string_concurrently(
+    let model_parameters: ModelParameters = ModelParameters::builder()
+        .model("minicpm-v")
+        .build()?;
+    let report: VectorizationReport = vectorize_string_concurrently(
         prompts,
         &mut vector, 
         client,
@@ -47,6 +47,7 @@ async fn main() -> Result<(), Error> {
     // Print vectorized result
     println!("Vector: {:?}", vector.get_vector());
     println!("Vector Length: {:?}", vector.get_vector().len());
+    println!("Tokens used: {} in {} requests", report.usage.total.total_tokens, report.usage.get_requests());
     
     Ok(())
 }
@@ -0,0 +1,35 @@
+use dim_rs::prelude::*;
+
+/// Vectorizes a text with prompts loaded from a file, using nothing but the prelude.
+///
+///     OPENAI_API_BASE=http://localhost:11434/v1 cargo run --example vectorize_with_prelude
+#[tokio::main]
+async fn main() -> Result<(), DimError> {
+    // Load prompts
+    let prompts: Vec<Prompt> = load_prompts("./examples/prompts/text_prompts.json")?.into();
+
+    // Create a Vector object from the text
+    let mut vector: Vector<String> = Vector::from_text(
+        "Hi, this is dim. I am here to vectorize whatever your want.".to_string()
+    );
+
+    // Initialize client; local servers like Ollama need no key
+    let client: OpenAIClient = instantiate_client(None, true)?;
+
+    // Vectorize text
+    let model_parameters: ModelParameters = ModelParameters::builder()
+        .model("minicpm-v")
+        .build()?;
+    let report: VectorizationReport = vectorize_concurrently(
+        prompts,
+        &mut vector,
+        client,
+        model_parameters
+    ).await?;
+
+    // Print vectorized result
+    println!("Vector: {:?}", vector.get_vector());
+    println!("Tokens used: {} in {} requests", report.usage.total.total_tokens, report.usage.get_requests());
+
+    Ok(())
+}
@@ -0,0 +1,390 @@
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use anyhow::{Error, Result};
+use async_openai::config::OpenAIConfig;
+use async_openai::Client;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use dim_rs::collection::search::{search_text, SearchHit};
+use dim_rs::collection::VectorCollection;
+use dim_rs::llm::EnvConfig;
+use dim_rs::pipeline::{process_jsonl, PipelineOptions, PipelineSummary};
+use dim_rs::prelude::*;
+use dim_rs::raw_data::{list_image_directory, load_texts_jsonl, ImageDirectoryOptions, ImageFile, TextDatasetOptions};
+use dim_rs::vector::io::{load_jsonl, read_jsonl};
+use dim_rs::vector::metrics::Metric;
+use dim_rs::vector::{METADATA_ID, METADATA_SOURCE};
+use dim_rs::vectorization::manifest::{load_manifest, sidecar_path, RunManifest};
+use dim_rs::vectorization::vectorizable::Vectorizable;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// The exit code when nothing was vectorized or the command could not run
+const EXIT_FAILURE: u8 = 1;
+/// The exit code when some items failed and the others were written
+const EXIT_PARTIAL_FAILURE: u8 = 2;
+/// The items vectorized per unit of concurrency before the output and manifest are written
+const ITEMS_PER_CHECKPOINT: usize = 16;
+
+/// Vectorizes texts and images with LLM prompts, and searches the results
+#[derive(Debug, Parser)]
+#[command(name = "dim", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// The subcommands of `dim`
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Vectorizes the texts of a JSONL file, one per line
+    VectorizeText(VectorizeTextArgs),
+    /// Vectorizes the images of a directory
+    VectorizeImages(VectorizeImagesArgs),
+    /// Finds the vectors of a JSONL file closest to a text query
+    Search(SearchArgs),
+    /// Vectorizes the JSONL texts of stdin, writing each vector to stdout as it completes
+    Pipe(PipeArgs),
+}
+
+/// Where requests are sent and how many at once
+#[derive(Debug, Args)]
+struct EndpointArgs {
+    /// The base URL of an OpenAI compatible API, e.g. http://localhost:11434/v1
+    #[arg(long, env = "OPENAI_API_BASE")]
+    api_base: Option<String>,
+    /// The environment variable holding the API key
+    #[arg(long, default_value = "OPENAI_API_KEY")]
+    api_key_env: String,
+    /// Sends a placeholder key when none is set, for local servers
+    #[arg(long)]
+    allow_unauthenticated: bool,
+    /// The model; the other parameters are read from the DIM_* variables
+    #[arg(long, env = "DIM_MODEL")]
+    model: Option<String>,
+    /// How many items are vectorized at once
+    #[arg(long, env = "DIM_CONCURRENCY", default_value_t = 4)]
+    concurrency: usize,
+}
+
+/// The prompts of a run and where its vectors go
+#[derive(Debug, Args)]
+struct RunArgs {
+    /// The JSON prompts file, a list of instructions or prompts
+    #[arg(long)]
+    prompts: PathBuf,
+    /// The JSONL file the vectors are written to, with the run's manifest next to it
+    #[arg(long)]
+    out: PathBuf,
+    /// Continues an interrupted run, skipping the items already written to --out
+    #[arg(long)]
+    resume: bool,
+}
+
+/// The arguments of `vectorize-text`
+#[derive(Debug, Args)]
+struct VectorizeTextArgs {
+    /// The JSONL file holding the texts
+    #[arg(long)]
+    input: PathBuf,
+    /// The field holding the text
+    #[arg(long, default_value = "text")]
+    text_field: String,
+    /// The field holding the identifier of each text
+    #[arg(long)]
+    id_field: Option<String>,
+    /// Skips malformed lines with a warning instead of failing
+    #[arg(long)]
+    skip_malformed: bool,
+    #[command(flatten)]
+    run: RunArgs,
+    #[command(flatten)]
+    endpoint: EndpointArgs,
+}
+
+/// The arguments of `vectorize-images`
+#[derive(Debug, Args)]
+struct VectorizeImagesArgs {
+    /// The directory holding the images
+    #[arg(long)]
+    dir: PathBuf,
+    /// Walks subdirectories too
+    #[arg(long)]
+    recursive: bool,
+    #[command(flatten)]
+    run: RunArgs,
+    #[command(flatten)]
+    endpoint: EndpointArgs,
+}
+
+/// The arguments of `search`
+#[derive(Debug, Args)]
+struct SearchArgs {
+    /// The JSONL file of vectors, written by a vectorize command
+    #[arg(long)]
+    index: PathBuf,
+    /// The text to search for
+    #[arg(long)]
+    query: String,
+    /// The prompts file the vectors were made with
+    #[arg(long)]
+    prompts: PathBuf,
+    /// How many results to print
+    #[arg(long, default_value_t = 10)]
+    top_k: usize,
+    /// How the query is compared with the vectors
+    #[arg(long, value_enum, default_value_t = MetricArg::Cosine)]
+    metric: MetricArg,
+    #[command(flatten)]
+    endpoint: EndpointArgs,
+}
+
+/// The arguments of `pipe`
+#[derive(Debug, Args)]
+struct PipeArgs {
+    /// The JSON prompts file, a list of instructions or prompts
+    #[arg(long)]
+    prompts: PathBuf,
+    /// The field holding the text
+    #[arg(long, default_value = "text")]
+    text_field: String,
+    /// The field holding the identifier of each text
+    #[arg(long)]
+    id_field: Option<String>,
+    /// The JSONL file failed lines are written to, with their error
+    #[arg(long)]
+    errors: Option<PathBuf>,
+    /// Writes the vectors in input order instead of as they complete
+    #[arg(long)]
+    preserve_order: bool,
+    #[command(flatten)]
+    endpoint: EndpointArgs,
+}
+
+/// The `Metric` choices of `search`
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum MetricArg {
+    Cosine,
+    Dot,
+    Euclidean,
+}
+
+impl From<MetricArg> for Metric {
+    fn from(metric: MetricArg) -> Self {
+        match metric {
+            MetricArg::Cosine => Metric::Cosine,
+            MetricArg::Dot => Metric::Dot,
+            MetricArg::Euclidean => Metric::Euclidean,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli: Cli = Cli::parse();
+    let result: Result<ExitCode, Error> = match cli.command {
+        Command::VectorizeText(args) => vectorize_text_file(args).await,
+        Command::VectorizeImages(args) => vectorize_image_directory(args).await,
+        Command::Search(args) => search(args).await,
+        Command::Pipe(args) => pipe(args).await,
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            ExitCode::from(EXIT_FAILURE)
+        }
+    }
+}
+
+/// Runs `vectorize-text`.
+async fn vectorize_text_file(args: VectorizeTextArgs) -> Result<ExitCode, Error> {
+    let options: TextDatasetOptions = TextDatasetOptions::new().with_skip_malformed(args.skip_malformed);
+    let vectors: Vec<Vector<String>> = load_texts_jsonl(&args.input, &args.text_field, args.id_field.as_deref(), &options)?;
+
+    vectorize_items(vectors, &args.run, &args.endpoint).await
+}
+
+/// Runs `vectorize-images`; images are decoded one batch at a time.
+async fn vectorize_image_directory(args: VectorizeImagesArgs) -> Result<ExitCode, Error> {
+    let options: ImageDirectoryOptions = ImageDirectoryOptions::new().with_recursive(args.recursive);
+    let vectors: Vec<Vector<ImageFile>> = list_image_directory(&args.dir, &options)?;
+
+    vectorize_items(vectors, &args.run, &args.endpoint).await
+}
+
+/// Vectorizes items in batches, appending each batch to the output and recording it in the manifest.
+///
+/// Items are identified by `METADATA_SOURCE`, so a resumed run skips the
+/// items already written and refuses prompts or parameters that differ from
+/// the manifest's.
+async fn vectorize_items<T: Vectorizable + Serialize>(
+    vectors: Vec<Vector<T>>,
+    run: &RunArgs,
+    endpoint: &EndpointArgs,
+) -> Result<ExitCode, Error> {
+    let prompts: PromptSet = load_prompts(&run.prompts)?;
+    let model_parameters: ModelParameters = model_parameters(endpoint)?;
+    let (mut manifest, written): (RunManifest, HashSet<String>) = match run.resume {
+        true => resume_run(&run.out, &prompts, &model_parameters)?,
+        false => (RunManifest::new(&prompts, &model_parameters), HashSet::new()),
+    };
+    let mut vectors: Vec<Vector<T>> = vectors
+        .into_iter()
+        .filter(|vector| vector.get_metadata(METADATA_SOURCE).is_none_or(|source| !written.contains(source)))
+        .collect();
+    eprintln!("Vectorizing {} items, {} already written", vectors.len(), written.len());
+
+    let file: File = match run.resume {
+        true => OpenOptions::new().create(true).append(true).open(&run.out),
+        false => File::create(&run.out),
+    }
+    .map_err(|e| Error::msg(format!("Failed to open {}: {}", run.out.display(), e)))?;
+    let mut writer: BufWriter<File> = BufWriter::new(file);
+    let vectorizer: Vectorizer<Client<OpenAIConfig>> = Vectorizer::builder()
+        .client(client(endpoint)?)
+        .prompts(prompts)
+        .model_parameters(model_parameters)
+        .max_concurrency(endpoint.concurrency)
+        .build()?;
+
+    let (mut succeeded, mut failed): (usize, usize) = (0, 0);
+    for batch in vectors.chunks_mut(endpoint.concurrency.max(1) * ITEMS_PER_CHECKPOINT) {
+        let results: Vec<Result<VectorizationReport, DimError>> = vectorizer.vectorize_batch(batch).await;
+        for (vector, result) in batch.iter().zip(results) {
+            // Items with a failed prompt are not written, so a resumed run vectorizes them again
+            let result: Result<VectorizationReport, DimError> = result.and_then(|report| report.ensure_complete().map(|_| report));
+            manifest.record_item(result.is_ok());
+            match result {
+                Ok(_) => {
+                    writeln!(writer, "{}", serde_json::to_string(vector)?)?;
+                    succeeded += 1;
+                }
+                Err(e) => {
+                    eprintln!("Failed to vectorize {}: {}", vector.get_metadata(METADATA_SOURCE).unwrap_or("an item"), e);
+                    failed += 1;
+                }
+            }
+        }
+        writer.flush()?;
+        manifest.save_sidecar(&run.out)?;
+    }
+    eprintln!("Vectorized {} items, {} failed", succeeded, failed);
+
+    Ok(match (succeeded, failed) {
+        (_, 0) => ExitCode::SUCCESS,
+        (0, _) => ExitCode::from(EXIT_FAILURE),
+        _ => ExitCode::from(EXIT_PARTIAL_FAILURE),
+    })
+}
+
+/// Runs `pipe`, keeping as many items in flight as the concurrency allows.
+async fn pipe(args: PipeArgs) -> Result<ExitCode, Error> {
+    let vectorizer: Vectorizer<Client<OpenAIConfig>> = Vectorizer::builder()
+        .client(client(&args.endpoint)?)
+        .prompts(load_prompts(&args.prompts)?)
+        .model_parameters(model_parameters(&args.endpoint)?)
+        .max_concurrency(args.endpoint.concurrency)
+        .build()?;
+    let mut options: PipelineOptions = PipelineOptions::new()
+        .with_text_field(args.text_field)
+        .with_max_in_flight(args.endpoint.concurrency)
+        .with_preserve_order(args.preserve_order);
+    if let Some(id_field) = args.id_field {
+        options = options.with_id_field(id_field);
+    }
+    if let Some(errors) = &args.errors {
+        let file: tokio::fs::File = tokio::fs::File::create(errors)
+            .await
+            .map_err(|e| Error::msg(format!("Failed to open {}: {}", errors.display(), e)))?;
+        options = options.with_errors(tokio::io::BufWriter::new(file));
+    }
+
+    let reader: tokio::io::BufReader<tokio::io::Stdin> = tokio::io::BufReader::new(tokio::io::stdin());
+    let summary: PipelineSummary = process_jsonl(reader, tokio::io::stdout(), &vectorizer, options).await?;
+    eprintln!("Vectorized {} items, {} failed", summary.succeeded, summary.failed);
+
+    Ok(match (summary.succeeded, summary.failed) {
+        (_, 0) => ExitCode::SUCCESS,
+        (0, _) => ExitCode::from(EXIT_FAILURE),
+        _ => ExitCode::from(EXIT_PARTIAL_FAILURE),
+    })
+}
+
+/// Loads the manifest and the items already written by an interrupted run, checking the run matches.
+fn resume_run(out: &Path, prompts: &PromptSet, model_parameters: &ModelParameters) -> Result<(RunManifest, HashSet<String>), Error> {
+    let manifest_path: PathBuf = sidecar_path(out);
+    if !out.exists() || !manifest_path.exists() {
+        return Ok((RunManifest::new(prompts, model_parameters), HashSet::new()));
+    }
+    let manifest: RunManifest = load_manifest(&manifest_path)?;
+    let mismatches: Vec<String> = manifest.mismatches(prompts, model_parameters);
+    if !mismatches.is_empty() {
+        return Err(Error::msg(format!(
+            "Cannot resume {}: {}",
+            out.display(),
+            mismatches.join("; ")
+        )));
+    }
+
+    let mut written: HashSet<String> = HashSet::new();
+    for vector in read_jsonl::<Vector<Value>>(out)? {
+        if let Some(source) = vector?.get_metadata(METADATA_SOURCE) {
+            written.insert(source.to_string());
+        }
+    }
+
+    Ok((manifest, written))
+}
+
+/// Reads the model parameters from the `DIM_*` variables, with the model of the command line.
+fn model_parameters(endpoint: &EndpointArgs) -> Result<ModelParameters, Error> {
+    let mut builder: ModelParametersBuilder = ModelParametersBuilder::from_env()?;
+    if let Some(model) = &endpoint.model {
+        builder = builder.model(model.clone());
+    }
+
+    builder.build()
+}
+
+/// Creates the client of the endpoint, reading the key from its variable.
+fn client(endpoint: &EndpointArgs) -> Result<Client<OpenAIConfig>, Error> {
+    let mut config: OpenAIConfig = OpenAIConfig::from_env(&endpoint.api_key_env, endpoint.allow_unauthenticated)?;
+    if let Some(api_base) = &endpoint.api_base {
+        config = config.with_api_base(api_base);
+    }
+
+    Ok(Client::with_config(config))
+}
+
+/// Runs `search`, printing one JSON line per result, closest first.
+async fn search(args: SearchArgs) -> Result<ExitCode, Error> {
+    let collection: VectorCollection<Value> = match sidecar_path(&args.index).exists() {
+        true => VectorCollection::load_with_manifest(&args.index)?,
+        false => VectorCollection::from_vectors(load_jsonl(&args.index)?)?,
+    };
+    let vectorizer: Vectorizer<Client<OpenAIConfig>> = Vectorizer::builder()
+        .client(client(&args.endpoint)?)
+        .prompts(load_prompts(&args.prompts)?)
+        .model_parameters(model_parameters(&args.endpoint)?)
+        .max_concurrency(args.endpoint.concurrency)
+        .build()?;
+
+    let hits: Vec<SearchHit<Value>> = search_text(&args.query, &collection, &vectorizer, args.top_k, args.metric.into()).await?;
+    for (rank, hit) in hits.into_iter().enumerate() {
+        let result: Value = json!({
+            "rank": rank + 1,
+            "index": hit.index,
+            "score": hit.score,
+            "id": hit.metadata.get(METADATA_ID),
+            "source": hit.metadata.get(METADATA_SOURCE),
+        });
+        println!("{}", result);
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,604 @@
+use std::path::Path;
+
+use anyhow::{Error, Result};
+use futures::stream::{self, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+pub mod filter;
+pub mod search;
+pub mod similarity;
+
+use crate::collection::filter::{Filter, FilteredSearch};
+use crate::collection::similarity::{compute_matrix, compute_pairs, SimilarityMatrix};
+use crate::error::DimError;
+use crate::llm::ChatBackend;
+use crate::prompt::{compute_fingerprint, PromptSet};
+use crate::vector::binary::{binarize_values, hamming_distance, BitVector};
+use crate::vector::explain::{explain_similarity, SimilarityExplanation};
+use crate::vector::metrics::Metric;
+use crate::vector::io::load_jsonl;
+use crate::vector::{Vector, VectorOperations};
+use crate::vectorization::manifest::{load_manifest, sidecar_path, RunManifest};
+use crate::vectorization::report::VectorizationReport;
+use crate::vectorization::vectorizable::Vectorizable;
+use crate::vectorization::hybrid::embedding_offset;
+use crate::vectorization::vectorizer::Vectorizer;
+use crate::vectorization::{extend_vector, extended_fingerprint, ModelParameters};
+
+/// An in-memory set of vectors searchable by brute force
+///
+/// All vectors share one dimensionality. Unless mixed fingerprints are
+/// explicitly allowed, they must also share a fingerprint, so vectors produced
+/// by different prompts or models are never ranked against each other.
+/// Collections loaded with a manifest also know the run that made them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorCollection<T> {
+    vectors: Vec<Vector<T>>,
+    #[serde(default)]
+    allow_mixed_fingerprints: bool,
+    #[serde(default)]
+    warn_on_query_mismatch: bool,
+    #[serde(default)]
+    explained_hits: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    manifest: Option<RunManifest>,
+}
+
+impl<T> Default for VectorCollection<T> {
+    fn default() -> Self {
+        Self {
+            vectors: Vec::new(),
+            allow_mixed_fingerprints: false,
+            warn_on_query_mismatch: false,
+            explained_hits: 0,
+            manifest: None,
+        }
+    }
+}
+
+impl<T: DeserializeOwned> VectorCollection<T> {
+    /// Loads a JSONL file of vectors along with the manifest saved next to it
+    ///
+    /// The manifest is read from `sidecar_path(path)`, e.g.
+    /// `vectors.manifest.json` for `vectors.jsonl`, and every stored vector must
+    /// carry its fingerprint. Query vectors are then checked against it.
+    ///
+    /// # Arguments
+    /// * `path` - The JSONL file written by `save_jsonl`
+    ///
+    /// # Returns
+    /// The collection, or an error if either file cannot be read or a stored
+    /// vector was made differently from what the manifest records
+    pub fn load_with_manifest(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let manifest: RunManifest = load_manifest(sidecar_path(path.as_ref()))?;
+        let vectors: Vec<Vector<T>> = load_jsonl(path.as_ref())?;
+        if let Some((index, vector)) = vectors
+            .iter()
+            .enumerate()
+            .find(|(_, vector)| vector.get_fingerprint() != Some(manifest.fingerprint.as_str()))
+        {
+            return Err(Error::msg(format!(
+                "Vector {} of {} has fingerprint {:?}, the manifest records {:?}",
+                index,
+                path.as_ref().display(),
+                vector.get_fingerprint(),
+                manifest.fingerprint
+            )));
+        }
+
+        let mut collection: Self = Self::from_vectors(vectors)?;
+        collection.manifest = Some(manifest);
+
+        Ok(collection)
+    }
+}
+
+impl<T> VectorCollection<T> {
+    /// Creates an empty collection
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a collection from existing vectors
+    ///
+    /// # Arguments
+    /// * `vectors` - The vectors to add, in index order
+    ///
+    /// # Returns
+    /// The collection, or the first error `push` would raise
+    pub fn from_vectors(vectors: Vec<Vector<T>>) -> Result<Self, Error> {
+        let mut collection: Self = Self::new();
+        for vector in vectors {
+            collection.push(vector)?;
+        }
+
+        Ok(collection)
+    }
+
+    /// Allows or forbids vectors with differing fingerprints
+    ///
+    /// Only allow mixing when the vectors are known to be comparable despite
+    /// the differing prompts or models.
+    pub fn with_mixed_fingerprints(mut self, allow: bool) -> Self {
+        self.allow_mixed_fingerprints = allow;
+        self
+    }
+
+    /// Logs queries with a differing fingerprint and searches anyway, instead of rejecting them
+    ///
+    /// `with_mixed_fingerprints(true)` is the silent escape hatch for deliberate comparisons.
+    pub fn with_query_mismatch_warnings(mut self, warn: bool) -> Self {
+        self.warn_on_query_mismatch = warn;
+        self
+    }
+
+    /// Attaches a `SimilarityExplanation` to the first `explained_hits` hits of `search_text` and `search_image`, none by default
+    pub fn with_explanations(mut self, explained_hits: usize) -> Self {
+        self.explained_hits = explained_hits;
+        self
+    }
+
+    pub fn get_explained_hits(&self) -> usize {
+        self.explained_hits
+    }
+
+    /// Returns the manifest of the run that made the vectors, if loaded with one
+    pub fn get_manifest(&self) -> Option<&RunManifest> {
+        self.manifest.as_ref()
+    }
+
+    /// Checks that queries made with a prompt set and parameters can be searched
+    ///
+    /// Call this before vectorizing queries, to catch changed prompts before
+    /// paying for them.
+    ///
+    /// # Arguments
+    /// * `prompts` - The prompts queries will be vectorized with
+    /// * `model_parameters` - The parameters queries will be vectorized with
+    ///
+    /// # Returns
+    /// An error listing every difference from the manifest, unless mixed
+    /// fingerprints are allowed; Ok for collections without a manifest
+    pub fn verify_run(&self, prompts: &PromptSet, model_parameters: &ModelParameters) -> Result<(), Error> {
+        let Some(manifest) = &self.manifest else {
+            return Ok(());
+        };
+        let mismatches: Vec<String> = manifest.mismatches(prompts, model_parameters);
+        if mismatches.is_empty() || self.allow_mixed_fingerprints {
+            return Ok(());
+        }
+        let message: String = format!("Queries would not match the collection: {}", mismatches.join("; "));
+        if self.warn_on_query_mismatch {
+            log::warn!("{}", message);
+            return Ok(());
+        }
+
+        Err(Error::msg(message))
+    }
+
+    /// Adds a vector to the collection
+    ///
+    /// # Arguments
+    /// * `vector` - The vector to add; its index is the current length
+    ///
+    /// # Returns
+    /// An error if the vector is empty, its dimensionality differs from the
+    /// collection's, or its fingerprint differs while mixing is not allowed
+    pub fn push(&mut self, vector: Vector<T>) -> Result<(), Error> {
+        if vector.get_dimensionality() == 0 {
+            return Err(Error::msg("Cannot add an empty vector to a collection"));
+        }
+        if let Some(first) = self.vectors.first() {
+            if first.get_dimensionality() != vector.get_dimensionality() {
+                return Err(Error::msg(format!(
+                    "Dimensionality mismatch: collection has {}, vector has {}",
+                    first.get_dimensionality(),
+                    vector.get_dimensionality()
+                )));
+            }
+            if !self.allow_mixed_fingerprints && !first.compatible_with(&vector) {
+                return Err(Error::msg(format!(
+                    "Fingerprint mismatch: collection has {:?}, vector has {:?}",
+                    first.get_fingerprint(),
+                    vector.get_fingerprint()
+                )));
+            }
+        }
+        self.vectors.push(vector);
+
+        Ok(())
+    }
+
+    /// Returns the vector stored at `index`, with its data and metadata
+    pub fn get(&self, index: usize) -> Option<&Vector<T>> {
+        self.vectors.get(index)
+    }
+
+    /// Returns the stored vectors in index order
+    pub fn get_vectors(&self) -> &[Vector<T>] {
+        &self.vectors
+    }
+
+    /// Returns the number of stored vectors
+    pub fn len(&self) -> usize {
+        self.vectors.len()
+    }
+
+    /// Returns whether the collection is empty
+    pub fn is_empty(&self) -> bool {
+        self.vectors.is_empty()
+    }
+
+    /// Returns the shared dimensionality, or `None` while empty
+    pub fn get_dimensionality(&self) -> Option<usize> {
+        self.vectors.first().map(|vector| vector.get_dimensionality())
+    }
+
+    /// Returns the fingerprint of the manifest or else the first vector, or `None` while empty or unfingerprinted
+    pub fn get_fingerprint(&self) -> Option<&str> {
+        match &self.manifest {
+            Some(manifest) => Some(manifest.fingerprint.as_str()),
+            None => self.vectors.first().and_then(|vector| vector.get_fingerprint()),
+        }
+    }
+
+    /// Finds the `k` stored vectors closest to a raw query
+    ///
+    /// # Arguments
+    /// * `query` - The query values, as long as the stored vectors
+    /// * `k` - How many results to return; larger values return the whole collection
+    /// * `metric` - How to compare the query with stored vectors
+    ///
+    /// # Returns
+    /// `(index, score)` pairs, closest first, or an error on a mismatched query
+    pub fn search(&self, query: &[f32], k: usize, metric: Metric) -> Result<Vec<(usize, f32)>, Error> {
+        Ok(self.search_where(query, k, metric, |_| true)?.results)
+    }
+
+    /// Finds the `k` closest stored vectors that meet a filter
+    ///
+    /// # Arguments
+    /// * `query` - The query values, as long as the stored vectors
+    /// * `k` - How many results to return
+    /// * `metric` - How to compare the query with stored vectors
+    /// * `filter` - The conditions on data type and metadata
+    ///
+    /// # Returns
+    /// The closest matches and how many stored vectors passed the filter
+    pub fn search_filtered(&self, query: &[f32], k: usize, metric: Metric, filter: &Filter) -> Result<FilteredSearch, Error> {
+        self.search_where(query, k, metric, |vector| filter.matches(vector))
+    }
+
+    /// Finds the `k` closest stored vectors accepted by a predicate
+    ///
+    /// The predicate runs before scoring, so rejected vectors cost nothing.
+    ///
+    /// # Arguments
+    /// * `query` - The query values, as long as the stored vectors
+    /// * `k` - How many results to return
+    /// * `metric` - How to compare the query with stored vectors
+    /// * `predicate` - Returns whether a stored vector should be considered
+    ///
+    /// # Returns
+    /// The closest matches and how many stored vectors passed the predicate
+    pub fn search_where<F>(&self, query: &[f32], k: usize, metric: Metric, predicate: F) -> Result<FilteredSearch, Error>
+    where
+        F: Fn(&Vector<T>) -> bool,
+    {
+        if let Some(dimensionality) = self.get_dimensionality() {
+            if query.len() != dimensionality {
+                return Err(Error::msg(format!(
+                    "Dimensionality mismatch: collection has {}, query has {}",
+                    dimensionality,
+                    query.len()
+                )));
+            }
+        }
+
+        let mut results: Vec<(usize, f32)> = self
+            .vectors
+            .iter()
+            .enumerate()
+            .filter(|(_, vector)| predicate(vector))
+            .map(|(index, vector)| Ok((index, metric.score(query, vector.as_slice())?)))
+            .collect::<Result<Vec<(usize, f32)>, Error>>()?;
+        let matched: usize = results.len();
+        results.sort_by(|a, b| metric.closest_first(a.1, b.1));
+        results.truncate(k);
+
+        Ok(FilteredSearch { matched, results })
+    }
+
+    /// Finds the `k` stored vectors closest to a query vector
+    ///
+    /// Unlike `search`, this checks that the query was produced the same way as
+    /// the stored vectors, unless mixed fingerprints are allowed. With query
+    /// mismatch warnings, a differing query is logged and searched anyway.
+    ///
+    /// # Arguments
+    /// * `query` - The query vector
+    /// * `k` - How many results to return
+    /// * `metric` - How to compare the query with stored vectors
+    ///
+    /// # Returns
+    /// `(index, score)` pairs, closest first, or an error on a mismatched query
+    pub fn search_vector<U>(&self, query: &Vector<U>, k: usize, metric: Metric) -> Result<Vec<(usize, f32)>, Error> {
+        self.check_fingerprint(query.get_fingerprint())?;
+
+        self.search(query.as_slice(), k, metric)
+    }
+
+    /// Explains the score of a stored vector against a query, dimension by dimension
+    ///
+    /// # Arguments
+    /// * `query` - The query vector, e.g. the one given to `search_vector`
+    /// * `index` - The position of the stored vector, e.g. from a search result
+    /// * `metric` - The metric searched with
+    ///
+    /// # Returns
+    /// The contribution of each dimension to the score, or an error on an
+    /// unknown index or a mismatched query
+    pub fn explain<U>(&self, query: &Vector<U>, index: usize, metric: Metric) -> Result<SimilarityExplanation, Error> {
+        let vector: &Vector<T> = self
+            .vectors
+            .get(index)
+            .ok_or_else(|| Error::msg(format!("No vector at index {} of {}", index, self.vectors.len())))?;
+
+        explain_similarity(query, vector, metric)
+    }
+
+    /// Finds the `k` stored hybrid vectors closest to a hybrid query, weighting its two blocks
+    ///
+    /// Each block, the scored dimensions and the embedding labeled
+    /// `emb_<index>`, is scored on its own with the metric, and the scores are
+    /// added up with their weights. The query is checked like in `search_vector`.
+    ///
+    /// # Arguments
+    /// * `query` - The query vector, from `vectorize_string_hybrid` like the stored ones
+    /// * `k` - How many results to return
+    /// * `metric` - How to compare each block of the query with stored vectors
+    /// * `weights` - How much each block counts
+    ///
+    /// # Returns
+    /// `(index, score)` pairs, closest first, or an error on a mismatched or non-hybrid query
+    pub fn search_hybrid<U>(&self, query: &Vector<U>, k: usize, metric: Metric, weights: HybridWeights) -> Result<Vec<(usize, f32)>, Error> {
+        self.check_fingerprint(query.get_fingerprint())?;
+        let offset: usize = embedding_offset(query.get_labels())
+            .filter(|offset| *offset > 0)
+            .ok_or_else(|| Error::msg("The query is not a hybrid vector with scored dimensions and an embedding"))?;
+        if let Some(dimensionality) = self.get_dimensionality().filter(|dimensionality| *dimensionality != query.get_dimensionality()) {
+            return Err(Error::msg(format!(
+                "Dimensionality mismatch: collection has {}, query has {}",
+                dimensionality,
+                query.get_dimensionality()
+            )));
+        }
+
+        let (scored, embedding): (&[f32], &[f32]) = query.as_slice().split_at(offset);
+        let mut results: Vec<(usize, f32)> = self
+            .vectors
+            .iter()
+            .enumerate()
+            .map(|(index, vector)| {
+                let (stored_scored, stored_embedding): (&[f32], &[f32]) = vector.as_slice().split_at(offset);
+                let score: f32 = weights.scored * metric.score(scored, stored_scored)?
+                    + weights.embedding * metric.score(embedding, stored_embedding)?;
+                Ok((index, score))
+            })
+            .collect::<Result<Vec<(usize, f32)>, Error>>()?;
+        results.sort_by(|a, b| metric.closest_first(a.1, b.1));
+        results.truncate(k);
+
+        Ok(results)
+    }
+
+    /// Ensures a query's fingerprint matches the stored vectors, or only warns when configured to.
+    pub(crate) fn check_fingerprint(&self, fingerprint: Option<&str>) -> Result<(), Error> {
+        let matches: bool = match (&self.manifest, self.vectors.first()) {
+            (Some(manifest), _) => fingerprint == Some(manifest.fingerprint.as_str()),
+            (None, Some(first)) => first.get_fingerprint() == fingerprint,
+            (None, None) => true,
+        };
+        if !matches && !self.allow_mixed_fingerprints {
+            let message: String = format!(
+                "Fingerprint mismatch: collection has {:?}, query has {:?}",
+                self.get_fingerprint(),
+                fingerprint
+            );
+            if !self.warn_on_query_mismatch {
+                return Err(Error::msg(message));
+            }
+            log::warn!("{}", message);
+        }
+
+        Ok(())
+    }
+}
+
+/// How much each block of a hybrid vector counts in `VectorCollection::search_hybrid`
+///
+/// # Fields
+/// * `scored` - The weight of the prompt-scored dimensions
+/// * `embedding` - The weight of the embedding dimensions
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HybridWeights {
+    pub scored: f32,
+    pub embedding: f32,
+}
+
+impl HybridWeights {
+    /// Creates weights for the scored and the embedding block
+    pub fn new(scored: f32, embedding: f32) -> Self {
+        Self { scored, embedding }
+    }
+}
+
+impl Default for HybridWeights {
+    /// Weights both blocks equally
+    fn default() -> Self {
+        Self::new(1.0, 1.0)
+    }
+}
+
+/// Binarized copies of a collection's vectors for fast Hamming pre-filtering
+///
+/// Built with `VectorCollection::binary_index` and only valid for the
+/// collection it was built from; rebuild it after pushing new vectors.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BinaryIndex {
+    thresholds: Vec<f32>,
+    bits: Vec<BitVector>,
+}
+
+impl BinaryIndex {
+    /// Returns the thresholds the vectors were binarized with
+    pub fn get_thresholds(&self) -> &[f32] {
+        &self.thresholds
+    }
+
+    /// Returns the binarized vectors, indexed like the collection
+    pub fn get_bits(&self) -> &[BitVector] {
+        &self.bits
+    }
+}
+
+impl<T> VectorCollection<T> {
+    /// Binarizes every stored vector for two-stage search
+    ///
+    /// # Arguments
+    /// * `thresholds` - One threshold per dimension, e.g. `DimensionStats::median`
+    ///
+    /// # Returns
+    /// The index, or an error if the thresholds do not match the dimensionality
+    pub fn binary_index(&self, thresholds: &[f32]) -> Result<BinaryIndex, Error> {
+        let bits: Vec<BitVector> = self
+            .vectors
+            .iter()
+            .map(|vector| binarize_values(vector.as_slice(), thresholds))
+            .collect::<Result<Vec<BitVector>, Error>>()?;
+
+        Ok(BinaryIndex {
+            thresholds: thresholds.to_vec(),
+            bits,
+        })
+    }
+
+    /// Shortlists by Hamming distance, then reranks the shortlist by cosine similarity
+    ///
+    /// # Arguments
+    /// * `query` - The query values, as long as the stored vectors
+    /// * `index` - The binary index built from this collection
+    /// * `shortlist` - How many candidates to keep after the Hamming stage
+    /// * `k` - How many results to return
+    ///
+    /// # Returns
+    /// `(index, cosine similarity)` pairs, most similar first, or an error on a
+    /// mismatched query or a stale index
+    pub fn search_two_stage(&self, query: &[f32], index: &BinaryIndex, shortlist: usize, k: usize) -> Result<Vec<(usize, f32)>, Error> {
+        if index.bits.len() != self.vectors.len() {
+            return Err(Error::msg(format!(
+                "Stale binary index: built for {} vectors, collection has {}",
+                index.bits.len(),
+                self.vectors.len()
+            )));
+        }
+
+        let query_bits: BitVector = binarize_values(query, &index.thresholds)?;
+        let mut candidates: Vec<(usize, u32)> = index
+            .bits
+            .iter()
+            .enumerate()
+            .map(|(position, bits)| Ok((position, hamming_distance(&query_bits, bits)?)))
+            .collect::<Result<Vec<(usize, u32)>, Error>>()?;
+        candidates.sort_by_key(|(_, distance)| *distance);
+        candidates.truncate(shortlist);
+
+        let mut results: Vec<(usize, f32)> = candidates
+            .into_iter()
+            .map(|(position, _)| Ok((position, Metric::Cosine.score(query, self.vectors[position].as_slice())?)))
+            .collect::<Result<Vec<(usize, f32)>, Error>>()?;
+        results.sort_by(|a, b| Metric::Cosine.closest_first(a.1, b.1));
+        results.truncate(k);
+
+        Ok(results)
+    }
+}
+
+impl<T: Vectorizable> VectorCollection<T> {
+    /// Appends the dimensions of new prompts to every stored vector, as `extend_vector` does
+    ///
+    /// Vectors are extended at most `max_concurrency` at a time with the
+    /// vectorizer's client and parameters, and answers are cached as usual. A
+    /// failed vector does not stop the others; calling again with the same
+    /// vectorizer resumes, skipping the vectors already extended. Once every
+    /// vector is extended, a manifest records the new prompts and fingerprint.
+    ///
+    /// # Arguments
+    /// * `vectorizer` - A vectorizer holding the new prompts
+    ///
+    /// # Returns
+    /// The report or error of every vector, in index order; an empty report for a vector extended before
+    pub async fn extend_all<B: ChatBackend>(&mut self, vectorizer: &Vectorizer<B>) -> Vec<Result<VectorizationReport, DimError>> {
+        let model_parameters: &ModelParameters = vectorizer.get_model_parameters();
+        let extension: String = compute_fingerprint(vectorizer.get_prompts().get_prompts(), &model_parameters.get_model());
+        let mut extended: Vec<String> = Vec::new();
+        for fingerprint in self.vectors.iter().filter_map(|vector| vector.get_fingerprint()) {
+            let fingerprint: String = extended_fingerprint(fingerprint, &extension);
+            if !extended.contains(&fingerprint) {
+                extended.push(fingerprint);
+            }
+        }
+
+        let results: Vec<Result<VectorizationReport, DimError>> =
+            stream::iter(self.vectors.iter_mut().enumerate().map(|(item_index, vector)| {
+                let done: bool = vector.get_fingerprint().is_some_and(|fingerprint| extended.iter().any(|extended| extended == fingerprint));
+                let prompts: &PromptSet = vectorizer.get_prompts();
+                let client = vectorizer.get_client().clone();
+                let model_parameters: ModelParameters = model_parameters.for_item(item_index);
+                async move {
+                    if done {
+                        return Ok(VectorizationReport::default());
+                    }
+                    extend_vector(prompts, vector, client, model_parameters).await
+                }
+            }))
+            .buffered(vectorizer.get_max_concurrency())
+            .collect()
+            .await;
+
+        if let Some(manifest) = self.manifest.as_mut().filter(|_| results.iter().all(Result::is_ok)) {
+            manifest.fingerprint = extended_fingerprint(&manifest.fingerprint, &extension);
+            manifest
+                .prompts
+                .extend(vectorizer.get_prompts().get_prompts().iter().map(|prompt| prompt.get_instruction()));
+        }
+
+        results
+    }
+}
+
+impl<T: Sync> VectorCollection<T> {
+    /// Computes the pairwise scores of every stored vector
+    ///
+    /// # Arguments
+    /// * `metric` - How to compare vectors
+    ///
+    /// # Returns
+    /// The N×N matrix indexed like the collection
+    pub fn similarity_matrix(&self, metric: Metric) -> Result<SimilarityMatrix, Error> {
+        compute_matrix(&self.vectors, metric)
+    }
+
+    /// Finds every pair of stored vectors whose score is within a threshold
+    ///
+    /// # Arguments
+    /// * `metric` - How to compare vectors
+    /// * `threshold` - The score a pair must reach, see `similar_pairs`
+    ///
+    /// # Returns
+    /// `(i, j, score)` triples with `i < j`, ordered by `i` then `j`
+    pub fn similar_pairs(&self, metric: Metric, threshold: f32) -> Result<Vec<(usize, usize, f32)>, Error> {
+        compute_pairs(&self.vectors, metric, threshold)
+    }
+}
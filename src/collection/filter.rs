@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::vector::{DataType, Vector, VectorOperations};
+
+/// Conditions a stored vector must meet to be considered by a search
+///
+/// All conditions must hold; an empty filter matches everything.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Filter {
+    #[serde(default)]
+    data_type: Option<DataType>,
+    #[serde(default)]
+    metadata: BTreeMap<String, String>,
+}
+
+impl Filter {
+    /// Creates a filter that matches everything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires the vector's data to be of the given type
+    pub fn data_type(mut self, data_type: DataType) -> Self {
+        self.data_type = Some(data_type);
+        self
+    }
+
+    /// Requires the metadata entry `key` to equal `value`
+    pub fn metadata_eq(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Returns whether a vector meets every condition
+    pub fn matches<T>(&self, vector: &Vector<T>) -> bool {
+        if let Some(data_type) = self.data_type {
+            if vector.get_data_type() != data_type {
+                return false;
+            }
+        }
+
+        self.metadata
+            .iter()
+            .all(|(key, value)| vector.get_metadata(key) == Some(value.as_str()))
+    }
+}
+
+/// The outcome of a filtered search
+///
+/// # Fields
+/// * `matched` - How many stored vectors passed the filter and were scored
+/// * `results` - `(index, score)` pairs, closest first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilteredSearch {
+    pub matched: usize,
+    pub results: Vec<(usize, f32)>,
+}
@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Error, Result};
+#[cfg(feature = "image")]
+use image::DynamicImage;
+
+use crate::collection::VectorCollection;
+use crate::llm::ChatBackend;
+use crate::prompt::compute_fingerprint;
+use crate::vector::explain::{explain_similarity, SimilarityExplanation};
+use crate::vector::metrics::Metric;
+use crate::vector::{Vector, VectorOperations};
+use crate::vectorization::vectorizable::Vectorizable;
+use crate::vectorization::vectorizer::Vectorizer;
+use crate::vectorization::ModelParameters;
+
+/// One result of `search_text` or `search_image`
+///
+/// # Fields
+/// * `index` - The position of the stored vector in the collection
+/// * `score` - How close it is to the query, by the metric searched with
+/// * `metadata` - The metadata of the stored vector, e.g. its `id` and `source`
+/// * `data` - The stored data, e.g. the text that was vectorized
+/// * `explanation` - Why it matched, for the hits the collection explains with `with_explanations`
+#[derive(Debug, Clone)]
+pub struct SearchHit<'a, T> {
+    pub index: usize,
+    pub score: f32,
+    pub metadata: &'a BTreeMap<String, String>,
+    pub data: &'a T,
+    pub explanation: Option<SimilarityExplanation>,
+}
+
+/// Finds the `k` stored vectors closest to a natural-language query
+///
+/// The query is vectorized with the vectorizer's prompts and parameters, which
+/// must be those the collection was made with: the fingerprint they produce
+/// is checked against the collection's before any request is sent, like
+/// `VectorCollection::search_vector` checks query vectors.
+///
+/// # Arguments
+/// * `query` - The text to search for
+/// * `collection` - The vectors to search
+/// * `vectorizer` - The client, prompts and parameters the collection was vectorized with
+/// * `k` - How many results to return
+/// * `metric` - How to compare the query with stored vectors
+///
+/// # Returns
+/// The hits, closest first, or an error if the vectorizer does not match the
+/// collection or vectorizing the query fails
+pub async fn search_text<'a, T, B: ChatBackend>(
+    query: &str,
+    collection: &'a VectorCollection<T>,
+    vectorizer: &Vectorizer<B>,
+    k: usize,
+    metric: Metric,
+) -> Result<Vec<SearchHit<'a, T>>, Error> {
+    search_query(Vector::from_text(query.to_string()), collection, vectorizer, k, metric).await
+}
+
+/// Finds the `k` stored vectors closest to a query image
+///
+/// The image is vectorized and checked like the text of `search_text`.
+///
+/// # Arguments
+/// * `query` - The image to search for
+/// * `collection` - The vectors to search
+/// * `vectorizer` - The client, prompts and parameters the collection was vectorized with
+/// * `k` - How many results to return
+/// * `metric` - How to compare the query with stored vectors
+///
+/// # Returns
+/// The hits, closest first, or an error if the vectorizer does not match the
+/// collection or vectorizing the query fails
+#[cfg(feature = "image")]
+pub async fn search_image<'a, T, B: ChatBackend>(
+    query: &DynamicImage,
+    collection: &'a VectorCollection<T>,
+    vectorizer: &Vectorizer<B>,
+    k: usize,
+    metric: Metric,
+) -> Result<Vec<SearchHit<'a, T>>, Error> {
+    search_query(Vector::from_image(query.clone()), collection, vectorizer, k, metric).await
+}
+
+/// Checks the vectorizer against the collection, vectorizes the query and searches with it.
+async fn search_query<'a, Q: Vectorizable, T, B: ChatBackend>(
+    mut query: Vector<Q>,
+    collection: &'a VectorCollection<T>,
+    vectorizer: &Vectorizer<B>,
+    k: usize,
+    metric: Metric,
+) -> Result<Vec<SearchHit<'a, T>>, Error> {
+    let model_parameters: &ModelParameters = vectorizer.get_model_parameters();
+    match collection.get_manifest() {
+        Some(_) => collection.verify_run(vectorizer.get_prompts(), model_parameters)?,
+        None => {
+            let fingerprint: String = compute_fingerprint(vectorizer.get_prompts().get_prompts(), &model_parameters.get_model());
+            collection.check_fingerprint(Some(&fingerprint))?;
+        }
+    }
+
+    vectorizer.vectorize(&mut query).await?;
+    let results: Vec<(usize, f32)> = collection.search(query.as_slice(), k, metric)?;
+
+    results
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (index, score))| {
+            let vector: &Vector<T> = &collection.get_vectors()[index];
+            let explanation: Option<SimilarityExplanation> = match rank < collection.get_explained_hits() {
+                true => Some(explain_similarity(&query, vector, metric)?),
+                false => None,
+            };
+            Ok(SearchHit {
+                index,
+                score,
+                metadata: vector.get_metadata_map(),
+                data: vector.get_data(),
+                explanation,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,176 @@
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::vector::metrics::Metric;
+use crate::vector::{Vector, VectorOperations};
+
+/// Pairwise scores of a set of vectors, stored row-major
+///
+/// Row `i`, column `j` holds the score between vector `i` and vector `j`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimilarityMatrix {
+    size: usize,
+    scores: Vec<f32>,
+}
+
+impl SimilarityMatrix {
+    /// Returns the number of rows, which equals the number of columns
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the score between vectors `i` and `j`
+    pub fn get(&self, i: usize, j: usize) -> Option<f32> {
+        if i >= self.size || j >= self.size {
+            return None;
+        }
+
+        Some(self.scores[i * self.size + j])
+    }
+
+    /// Returns the scores of vector `i` against every vector
+    pub fn row(&self, i: usize) -> Option<&[f32]> {
+        if i >= self.size {
+            return None;
+        }
+
+        Some(&self.scores[i * self.size..(i + 1) * self.size])
+    }
+
+    /// Returns all scores, row-major
+    pub fn as_slice(&self) -> &[f32] {
+        &self.scores
+    }
+
+    /// Returns the scores as nested rows
+    pub fn to_rows(&self) -> Vec<Vec<f32>> {
+        self.scores.chunks(self.size.max(1)).map(|row| row.to_vec()).collect()
+    }
+}
+
+/// Ensures vectors share a non-zero dimensionality and a fingerprint
+pub(crate) fn check_comparable<T>(vectors: &[Vector<T>], allow_mixed_fingerprints: bool) -> Result<(), Error> {
+    let Some(first) = vectors.first() else {
+        return Ok(());
+    };
+
+    for (index, vector) in vectors.iter().enumerate() {
+        if vector.get_dimensionality() == 0 || vector.get_dimensionality() != first.get_dimensionality() {
+            return Err(Error::msg(format!(
+                "Dimensionality mismatch: vector 0 has {}, vector {} has {}",
+                first.get_dimensionality(),
+                index,
+                vector.get_dimensionality()
+            )));
+        }
+        if !allow_mixed_fingerprints && !first.compatible_with(vector) {
+            return Err(Error::msg(format!(
+                "Fingerprint mismatch: vector 0 has {:?}, vector {} has {:?}",
+                first.get_fingerprint(),
+                index,
+                vector.get_fingerprint()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Scores row `i` against columns `i..`, the upper triangle including the diagonal
+fn upper_row<T>(vectors: &[Vector<T>], i: usize, metric: Metric) -> Result<Vec<f32>, Error> {
+    vectors[i..]
+        .iter()
+        .map(|other| metric.score(vectors[i].as_slice(), other.as_slice()))
+        .collect()
+}
+
+/// Collects the pairs `(i, j)` with `j > i` whose score is within `threshold`
+fn row_pairs<T>(vectors: &[Vector<T>], i: usize, metric: Metric, threshold: f32) -> Result<Vec<(usize, usize, f32)>, Error> {
+    let mut pairs: Vec<(usize, usize, f32)> = Vec::new();
+    for (j, other) in vectors.iter().enumerate().skip(i + 1) {
+        let score: f32 = metric.score(vectors[i].as_slice(), other.as_slice())?;
+        if metric.within(score, threshold) {
+            pairs.push((i, j, score));
+        }
+    }
+
+    Ok(pairs)
+}
+
+/// Computes the full pairwise score matrix
+///
+/// Only the upper triangle is computed and then mirrored. With the `parallel`
+/// feature rows are computed on the rayon thread pool.
+///
+/// # Arguments
+/// * `vectors` - The vectors to compare, all of the same dimensionality and fingerprint
+/// * `metric` - How to compare vectors
+///
+/// # Returns
+/// The N×N matrix, or an error if the vectors are not comparable
+pub fn similarity_matrix<T: Sync>(vectors: &[Vector<T>], metric: Metric) -> Result<SimilarityMatrix, Error> {
+    check_comparable(vectors, false)?;
+    compute_matrix(vectors, metric)
+}
+
+/// Computes the matrix without checking the vectors are comparable
+pub(crate) fn compute_matrix<T: Sync>(vectors: &[Vector<T>], metric: Metric) -> Result<SimilarityMatrix, Error> {
+    let size: usize = vectors.len();
+
+    #[cfg(feature = "parallel")]
+    let rows: Vec<Vec<f32>> = (0..size)
+        .into_par_iter()
+        .map(|i| upper_row(vectors, i, metric))
+        .collect::<Result<Vec<Vec<f32>>, Error>>()?;
+    #[cfg(not(feature = "parallel"))]
+    let rows: Vec<Vec<f32>> = (0..size)
+        .map(|i| upper_row(vectors, i, metric))
+        .collect::<Result<Vec<Vec<f32>>, Error>>()?;
+
+    let mut scores: Vec<f32> = vec![0.0; size * size];
+    for (i, row) in rows.into_iter().enumerate() {
+        for (offset, score) in row.into_iter().enumerate() {
+            let j: usize = i + offset;
+            scores[i * size + j] = score;
+            scores[j * size + i] = score;
+        }
+    }
+
+    Ok(SimilarityMatrix { size, scores })
+}
+
+/// Finds every pair of vectors whose score is within a threshold
+///
+/// Only qualifying pairs are kept, so memory stays bounded for large N. For
+/// cosine and dot a pair qualifies when its score is at least `threshold`; for
+/// euclidean when its distance is at most `threshold`.
+///
+/// # Arguments
+/// * `vectors` - The vectors to compare, all of the same dimensionality and fingerprint
+/// * `metric` - How to compare vectors
+/// * `threshold` - The score a pair must reach
+///
+/// # Returns
+/// `(i, j, score)` triples with `i < j`, ordered by `i` then `j`
+pub fn similar_pairs<T: Sync>(vectors: &[Vector<T>], metric: Metric, threshold: f32) -> Result<Vec<(usize, usize, f32)>, Error> {
+    check_comparable(vectors, false)?;
+    compute_pairs(vectors, metric, threshold)
+}
+
+/// Finds the pairs without checking the vectors are comparable
+pub(crate) fn compute_pairs<T: Sync>(vectors: &[Vector<T>], metric: Metric, threshold: f32) -> Result<Vec<(usize, usize, f32)>, Error> {
+    #[cfg(feature = "parallel")]
+    let rows: Vec<Vec<(usize, usize, f32)>> = (0..vectors.len())
+        .into_par_iter()
+        .map(|i| row_pairs(vectors, i, metric, threshold))
+        .collect::<Result<Vec<Vec<(usize, usize, f32)>>, Error>>()?;
+    #[cfg(not(feature = "parallel"))]
+    let rows: Vec<Vec<(usize, usize, f32)>> = (0..vectors.len())
+        .map(|i| row_pairs(vectors, i, metric, threshold))
+        .collect::<Result<Vec<Vec<(usize, usize, f32)>>, Error>>()?;
+
+    Ok(rows.into_iter().flatten().collect())
+}
@@ -0,0 +1,109 @@
+use std::fmt::{Debug, Display};
+
+use anyhow::Error;
+use async_openai::error::OpenAIError;
+use thiserror::Error;
+
+/// API error codes and types that mean the credentials were rejected
+const AUTHENTICATION_ERRORS: [&str; 3] = ["invalid_api_key", "authentication_error", "unauthorized"];
+
+/// Why a vectorization failed
+///
+/// The public vectorization functions return this error, so callers can tell
+/// a rejected API key from a timeout or an unusable answer. It converts into
+/// `anyhow::Error` with `?`, and back with `From`, which recovers the variant
+/// an `anyhow::Error` was created from.
+#[derive(Debug, Error)]
+pub enum DimError {
+    /// The API answered with an error
+    #[error("API error{}: {message}", status_suffix(.status))]
+    ApiError { status: Option<u16>, message: String },
+    /// The API rejected the credentials
+    #[error("Authentication failed: {message}")]
+    Unauthorized { message: String },
+    /// A request did not finish in time
+    #[error("{message}")]
+    Timeout { message: String },
+    /// The endpoint does not serve the model
+    #[error("{message}")]
+    ModelNotFound { model: String, message: String },
+    /// An answer was parsed but did not pass validation
+    #[error("Validation error for prompt {prompt_index}: {reason}")]
+    ValidationFailed { prompt_index: usize, reason: String },
+    /// An answer could not be parsed
+    #[error("Failed to parse the answer: {raw}")]
+    ParseFailed { raw: String },
+    /// A vector has a different number of dimensions than expected
+    #[error("Dimensionality mismatch: expected {expected}, got {actual}")]
+    DimensionalityMismatch { expected: usize, actual: usize },
+    /// The work was cancelled before it finished
+    #[error("The vectorization was cancelled")]
+    Cancelled,
+    /// Any other failure, such as invalid parameters or unreadable input
+    #[error(transparent)]
+    Other(Error),
+}
+
+impl DimError {
+    /// Creates an `Other` error from a message, like `anyhow::Error::msg`
+    pub fn msg<M>(message: M) -> Self
+    where
+        M: Display + Debug + Send + Sync + 'static,
+    {
+        DimError::Other(Error::msg(message))
+    }
+
+    /// Prefixes the message of the error with what was being done, keeping its variant.
+    pub(crate) fn context(self, context: &str) -> Self {
+        let prefix = |message: String| format!("{}: {}", context, message);
+        match self {
+            DimError::ApiError { status, message } => DimError::ApiError { status, message: prefix(message) },
+            DimError::Unauthorized { message } => DimError::Unauthorized { message: prefix(message) },
+            DimError::Timeout { message } => DimError::Timeout { message: prefix(message) },
+            DimError::ModelNotFound { model, message } => DimError::ModelNotFound { model, message: prefix(message) },
+            DimError::Other(error) => DimError::msg(prefix(error.to_string())),
+            error => error,
+        }
+    }
+}
+
+impl From<Error> for DimError {
+    fn from(error: Error) -> Self {
+        match error.downcast::<DimError>() {
+            Ok(error) => error,
+            Err(error) => match error.downcast::<OpenAIError>() {
+                Ok(error) => DimError::from(error),
+                Err(error) => DimError::Other(error),
+            },
+        }
+    }
+}
+
+impl From<OpenAIError> for DimError {
+    fn from(error: OpenAIError) -> Self {
+        match error {
+            OpenAIError::ApiError(api_error) => {
+                let rejected: bool = [&api_error.code, &api_error.r#type]
+                    .into_iter()
+                    .flatten()
+                    .any(|kind| AUTHENTICATION_ERRORS.contains(&kind.as_str()));
+                match rejected {
+                    true => DimError::Unauthorized { message: api_error.message },
+                    false => DimError::ApiError { status: None, message: api_error.message },
+                }
+            }
+            OpenAIError::Reqwest(error) if error.is_timeout() => DimError::Timeout { message: error.to_string() },
+            OpenAIError::Reqwest(error) => match error.status().map(|status| status.as_u16()) {
+                Some(401) | Some(403) => DimError::Unauthorized { message: error.to_string() },
+                status => DimError::ApiError { status, message: error.to_string() },
+            },
+            OpenAIError::JSONDeserialize(error) => DimError::ParseFailed { raw: error.to_string() },
+            error => DimError::Other(Error::new(error)),
+        }
+    }
+}
+
+/// Formats an HTTP status for an error message, if there is one.
+fn status_suffix(status: &Option<u16>) -> String {
+    status.map(|status| format!(" ({})", status)).unwrap_or_default()
+}
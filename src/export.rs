@@ -0,0 +1,11 @@
+//! Writers that hand vector collections to other tools and storage systems.
+
+#[cfg(feature = "npy")]
+pub mod npy;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod pgvector;
+#[cfg(feature = "polars")]
+pub mod polars;
+#[cfg(feature = "qdrant")]
+pub mod qdrant;
@@ -0,0 +1,117 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{Error, Result};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::vector::io::consistent_labels;
+use crate::vector::{Vector, VectorOperations};
+
+/// Writes the vectors as a 2-D little-endian f32 NumPy array (items × dims)
+///
+/// Load it in Python with `numpy.load(path)`.
+///
+/// # Arguments
+/// * `path` - The .npy file to create or truncate
+/// * `vectors` - The vectors, one row each
+///
+/// # Returns
+/// An error before anything is written if the vectors disagree on dimensionality,
+/// or an error if the file cannot be written
+pub fn write_npy<T>(path: impl AsRef<Path>, vectors: &[Vector<T>]) -> Result<(), Error> {
+    consistent_labels(vectors)?;
+
+    let mut writer: BufWriter<File> = BufWriter::new(File::create(path)?);
+    writer.write_all(&matrix_npy(vectors))?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Writes the vectors plus their ids and labels as a NumPy .npz archive
+///
+/// The archive holds three arrays:
+/// * `vectors` - the f32 matrix (items × dims)
+/// * `ids` - one unicode string per item, taken from the `id` metadata (empty if unset)
+/// * `labels` - one unicode string per dimension
+///
+/// Load it in Python with `numpy.load(path)` and index by array name.
+///
+/// # Arguments
+/// * `path` - The .npz file to create or truncate
+/// * `vectors` - The vectors, one row each
+///
+/// # Returns
+/// An error before anything is written if the vectors disagree on dimensionality
+/// or labels, or an error if the file cannot be written
+pub fn write_npz<T>(path: impl AsRef<Path>, vectors: &[Vector<T>]) -> Result<(), Error> {
+    let labels: Vec<String> = consistent_labels(vectors)?;
+    let ids: Vec<String> = vectors
+        .iter()
+        .map(|vector| vector.get_id().unwrap_or_default().to_string())
+        .collect();
+
+    let mut archive: ZipWriter<File> = ZipWriter::new(File::create(path)?);
+    let options: SimpleFileOptions = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Stored);
+    for (name, contents) in [
+        ("vectors.npy", matrix_npy(vectors)),
+        ("ids.npy", strings_npy(&ids)),
+        ("labels.npy", strings_npy(&labels)),
+    ] {
+        archive.start_file(name, options)?;
+        archive.write_all(&contents)?;
+    }
+    archive.finish()?;
+
+    Ok(())
+}
+
+/// Encodes the vectors as a complete .npy file
+fn matrix_npy<T>(vectors: &[Vector<T>]) -> Vec<u8> {
+    let dimensionality: usize = vectors.first().map(|vector| vector.get_dimensionality()).unwrap_or(0);
+    let mut bytes: Vec<u8> = npy_header("<f4", &format!("({}, {})", vectors.len(), dimensionality));
+    for vector in vectors {
+        for value in vector.as_slice() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    bytes
+}
+
+/// Encodes strings as a complete .npy file of fixed-width unicode (UTF-32)
+fn strings_npy(strings: &[String]) -> Vec<u8> {
+    let width: usize = strings.iter().map(|string| string.chars().count()).max().unwrap_or(0).max(1);
+    let mut bytes: Vec<u8> = npy_header(&format!("<U{}", width), &format!("({},)", strings.len()));
+    for string in strings {
+        let mut characters: usize = 0;
+        for character in string.chars() {
+            bytes.extend_from_slice(&(character as u32).to_le_bytes());
+            characters += 1;
+        }
+        bytes.resize(bytes.len() + (width - characters) * 4, 0);
+    }
+
+    bytes
+}
+
+/// Builds a version 1.0 .npy header, padded so the payload is 64-byte aligned
+fn npy_header(descr: &str, shape: &str) -> Vec<u8> {
+    let mut dictionary: String = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+        descr, shape
+    );
+    // 6 magic bytes + 2 version bytes + 2 length bytes precede the dictionary
+    let unpadded: usize = 10 + dictionary.len() + 1;
+    dictionary.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+    dictionary.push('\n');
+
+    let mut bytes: Vec<u8> = b"\x93NUMPY\x01\x00".to_vec();
+    bytes.extend_from_slice(&(dictionary.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(dictionary.as_bytes());
+
+    bytes
+}
@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use arrow::array::{Array, ArrayRef, AsArray, FixedSizeListArray, Float32Array, ListBuilder, StringArray, StringBuilder};
+use arrow::datatypes::{DataType as ArrowType, Field, Float32Type, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+
+use crate::collection::similarity::check_comparable;
+use crate::raw_data::VectorData;
+use crate::vector::{Vector, VectorOperations, METADATA_ID, METADATA_SOURCE};
+
+/// How many items go into one row group, and are converted to Arrow at a time
+const ROW_GROUP_SIZE: usize = 65_536;
+
+/// Writes the vectors as a Parquet file, one row per item
+///
+/// The schema is stable, with these columns in order:
+/// * `id` - utf8, nullable: the `id` metadata
+/// * `text` - utf8, nullable: the text of textual vectors, else the `source` metadata, e.g. an image path
+/// * `data_type` - utf8: the `DataType` variant name, e.g. `Text` or `Image`
+/// * `embedding` - fixed_size_list<float32, N>: the values
+/// * `labels` - list<utf8>: the dimension labels, empty when unlabeled
+/// * `metadata` - utf8: all metadata as a JSON object
+/// * `fingerprint` - utf8, nullable: the fingerprint of the prompts and model
+///
+/// Items are converted and written one row group at a time, so no second copy
+/// of the whole collection is held in memory.
+///
+/// # Arguments
+/// * `path` - The .parquet file to create or truncate
+/// * `vectors` - The vectors, one row each
+///
+/// # Returns
+/// An error before anything is written if the vectors are empty or disagree on
+/// dimensionality, or an error if the file cannot be written
+pub fn write_parquet<T: VectorData>(path: impl AsRef<Path>, vectors: &[Vector<T>]) -> Result<(), Error> {
+    let Some(first) = vectors.first() else {
+        return Err(Error::msg("Cannot write a Parquet file for no vectors, as the dimensionality is unknown"));
+    };
+    check_comparable(vectors, true)?;
+
+    let dimensionality: usize = first.get_dimensionality();
+    let schema: SchemaRef = Arc::new(schema(dimensionality));
+    let properties: WriterProperties = WriterProperties::builder()
+        .set_max_row_group_size(ROW_GROUP_SIZE)
+        .set_compression(Compression::SNAPPY)
+        .build();
+
+    let mut writer: ArrowWriter<File> = ArrowWriter::try_new(File::create(path)?, schema.clone(), Some(properties))?;
+    for chunk in vectors.chunks(ROW_GROUP_SIZE) {
+        writer.write(&record_batch(&schema, chunk, dimensionality)?)?;
+    }
+    writer.close()?;
+
+    Ok(())
+}
+
+/// Reads a Parquet file written by `write_parquet`
+///
+/// The `text` column becomes the data of each vector, so images come back as
+/// text vectors holding their source reference.
+///
+/// # Arguments
+/// * `path` - The file to read
+///
+/// # Returns
+/// The vectors in file order, or an error if a column is missing or has another type
+pub fn read_parquet(path: impl AsRef<Path>) -> Result<Vec<Vector<String>>, Error> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?.build()?;
+
+    let mut vectors: Vec<Vector<String>> = Vec::new();
+    for batch in reader {
+        let batch: RecordBatch = batch?;
+        let ids: &StringArray = string_column(&batch, "id")?;
+        let texts: &StringArray = string_column(&batch, "text")?;
+        let metadata: &StringArray = string_column(&batch, "metadata")?;
+        let fingerprints: &StringArray = string_column(&batch, "fingerprint")?;
+        let embeddings: &FixedSizeListArray = column(&batch, "embedding")?
+            .as_fixed_size_list_opt()
+            .ok_or_else(|| Error::msg("Column 'embedding' is not a fixed size list"))?;
+        let labels = column(&batch, "labels")?
+            .as_list_opt::<i32>()
+            .ok_or_else(|| Error::msg("Column 'labels' is not a list"))?;
+
+        for row in 0..batch.num_rows() {
+            let text: &str = if texts.is_null(row) { "" } else { texts.value(row) };
+            let mut vector: Vector<String> = Vector::from_text(text.to_string());
+
+            let values: Vec<f32> = embeddings
+                .value(row)
+                .as_primitive_opt::<Float32Type>()
+                .ok_or_else(|| Error::msg("Column 'embedding' does not hold float32 values"))?
+                .values()
+                .to_vec();
+            let row_labels: Vec<String> = labels
+                .value(row)
+                .as_string_opt::<i32>()
+                .ok_or_else(|| Error::msg("Column 'labels' does not hold strings"))?
+                .iter()
+                .map(|label| label.unwrap_or_default().to_string())
+                .collect();
+            if row_labels.is_empty() {
+                vector.overwrite_vector(values);
+            } else {
+                vector.overwrite_vector_with_labels(values, row_labels)?;
+            }
+
+            let entries: BTreeMap<String, String> = serde_json::from_str(metadata.value(row))?;
+            for (key, value) in entries {
+                vector.set_metadata(key, value);
+            }
+            if !ids.is_null(row) {
+                vector.set_metadata(METADATA_ID, ids.value(row));
+            }
+            if !fingerprints.is_null(row) {
+                vector.set_fingerprint(fingerprints.value(row).to_string());
+            }
+            vectors.push(vector);
+        }
+    }
+
+    Ok(vectors)
+}
+
+/// Returns the schema documented on `write_parquet`.
+fn schema(dimensionality: usize) -> Schema {
+    Schema::new(vec![
+        Field::new("id", ArrowType::Utf8, true),
+        Field::new("text", ArrowType::Utf8, true),
+        Field::new("data_type", ArrowType::Utf8, false),
+        Field::new(
+            "embedding",
+            ArrowType::FixedSizeList(Arc::new(Field::new("item", ArrowType::Float32, false)), dimensionality as i32),
+            false,
+        ),
+        Field::new("labels", ArrowType::List(Arc::new(Field::new("item", ArrowType::Utf8, true))), false),
+        Field::new("metadata", ArrowType::Utf8, false),
+        Field::new("fingerprint", ArrowType::Utf8, true),
+    ])
+}
+
+/// Converts one row group of vectors into Arrow arrays.
+fn record_batch<T: VectorData>(schema: &SchemaRef, vectors: &[Vector<T>], dimensionality: usize) -> Result<RecordBatch, Error> {
+    let ids: StringArray = vectors.iter().map(|vector| vector.get_id()).collect();
+    let texts: StringArray = vectors
+        .iter()
+        .map(|vector| vector.get_data().as_text().or(vector.get_metadata(METADATA_SOURCE)))
+        .collect();
+    let data_types: StringArray = vectors
+        .iter()
+        .map(|vector| Some(format!("{:?}", vector.get_data_type())))
+        .collect();
+
+    let values: Float32Array = Float32Array::from_iter_values(vectors.iter().flat_map(|vector| vector.as_slice().iter().copied()));
+    let embeddings: FixedSizeListArray = FixedSizeListArray::try_new(
+        Arc::new(Field::new("item", ArrowType::Float32, false)),
+        dimensionality as i32,
+        Arc::new(values),
+        None,
+    )?;
+
+    let mut labels: ListBuilder<StringBuilder> = ListBuilder::new(StringBuilder::new());
+    for vector in vectors {
+        for label in vector.get_labels() {
+            labels.values().append_value(label);
+        }
+        labels.append(true);
+    }
+
+    let metadata: StringArray = vectors
+        .iter()
+        .map(|vector| serde_json::to_string(vector.get_metadata_map()).map(Some))
+        .collect::<Result<StringArray, serde_json::Error>>()?;
+    let fingerprints: StringArray = vectors.iter().map(|vector| vector.get_fingerprint()).collect();
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(ids),
+        Arc::new(texts),
+        Arc::new(data_types),
+        Arc::new(embeddings),
+        Arc::new(labels.finish()),
+        Arc::new(metadata),
+        Arc::new(fingerprints),
+    ];
+
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+/// Looks up a column by name.
+fn column<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a ArrayRef, Error> {
+    batch
+        .column_by_name(name)
+        .ok_or_else(|| Error::msg(format!("Parquet file has no '{}' column", name)))
+}
+
+/// Looks up a utf8 column by name.
+fn string_column<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a StringArray, Error> {
+    column(batch, name)?
+        .as_string_opt::<i32>()
+        .ok_or_else(|| Error::msg(format!("Column '{}' is not utf8", name)))
+}
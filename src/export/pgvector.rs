@@ -0,0 +1,263 @@
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Error, Result};
+
+use crate::collection::similarity::check_comparable;
+use crate::raw_data::VectorData;
+use crate::vector::{Vector, VectorOperations, METADATA_SOURCE};
+
+/// How the rows are written
+///
+/// # Variants
+/// * `Inserts` - Batched `INSERT` statements, runnable by any client
+/// * `Copy` - A `COPY ... FROM stdin` block, for bulk loading with `psql -f`
+/// * `CopyData` - Only the rows in COPY text format, for `\copy table FROM 'file'`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgvectorFormat {
+    Inserts,
+    Copy,
+    CopyData,
+}
+
+/// Options of the pgvector SQL export
+///
+/// # Fields
+/// * `format` - How the rows are written, `Inserts` by default
+/// * `create_table` - Whether a `CREATE TABLE IF NOT EXISTS` precedes the rows, true by default
+/// * `batch_size` - The rows per `INSERT` statement, 500 by default
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgvectorOptions {
+    format: PgvectorFormat,
+    create_table: bool,
+    batch_size: usize,
+}
+
+impl Default for PgvectorOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PgvectorOptions {
+    /// Creates options writing batched INSERT statements after the table definition
+    pub fn new() -> Self {
+        Self {
+            format: PgvectorFormat::Inserts,
+            create_table: true,
+            batch_size: 500,
+        }
+    }
+
+    /// Writes the rows in another format
+    pub fn with_format(mut self, format: PgvectorFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Includes or leaves out the table definition, e.g. to append to an existing table
+    ///
+    /// `CopyData` never includes it, as the output is not SQL.
+    pub fn with_create_table(mut self, create_table: bool) -> Self {
+        self.create_table = create_table;
+        self
+    }
+
+    /// Puts `batch_size` rows in each INSERT statement, at least 1
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+}
+
+/// Writes the vectors as SQL for Postgres with the pgvector extension
+///
+/// The table has the columns `id text PRIMARY KEY`, `data_ref text`,
+/// `embedding vector(N)` and `metadata jsonb`. The id is the `id` metadata,
+/// or the position of the vector when unset; `data_ref` is the text of textual
+/// vectors, else their `source` metadata. The table name may be schema-qualified.
+///
+/// # Arguments
+/// * `writer` - Where the SQL goes
+/// * `vectors` - The vectors, one row each
+/// * `table` - The table to create and fill
+/// * `options` - The format and batching
+///
+/// # Returns
+/// An error before anything is written if the vectors disagree on dimensionality,
+/// hold non-finite values or NUL characters, or an error if writing fails
+pub fn write_sql<T: VectorData>(
+    mut writer: impl Write,
+    vectors: &[Vector<T>],
+    table: &str,
+    options: &PgvectorOptions,
+) -> Result<(), Error> {
+    let rows: Vec<Row> = rows(vectors)?;
+    let table: String = quote_identifier(table)?;
+    let columns: &str = "(id, data_ref, embedding, metadata)";
+
+    if options.create_table && options.format != PgvectorFormat::CopyData {
+        let Some(first) = vectors.first() else {
+            return Err(Error::msg("Cannot create a table for no vectors, as the dimensionality is unknown"));
+        };
+        writeln!(writer, "CREATE EXTENSION IF NOT EXISTS vector;")?;
+        writeln!(
+            writer,
+            "CREATE TABLE IF NOT EXISTS {} (id text PRIMARY KEY, data_ref text, embedding vector({}) NOT NULL, metadata jsonb NOT NULL DEFAULT '{{}}');",
+            table,
+            first.get_dimensionality()
+        )?;
+    }
+
+    match options.format {
+        PgvectorFormat::Inserts => {
+            for batch in rows.chunks(options.batch_size) {
+                writeln!(writer, "INSERT INTO {} {} VALUES", table, columns)?;
+                for (index, row) in batch.iter().enumerate() {
+                    writeln!(
+                        writer,
+                        "  ({}, {}, {}::vector, {}::jsonb){}",
+                        quote_literal(&row.id),
+                        row.data_ref.as_deref().map_or("NULL".to_string(), quote_literal),
+                        quote_literal(&row.embedding),
+                        quote_literal(&row.metadata),
+                        if index + 1 == batch.len() { ";" } else { "," }
+                    )?;
+                }
+            }
+        }
+        PgvectorFormat::Copy | PgvectorFormat::CopyData => {
+            let statement: bool = options.format == PgvectorFormat::Copy;
+            if statement {
+                writeln!(writer, "COPY {} {} FROM stdin;", table, columns)?;
+            }
+            for row in &rows {
+                writeln!(
+                    writer,
+                    "{}\t{}\t{}\t{}",
+                    copy_field(&row.id),
+                    row.data_ref.as_deref().map_or("\\N".to_string(), copy_field),
+                    row.embedding,
+                    copy_field(&row.metadata)
+                )?;
+            }
+            if statement {
+                writeln!(writer, "\\.")?;
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Writes the vectors as SQL for pgvector to a file
+///
+/// # Arguments
+/// * `path` - The file to create or truncate
+/// * `vectors` - The vectors, one row each
+/// * `table` - The table to create and fill
+/// * `options` - The format and batching
+///
+/// # Returns
+/// The same errors as `write_sql`, before the file is created, or an error if it cannot be written
+pub fn save_sql<T: VectorData>(
+    path: impl AsRef<Path>,
+    vectors: &[Vector<T>],
+    table: &str,
+    options: &PgvectorOptions,
+) -> Result<(), Error> {
+    let mut sql: Vec<u8> = Vec::new();
+    write_sql(&mut sql, vectors, table, options)?;
+    std::fs::write(path, sql)?;
+
+    Ok(())
+}
+
+/// Formats values as a pgvector literal, e.g. `[1,-0.5,3]`
+///
+/// # Arguments
+/// * `values` - The values, all finite
+///
+/// # Returns
+/// The literal, or an error if a value is NaN or infinite, which pgvector rejects
+pub fn vector_literal(values: &[f32]) -> Result<String, Error> {
+    if let Some(position) = values.iter().position(|value| !value.is_finite()) {
+        return Err(Error::msg(format!("pgvector does not accept {} at dimension {}", values[position], position)));
+    }
+
+    Ok(format!(
+        "[{}]",
+        values.iter().map(|value| value.to_string()).collect::<Vec<String>>().join(",")
+    ))
+}
+
+/// The column values of one vector, unescaped
+struct Row {
+    id: String,
+    data_ref: Option<String>,
+    embedding: String,
+    metadata: String,
+}
+
+/// Validates the vectors and converts them into rows.
+fn rows<T: VectorData>(vectors: &[Vector<T>]) -> Result<Vec<Row>, Error> {
+    check_comparable(vectors, true)?;
+
+    vectors
+        .iter()
+        .enumerate()
+        .map(|(index, vector)| {
+            let row: Row = Row {
+                id: vector.get_id().map_or(index.to_string(), str::to_string),
+                data_ref: vector
+                    .get_data()
+                    .as_text()
+                    .or(vector.get_metadata(METADATA_SOURCE))
+                    .map(str::to_string),
+                embedding: vector_literal(vector.as_slice())
+                    .map_err(|e| Error::msg(format!("Vector {}: {}", index, e)))?,
+                metadata: serde_json::to_string(vector.get_metadata_map())?,
+            };
+            if row.id.contains('\0') || row.data_ref.as_deref().is_some_and(|text| text.contains('\0')) || row.metadata.contains("\\u0000") {
+                return Err(Error::msg(format!("Vector {} contains a NUL character, which Postgres text cannot hold", index)));
+            }
+
+            Ok(row)
+        })
+        .collect()
+}
+
+/// Quotes a possibly schema-qualified table name.
+fn quote_identifier(name: &str) -> Result<String, Error> {
+    if name.is_empty() || name.split('.').any(str::is_empty) || name.contains('\0') {
+        return Err(Error::msg(format!("Invalid table name {:?}", name)));
+    }
+
+    Ok(name
+        .split('.')
+        .map(|part| format!("\"{}\"", part.replace('"', "\"\"")))
+        .collect::<Vec<String>>()
+        .join("."))
+}
+
+/// Quotes a string literal, assuming `standard_conforming_strings`, the default since Postgres 9.1.
+fn quote_literal(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// Escapes a field of the COPY text format.
+fn copy_field(text: &str) -> String {
+    let mut escaped: String = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(character),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,130 @@
+use std::collections::BTreeSet;
+
+use anyhow::{Error, Result};
+use polars::prelude::{Column, DataFrame, DataType};
+
+use crate::raw_data::VectorData;
+use crate::vector::io::consistent_labels;
+use crate::vector::{Vector, VectorOperations, METADATA_ID, METADATA_SOURCE};
+
+/// The column holding the `id` metadata of each item
+const ID_COLUMN: &str = "id";
+/// The column holding the text of each item
+const TEXT_COLUMN: &str = "text";
+
+/// Converts vectors to a DataFrame with one row per item and one column per dimension
+///
+/// The columns are, in order:
+/// * `id` - str, nullable: the `id` metadata
+/// * `text` - str, nullable: the text of textual vectors, else the `source` metadata, e.g. an image path
+/// * one f32 column per dimension, named by its label in label order, or `dim_0..dim_n` when unlabeled
+///
+/// # Arguments
+/// * `vectors` - The vectors, one row each
+///
+/// # Returns
+/// The DataFrame, or an error if the vectors disagree on dimensionality or
+/// labels, or a label is repeated or named like the `id` or `text` column
+pub fn to_dataframe<T: VectorData>(vectors: &[Vector<T>]) -> Result<DataFrame, Error> {
+    let labels: Vec<String> = consistent_labels(vectors)?;
+    if let Some(label) = labels.iter().find(|label| *label == ID_COLUMN || *label == TEXT_COLUMN) {
+        return Err(Error::msg(format!("Dimension label '{}' collides with the '{}' column", label, label)));
+    }
+
+    let ids: Vec<Option<&str>> = vectors.iter().map(|vector| vector.get_id()).collect();
+    let texts: Vec<Option<&str>> = vectors
+        .iter()
+        .map(|vector| vector.get_data().as_text().or(vector.get_metadata(METADATA_SOURCE)))
+        .collect();
+
+    let mut columns: Vec<Column> = vec![Column::new(ID_COLUMN.into(), ids), Column::new(TEXT_COLUMN.into(), texts)];
+    for (index, label) in labels.iter().enumerate() {
+        let values: Vec<f32> = vectors.iter().map(|vector| vector.as_slice()[index]).collect();
+        columns.push(Column::new(label.as_str().into(), values));
+    }
+
+    DataFrame::new(columns).map_err(|e| Error::msg(format!("Failed to build the DataFrame: {}", e)))
+}
+
+/// Converts the rows of a DataFrame, e.g. annotated data, to text vectors
+///
+/// The dimension columns become the labels, in the given order, and any
+/// numeric column is accepted. An `id` column, when present and not used
+/// otherwise, is kept as the `id` metadata.
+///
+/// # Arguments
+/// * `df` - The DataFrame, one item per row
+/// * `text_col` - The column holding the text of each item
+/// * `dim_cols` - The columns holding the dimensions, in order
+///
+/// # Returns
+/// The vectors in row order, or an error if a column is missing, repeated,
+/// not numeric, or holds a missing value
+pub fn from_dataframe(df: &DataFrame, text_col: &str, dim_cols: &[&str]) -> Result<Vec<Vector<String>>, Error> {
+    if dim_cols.is_empty() {
+        return Err(Error::msg("Cannot read vectors from no dimension columns"));
+    }
+    let mut seen: BTreeSet<&str> = BTreeSet::new();
+    if let Some(column) = dim_cols.iter().find(|column| !seen.insert(column)) {
+        return Err(Error::msg(format!("Duplicate dimension column '{}'", column)));
+    }
+
+    let texts: Vec<Option<String>> = string_column(df, text_col)?;
+    let ids: Option<Vec<Option<String>>> = match df.get_column_names().iter().any(|name| name.as_str() == ID_COLUMN)
+        && text_col != ID_COLUMN
+        && !dim_cols.contains(&ID_COLUMN)
+    {
+        true => Some(string_column(df, ID_COLUMN)?),
+        false => None,
+    };
+    let dimensions: Vec<Vec<f32>> = dim_cols
+        .iter()
+        .map(|name| float_column(df, name))
+        .collect::<Result<_, Error>>()?;
+    let labels: Vec<String> = dim_cols.iter().map(|name| name.to_string()).collect();
+
+    let mut vectors: Vec<Vector<String>> = Vec::with_capacity(df.height());
+    for (row, text) in texts.into_iter().enumerate() {
+        let mut vector: Vector<String> = Vector::from_text(text.unwrap_or_default());
+        vector.overwrite_vector_with_labels(dimensions.iter().map(|values| values[row]).collect(), labels.clone())?;
+        if let Some(id) = ids.as_ref().and_then(|ids| ids[row].as_ref()) {
+            vector.set_metadata(METADATA_ID, id);
+        }
+        vectors.push(vector);
+    }
+
+    Ok(vectors)
+}
+
+/// Looks up a column by name.
+fn column<'a>(df: &'a DataFrame, name: &str) -> Result<&'a Column, Error> {
+    df.column(name)
+        .map_err(|_| Error::msg(format!("DataFrame has no '{}' column", name)))
+}
+
+/// Reads a str column, keeping its missing values.
+fn string_column(df: &DataFrame, name: &str) -> Result<Vec<Option<String>>, Error> {
+    let values = column(df, name)?
+        .str()
+        .map_err(|_| Error::msg(format!("Column '{}' is not str", name)))?;
+
+    Ok(values.into_iter().map(|value| value.map(str::to_string)).collect())
+}
+
+/// Reads a numeric column as f32, refusing missing values.
+fn float_column(df: &DataFrame, name: &str) -> Result<Vec<f32>, Error> {
+    let column: &Column = column(df, name)?;
+    if !column.dtype().is_primitive_numeric() {
+        return Err(Error::msg(format!("Column '{}' is {}, not numeric", name, column.dtype())));
+    }
+    let cast: Column = column
+        .cast(&DataType::Float32)
+        .map_err(|e| Error::msg(format!("Failed to read column '{}' as f32: {}", name, e)))?;
+    let values = cast.f32().map_err(|e| Error::msg(format!("Failed to read column '{}' as f32: {}", name, e)))?;
+
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(row, value)| value.ok_or_else(|| Error::msg(format!("Column '{}' has a missing value in row {}", name, row))))
+        .collect()
+}
@@ -0,0 +1,269 @@
+use std::time::Duration;
+
+use anyhow::{Error, Result};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::collection::similarity::check_comparable;
+use crate::raw_data::VectorData;
+use crate::runtime::sleep;
+use crate::vector::{Vector, VectorOperations, METADATA_ID};
+
+/// The payload field holding the fingerprint of each point's vector
+const FINGERPRINT_FIELD: &str = "fingerprint";
+
+/// Where and how points are written to Qdrant
+///
+/// # Fields
+/// * `url` - The REST endpoint, e.g. `http://localhost:6333`
+/// * `api_key` - Sent as the `api-key` header when set
+/// * `batch_size` - The points sent per request, 64 by default
+/// * `max_retries` - How often a request failing with a transient error is retried, 3 by default
+/// * `retry_delay` - The wait before the first retry, doubled for every further one, 500 ms by default
+/// * `timeout` - The time allowed for each request, 30 seconds by default
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QdrantConfig {
+    url: String,
+    api_key: Option<String>,
+    batch_size: usize,
+    max_retries: usize,
+    retry_delay: Duration,
+    timeout: Duration,
+}
+
+impl QdrantConfig {
+    /// Creates a configuration for a Qdrant REST endpoint with the default limits
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            api_key: None,
+            batch_size: 64,
+            max_retries: 3,
+            retry_delay: Duration::from_millis(500),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Authenticates with an API key
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Sends `batch_size` points per request, at least 1
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Retries requests failing with connection errors, 429 or 5xx up to `max_retries` times
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Waits `retry_delay` before the first retry, doubling it for every further one
+    pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Bounds every request, from connecting to reading the response
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Upserts vectors into a Qdrant collection
+///
+/// The collection is created with the vectors' dimensionality and cosine
+/// distance if it does not exist. An existing collection must have the same
+/// dimensionality, and its points the same fingerprint; both are checked
+/// before any point is written. Metadata entries become payload fields, next
+/// to the `fingerprint`. Point ids come from `METADATA_ID`: unsigned integers
+/// and UUIDs are used as they are, other ids are mapped to a UUID derived
+/// from them, so upserting again replaces the point. Vectors without an id
+/// get a random UUID.
+///
+/// # Arguments
+/// * `collection_name` - The collection to write to
+/// * `vectors` - The vectors, all of the same dimensionality and fingerprint
+/// * `config` - The endpoint, batching and retries
+///
+/// # Returns
+/// The number of points written, or an error naming what did not match or which batch failed
+pub async fn upsert<T: VectorData>(collection_name: &str, vectors: &[Vector<T>], config: &QdrantConfig) -> Result<usize, Error> {
+    let Some(first) = vectors.first() else {
+        return Ok(0);
+    };
+    check_comparable(vectors, false)?;
+
+    let client: reqwest::Client = reqwest::Client::builder()
+        .timeout(config.timeout)
+        .build()
+        .map_err(|e| Error::msg(format!("Failed to build HTTP client: {}", e)))?;
+    let collection_url: String = format!("{}/collections/{}", config.url, collection_name);
+    let dimensionality: usize = first.get_dimensionality();
+
+    match send(&client, config, reqwest::Method::GET, &collection_url, None).await? {
+        None => {
+            let body: Value = json!({"vectors": {"size": dimensionality, "distance": "Cosine"}});
+            send(&client, config, reqwest::Method::PUT, &collection_url, Some(&body))
+                .await?
+                .ok_or_else(|| Error::msg(format!("Failed to create Qdrant collection '{}'", collection_name)))?;
+        }
+        Some(collection) => {
+            let size: Option<u64> = collection["result"]["config"]["params"]["vectors"]["size"].as_u64();
+            if size != Some(dimensionality as u64) {
+                return Err(Error::msg(format!(
+                    "Dimensionality mismatch: Qdrant collection '{}' holds vectors of size {}, the vectors have {}",
+                    collection_name,
+                    size.map_or("unknown".to_string(), |size| size.to_string()),
+                    dimensionality
+                )));
+            }
+            check_collection_fingerprint(&client, config, &collection_url, collection_name, first.get_fingerprint()).await?;
+        }
+    }
+
+    let points_url: String = format!("{}/points?wait=true", collection_url);
+    for (batch, chunk) in vectors.chunks(config.batch_size).enumerate() {
+        let points: Vec<Value> = chunk.iter().map(point).collect();
+        send(&client, config, reqwest::Method::PUT, &points_url, Some(&json!({"points": points})))
+            .await
+            .map_err(|e| Error::msg(format!("Failed to upsert batch {} of {} points: {}", batch, chunk.len(), e)))?;
+    }
+
+    Ok(vectors.len())
+}
+
+/// Rejects a collection whose points were produced by other prompts or another model.
+async fn check_collection_fingerprint(
+    client: &reqwest::Client,
+    config: &QdrantConfig,
+    collection_url: &str,
+    collection_name: &str,
+    fingerprint: Option<&str>,
+) -> Result<(), Error> {
+    let body: Value = json!({"limit": 1, "with_payload": [FINGERPRINT_FIELD], "with_vector": false});
+    let response: Value = send(client, config, reqwest::Method::POST, &format!("{}/points/scroll", collection_url), Some(&body))
+        .await?
+        .unwrap_or_default();
+    let Some(existing) = response["result"]["points"].get(0) else {
+        return Ok(());
+    };
+
+    let existing: Option<&str> = existing["payload"][FINGERPRINT_FIELD].as_str();
+    if existing != fingerprint {
+        return Err(Error::msg(format!(
+            "Fingerprint mismatch: Qdrant collection '{}' holds vectors with {:?}, the vectors have {:?}",
+            collection_name, existing, fingerprint
+        )));
+    }
+
+    Ok(())
+}
+
+/// Converts a vector into a Qdrant point.
+fn point<T>(vector: &Vector<T>) -> Value {
+    let mut payload: Map<String, Value> = vector
+        .get_metadata_map()
+        .iter()
+        .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+        .collect();
+    if let Some(fingerprint) = vector.get_fingerprint() {
+        payload.insert(FINGERPRINT_FIELD.to_string(), Value::String(fingerprint.to_string()));
+    }
+
+    json!({
+        "id": point_id(vector.get_metadata(METADATA_ID)),
+        "vector": vector.get_vector(),
+        "payload": payload,
+    })
+}
+
+/// Returns the Qdrant id of an item: its own id when Qdrant accepts it, else a UUID.
+fn point_id(id: Option<&str>) -> Value {
+    let bytes: [u8; 16] = match id {
+        Some(id) if id.parse::<u64>().is_ok() => return json!(id.parse::<u64>().unwrap_or_default()),
+        Some(id) if is_uuid(id) => return Value::String(id.to_lowercase()),
+        Some(id) => {
+            let digest = Sha256::digest(id.as_bytes());
+            let mut bytes: [u8; 16] = [0; 16];
+            bytes.copy_from_slice(&digest[..16]);
+            bytes
+        }
+        None => rand::random::<[u8; 16]>(),
+    };
+
+    Value::String(format_uuid(bytes))
+}
+
+/// Returns whether a string is a hyphenated UUID.
+fn is_uuid(id: &str) -> bool {
+    let groups: Vec<&str> = id.split('-').collect();
+    groups.len() == 5
+        && groups.iter().zip([8, 4, 4, 4, 12]).all(|(group, length)| {
+            group.len() == length && group.chars().all(|character| character.is_ascii_hexdigit())
+        })
+}
+
+/// Formats 16 bytes as a version 4 style UUID.
+fn format_uuid(mut bytes: [u8; 16]) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = hex::encode(bytes);
+
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/// Sends a request, retrying transient failures.
+///
+/// Returns the response body, or None when the resource does not exist.
+async fn send(
+    client: &reqwest::Client,
+    config: &QdrantConfig,
+    method: reqwest::Method,
+    url: &str,
+    body: Option<&Value>,
+) -> Result<Option<Value>, Error> {
+    let mut attempt: usize = 0;
+    loop {
+        let mut request: reqwest::RequestBuilder = client.request(method.clone(), url);
+        if let Some(api_key) = &config.api_key {
+            request = request.header("api-key", api_key);
+        }
+        if let Some(body) = body {
+            request = request
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.to_string());
+        }
+
+        let error: Error = match request.send().await {
+            Ok(response) if response.status() == reqwest::StatusCode::NOT_FOUND => return Ok(None),
+            Ok(response) if response.status().is_success() => {
+                let text: String = response.text().await?;
+                return Ok(Some(serde_json::from_str(&text).unwrap_or_default()));
+            }
+            Ok(response) => {
+                let status: reqwest::StatusCode = response.status();
+                let text: String = response.text().await.unwrap_or_default();
+                let error: Error = Error::msg(format!("Qdrant answered {} {} with {}: {}", method, url, status, text));
+                if status != reqwest::StatusCode::TOO_MANY_REQUESTS && !status.is_server_error() {
+                    return Err(error);
+                }
+                error
+            }
+            Err(e) if e.is_connect() || e.is_timeout() => Error::msg(format!("Failed to reach Qdrant at {}: {}", url, e)),
+            Err(e) => return Err(Error::msg(format!("Failed to reach Qdrant at {}: {}", url, e))),
+        };
+
+        if attempt >= config.max_retries {
+            return Err(error);
+        }
+        log::warn!("Retrying Qdrant request: {}", error);
+        sleep(config.retry_delay * 2u32.saturating_pow(attempt as u32)).await;
+        attempt += 1;
+    }
+}
@@ -1,6 +1,22 @@
+pub mod collection;
+pub mod error;
+pub mod export;
+pub mod llm;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod pipeline;
 pub mod prelude;
 pub mod vector;
 pub mod vectorization;
 pub mod prompt;
+pub mod raw_data;
+pub mod runtime;
+pub mod telemetry;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "server")]
+pub mod server;
+
+#[cfg(all(target_arch = "wasm32", not(feature = "wasm")))]
+compile_error!("Building dim-rs for wasm32 needs the `wasm` feature, e.g. `--no-default-features --features wasm`");
 
 pub use crate::prelude::*;
\ No newline at end of file
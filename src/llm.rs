@@ -0,0 +1,491 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Error, Result};
+use async_openai::{config::{AzureConfig, Config, OpenAIConfig}, error::OpenAIError, types::{ChatCompletionRequestUserMessageArgs, CreateChatCompletionRequest, CreateChatCompletionRequestArgs, CreateChatCompletionResponse, CreateEmbeddingRequest, CreateEmbeddingResponse, CreateTranscriptionRequest, Model}, Client};
+use serde::{Deserialize, Serialize};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::error::DimError;
+#[cfg(target_arch = "wasm32")]
+use crate::runtime::run_local;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod failover;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+/// The environment variable holding the OpenAI API key, unless another one is named
+pub const DEFAULT_API_KEY_ENV: &str = "OPENAI_API_KEY";
+/// The environment variable holding the API base URL, e.g. `http://localhost:11434/v1` for Ollama
+pub const API_BASE_ENV: &str = "OPENAI_API_BASE";
+/// The environment variable holding the Azure OpenAI API key, unless another one is named
+pub const AZURE_API_KEY_ENV: &str = "AZURE_OPENAI_API_KEY";
+/// The environment variable holding the Azure resource endpoint, e.g. `https://my-resource.openai.azure.com`
+pub const AZURE_ENDPOINT_ENV: &str = "AZURE_OPENAI_ENDPOINT";
+/// The environment variable holding the Azure deployment name
+pub const AZURE_DEPLOYMENT_ENV: &str = "AZURE_OPENAI_DEPLOYMENT";
+/// The environment variable holding the Azure API version
+pub const AZURE_API_VERSION_ENV: &str = "AZURE_OPENAI_API_VERSION";
+/// The Azure API version used when `AZURE_OPENAI_API_VERSION` is not set
+pub const DEFAULT_AZURE_API_VERSION: &str = "2024-10-21";
+/// The key sent to local servers that do not check it
+const UNAUTHENTICATED_API_KEY: &str = "unauthenticated";
+
+/// A client for OpenAI and compatible servers such as Ollama
+pub type OpenAIClient = Client<OpenAIConfig>;
+
+/// Anything that can answer chat completion requests
+///
+/// Implemented for `Client<C>` of every provider, so the `vectorize_*`
+/// functions accept plain clients as well as wrappers such as `FailoverClient`.
+/// Wrap a backend in an `Arc` to keep access to it after a call.
+pub trait ChatBackend: Send + Sync + 'static {
+    /// Sends one chat completion request
+    fn create_chat(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> impl Future<Output = Result<CreateChatCompletionResponse, OpenAIError>> + Send;
+
+    /// Lists the models the endpoint serves
+    ///
+    /// Backends without a models listing keep the default, which reports it as unsupported.
+    fn list_models(&self) -> impl Future<Output = Result<Vec<Model>, OpenAIError>> + Send {
+        async { Err(OpenAIError::InvalidArgument("Listing models is not supported".to_string())) }
+    }
+
+    /// Describes the endpoint for error messages, e.g. its API base
+    fn describe_endpoint(&self) -> String {
+        "the endpoint".to_string()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<C: Config + Send + Sync + 'static> ChatBackend for Client<C> {
+    async fn create_chat(&self, request: CreateChatCompletionRequest) -> Result<CreateChatCompletionResponse, OpenAIError> {
+        self.chat().create(request).await
+    }
+
+    async fn list_models(&self) -> Result<Vec<Model>, OpenAIError> {
+        Ok(self.models().list().await?.data)
+    }
+
+    fn describe_endpoint(&self) -> String {
+        self.config().api_base().to_string()
+    }
+}
+
+/// On wasm32 the client sends its requests with `fetch`, whose futures are not
+/// `Send`, so they run on the JavaScript event loop and only their results come back.
+#[cfg(target_arch = "wasm32")]
+impl<C: Config + Clone + Send + Sync + 'static> ChatBackend for Client<C> {
+    fn create_chat(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> impl Future<Output = Result<CreateChatCompletionResponse, OpenAIError>> + Send {
+        let client: Client<C> = self.clone();
+        run_local(async move { client.chat().create(request).await })
+    }
+
+    fn list_models(&self) -> impl Future<Output = Result<Vec<Model>, OpenAIError>> + Send {
+        let client: Client<C> = self.clone();
+        run_local(async move { Ok::<Vec<Model>, OpenAIError>(client.models().list().await?.data) })
+    }
+
+    fn describe_endpoint(&self) -> String {
+        self.config().api_base().to_string()
+    }
+}
+
+impl<B: ChatBackend> ChatBackend for Arc<B> {
+    fn create_chat(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> impl Future<Output = Result<CreateChatCompletionResponse, OpenAIError>> + Send {
+        self.as_ref().create_chat(request)
+    }
+
+    fn list_models(&self) -> impl Future<Output = Result<Vec<Model>, OpenAIError>> + Send {
+        self.as_ref().list_models()
+    }
+
+    fn describe_endpoint(&self) -> String {
+        self.as_ref().describe_endpoint()
+    }
+}
+
+/// Anything that can transcribe audio, like the `/audio/transcriptions` endpoint
+///
+/// Needed next to `ChatBackend` by `vectorize_audio_concurrently`.
+pub trait TranscriptionBackend: Send + Sync + 'static {
+    /// Transcribes one audio file
+    ///
+    /// # Returns
+    /// The transcript as plain text
+    fn transcribe(
+        &self,
+        request: CreateTranscriptionRequest,
+    ) -> impl Future<Output = Result<String, OpenAIError>> + Send;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<C: Config + Send + Sync + 'static> TranscriptionBackend for Client<C> {
+    async fn transcribe(&self, request: CreateTranscriptionRequest) -> Result<String, OpenAIError> {
+        Ok(self.audio().transcribe(request).await?.text)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl<C: Config + Clone + Send + Sync + 'static> TranscriptionBackend for Client<C> {
+    fn transcribe(&self, request: CreateTranscriptionRequest) -> impl Future<Output = Result<String, OpenAIError>> + Send {
+        let client: Client<C> = self.clone();
+        run_local(async move { Ok::<String, OpenAIError>(client.audio().transcribe(request).await?.text) })
+    }
+}
+
+impl<B: TranscriptionBackend> TranscriptionBackend for Arc<B> {
+    fn transcribe(
+        &self,
+        request: CreateTranscriptionRequest,
+    ) -> impl Future<Output = Result<String, OpenAIError>> + Send {
+        self.as_ref().transcribe(request)
+    }
+}
+
+/// Anything that can embed text, like the `/embeddings` endpoint
+///
+/// Needed next to `ChatBackend` by `vectorize_string_hybrid`.
+pub trait EmbeddingBackend: Send + Sync + 'static {
+    /// Sends one embeddings request
+    fn create_embeddings(
+        &self,
+        request: CreateEmbeddingRequest,
+    ) -> impl Future<Output = Result<CreateEmbeddingResponse, OpenAIError>> + Send;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<C: Config + Send + Sync + 'static> EmbeddingBackend for Client<C> {
+    async fn create_embeddings(&self, request: CreateEmbeddingRequest) -> Result<CreateEmbeddingResponse, OpenAIError> {
+        self.embeddings().create(request).await
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl<C: Config + Clone + Send + Sync + 'static> EmbeddingBackend for Client<C> {
+    fn create_embeddings(
+        &self,
+        request: CreateEmbeddingRequest,
+    ) -> impl Future<Output = Result<CreateEmbeddingResponse, OpenAIError>> + Send {
+        let client: Client<C> = self.clone();
+        run_local(async move { client.embeddings().create(request).await })
+    }
+}
+
+impl<B: EmbeddingBackend> EmbeddingBackend for Arc<B> {
+    fn create_embeddings(
+        &self,
+        request: CreateEmbeddingRequest,
+    ) -> impl Future<Output = Result<CreateEmbeddingResponse, OpenAIError>> + Send {
+        self.as_ref().create_embeddings(request)
+    }
+}
+
+/// A model confirmed to be served by an endpoint
+///
+/// # Fields
+/// * `id` - The model name
+/// * `owned_by` - The owner reported by the models listing, if any
+/// * `listed` - Whether the model was found in the listing, rather than confirmed by a test completion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub id: String,
+    pub owned_by: Option<String>,
+    pub listed: bool,
+}
+
+/// Checks that an endpoint serves a model before a batch starts
+///
+/// The models listing is consulted first. Servers that do not implement it are
+/// sent a one-token completion instead. A misspelled model name then fails
+/// once, with the available models, instead of on every request.
+///
+/// # Arguments
+/// * `client` - The client or other `ChatBackend` to check
+/// * `model` - The model name, e.g. "gpt-4o-mini"
+///
+/// # Returns
+/// The model's details, or `DimError::ModelNotFound` naming the endpoint and
+/// the models it serves. A test completion rejected for its credentials or
+/// timing out is reported as such instead.
+pub async fn verify_model<B: ChatBackend>(client: &B, model: &str) -> Result<ModelInfo, DimError> {
+    match client.list_models().await {
+        Ok(models) => {
+            if let Some(found) = models.iter().find(|candidate| candidate.id == model) {
+                return Ok(ModelInfo {
+                    id: found.id.clone(),
+                    owned_by: Some(found.owned_by.clone()).filter(|owner| !owner.is_empty()),
+                    listed: true,
+                });
+            }
+
+            let available: Vec<&str> = models.iter().map(|candidate| candidate.id.as_str()).collect();
+            Err(DimError::ModelNotFound {
+                model: model.to_string(),
+                message: format!(
+                    "model '{}' not found on {}; available: [{}]",
+                    model,
+                    client.describe_endpoint(),
+                    available.join(", ")
+                ),
+            })
+        }
+        Err(error) => {
+            log::info!("Listing models failed ({}), sending a test completion instead", error);
+            let request: CreateChatCompletionRequest = test_completion_request(model)
+                .map_err(|e| DimError::msg(format!("Failed to build test completion: {}", e)))?;
+            match client.create_chat(request).await {
+                Ok(_) => Ok(ModelInfo {
+                    id: model.to_string(),
+                    owned_by: None,
+                    listed: false,
+                }),
+                Err(error) => {
+                    let message: String = format!(
+                        "model '{}' not found on {}; the test completion failed: {}",
+                        model,
+                        client.describe_endpoint(),
+                        error
+                    );
+                    match DimError::from(error) {
+                        error @ (DimError::Unauthorized { .. } | DimError::Timeout { .. }) => Err(error),
+                        _ => Err(DimError::ModelNotFound { model: model.to_string(), message }),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Builds the smallest possible completion request for a model
+fn test_completion_request(model: &str) -> Result<CreateChatCompletionRequest, OpenAIError> {
+    let mut args: CreateChatCompletionRequestArgs = CreateChatCompletionRequestArgs::default();
+    args.model(model)
+        .messages(vec![ChatCompletionRequestUserMessageArgs::default().content("ping").build()?.into()]);
+    // `max_completion_tokens` is not understood by most local servers yet
+    #[allow(deprecated)]
+    args.max_tokens(1u32);
+
+    args.build()
+}
+
+/// Reads an environment variable, treating empty values as absent
+fn read_env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.trim().is_empty())
+}
+
+/// Reads an environment variable that must be set
+fn require_env(name: &str) -> Result<String, Error> {
+    read_env(name).ok_or_else(|| Error::msg(format!("{} is not set", name)))
+}
+
+/// Reads the API key, falling back to a placeholder only when allowed
+fn read_api_key(api_key_env: &str, allow_unauthenticated: bool) -> Result<String, Error> {
+    match read_env(api_key_env) {
+        Some(api_key) => Ok(api_key),
+        None if allow_unauthenticated => Ok(UNAUTHENTICATED_API_KEY.to_string()),
+        None => Err(Error::msg(format!(
+            "No API key found in {}; set it, or allow unauthenticated access for local servers",
+            api_key_env
+        ))),
+    }
+}
+
+/// Client configurations that can be built from environment variables
+pub trait EnvConfig: Config + Sized {
+    /// The environment variable holding the API key when none is named
+    const DEFAULT_API_KEY_ENV: &'static str;
+
+    /// Builds the configuration from the environment
+    ///
+    /// # Arguments
+    /// * `api_key_env` - The environment variable holding the API key
+    /// * `allow_unauthenticated` - Use a placeholder key when none is set
+    ///
+    /// # Returns
+    /// The configuration, or an error naming the missing variable
+    fn from_env(api_key_env: &str, allow_unauthenticated: bool) -> Result<Self, Error>;
+}
+
+impl EnvConfig for OpenAIConfig {
+    const DEFAULT_API_KEY_ENV: &'static str = DEFAULT_API_KEY_ENV;
+
+    /// Reads the key and, if set, `OPENAI_API_BASE`; the base defaults to OpenAI
+    fn from_env(api_key_env: &str, allow_unauthenticated: bool) -> Result<Self, Error> {
+        let mut config: OpenAIConfig = OpenAIConfig::new().with_api_key(read_api_key(api_key_env, allow_unauthenticated)?);
+        if let Some(api_base) = read_env(API_BASE_ENV) {
+            log::info!("Using API base {}", api_base);
+            config = config.with_api_base(api_base);
+        }
+
+        Ok(config)
+    }
+}
+
+impl EnvConfig for AzureConfig {
+    const DEFAULT_API_KEY_ENV: &'static str = AZURE_API_KEY_ENV;
+
+    /// Reads the key, `AZURE_OPENAI_ENDPOINT`, `AZURE_OPENAI_DEPLOYMENT` and,
+    /// if set, `AZURE_OPENAI_API_VERSION`
+    fn from_env(api_key_env: &str, allow_unauthenticated: bool) -> Result<Self, Error> {
+        let endpoint: String = require_env(AZURE_ENDPOINT_ENV)?;
+        let deployment: String = require_env(AZURE_DEPLOYMENT_ENV)?;
+        let api_version: String = read_env(AZURE_API_VERSION_ENV).unwrap_or_else(|| DEFAULT_AZURE_API_VERSION.to_string());
+        log::info!("Using Azure endpoint {} with deployment {}", endpoint, deployment);
+
+        Ok(AzureConfig::new()
+            .with_api_base(endpoint)
+            .with_deployment_id(deployment)
+            .with_api_version(api_version)
+            .with_api_key(read_api_key(api_key_env, allow_unauthenticated)?))
+    }
+}
+
+/// Creates a client configured from the environment
+///
+/// The configuration type picks the provider: `OpenAIConfig` for OpenAI and
+/// compatible servers such as Ollama, `AzureConfig` for Azure OpenAI. The
+/// returned client can be passed to any `vectorize_*` function.
+///
+/// # Arguments
+/// * `api_key_env` - The environment variable holding the API key, the provider's default when `None`
+/// * `allow_unauthenticated` - Send a placeholder key when none is set, for local servers like Ollama or LM Studio
+///
+/// # Returns
+/// The client, or an error if a required variable is missing
+pub fn instantiate_client<C: EnvConfig>(api_key_env: Option<&str>, allow_unauthenticated: bool) -> Result<Client<C>, Error> {
+    let config: C = C::from_env(api_key_env.unwrap_or(C::DEFAULT_API_KEY_ENV), allow_unauthenticated)?;
+
+    Ok(Client::with_config(config))
+}
+
+/// Creates an Azure OpenAI client from the `AZURE_OPENAI_*` environment variables
+///
+/// # Returns
+/// The client, or an error if the endpoint, deployment or key is missing
+pub fn instantiate_azure_client() -> Result<Client<AzureConfig>, Error> {
+    instantiate_client::<AzureConfig>(None, false)
+}
+
+/// Settings of the HTTP client underneath the LLM client
+///
+/// The request timeout bounds each HTTP attempt. The LLM client retries rate
+/// limited and failed requests with exponential backoff, so a whole call can
+/// take longer than the request timeout; bound the call itself (e.g. with
+/// `tokio::time::timeout`) when an overall deadline is needed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HttpOptions {
+    request_timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    proxy: Option<String>,
+    headers: BTreeMap<String, String>,
+}
+
+impl HttpOptions {
+    /// Creates options that keep the HTTP client defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bounds each HTTP attempt, from connecting to reading the full response
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Bounds establishing the connection
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Routes HTTP and HTTPS traffic through a proxy, e.g. `http://proxy.internal:3128`
+    pub fn with_proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Sends a header with every request, e.g. a gateway token
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// Builds the HTTP client
+    ///
+    /// On wasm32 requests go through `fetch`, which has no timeouts or
+    /// proxies, so only the headers can be set there.
+    ///
+    /// # Returns
+    /// The client, or an error for an invalid proxy URL, header name or header value
+    pub fn build_http_client(&self) -> Result<reqwest::Client, Error> {
+        let mut headers: HeaderMap = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let header_name: HeaderName = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| Error::msg(format!("Invalid header name {:?}: {}", name, e)))?;
+            let header_value: HeaderValue = HeaderValue::from_str(value)
+                .map_err(|e| Error::msg(format!("Invalid value for header {:?}: {}", name, e)))?;
+            headers.insert(header_name, header_value);
+        }
+
+        let builder: reqwest::ClientBuilder = reqwest::Client::builder().default_headers(headers);
+        #[cfg(not(target_arch = "wasm32"))]
+        let builder: reqwest::ClientBuilder = self.configure_connection(builder)?;
+        #[cfg(target_arch = "wasm32")]
+        if self.request_timeout.is_some() || self.connect_timeout.is_some() || self.proxy.is_some() {
+            return Err(Error::msg("Timeouts and proxies are not supported by the fetch client of wasm32"));
+        }
+
+        builder
+            .build()
+            .map_err(|e| Error::msg(format!("Failed to build HTTP client: {}", e)))
+    }
+
+    /// Applies the timeouts and proxy to a native HTTP client.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn configure_connection(&self, mut builder: reqwest::ClientBuilder) -> Result<reqwest::ClientBuilder, Error> {
+        if let Some(timeout) = self.request_timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            let proxy: reqwest::Proxy = reqwest::Proxy::all(proxy)
+                .map_err(|e| Error::msg(format!("Invalid proxy {:?}: {}", proxy, e)))?;
+            builder = builder.proxy(proxy);
+        }
+
+        Ok(builder)
+    }
+}
+
+/// Creates a client configured from the environment with custom HTTP settings
+///
+/// # Arguments
+/// * `api_key_env` - The environment variable holding the API key, the provider's default when `None`
+/// * `allow_unauthenticated` - Send a placeholder key when none is set
+/// * `options` - Timeouts, proxy and default headers of the HTTP client
+///
+/// # Returns
+/// The client, or an error if a required variable is missing or the options are invalid
+pub fn instantiate_client_with_options<C: EnvConfig>(
+    api_key_env: Option<&str>,
+    allow_unauthenticated: bool,
+    options: &HttpOptions,
+) -> Result<Client<C>, Error> {
+    let http_client: reqwest::Client = options.build_http_client()?;
+
+    Ok(instantiate_client::<C>(api_key_env, allow_unauthenticated)?.with_http_client(http_client))
+}
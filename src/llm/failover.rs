@@ -0,0 +1,130 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use anyhow::{Error, Result};
+use async_openai::{config::Config, error::OpenAIError, types::{CreateChatCompletionRequest, CreateChatCompletionResponse, Model}, Client};
+use serde::{Deserialize, Serialize};
+
+use crate::llm::ChatBackend;
+
+/// How often one endpoint of a `FailoverClient` was used
+///
+/// # Fields
+/// * `api_base` - The endpoint's API base URL
+/// * `served` - Requests the endpoint answered
+/// * `failed` - Requests that could not reach the endpoint and moved on to the next one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointStats {
+    pub api_base: String,
+    pub served: u64,
+    pub failed: u64,
+}
+
+/// A chat backend that tries several endpoints in order
+///
+/// Requests go to the endpoint that last succeeded. When it cannot be reached
+/// (connection refused, timed out), the request moves on to the next endpoint,
+/// which then becomes the preferred one. Errors returned by a reachable
+/// endpoint, such as 401 or 400, are returned as is since they would fail
+/// everywhere.
+pub struct FailoverClient<C: Config> {
+    endpoints: Vec<Client<C>>,
+    preferred: AtomicUsize,
+    served: Vec<AtomicU64>,
+    failed: Vec<AtomicU64>,
+}
+
+impl<C: Config> FailoverClient<C> {
+    /// Creates a failover client
+    ///
+    /// # Arguments
+    /// * `endpoints` - The clients in order of preference
+    ///
+    /// # Returns
+    /// The failover client, or an error if no endpoint is given
+    pub fn new(endpoints: Vec<Client<C>>) -> Result<Self, Error> {
+        if endpoints.is_empty() {
+            return Err(Error::msg("A failover client needs at least one endpoint"));
+        }
+
+        Ok(Self {
+            served: endpoints.iter().map(|_| AtomicU64::new(0)).collect(),
+            failed: endpoints.iter().map(|_| AtomicU64::new(0)).collect(),
+            endpoints,
+            preferred: AtomicUsize::new(0),
+        })
+    }
+
+    /// Returns the index of the endpoint requests currently go to first
+    pub fn get_preferred(&self) -> usize {
+        self.preferred.load(Ordering::Relaxed)
+    }
+
+    /// Returns how often each endpoint was used, in the order given to `new`
+    pub fn get_endpoint_stats(&self) -> Vec<EndpointStats> {
+        self.endpoints
+            .iter()
+            .enumerate()
+            .map(|(index, endpoint)| EndpointStats {
+                api_base: endpoint.config().api_base().to_string(),
+                served: self.served[index].load(Ordering::Relaxed),
+                failed: self.failed[index].load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+}
+
+/// Whether an error means the endpoint could not be reached at all
+fn is_unreachable(error: &OpenAIError) -> bool {
+    match error {
+        OpenAIError::Reqwest(error) => error.is_connect() || error.is_timeout(),
+        _ => false,
+    }
+}
+
+impl<C: Config + Send + Sync + 'static> ChatBackend for FailoverClient<C> {
+    async fn create_chat(&self, request: CreateChatCompletionRequest) -> Result<CreateChatCompletionResponse, OpenAIError> {
+        let start: usize = self.get_preferred();
+        let mut last_error: Option<OpenAIError> = None;
+        for offset in 0..self.endpoints.len() {
+            let index: usize = (start + offset) % self.endpoints.len();
+            match self.endpoints[index].chat().create(request.clone()).await {
+                Ok(response) => {
+                    self.preferred.store(index, Ordering::Relaxed);
+                    self.served[index].fetch_add(1, Ordering::Relaxed);
+                    return Ok(response);
+                }
+                Err(error) if is_unreachable(&error) => {
+                    log::warn!(
+                        "Endpoint {} is unreachable, failing over: {}",
+                        self.endpoints[index].config().api_base(),
+                        error
+                    );
+                    self.failed[index].fetch_add(1, Ordering::Relaxed);
+                    last_error = Some(error);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| OpenAIError::InvalidArgument("No endpoint available".to_string())))
+    }
+
+    async fn list_models(&self) -> Result<Vec<Model>, OpenAIError> {
+        let start: usize = self.get_preferred();
+        let mut last_error: Option<OpenAIError> = None;
+        for offset in 0..self.endpoints.len() {
+            let index: usize = (start + offset) % self.endpoints.len();
+            match self.endpoints[index].models().list().await {
+                Ok(response) => return Ok(response.data),
+                Err(error) if is_unreachable(&error) => last_error = Some(error),
+                Err(error) => return Err(error),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| OpenAIError::InvalidArgument("No endpoint available".to_string())))
+    }
+
+    fn describe_endpoint(&self) -> String {
+        self.endpoints[self.get_preferred()].config().api_base().to_string()
+    }
+}
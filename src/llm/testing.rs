@@ -0,0 +1,192 @@
+use std::sync::Mutex;
+
+use async_openai::error::{ApiError, OpenAIError};
+use async_openai::types::{CreateChatCompletionRequest, CreateChatCompletionResponse};
+use serde_json::Value;
+
+use crate::llm::ChatBackend;
+
+/// Builds a chat completion response whose message has the given content
+///
+/// # Arguments
+/// * `content` - The answer of the model, e.g. `{"score": 5}`
+///
+/// # Returns
+/// The response, with a usage of 10 prompt and 5 completion tokens
+pub fn completion_response(content: &str) -> CreateChatCompletionResponse {
+    serde_json::from_value(serde_json::json!({
+        "id": "chatcmpl-mock",
+        "object": "chat.completion",
+        "created": 0,
+        "model": "mock-model",
+        "choices": [{
+            "index": 0,
+            "message": { "role": "assistant", "content": content },
+            "finish_reason": "stop"
+        }],
+        "usage": { "prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15 }
+    }))
+    .expect("a valid chat completion response")
+}
+
+/// Builds the error an OpenAI-compatible server returns, e.g. `invalid_request_error` for a rejected request
+fn api_error(message: &str, kind: &str) -> OpenAIError {
+    let error: ApiError = serde_json::from_value(serde_json::json!({
+        "message": message,
+        "type": kind,
+        "param": null,
+        "code": null
+    }))
+    .expect("a valid API error");
+
+    OpenAIError::ApiError(error)
+}
+
+/// Concatenates the text of the last message of a request, which holds the real input.
+fn last_message_text(request: &CreateChatCompletionRequest) -> String {
+    fn collect(value: &Value, text: &mut String) {
+        match value {
+            Value::String(part) => text.push_str(part),
+            Value::Array(values) => values.iter().for_each(|value| collect(value, text)),
+            Value::Object(map) => map.values().for_each(|value| collect(value, text)),
+            _ => {}
+        }
+    }
+
+    let mut text: String = String::new();
+    if let Some(message) = request.messages.last() {
+        collect(&serde_json::to_value(message).unwrap_or_default(), &mut text);
+    }
+
+    text
+}
+
+/// A `ChatBackend` answering from canned responses, for tests without a model server
+///
+/// Each request is answered with the response of the first rule whose
+/// substring occurs in the request's last message, i.e. the prompt and input.
+#[derive(Debug, Default)]
+pub struct MockBackend {
+    rules: Vec<(String, String)>,
+    fallback: Option<String>,
+    requests: Mutex<Vec<CreateChatCompletionRequest>>,
+}
+
+impl MockBackend {
+    /// Creates a backend without any canned responses
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answers requests containing `substring` with `content`
+    ///
+    /// # Arguments
+    /// * `substring` - Text of the prompt or input to match, e.g. a JSON key
+    /// * `content` - The answer, e.g. `{"score": 5}`
+    ///
+    /// # Returns
+    /// The backend with the rule added after the existing ones
+    pub fn with_response(mut self, substring: impl Into<String>, content: impl Into<String>) -> Self {
+        self.rules.push((substring.into(), content.into()));
+        self
+    }
+
+    /// Answers requests matching no rule with `content`, instead of an error
+    pub fn with_fallback(mut self, content: impl Into<String>) -> Self {
+        self.fallback = Some(content.into());
+        self
+    }
+
+    /// Returns the requests received so far
+    pub fn get_requests(&self) -> Vec<CreateChatCompletionRequest> {
+        self.requests.lock().map(|requests| requests.clone()).unwrap_or_default()
+    }
+}
+
+impl ChatBackend for MockBackend {
+    async fn create_chat(&self, request: CreateChatCompletionRequest) -> Result<CreateChatCompletionResponse, OpenAIError> {
+        let text: String = last_message_text(&request);
+        if let Ok(mut requests) = self.requests.lock() {
+            requests.push(request);
+        }
+
+        self.rules
+            .iter()
+            .find(|(substring, _)| text.contains(substring.as_str()))
+            .map(|(_, content)| content)
+            .or(self.fallback.as_ref())
+            .map(|content| completion_response(content))
+            .ok_or_else(|| api_error(&format!("MockBackend has no response for: {}", text), "invalid_request_error"))
+    }
+
+    fn describe_endpoint(&self) -> String {
+        "the mock backend".to_string()
+    }
+}
+
+/// One reply of a `ScriptedBackend`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptedReply {
+    /// A response whose message has this content
+    Answer(String),
+    /// A server error with this message, which is retried
+    ApiError(String),
+}
+
+/// A `ChatBackend` replaying a fixed sequence of replies, for testing retries
+///
+/// Replies are given out in request order, whatever the request; the last one
+/// repeats once the script is exhausted. Concurrent prompts take their replies
+/// in the order their requests arrive, so script one prompt at a time when
+/// the order matters.
+#[derive(Debug)]
+pub struct ScriptedBackend {
+    replies: Vec<ScriptedReply>,
+    position: Mutex<usize>,
+    requests: Mutex<Vec<CreateChatCompletionRequest>>,
+}
+
+impl ScriptedBackend {
+    /// Creates a backend replaying `replies`
+    ///
+    /// # Arguments
+    /// * `replies` - The replies in order, at least one
+    ///
+    /// # Returns
+    /// The backend, positioned at the first reply
+    pub fn new(replies: Vec<ScriptedReply>) -> Self {
+        Self {
+            replies,
+            position: Mutex::new(0),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the requests received so far
+    pub fn get_requests(&self) -> Vec<CreateChatCompletionRequest> {
+        self.requests.lock().map(|requests| requests.clone()).unwrap_or_default()
+    }
+}
+
+impl ChatBackend for ScriptedBackend {
+    async fn create_chat(&self, request: CreateChatCompletionRequest) -> Result<CreateChatCompletionResponse, OpenAIError> {
+        if let Ok(mut requests) = self.requests.lock() {
+            requests.push(request);
+        }
+        let reply: Option<ScriptedReply> = self.position.lock().ok().and_then(|mut position| {
+            let reply: Option<ScriptedReply> = self.replies.get(*position).or(self.replies.last()).cloned();
+            *position += 1;
+            reply
+        });
+
+        match reply {
+            Some(ScriptedReply::Answer(content)) => Ok(completion_response(&content)),
+            Some(ScriptedReply::ApiError(message)) => Err(api_error(&message, "server_error")),
+            None => Err(api_error("ScriptedBackend has no replies", "invalid_request_error")),
+        }
+    }
+
+    fn describe_endpoint(&self) -> String {
+        "the scripted backend".to_string()
+    }
+}
@@ -0,0 +1,251 @@
+//! Streams JSONL texts through a `Vectorizer`, e.g. from stdin to stdout.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+
+use anyhow::{Error, Result};
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, Lines};
+
+use crate::llm::ChatBackend;
+use crate::raw_data::texts::parse_text_line;
+use crate::vector::Vector;
+use crate::vectorization::vectorizer::Vectorizer;
+
+/// How many items `process_jsonl` keeps in flight by default
+const DEFAULT_MAX_IN_FLIGHT: usize = 16;
+
+/// The name lines are recorded under in `METADATA_SOURCE` by default, as `stdin:<line>`
+const DEFAULT_SOURCE: &str = "stdin";
+
+/// How `process_jsonl` reads its input and writes its results
+///
+/// # Fields
+/// * `text_field` - The field holding the text, "text" by default
+/// * `id_field` - The field holding the identifier of each text, none by default
+/// * `source` - The name lines are recorded under in `METADATA_SOURCE`, "stdin" by default
+/// * `max_in_flight` - The most items read but not yet written, 16 by default
+/// * `preserve_order` - Whether results are written in input order, false by default
+/// * `errors` - Where failed items are written, none by default
+pub struct PipelineOptions {
+    text_field: String,
+    id_field: Option<String>,
+    source: String,
+    max_in_flight: usize,
+    preserve_order: bool,
+    errors: Option<Pin<Box<dyn AsyncWrite + Send>>>,
+}
+
+impl std::fmt::Debug for PipelineOptions {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("PipelineOptions")
+            .field("text_field", &self.text_field)
+            .field("id_field", &self.id_field)
+            .field("source", &self.source)
+            .field("max_in_flight", &self.max_in_flight)
+            .field("preserve_order", &self.preserve_order)
+            .field("errors", &self.errors.is_some())
+            .finish()
+    }
+}
+
+impl Default for PipelineOptions {
+    fn default() -> Self {
+        Self {
+            text_field: "text".to_string(),
+            id_field: None,
+            source: DEFAULT_SOURCE.to_string(),
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            preserve_order: false,
+            errors: None,
+        }
+    }
+}
+
+impl PipelineOptions {
+    /// Creates options reading the "text" field and writing results as they complete
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the text from another field
+    pub fn with_text_field(mut self, text_field: impl Into<String>) -> Self {
+        self.text_field = text_field.into();
+        self
+    }
+
+    /// Stores a field, a string or number, as the `id` metadata of each vector
+    pub fn with_id_field(mut self, id_field: impl Into<String>) -> Self {
+        self.id_field = Some(id_field.into());
+        self
+    }
+
+    /// Records lines as `<source>:<line>` in `METADATA_SOURCE`, e.g. with a file name
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    /// Sets the most items read but not yet written, at least 1
+    ///
+    /// This bounds memory: no line is read while this many items are being
+    /// vectorized or, with `preserve_order`, waiting for an earlier one.
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    /// Writes results in input order, holding finished items until the earlier ones are written
+    pub fn with_preserve_order(mut self, preserve_order: bool) -> Self {
+        self.preserve_order = preserve_order;
+        self
+    }
+
+    /// Writes each failed item as a JSON line `{"line", "input", "error"}`, instead of only logging it
+    ///
+    /// Items vectorized with a failed prompt also carry `"failed_prompts"`, the indices of those prompts.
+    pub fn with_errors(mut self, errors: impl AsyncWrite + Send + 'static) -> Self {
+        self.errors = Some(Box::pin(errors));
+        self
+    }
+}
+
+/// What `process_jsonl` did
+///
+/// # Fields
+/// * `succeeded` - The items vectorized and written
+/// * `failed` - The lines that could not be read or vectorized, including items with a failed prompt
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipelineSummary {
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+/// A finished item: its position, line and input, its vector or error, and the prompts that failed
+struct Finished {
+    sequence: usize,
+    line_number: usize,
+    input: String,
+    result: Result<Vector<String>, Error>,
+    failed_prompts: Vec<usize>,
+}
+
+/// Vectorizes the texts of a JSONL stream, writing one vector per line as each completes
+///
+/// Lines are read lazily: at most `max_in_flight` items are read but not yet
+/// written, so memory is bounded by that count whatever the input size.
+/// Blank lines are ignored. A line that is malformed or fails to vectorize,
+/// even for a single prompt, is written to the errors stream, or logged when
+/// there is none, and the others go on. Each result is flushed as soon as it is written.
+///
+/// # Arguments
+/// * `reader` - The JSONL input, e.g. `tokio::io::BufReader::new(tokio::io::stdin())`
+/// * `writer` - Where the vectors are written as JSON lines, e.g. `tokio::io::stdout()`
+/// * `vectorizer` - The client, prompts and parameters of every item
+/// * `options` - The fields read, the concurrency, the order and the errors stream
+///
+/// # Returns
+/// How many items were written and how many failed, or an error if reading
+/// the input or writing the output fails
+pub async fn process_jsonl<R, W, B>(reader: R, writer: W, vectorizer: &Vectorizer<B>, options: PipelineOptions) -> Result<PipelineSummary, Error>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+    B: ChatBackend,
+{
+    let PipelineOptions { text_field, id_field, source, max_in_flight, preserve_order, mut errors } = options;
+    let mut lines: Lines<R> = reader.lines();
+    let mut writer: W = writer;
+    let mut in_flight: FuturesUnordered<Pin<Box<dyn Future<Output = Finished> + Send + '_>>> = FuturesUnordered::new();
+    let mut reorder: BTreeMap<usize, Finished> = BTreeMap::new();
+    let (mut next_sequence, mut next_to_write, mut line_number): (usize, usize, usize) = (0, 0, 0);
+    let mut exhausted: bool = false;
+    let mut summary: PipelineSummary = PipelineSummary::default();
+
+    loop {
+        let has_capacity: bool = !exhausted && in_flight.len() + reorder.len() < max_in_flight;
+        if !has_capacity && in_flight.is_empty() {
+            break;
+        }
+
+        tokio::select! {
+            line = lines.next_line(), if has_capacity => {
+                let Some(input) = line.map_err(|e| Error::msg(format!("Failed to read line {}: {}", line_number + 1, e)))? else {
+                    exhausted = true;
+                    continue;
+                };
+                line_number += 1;
+                if input.trim().is_empty() {
+                    continue;
+                }
+
+                let (sequence, item_line): (usize, usize) = (next_sequence, line_number);
+                next_sequence += 1;
+                let parsed: Result<Vector<String>, Error> = parse_text_line(&input, &text_field, id_field.as_deref(), &source, item_line);
+                in_flight.push(Box::pin(async move {
+                    let mut failed_prompts: Vec<usize> = Vec::new();
+                    let result: Result<Vector<String>, Error> = match parsed {
+                        Ok(mut vector) => match vectorizer.vectorize_item(&mut vector, sequence).await {
+                            Ok(report) => {
+                                failed_prompts = report.failed_prompts();
+                                report.ensure_complete().map(|_| vector).map_err(Error::from)
+                            }
+                            Err(e) => Err(Error::from(e)),
+                        },
+                        Err(e) => Err(e),
+                    };
+                    Finished { sequence, line_number: item_line, input, result, failed_prompts }
+                }));
+            }
+            Some(finished) = in_flight.next() => {
+                if !preserve_order {
+                    write_finished(finished, &mut writer, &mut errors, &mut summary).await?;
+                    continue;
+                }
+                reorder.insert(finished.sequence, finished);
+                while let Some(finished) = reorder.remove(&next_to_write) {
+                    write_finished(finished, &mut writer, &mut errors, &mut summary).await?;
+                    next_to_write += 1;
+                }
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Writes a finished item to the output or the errors stream, counting it.
+async fn write_finished<W: AsyncWrite + Unpin>(
+    finished: Finished,
+    writer: &mut W,
+    errors: &mut Option<Pin<Box<dyn AsyncWrite + Send>>>,
+    summary: &mut PipelineSummary,
+) -> Result<(), Error> {
+    match finished.result {
+        Ok(vector) => {
+            let mut line: Vec<u8> = serde_json::to_vec(&vector)?;
+            line.push(b'\n');
+            writer.write_all(&line).await?;
+            writer.flush().await?;
+            summary.succeeded += 1;
+        }
+        Err(e) => {
+            log::warn!("Failed to vectorize line {}: {}", finished.line_number, e);
+            if let Some(errors) = errors.as_mut() {
+                let mut record = json!({ "line": finished.line_number, "input": finished.input, "error": e.to_string() });
+                if !finished.failed_prompts.is_empty() {
+                    record["failed_prompts"] = json!(finished.failed_prompts);
+                }
+                errors.write_all(format!("{}\n", record).as_bytes()).await?;
+                errors.flush().await?;
+            }
+            summary.failed += 1;
+        }
+    }
+
+    Ok(())
+}
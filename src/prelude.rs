@@ -1,6 +1,18 @@
 pub use crate::vector::{Vector, VectorOperations, DataType};
-pub use crate::prompt::Prompt;
+pub use crate::prompt::{Prompt, PromptSet, load_prompts};
+pub use crate::error::DimError;
+pub use crate::vectorization::{
+    ModelParameters,
+    ModelParametersBuilder,
+    vectorize_concurrently,
+    vectorize_string_concurrently,
+    vectorize_text
+};
+#[cfg(feature = "image")]
 pub use crate::vectorization::{
     vectorize_image_concurrently,
-    vectorize_string_concurrently
-};
\ No newline at end of file
+    vectorize_image
+};
+pub use crate::vectorization::report::VectorizationReport;
+pub use crate::vectorization::vectorizer::Vectorizer;
+pub use crate::llm::{ChatBackend, OpenAIClient, instantiate_client};
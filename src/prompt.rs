@@ -1,12 +1,176 @@
+use std::path::Path;
+
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+pub mod lint;
+
+use crate::prompt::lint::{lint_prompt, LintOptions, LintWarning};
+use crate::vector::Scalar;
+
+/// A worked example shown to the LLM before the real input
+///
+/// Few-shot examples are sent as alternating user/assistant messages ahead of
+/// the actual request, which noticeably stabilizes scores for subtle attributes.
+///
+/// # Fields
+/// * `input` - The example input. For image prompts this is a text description of an image
+/// * `output` - The JSON answer the LLM is expected to give for `input`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FewShotExample {
+    pub input: String,
+    pub output: String,
+}
+
 /// A prompt to be used for LLM-based vector generation
-/// 
+///
 /// This struct represents an instruction prompt that will be sent to a Large Language Model
 /// for generating vector representations based on the provided attribute description.
 ///
 /// # Fields
 /// * `instruction` - The formatted instruction string that will be sent to the LLM
+/// * `examples` - Optional few-shot examples sent before the real input
+/// * `expected_dims` - How many numbers the LLM must return for this prompt
+/// * `scale` - The inclusive range the scores must fall in, if declared
+/// * `labels` - Explicit names for the dimensions this prompt produces
+/// * `json_schema` - The JSON schema of the answer, used in JSON schema extraction mode
+/// * `aggregation` - How the samples of this prompt are combined, instead of the call-level setting
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Prompt {
-    instruction: String
+    instruction: String,
+    #[serde(default)]
+    examples: Vec<FewShotExample>,
+    #[serde(default = "default_expected_dims")]
+    expected_dims: usize,
+    #[serde(default)]
+    scale: Option<(f32, f32)>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    model_override: Option<ModelOverride>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    json_schema: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    aggregation: Option<SampleAggregation>,
+}
+
+/// Model settings a prompt uses instead of the call-level `ModelParameters`
+///
+/// Unset fields fall back to the call-level values.
+///
+/// # Fields
+/// * `model` - The model to send this prompt to, e.g. a vision-strong model
+/// * `temperature` - The sampling temperature for this prompt
+/// * `max_tokens` - The generation cap for this prompt
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelOverride {
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+}
+
+/// A bucket of scores that stands for one category, e.g. 1-3 for "informative"
+///
+/// # Fields
+/// * `min` - The lowest score in the bin, inclusive
+/// * `max` - The highest score in the bin, inclusive
+/// * `value` - The score emitted when the bin wins a majority vote
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScoreBin {
+    pub min: f32,
+    pub max: f32,
+    pub value: f32,
+}
+
+impl ScoreBin {
+    /// Creates a bin represented by its midpoint
+    ///
+    /// # Arguments
+    /// * `min` - The lowest score in the bin, inclusive
+    /// * `max` - The highest score in the bin, inclusive
+    ///
+    /// # Returns
+    /// A new ScoreBin whose value is `(min + max) / 2`
+    pub fn new(min: f32, max: f32) -> Self {
+        Self { min, max, value: (min + max) / 2.0 }
+    }
+
+    /// Returns whether a score falls in the bin
+    pub fn contains(&self, score: f32) -> bool {
+        self.min <= score && score <= self.max
+    }
+}
+
+/// How the samples of a prompt are combined into one score
+///
+/// Only used when a prompt is sampled more than once, see
+/// `ModelParametersBuilder::samples_per_prompt`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum SampleAggregation {
+    /// The arithmetic mean of the samples
+    #[default]
+    Mean,
+    /// The median of the samples, robust to a single outlying answer
+    Median,
+    /// The highest sample, e.g. to flag a video if any frame scores high
+    Max,
+    /// The value of the bin most samples fall in, for categorical prompts
+    ///
+    /// Ties go to the bin with the lowest `min`. Samples outside every bin do
+    /// not vote; if no sample falls in a bin, the median is used instead.
+    MajorityVote { bins: Vec<ScoreBin> },
+}
+
+impl SampleAggregation {
+    /// Combines the samples of one dimension
+    ///
+    /// # Arguments
+    /// * `samples` - The accepted scores, at least one
+    ///
+    /// # Returns
+    /// The combined score
+    pub fn aggregate<S: Scalar>(&self, samples: &[S]) -> S {
+        match self {
+            SampleAggregation::Mean => samples.iter().copied().sum::<S>() / S::from_f64(samples.len() as f64),
+            SampleAggregation::Max => samples.iter().copied().fold(S::from_f64(f64::NEG_INFINITY), S::max),
+            SampleAggregation::Median => {
+                let mut sorted: Vec<S> = samples.to_vec();
+                sorted.sort_by(S::total_cmp);
+                let middle: usize = sorted.len() / 2;
+                if sorted.len().is_multiple_of(2) {
+                    (sorted[middle - 1] + sorted[middle]) / S::from_f64(2.0)
+                } else {
+                    sorted[middle]
+                }
+            }
+            SampleAggregation::MajorityVote { bins } => {
+                let mut bins: Vec<&ScoreBin> = bins.iter().collect();
+                bins.sort_by(|a, b| a.min.total_cmp(&b.min));
+
+                let mut votes: Vec<usize> = vec![0; bins.len()];
+                for sample in samples {
+                    if let Some(bin) = bins.iter().position(|bin| bin.contains(sample.to_f64() as f32)) {
+                        votes[bin] += 1;
+                    }
+                }
+
+                // max_by_key keeps the last maximum, so scan from the highest bin down
+                match votes.iter().enumerate().rev().max_by_key(|(_, votes)| **votes) {
+                    Some((bin, votes)) if *votes > 0 => S::from_f64(bins[bin].value as f64),
+                    _ => SampleAggregation::Median.aggregate(samples),
+                }
+            }
+        }
+    }
+}
+
+fn default_expected_dims() -> usize {
+    1
 }
 
 impl Prompt {
@@ -18,14 +182,69 @@ impl Prompt {
     /// # Returns
     /// A new Prompt instance configured with the formatted instruction
     pub fn new(attribute_description: String) -> Self {
+        Self::from_instruction(format!(
+            "output in json. Rate the text based on the guideline provided. Rate from 0.0 to 10.0. {{'offensiveness': your score}}\nGuideline: {}",
+            attribute_description
+        ))
+    }
+
+    /// Creates a new Prompt that sends `instruction` to the LLM verbatim
+    ///
+    /// # Arguments
+    /// * `instruction` - The complete instruction, including the expected JSON format
+    ///
+    /// # Returns
+    /// A new Prompt instance without few-shot examples
+    pub fn from_instruction(instruction: String) -> Self {
         Self {
-            instruction: format!(
-                "output in json. Rate the text based on the guideline provided. Rate from 0.0 to 10.0. {{'offensiveness': your score}}\nGuideline: {}",
-                attribute_description
-            )
+            instruction,
+            examples: Vec::new(),
+            expected_dims: default_expected_dims(),
+            scale: None,
+            labels: Vec::new(),
+            model_override: None,
+            json_schema: None,
+            aggregation: None,
         }
     }
 
+    /// Sets how many numbers the LLM must return for this prompt
+    ///
+    /// # Arguments
+    /// * `expected_dims` - The number of dimensions this prompt contributes, 1 by default
+    ///
+    /// # Returns
+    /// The prompt with the expectation applied
+    pub fn with_expected_dims(mut self, expected_dims: usize) -> Self {
+        self.expected_dims = expected_dims;
+        self
+    }
+
+    /// Adds a few-shot example and returns the prompt
+    ///
+    /// # Arguments
+    /// * `input` - The example input text (or image description for image prompts)
+    /// * `output` - The expected JSON answer, e.g. `{"politeness_score": 8}`
+    ///
+    /// # Returns
+    /// The prompt with the example appended
+    pub fn with_example(mut self, input: impl Into<String>, output: impl Into<String>) -> Self {
+        self.add_example(input, output);
+        self
+    }
+
+    /// Appends a few-shot example in place
+    ///
+    /// # Arguments
+    /// * `input` - The example input text (or image description for image prompts)
+    /// * `output` - The expected JSON answer
+    pub fn add_example(&mut self, input: impl Into<String>, output: impl Into<String>) {
+        self.examples.push(FewShotExample {
+            input: input.into(),
+            output: output.into(),
+        });
+    }
+
     /// Returns a clone of the instruction string
     ///
     /// # Returns
@@ -33,4 +252,643 @@ impl Prompt {
     pub fn get_instruction(&self) -> String {
         self.instruction.clone()
     }
-}
\ No newline at end of file
+
+    /// Declares the inclusive range the scores must fall in
+    ///
+    /// Results outside the scale are rejected and retried like any other
+    /// validation failure.
+    ///
+    /// # Arguments
+    /// * `scale` - The `(min, max)` bounds of the score
+    ///
+    /// # Returns
+    /// The prompt with the scale applied
+    pub fn with_scale(mut self, scale: (f32, f32)) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
+    /// Names the dimensions this prompt produces
+    ///
+    /// Without explicit labels, the keys of the instruction's JSON example are used.
+    ///
+    /// # Arguments
+    /// * `labels` - One label per expected dimension
+    ///
+    /// # Returns
+    /// The prompt with the labels applied
+    pub fn with_labels(mut self, labels: Vec<String>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Routes this prompt to different model settings than the rest of the call
+    ///
+    /// # Arguments
+    /// * `model_override` - The settings that replace the call-level ones
+    ///
+    /// # Returns
+    /// The prompt with the override applied
+    pub fn with_model_override(mut self, model_override: ModelOverride) -> Self {
+        self.model_override = Some(model_override);
+        self
+    }
+
+    /// Returns the model settings this prompt overrides, if any
+    pub fn get_model_override(&self) -> Option<&ModelOverride> {
+        self.model_override.as_ref()
+    }
+
+    /// Sets the JSON schema the answer must follow in JSON schema extraction mode
+    ///
+    /// # Arguments
+    /// * `json_schema` - A JSON schema object, e.g. `{"type": "object", "properties": {...}}`
+    ///
+    /// # Returns
+    /// The prompt with the schema applied
+    pub fn with_json_schema(mut self, json_schema: Value) -> Self {
+        self.json_schema = Some(json_schema);
+        self
+    }
+
+    /// Sets how the samples of this prompt are combined
+    ///
+    /// Overrides the call-level aggregation, so categorical and continuous
+    /// prompts can be mixed in one call.
+    ///
+    /// # Arguments
+    /// * `aggregation` - The aggregation for this prompt's samples
+    ///
+    /// # Returns
+    /// The prompt with the aggregation applied
+    pub fn with_aggregation(mut self, aggregation: SampleAggregation) -> Self {
+        self.aggregation = Some(aggregation);
+        self
+    }
+
+    /// Returns how the samples of this prompt are combined, if set on the prompt
+    pub fn get_aggregation(&self) -> Option<&SampleAggregation> {
+        self.aggregation.as_ref()
+    }
+
+    /// Returns the JSON schema the answer must follow
+    ///
+    /// An explicit schema wins. Otherwise one is generated from the labels,
+    /// with one required number per label bounded by the scale, e.g.
+    /// `{"type": "object", "properties": {"score": {"type": "number"}}, ...}`.
+    ///
+    /// # Returns
+    /// The schema, or None when the labels are unknown or nested
+    pub fn get_json_schema(&self) -> Option<Value> {
+        if let Some(json_schema) = &self.json_schema {
+            return Some(json_schema.clone());
+        }
+
+        let labels: Vec<String> = self.get_labels();
+        if labels.is_empty() || labels.iter().any(|label| label.contains(['.', '['])) {
+            return None;
+        }
+
+        let mut property: serde_json::Map<String, Value> = serde_json::Map::new();
+        property.insert("type".to_string(), Value::from("number"));
+        if let Some((min, max)) = self.scale {
+            property.insert("minimum".to_string(), Value::from(min));
+            property.insert("maximum".to_string(), Value::from(max));
+        }
+        let properties: serde_json::Map<String, Value> = labels
+            .iter()
+            .map(|label| (label.clone(), Value::Object(property.clone())))
+            .collect();
+
+        Some(serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": labels,
+            "additionalProperties": false,
+        }))
+    }
+
+    /// Returns the few-shot examples in the order they are sent
+    pub fn get_examples(&self) -> &[FewShotExample] {
+        &self.examples
+    }
+
+    /// Returns how many numbers the LLM must return for this prompt
+    pub fn get_expected_dims(&self) -> usize {
+        self.expected_dims
+    }
+
+    /// Returns the declared `(min, max)` score range, if any
+    pub fn get_scale(&self) -> Option<(f32, f32)> {
+        self.scale
+    }
+
+    /// Returns the labels of the dimensions this prompt produces
+    ///
+    /// Explicit labels win; otherwise the key paths of the instruction's JSON
+    /// example are used when they match the expected dimensions. An empty list
+    /// means the labels are unknown until the LLM answers.
+    pub fn get_labels(&self) -> Vec<String> {
+        if !self.labels.is_empty() {
+            return self.labels.clone();
+        }
+
+        let mut keys: Vec<String> = Vec::new();
+        if let Some(example) = self.get_json_example().and_then(parse_json_example) {
+            collect_leaf_keys(&example, "", &mut keys);
+        }
+        if keys.len() != self.expected_dims {
+            keys.clear();
+        }
+
+        keys
+    }
+
+    /// Returns the JSON example embedded in the instruction, if any
+    ///
+    /// The first brace-delimited block in the instruction is taken as the
+    /// example, e.g. `{'sentiment_score': 7}`. Single-quoted keys, as used in
+    /// the bundled examples, are accepted.
+    ///
+    /// # Returns
+    /// The raw example text, or None if the instruction has no braces
+    pub fn get_json_example(&self) -> Option<&str> {
+        find_json_block(&self.instruction)
+    }
+
+    /// Checks the prompt for common authoring mistakes
+    ///
+    /// # Arguments
+    /// * `options` - The lint configuration, including strict mode
+    ///
+    /// # Returns
+    /// The list of warnings, or an error listing them when `options.strict` is set
+    pub fn lint(&self, options: &LintOptions) -> Result<Vec<LintWarning>, Error> {
+        lint::finish(lint_prompt(self, 0, options), options)
+    }
+}
+
+impl From<String> for Prompt {
+    fn from(instruction: String) -> Self {
+        Self::from_instruction(instruction)
+    }
+}
+
+impl From<&str> for Prompt {
+    fn from(instruction: &str) -> Self {
+        Self::from_instruction(instruction.to_string())
+    }
+}
+
+/// The built-in templates used to generate prompts from attribute names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PromptTemplate {
+    /// Scores a text, for use with `vectorize_string_concurrently`
+    Text,
+    /// Scores an image, for use with `vectorize_image_concurrently`
+    Image,
+    /// Scores an image and its text together, for use with `vectorize_multimodal_concurrently`
+    ImageWithText,
+}
+
+impl PromptTemplate {
+    /// Renders the template for one attribute
+    ///
+    /// # Arguments
+    /// * `attribute` - The attribute name as given by the user
+    /// * `key` - The JSON key the LLM must answer with
+    /// * `scale` - The `(min, max)` bounds of the score
+    ///
+    /// # Returns
+    /// The instruction string
+    pub fn render(&self, attribute: &str, key: &str, scale: (f32, f32)) -> String {
+        let (subject, scope): (&str, &str) = match self {
+            PromptTemplate::Text => ("the text", "the whole text"),
+            PromptTemplate::Image => ("the image", "the whole image"),
+            PromptTemplate::ImageWithText => ("the image and its text", "both the image and the text"),
+        };
+        let example: f32 = ((scale.0 + scale.1) / 2.0).round();
+
+        format!(
+            "Score the {attribute} of {subject} from {min} (lowest {attribute}) to {max} (highest {attribute}). Consider {scope} before answering. Format your response exactly like this example: {{'{key}': {example}}}",
+            attribute = attribute,
+            subject = subject,
+            scope = scope,
+            min = scale.0,
+            max = scale.1,
+            key = key,
+            example = example,
+        )
+    }
+}
+
+/// An ordered collection of prompts that together define a vector's dimensions
+///
+/// # Fields
+/// * `prompts` - The prompts in the order their dimensions appear in the vector
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PromptSet {
+    prompts: Vec<Prompt>,
+}
+
+impl PromptSet {
+    /// Creates a new PromptSet from a list of prompts
+    ///
+    /// # Arguments
+    /// * `prompts` - The prompts, as `Prompt` or plain instruction strings
+    ///
+    /// # Returns
+    /// A new PromptSet preserving the given order
+    pub fn new<P: Into<Prompt>>(prompts: Vec<P>) -> Self {
+        Self {
+            prompts: prompts.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Generates one scored text prompt per attribute
+    ///
+    /// Each prompt asks for a single score on `scale` and answers with a JSON key
+    /// derived from the attribute name, snake_cased with a `_score` suffix
+    /// (e.g. "Sarcasm level" becomes `sarcasm_level_score`).
+    ///
+    /// # Arguments
+    /// * `attributes` - The attribute names, one dimension each
+    /// * `scale` - The `(min, max)` bounds of every score
+    ///
+    /// # Returns
+    /// The generated PromptSet, or an error if an attribute yields an empty or duplicate key
+    pub fn from_attributes(attributes: &[&str], scale: (f32, f32)) -> Result<Self, Error> {
+        Self::from_attributes_with_template(attributes, scale, PromptTemplate::Text)
+    }
+
+    /// Generates one scored image prompt per attribute
+    ///
+    /// Same as `from_attributes`, but the instructions talk about an image.
+    pub fn from_image_attributes(attributes: &[&str], scale: (f32, f32)) -> Result<Self, Error> {
+        Self::from_attributes_with_template(attributes, scale, PromptTemplate::Image)
+    }
+
+    /// Generates one scored prompt per attribute for an image and its text
+    ///
+    /// Same as `from_attributes`, but the instructions talk about the image and the text.
+    pub fn from_multimodal_attributes(attributes: &[&str], scale: (f32, f32)) -> Result<Self, Error> {
+        Self::from_attributes_with_template(attributes, scale, PromptTemplate::ImageWithText)
+    }
+
+    /// Generates one scored prompt per attribute with the given template
+    ///
+    /// # Arguments
+    /// * `attributes` - The attribute names, one dimension each
+    /// * `scale` - The `(min, max)` bounds of every score
+    /// * `template` - The template to render each prompt with
+    ///
+    /// # Returns
+    /// The generated PromptSet, or an error if an attribute yields an empty or duplicate key
+    pub fn from_attributes_with_template(
+        attributes: &[&str],
+        scale: (f32, f32),
+        template: PromptTemplate,
+    ) -> Result<Self, Error> {
+        if scale.0.is_nan() || scale.1.is_nan() || scale.0 >= scale.1 {
+            return Err(Error::msg(format!(
+                "Invalid scale: minimum {} must be below maximum {}",
+                scale.0, scale.1
+            )));
+        }
+
+        let mut keys: Vec<String> = Vec::new();
+        let mut prompts: Vec<Prompt> = Vec::new();
+        for attribute in attributes {
+            let key: String = attribute_key(attribute)?;
+            if keys.contains(&key) {
+                return Err(Error::msg(format!(
+                    "Duplicate attribute key '{}' derived from '{}'",
+                    key, attribute
+                )));
+            }
+
+            prompts.push(
+                Prompt::from_instruction(template.render(attribute.trim(), &key, scale))
+                    .with_scale(scale),
+            );
+            keys.push(key);
+        }
+
+        Ok(Self { prompts })
+    }
+
+    /// Appends a prompt to the end of the set
+    pub fn push(&mut self, prompt: impl Into<Prompt>) {
+        self.prompts.push(prompt.into());
+    }
+
+    /// Returns the prompts in order
+    pub fn get_prompts(&self) -> &[Prompt] {
+        &self.prompts
+    }
+
+    /// Returns the number of prompts in the set
+    pub fn len(&self) -> usize {
+        self.prompts.len()
+    }
+
+    /// Returns true if the set contains no prompts
+    pub fn is_empty(&self) -> bool {
+        self.prompts.is_empty()
+    }
+
+    /// Returns the labels of all dimensions the set produces, in order
+    ///
+    /// Prompts whose labels are unknown contribute `dim_<index>` placeholders.
+    pub fn get_labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = Vec::new();
+        for prompt in &self.prompts {
+            let prompt_labels: Vec<String> = prompt.get_labels();
+            if prompt_labels.is_empty() {
+                for _ in 0..prompt.expected_dims {
+                    labels.push(format!("dim_{}", labels.len()));
+                }
+            } else {
+                labels.extend(prompt_labels);
+            }
+        }
+
+        labels
+    }
+
+    /// Returns the total number of dimensions the set produces
+    pub fn total_dims(&self) -> usize {
+        self.prompts.iter().map(|prompt| prompt.expected_dims).sum()
+    }
+
+    /// Checks every prompt in the set for common authoring mistakes
+    ///
+    /// # Arguments
+    /// * `options` - The lint configuration, including strict mode
+    ///
+    /// # Returns
+    /// The warnings of all prompts, or an error listing them when `options.strict` is set
+    pub fn lint(&self, options: &LintOptions) -> Result<Vec<LintWarning>, Error> {
+        let warnings: Vec<LintWarning> = self.prompts
+            .iter()
+            .enumerate()
+            .flat_map(|(index, prompt)| lint_prompt(prompt, index, options))
+            .collect();
+
+        lint::finish(warnings, options)
+    }
+}
+
+impl From<Vec<Prompt>> for PromptSet {
+    fn from(prompts: Vec<Prompt>) -> Self {
+        Self { prompts }
+    }
+}
+
+impl From<PromptSet> for Vec<Prompt> {
+    fn from(prompt_set: PromptSet) -> Self {
+        prompt_set.prompts
+    }
+}
+
+/// Prompts the vectorization functions accept
+///
+/// Implemented for owned and borrowed lists of anything that converts into a
+/// `Prompt`, such as `Vec<String>`, `&[Prompt]` or `&[&str]`, and for `PromptSet`,
+/// so a batch can pass the same prompts to every item without cloning them.
+pub trait IntoPrompts {
+    /// Converts the prompts into a list in dimension order
+    fn into_prompts(self) -> Vec<Prompt>;
+}
+
+impl<P: Into<Prompt>> IntoPrompts for Vec<P> {
+    fn into_prompts(self) -> Vec<Prompt> {
+        self.into_iter().map(Into::into).collect()
+    }
+}
+
+impl<P: Into<Prompt>, const N: usize> IntoPrompts for [P; N] {
+    fn into_prompts(self) -> Vec<Prompt> {
+        self.into_iter().map(Into::into).collect()
+    }
+}
+
+impl<P: Into<Prompt> + Clone> IntoPrompts for &[P] {
+    fn into_prompts(self) -> Vec<Prompt> {
+        self.iter().cloned().map(Into::into).collect()
+    }
+}
+
+impl<P: Into<Prompt> + Clone> IntoPrompts for &Vec<P> {
+    fn into_prompts(self) -> Vec<Prompt> {
+        self.as_slice().into_prompts()
+    }
+}
+
+impl IntoPrompts for PromptSet {
+    fn into_prompts(self) -> Vec<Prompt> {
+        self.prompts
+    }
+}
+
+impl IntoPrompts for &PromptSet {
+    fn into_prompts(self) -> Vec<Prompt> {
+        self.prompts.clone()
+    }
+}
+
+/// A prompt in a prompts file, either a plain instruction or a full `Prompt`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PromptEntry {
+    Instruction(String),
+    Prompt(Box<Prompt>),
+}
+
+/// The contents of a prompts file, a list of prompts or a serialized `PromptSet`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PromptFile {
+    List(Vec<PromptEntry>),
+    Set { prompts: Vec<PromptEntry> },
+}
+
+/// Loads the prompts of a vectorization from a JSON file
+///
+/// The file holds either a list or a serialized `PromptSet`. Each prompt is a
+/// plain instruction string or a serialized `Prompt`, and the two may be mixed.
+///
+/// # Arguments
+/// * `path` - The JSON file to read
+///
+/// # Returns
+/// The prompts in file order, or an error if the file cannot be read, parsed or is empty
+pub fn load_prompts(path: impl AsRef<Path>) -> Result<PromptSet, Error> {
+    let path: &Path = path.as_ref();
+    let contents: String = std::fs::read_to_string(path)
+        .map_err(|e| Error::msg(format!("Failed to read prompts from {}: {}", path.display(), e)))?;
+    let entries: Vec<PromptEntry> = match serde_json::from_str::<PromptFile>(&contents) {
+        Ok(PromptFile::List(entries)) | Ok(PromptFile::Set { prompts: entries }) => entries,
+        Err(_) => {
+            return Err(Error::msg(format!(
+                "Failed to parse prompts from {}: expected a list of instructions or prompts",
+                path.display()
+            )))
+        }
+    };
+    if entries.is_empty() {
+        return Err(Error::msg(format!("{} contains no prompts", path.display())));
+    }
+
+    Ok(PromptSet::new(
+        entries
+            .into_iter()
+            .map(|entry| match entry {
+                PromptEntry::Instruction(instruction) => Prompt::from_instruction(instruction),
+                PromptEntry::Prompt(prompt) => *prompt,
+            })
+            .collect(),
+    ))
+}
+
+/// Derives the JSON key for an attribute: snake_case plus a `_score` suffix
+fn attribute_key(attribute: &str) -> Result<String, Error> {
+    let mut key: String = String::new();
+    for character in attribute.trim().chars() {
+        if character.is_alphanumeric() {
+            key.extend(character.to_lowercase());
+        } else if !key.is_empty() && !key.ends_with('_') {
+            key.push('_');
+        }
+    }
+    let key: &str = key.trim_end_matches('_');
+
+    if key.is_empty() {
+        return Err(Error::msg(format!(
+            "Attribute '{}' does not contain any letters or digits",
+            attribute
+        )));
+    }
+    if key.ends_with("_score") {
+        return Ok(key.to_string());
+    }
+
+    Ok(format!("{}_score", key))
+}
+
+/// Finds the first balanced brace-delimited block in a string
+fn find_json_block(text: &str) -> Option<&str> {
+    let start: usize = text.find('{')?;
+    let mut depth: usize = 0;
+    for (offset, character) in text[start..].char_indices() {
+        match character {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[start..start + offset + 1]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Parses a JSON example, accepting the single-quoted style used in prompts
+pub(crate) fn parse_json_example(example: &str) -> Option<Value> {
+    serde_json::from_str::<Value>(example)
+        .or_else(|_| serde_json::from_str::<Value>(&example.replace('\'', "\"")))
+        .ok()
+}
+
+/// Collects the key paths of all leaf values in a JSON example
+pub(crate) fn collect_leaf_keys(value: &Value, prefix: &str, keys: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let path: String = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                collect_leaf_keys(child, &path, keys);
+            }
+        }
+        Value::Array(array) => {
+            for (index, child) in array.iter().enumerate() {
+                collect_leaf_keys(child, &format!("{}[{}]", prefix, index), keys);
+            }
+        }
+        _ => keys.push(prefix.to_string()),
+    }
+}
+
+/// Computes a stable fingerprint for an ordered list of prompts and a model
+///
+/// The fingerprint is a hex-encoded SHA-256 over the prompt instructions, their
+/// few-shot examples, per-prompt model overrides and sample aggregations and the model name. Vectors produced by the same prompts, in
+/// the same order, against the same model share a fingerprint; any change to the
+/// wording, the examples, the order or the model produces a different one.
+///
+/// # Arguments
+/// * `prompts` - The prompts in the order their dimensions appear in the vector
+/// * `model` - The name of the model that produces the scores
+///
+/// # Returns
+/// The fingerprint as a lowercase hex string
+pub fn compute_fingerprint(prompts: &[Prompt], model: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(b"model\0");
+    hasher.update(model.as_bytes());
+    for prompt in prompts {
+        hasher.update(b"\0instruction\0");
+        hasher.update(prompt.instruction.as_bytes());
+        hasher.update(b"\0expected_dims\0");
+        hasher.update(prompt.expected_dims.to_le_bytes());
+        if let Some((min, max)) = prompt.scale {
+            hasher.update(b"\0scale\0");
+            hasher.update(min.to_le_bytes());
+            hasher.update(max.to_le_bytes());
+        }
+        for example in &prompt.examples {
+            hasher.update(b"\0example_input\0");
+            hasher.update(example.input.as_bytes());
+            hasher.update(b"\0example_output\0");
+            hasher.update(example.output.as_bytes());
+        }
+        if let Some(model) = prompt.model_override.as_ref().and_then(|model_override| model_override.model.as_ref()) {
+            hasher.update(b"\0model_override\0");
+            hasher.update(model.as_bytes());
+        }
+        if let Some(aggregation) = &prompt.aggregation {
+            hasher.update(b"\0aggregation\0");
+            hasher.update(serde_json::to_string(aggregation).unwrap_or_default().as_bytes());
+        }
+    }
+
+    hex::encode(hasher.finalize())
+}
+
+/// Combines several fingerprints into one, in order
+///
+/// Used when a vector is assembled from several runs (concatenation, projection,
+/// extension), so the result is only compatible with vectors assembled the same way.
+///
+/// # Arguments
+/// * `operation` - What produced the combination, e.g. "concat"
+/// * `parts` - The fingerprints being combined, in dimension order
+///
+/// # Returns
+/// The combined fingerprint as a lowercase hex string
+pub fn combine_fingerprints(operation: &str, parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(operation.as_bytes());
+    for part in parts {
+        hasher.update(b"\0");
+        hasher.update(part.as_bytes());
+    }
+
+    hex::encode(hasher.finalize())
+}
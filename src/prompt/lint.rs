@@ -0,0 +1,182 @@
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::prompt::{collect_leaf_keys, parse_json_example, Prompt};
+
+/// The rule a lint warning was raised by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LintRule {
+    /// Neither the instruction nor a few-shot example shows the expected JSON
+    MissingJsonExample,
+    /// The instruction does not state a numeric range such as "from 1 to 9"
+    MissingNumericRange,
+    /// A JSON example is present but cannot be parsed
+    UnparsableJsonExample,
+    /// The number of keys in the JSON example differs from the expected dimensions
+    ExpectedDimsMismatch,
+    /// The instruction and examples exceed the character budget
+    ExceedsCharacterBudget,
+}
+
+/// A single problem found in a prompt
+///
+/// # Fields
+/// * `prompt_index` - The position of the prompt within its set, 0 for a lone prompt
+/// * `rule` - The rule that raised the warning
+/// * `message` - A human readable description of the problem
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LintWarning {
+    pub prompt_index: usize,
+    pub rule: LintRule,
+    pub message: String,
+}
+
+impl std::fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "prompt {}: {:?}: {}", self.prompt_index, self.rule, self.message)
+    }
+}
+
+/// Configuration for prompt linting
+///
+/// # Fields
+/// * `max_characters` - The character budget for the instruction plus its examples
+/// * `strict` - Turn warnings into an error, for checks before expensive batch jobs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LintOptions {
+    pub max_characters: usize,
+    pub strict: bool,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            max_characters: 2000,
+            strict: false,
+        }
+    }
+}
+
+impl LintOptions {
+    /// Creates lint options that fail on any warning
+    pub fn strict() -> Self {
+        Self {
+            strict: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// Runs every lint rule against a single prompt
+pub(crate) fn lint_prompt(prompt: &Prompt, prompt_index: usize, options: &LintOptions) -> Vec<LintWarning> {
+    let mut warnings: Vec<LintWarning> = Vec::new();
+    let mut warn = |rule: LintRule, message: String| {
+        warnings.push(LintWarning { prompt_index, rule, message });
+    };
+
+    // Gather the instruction's example and the few-shot answers
+    let mut examples: Vec<&str> = Vec::new();
+    if let Some(example) = prompt.get_json_example() {
+        examples.push(example);
+    }
+    examples.extend(prompt.get_examples().iter().map(|example| example.output.as_str()));
+
+    if examples.is_empty() {
+        warn(
+            LintRule::MissingJsonExample,
+            "no JSON example found; add one like \"Format your response exactly like this example: {'score': 5}\"".to_string(),
+        );
+    }
+
+    for example in examples {
+        match parse_json_example(example) {
+            Some(parsed) => {
+                let mut keys: Vec<String> = Vec::new();
+                collect_leaf_keys(&parsed, "", &mut keys);
+                if keys.len() != prompt.get_expected_dims() {
+                    warn(
+                        LintRule::ExpectedDimsMismatch,
+                        format!(
+                            "example {} has {} key(s) but the prompt expects {} dimension(s)",
+                            example,
+                            keys.len(),
+                            prompt.get_expected_dims()
+                        ),
+                    );
+                }
+            }
+            None => warn(
+                LintRule::UnparsableJsonExample,
+                format!("example {} is not valid JSON", example),
+            ),
+        }
+    }
+
+    if prompt.get_scale().is_none() && !declares_numeric_range(&prompt.get_instruction()) {
+        warn(
+            LintRule::MissingNumericRange,
+            "no explicit numeric range found; state one like \"from 1 to 9\"".to_string(),
+        );
+    }
+
+    let characters: usize = prompt.get_instruction().chars().count()
+        + prompt
+            .get_examples()
+            .iter()
+            .map(|example| example.input.chars().count() + example.output.chars().count())
+            .sum::<usize>();
+    if characters > options.max_characters {
+        warn(
+            LintRule::ExceedsCharacterBudget,
+            format!("{} characters exceed the budget of {}", characters, options.max_characters),
+        );
+    }
+
+    warnings
+}
+
+/// Turns warnings into an error when strict mode is enabled
+pub(crate) fn finish(warnings: Vec<LintWarning>, options: &LintOptions) -> Result<Vec<LintWarning>, Error> {
+    if options.strict && !warnings.is_empty() {
+        let lines: Vec<String> = warnings.iter().map(|warning| warning.to_string()).collect();
+        return Err(Error::msg(format!("Prompt lint failed:\n{}", lines.join("\n"))));
+    }
+
+    Ok(warnings)
+}
+
+/// Detects a numeric range such as "from 1 to 9", "0.0 to 10.0", "1-3" or "between 0 and 1"
+fn declares_numeric_range(text: &str) -> bool {
+    let mut numbers: Vec<(usize, usize)> = Vec::new();
+    let mut start: Option<usize> = None;
+    for (index, character) in text.char_indices() {
+        let is_numeric: bool = character.is_ascii_digit() || (character == '.' && start.is_some());
+        match (is_numeric, start) {
+            (true, None) => start = Some(index),
+            (false, Some(begin)) => {
+                numbers.push((begin, index));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(begin) = start {
+        numbers.push((begin, text.len()));
+    }
+
+    // Parenthesized anchors are skipped, as in "1 (negative) to 9 (positive)"
+    numbers.windows(2).any(|pair| {
+        let mut separator: String = String::new();
+        let mut depth: usize = 0;
+        for character in text[pair[0].1..pair[1].0].chars() {
+            match character {
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                _ if depth == 0 => separator.push(character),
+                _ => {}
+            }
+        }
+        matches!(separator.trim().to_lowercase().as_str(), "to" | "-" | "–" | "and")
+    })
+}
+
@@ -0,0 +1,178 @@
+//! Python bindings of the vectorization pipeline, built as the `dim_rs` module.
+
+use std::path::PathBuf;
+
+use anyhow::Error;
+use async_openai::config::OpenAIConfig;
+use async_openai::Client;
+use image::DynamicImage;
+use pyo3::prelude::*;
+use tokio::runtime::Runtime;
+
+use crate::error::DimError;
+use crate::llm::{EnvConfig, DEFAULT_API_KEY_ENV};
+use crate::prompt::{compute_fingerprint, load_prompts, PromptSet};
+use crate::vector::{Vector, VectorOperations};
+use crate::vectorization::report::VectorizationReport;
+use crate::vectorization::vectorizable::Vectorizable;
+use crate::vectorization::vectorizer::Vectorizer;
+use crate::vectorization::ModelParametersBuilder;
+
+/// The Python exceptions, one per `DimError` variant, all deriving from `DimError`
+pub mod exceptions {
+    use pyo3::create_exception;
+    use pyo3::exceptions::PyException;
+
+    create_exception!(dim_rs, DimError, PyException, "Base class of every error raised by dim_rs.");
+    create_exception!(dim_rs, ApiError, DimError, "The API answered with an error.");
+    create_exception!(dim_rs, Unauthorized, DimError, "The API rejected the credentials.");
+    create_exception!(dim_rs, Timeout, DimError, "A request did not finish in time.");
+    create_exception!(dim_rs, ModelNotFound, DimError, "The endpoint does not serve the model.");
+    create_exception!(dim_rs, ValidationFailed, DimError, "An answer was parsed but did not pass validation.");
+    create_exception!(dim_rs, ParseFailed, DimError, "An answer could not be parsed.");
+    create_exception!(dim_rs, DimensionalityMismatch, DimError, "A vector has a different number of dimensions than expected.");
+    create_exception!(dim_rs, Cancelled, DimError, "The work was cancelled before it finished.");
+    create_exception!(dim_rs, Other, DimError, "Any other failure, such as invalid parameters or unreadable input.");
+}
+
+/// Raises a `DimError` as the Python exception named after its variant.
+fn to_py_err(error: DimError) -> PyErr {
+    let message: String = error.to_string();
+    match error {
+        DimError::ApiError { .. } => exceptions::ApiError::new_err(message),
+        DimError::Unauthorized { .. } => exceptions::Unauthorized::new_err(message),
+        DimError::Timeout { .. } => exceptions::Timeout::new_err(message),
+        DimError::ModelNotFound { .. } => exceptions::ModelNotFound::new_err(message),
+        DimError::ValidationFailed { .. } => exceptions::ValidationFailed::new_err(message),
+        DimError::ParseFailed { .. } => exceptions::ParseFailed::new_err(message),
+        DimError::DimensionalityMismatch { .. } => exceptions::DimensionalityMismatch::new_err(message),
+        DimError::Cancelled => exceptions::Cancelled::new_err(message),
+        DimError::Other(_) => exceptions::Other::new_err(message),
+    }
+}
+
+/// Raises an `anyhow::Error` as the exception of the `DimError` it holds, `Other` when none.
+fn anyhow_to_py_err(error: Error) -> PyErr {
+    to_py_err(DimError::from(error))
+}
+
+/// A client, prompts and parameters for vectorizing from Python
+///
+/// Every call blocks the calling thread until the vectors are ready and
+/// releases the GIL meanwhile, so other Python threads keep running. Vectors
+/// always have the dimensions of `labels`, in order; a prompt answering with
+/// fewer raises `DimensionalityMismatch`.
+#[pyclass(name = "Vectorizer", module = "dim_rs")]
+pub struct PyVectorizer {
+    vectorizer: Vectorizer<Client<OpenAIConfig>>,
+    runtime: Runtime,
+}
+
+impl PyVectorizer {
+    /// Vectorizes items on the runtime with the GIL released, keeping their order.
+    fn vectorize_all<T: Vectorizable + Send>(&self, py: Python<'_>, mut vectors: Vec<Vector<T>>) -> PyResult<Vec<Vec<f32>>> {
+        let dims: usize = self.vectorizer.get_prompts().total_dims();
+        vectors = vectors.into_iter().map(|vector| vector.with_expected_dims(dims)).collect();
+        let results: Vec<Result<VectorizationReport, DimError>> =
+            py.allow_threads(|| self.runtime.block_on(self.vectorizer.vectorize_batch(&mut vectors)));
+
+        results
+            .into_iter()
+            .zip(vectors)
+            .map(|(result, vector)| result.map(|_| vector.get_vector()).map_err(to_py_err))
+            .collect()
+    }
+}
+
+#[pymethods]
+impl PyVectorizer {
+    /// Creates a vectorizer for an OpenAI compatible endpoint
+    ///
+    /// # Arguments
+    /// * `model` - The model name, e.g. "gpt-4o-mini"
+    /// * `prompt_file` - The JSON prompts file, as read by `load_prompts`
+    /// * `api_base` - The API base URL, `OPENAI_API_BASE` or OpenAI when None
+    /// * `api_key_env` - The environment variable holding the API key, `OPENAI_API_KEY` when None
+    /// * `allow_unauthenticated` - Send a placeholder key when none is set, for local servers
+    /// * `max_concurrency` - How many items `vectorize_batch` processes at once
+    ///
+    /// Other parameters are read from the `DIM_*` environment variables.
+    #[new]
+    #[pyo3(signature = (model, prompt_file, api_base = None, api_key_env = None, allow_unauthenticated = false, max_concurrency = 4))]
+    fn new(
+        model: String,
+        prompt_file: PathBuf,
+        api_base: Option<String>,
+        api_key_env: Option<String>,
+        allow_unauthenticated: bool,
+        max_concurrency: usize,
+    ) -> PyResult<Self> {
+        let mut config: OpenAIConfig = OpenAIConfig::from_env(api_key_env.as_deref().unwrap_or(DEFAULT_API_KEY_ENV), allow_unauthenticated)
+            .map_err(anyhow_to_py_err)?;
+        if let Some(api_base) = api_base {
+            config = config.with_api_base(api_base);
+        }
+        let prompts: PromptSet = load_prompts(prompt_file).map_err(anyhow_to_py_err)?;
+        let vectorizer: Vectorizer<Client<OpenAIConfig>> = Vectorizer::builder()
+            .client(Client::with_config(config))
+            .prompts(prompts)
+            .model_parameters(ModelParametersBuilder::from_env().and_then(|builder| builder.model(model).build()).map_err(anyhow_to_py_err)?)
+            .max_concurrency(max_concurrency)
+            .build()
+            .map_err(anyhow_to_py_err)?;
+        let runtime: Runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| exceptions::Other::new_err(format!("Failed to start the runtime: {}", e)))?;
+
+        Ok(Self { vectorizer, runtime })
+    }
+
+    /// The label of each dimension, `dim_<index>` where the prompts do not name it
+    #[getter]
+    fn labels(&self) -> Vec<String> {
+        self.vectorizer.get_prompts().get_labels()
+    }
+
+    /// The fingerprint of the prompts and model, shared by every vector this vectorizer makes
+    #[getter]
+    fn fingerprint(&self) -> String {
+        compute_fingerprint(self.vectorizer.get_prompts().get_prompts(), &self.vectorizer.get_model_parameters().get_model())
+    }
+
+    /// Vectorizes a text
+    fn vectorize_text(&self, py: Python<'_>, text: String) -> PyResult<Vec<f32>> {
+        Ok(self.vectorize_all(py, vec![Vector::from_text(text)])?.remove(0))
+    }
+
+    /// Vectorizes an image from the contents of its file, e.g. PNG or JPEG bytes
+    fn vectorize_image(&self, py: Python<'_>, image: &[u8]) -> PyResult<Vec<f32>> {
+        let vector: Vector<DynamicImage> = Vector::from_bytes(image).map_err(anyhow_to_py_err)?;
+
+        Ok(self.vectorize_all(py, vec![vector])?.remove(0))
+    }
+
+    /// Vectorizes many texts, at most `max_concurrency` at a time, raising the first failure
+    fn vectorize_batch(&self, py: Python<'_>, texts: Vec<String>) -> PyResult<Vec<Vec<f32>>> {
+        self.vectorize_all(py, texts.into_iter().map(Vector::from_text).collect())
+    }
+}
+
+/// The `dim_rs` Python module.
+#[pymodule]
+fn dim_rs(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py: Python<'_> = module.py();
+    module.add_class::<PyVectorizer>()?;
+    module.add("DimError", py.get_type::<exceptions::DimError>())?;
+    module.add("ApiError", py.get_type::<exceptions::ApiError>())?;
+    module.add("Unauthorized", py.get_type::<exceptions::Unauthorized>())?;
+    module.add("Timeout", py.get_type::<exceptions::Timeout>())?;
+    module.add("ModelNotFound", py.get_type::<exceptions::ModelNotFound>())?;
+    module.add("ValidationFailed", py.get_type::<exceptions::ValidationFailed>())?;
+    module.add("ParseFailed", py.get_type::<exceptions::ParseFailed>())?;
+    module.add("DimensionalityMismatch", py.get_type::<exceptions::DimensionalityMismatch>())?;
+    module.add("Cancelled", py.get_type::<exceptions::Cancelled>())?;
+    module.add("Other", py.get_type::<exceptions::Other>())?;
+
+    Ok(())
+}
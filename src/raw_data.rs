@@ -0,0 +1,485 @@
+use anyhow::{Error, Result};
+#[cfg(feature = "image")]
+use base64::prelude::*;
+#[cfg(feature = "image")]
+use image::metadata::Orientation;
+#[cfg(feature = "image")]
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "document")]
+pub mod document;
+#[cfg(feature = "image")]
+pub mod directory;
+#[cfg(feature = "image")]
+pub mod download;
+#[cfg(feature = "video")]
+pub mod frames;
+#[cfg(feature = "html")]
+pub mod html;
+#[cfg(feature = "image")]
+pub mod perceptual;
+pub mod record;
+pub mod texts;
+#[cfg(feature = "tokens")]
+pub mod tokens;
+pub mod utilities;
+
+#[cfg(feature = "image")]
+pub use directory::{list_image_directory, load_image_directory, stream_image_directory, ImageDirectoryOptions, ImageDirectoryStream, ImageFile};
+#[cfg(feature = "image")]
+pub use download::{load_image_from_url, ImageDownloadOptions};
+pub use texts::{load_texts_csv, load_texts_jsonl, read_texts_csv, read_texts_jsonl, TextDatasetOptions, TextDatasetReader};
+#[cfg(feature = "image")]
+pub use perceptual::{find_near_duplicates, perceptual_hash, ImageHash};
+pub use utilities::{ImageDetail, ImageEncoding};
+#[cfg(feature = "image")]
+pub use utilities::image_sha256;
+#[cfg(feature = "video")]
+pub use frames::{extract_frames, FrameStrategy, VideoError, VideoSource};
+#[cfg(feature = "html")]
+pub use html::extract_readable_text;
+#[cfg(feature = "tokens")]
+pub use tokens::count_tokens;
+
+/// Common behaviors of the payloads a `Vector` can carry
+pub trait VectorData {
+    /// Returns the payload as text, if it is textual
+    fn as_text(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl VectorData for String {
+    fn as_text(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+#[cfg(feature = "image")]
+impl VectorData for DynamicImage {}
+
+#[cfg(feature = "image")]
+impl VectorData for Vec<DynamicImage> {}
+
+#[cfg(feature = "image")]
+impl VectorData for (DynamicImage, String) {
+    fn as_text(&self) -> Option<&str> {
+        Some(&self.1)
+    }
+}
+
+impl VectorData for AudioData {}
+
+impl VectorData for VideoData {}
+
+#[cfg(feature = "image")]
+impl VectorData for EncodedImage {}
+
+#[cfg(feature = "image")]
+impl VectorData for ImageFile {}
+
+impl VectorData for RemoteImage {}
+
+impl VectorData for Vec<ChatTurn> {}
+
+impl VectorData for serde_json::Value {}
+
+/// The encoding of an audio payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AudioFormat {
+    Wav,
+    Mp3,
+    Flac,
+    Ogg,
+    M4a,
+}
+
+impl AudioFormat {
+    /// Returns the conventional file extension of the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Ogg => "ogg",
+            AudioFormat::M4a => "m4a",
+        }
+    }
+}
+
+/// The container format of a video payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VideoFormat {
+    Mp4,
+    WebM,
+    Mov,
+    Avi,
+    Mkv,
+}
+
+impl VideoFormat {
+    /// Returns the conventional file extension of the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            VideoFormat::Mp4 => "mp4",
+            VideoFormat::WebM => "webm",
+            VideoFormat::Mov => "mov",
+            VideoFormat::Avi => "avi",
+            VideoFormat::Mkv => "mkv",
+        }
+    }
+}
+
+/// Raw audio bytes together with their format
+///
+/// The bytes are kept encoded exactly as given; serialization writes them
+/// as a base64 string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioData {
+    #[serde(with = "base64_bytes")]
+    bytes: Vec<u8>,
+    format: AudioFormat,
+}
+
+impl AudioData {
+    /// Creates a new audio payload
+    ///
+    /// # Arguments
+    /// * `bytes` - The encoded audio file contents
+    /// * `format` - The encoding of `bytes`
+    pub fn new(bytes: Vec<u8>, format: AudioFormat) -> Self {
+        Self { bytes, format }
+    }
+
+    /// Returns the encoded audio bytes
+    pub fn get_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the encoding of the audio bytes
+    pub fn get_format(&self) -> AudioFormat {
+        self.format
+    }
+}
+
+/// The image formats vision models accept as they are
+#[cfg(feature = "image")]
+const SUPPORTED_IMAGE_FORMATS: [ImageFormat; 4] = [ImageFormat::Png, ImageFormat::Jpeg, ImageFormat::Gif, ImageFormat::WebP];
+
+/// Decodes an image, detecting its format from the bytes
+///
+/// # Arguments
+/// * `bytes` - The encoded image file contents
+///
+/// # Returns
+/// The decoded image, or an error if the format is unknown or the bytes are invalid
+#[cfg(feature = "image")]
+pub fn image_from_bytes(bytes: &[u8]) -> Result<DynamicImage, Error> {
+    let format: ImageFormat = image::guess_format(bytes)
+        .map_err(|_| Error::msg("Unrecognized image format"))?;
+
+    image::load_from_memory_with_format(bytes, format)
+        .map_err(|e| Error::msg(format!("Failed to decode {:?} image: {}", format, e)))
+}
+
+/// Decodes an image and reads its EXIF orientation, which decoding alone discards
+///
+/// The pixels are returned as stored; apply the orientation with
+/// `apply_exif_orientation` to turn the image upright.
+///
+/// # Arguments
+/// * `bytes` - The encoded image file contents
+///
+/// # Returns
+/// The image and its EXIF orientation from 1 to 8, 1 when the file has none
+#[cfg(feature = "image")]
+pub fn decode_image_with_orientation(bytes: &[u8]) -> Result<(DynamicImage, u8), Error> {
+    let mut decoder = ImageReader::new(std::io::Cursor::new(bytes))
+        .with_guessed_format()?
+        .into_decoder()
+        .map_err(|e| Error::msg(format!("Failed to read image: {}", e)))?;
+    let orientation: Orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let image: DynamicImage = DynamicImage::from_decoder(decoder)
+        .map_err(|e| Error::msg(format!("Failed to decode image: {}", e)))?;
+
+    Ok((image, orientation.to_exif()))
+}
+
+/// Rotates and flips an image as its EXIF orientation says
+///
+/// # Arguments
+/// * `image` - The image as stored in the file
+/// * `orientation` - The EXIF orientation from 1 to 8; other values leave the image as it is
+#[cfg(feature = "image")]
+pub fn apply_exif_orientation(image: &mut DynamicImage, orientation: u8) {
+    if let Some(orientation) = Orientation::from_exif(orientation) {
+        image.apply_orientation(orientation);
+    }
+}
+
+/// An image kept base64-encoded exactly as given
+///
+/// Vectorizing it sends the encoding to the model as it is, skipping the
+/// decoding and PNG re-encoding of `DynamicImage`s. Only PNG, JPEG, GIF and
+/// WebP are accepted, since other formats would have to be converted.
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncodedImage {
+    data: String,
+    mime: String,
+}
+
+#[cfg(feature = "image")]
+impl EncodedImage {
+    /// Wraps an image that is already base64-encoded
+    ///
+    /// # Arguments
+    /// * `data` - The base64 encoding of the image file
+    /// * `mime` - The MIME type of the image, e.g. `image/jpeg`
+    ///
+    /// # Returns
+    /// The image, or an error if the MIME type is unsupported, `data` is not
+    /// valid base64 or its contents are not of type `mime`
+    pub fn from_base64(data: String, mime: &str) -> Result<Self, Error> {
+        let mime: String = match mime.trim().to_ascii_lowercase().as_str() {
+            "image/jpg" => "image/jpeg".to_string(),
+            mime => mime.to_string(),
+        };
+        if !SUPPORTED_IMAGE_FORMATS.iter().any(|format| format.to_mime_type() == mime) {
+            return Err(Error::msg(format!("Unsupported image MIME type {:?}", mime)));
+        }
+        let bytes: Vec<u8> = BASE64_STANDARD
+            .decode(&data)
+            .map_err(|e| Error::msg(format!("Invalid base64 image data: {}", e)))?;
+        let sniffed: &str = image::guess_format(&bytes).map(|format| format.to_mime_type()).unwrap_or("unknown");
+        if sniffed != mime {
+            return Err(Error::msg(format!("Image data is {}, not {}", sniffed, mime)));
+        }
+
+        Ok(Self { data, mime })
+    }
+
+    /// Encodes an image file without decoding it
+    ///
+    /// # Arguments
+    /// * `bytes` - The encoded image file contents
+    ///
+    /// # Returns
+    /// The image, or an error if its format is unknown or unsupported
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let format: ImageFormat = image::guess_format(bytes)
+            .map_err(|_| Error::msg("Unrecognized image format"))?;
+        if !SUPPORTED_IMAGE_FORMATS.contains(&format) {
+            return Err(Error::msg(format!("Unsupported image format {:?}", format)));
+        }
+
+        Ok(Self {
+            data: BASE64_STANDARD.encode(bytes),
+            mime: format.to_mime_type().to_string(),
+        })
+    }
+
+    /// Returns the base64 encoding of the image
+    pub fn get_data(&self) -> &str {
+        &self.data
+    }
+
+    /// Returns the MIME type of the image
+    pub fn get_mime(&self) -> &str {
+        &self.mime
+    }
+
+    /// Returns the image as a `data:` URL, as sent to the model
+    pub fn to_data_url(&self) -> String {
+        format!("data:{};base64,{}", self.mime, self.data)
+    }
+}
+
+/// An image hosted at a public https URL, sent to the model as the URL
+///
+/// The model downloads the image itself, so it is never downloaded, decoded or
+/// re-encoded here, and the local preprocessing options (`max_image_dimension`,
+/// `image_encoding` and `apply_exif_orientation`) do not apply to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RemoteImage {
+    url: String,
+}
+
+impl RemoteImage {
+    /// Wraps the URL of a hosted image
+    ///
+    /// # Arguments
+    /// * `url` - The https URL of the image
+    ///
+    /// # Returns
+    /// The image, or an error if `url` is not a valid https URL with a host
+    pub fn new(url: impl Into<String>) -> Result<Self, Error> {
+        let url: String = url.into();
+        let parsed: reqwest::Url = reqwest::Url::parse(&url)
+            .map_err(|e| Error::msg(format!("Invalid image URL {:?}: {}", url, e)))?;
+        if parsed.scheme() != "https" || parsed.host_str().is_none() {
+            return Err(Error::msg(format!("Image URL {:?} is not an https URL", url)));
+        }
+
+        Ok(Self { url })
+    }
+
+    /// Returns the URL of the image
+    pub fn get_url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl TryFrom<String> for RemoteImage {
+    type Error = Error;
+
+    fn try_from(url: String) -> Result<Self, Self::Error> {
+        Self::new(url)
+    }
+}
+
+impl From<RemoteImage> for String {
+    fn from(image: RemoteImage) -> Self {
+        image.url
+    }
+}
+
+/// Raw video bytes together with their container format
+///
+/// The bytes are kept encoded exactly as given; serialization writes them
+/// as a base64 string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VideoData {
+    #[serde(with = "base64_bytes")]
+    bytes: Vec<u8>,
+    format: VideoFormat,
+}
+
+impl VideoData {
+    /// Creates a new video payload
+    ///
+    /// # Arguments
+    /// * `bytes` - The encoded video file contents
+    /// * `format` - The container format of `bytes`
+    pub fn new(bytes: Vec<u8>, format: VideoFormat) -> Self {
+        Self { bytes, format }
+    }
+
+    /// Returns the encoded video bytes
+    pub fn get_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the container format of the video bytes
+    pub fn get_format(&self) -> VideoFormat {
+        self.format
+    }
+}
+
+/// A decoded frame of a video
+///
+/// Decoding is left to the caller, e.g. with ffmpeg.
+///
+/// # Fields
+/// * `timestamp` - The position of the frame in the video, in seconds
+/// * `image` - The frame
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoFrame {
+    pub timestamp: f32,
+    pub image: DynamicImage,
+}
+
+/// Picks evenly spaced frames, always including the first and last
+///
+/// # Arguments
+/// * `frames` - The decoded frames in playback order
+/// * `count` - How many frames to keep
+///
+/// # Returns
+/// The kept frames in playback order, or all frames if there are at most `count`
+#[cfg(feature = "image")]
+pub fn sample_frames_evenly(mut frames: Vec<VideoFrame>, count: usize) -> Vec<VideoFrame> {
+    if frames.len() <= count {
+        return frames;
+    }
+    if count == 1 {
+        return vec![frames.swap_remove(frames.len() / 2)];
+    }
+
+    let last: usize = frames.len() - 1;
+    let keep: Vec<usize> = (0..count).map(|i| (i * last + (count - 1) / 2) / (count - 1)).collect();
+    frames
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| keep.contains(index))
+        .map(|(_, frame)| frame)
+        .collect()
+}
+
+/// One message of a conversation
+///
+/// # Fields
+/// * `role` - Who wrote the message, e.g. `customer` or `agent`
+/// * `content` - The text of the message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatTurn {
+    pub role: String,
+    pub content: String,
+}
+
+impl ChatTurn {
+    /// Creates a turn from its role and content
+    pub fn new(role: impl Into<String>, content: impl Into<String>) -> Self {
+        Self { role: role.into(), content: content.into() }
+    }
+}
+
+/// How a conversation is written out for the LLM
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConversationFormat {
+    /// A transcript with numbered turns, speaker labels and separators
+    #[default]
+    Transcript,
+    /// A JSON array of `{"role", "content"}` objects, one per turn
+    Json,
+}
+
+/// Writes a conversation out as text for the LLM
+///
+/// # Arguments
+/// * `turns` - The turns in the order they were written
+/// * `format` - How to write them out
+///
+/// # Returns
+/// The rendered conversation
+pub fn render_conversation(turns: &[ChatTurn], format: ConversationFormat) -> String {
+    match format {
+        ConversationFormat::Transcript => turns
+            .iter()
+            .enumerate()
+            .map(|(index, turn)| format!("[Turn {}] {}:\n{}", index + 1, turn.role, turn.content.trim()))
+            .collect::<Vec<String>>()
+            .join("\n---\n"),
+        ConversationFormat::Json => serde_json::to_string_pretty(turns).unwrap_or_default(),
+    }
+}
+
+/// Serializes byte buffers as base64 strings instead of number arrays
+pub(crate) mod base64_bytes {
+    use base64::prelude::*;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64_STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded: String = String::deserialize(deserializer)?;
+        BASE64_STANDARD
+            .decode(encoded)
+            .map_err(serde::de::Error::custom)
+    }
+}
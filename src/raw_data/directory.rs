@@ -0,0 +1,291 @@
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use anyhow::{Error, Result};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+use crate::raw_data::decode_image_with_orientation;
+use crate::vector::{Vector, METADATA_FILE_SIZE, METADATA_MODIFIED_AT, METADATA_SOURCE};
+
+/// Extensions of the image files loaded by default
+const DEFAULT_IMAGE_EXTENSIONS: [&str; 8] = ["png", "jpg", "jpeg", "gif", "webp", "bmp", "tif", "tiff"];
+
+/// How a directory of images is walked and loaded
+///
+/// # Fields
+/// * `recursive` - Whether subdirectories are walked too, false by default
+/// * `extensions` - The lowercase file extensions loaded, common image formats by default
+/// * `skip_undecodable` - Whether unreadable files are skipped with a warning instead of failing, true by default
+/// * `limit` - The most images loaded, all by default
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageDirectoryOptions {
+    recursive: bool,
+    extensions: Vec<String>,
+    skip_undecodable: bool,
+    limit: Option<usize>,
+}
+
+impl Default for ImageDirectoryOptions {
+    fn default() -> Self {
+        Self {
+            recursive: false,
+            extensions: DEFAULT_IMAGE_EXTENSIONS.iter().map(|extension| extension.to_string()).collect(),
+            skip_undecodable: true,
+            limit: None,
+        }
+    }
+}
+
+impl ImageDirectoryOptions {
+    /// Creates options loading every image directly in the directory
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Walks subdirectories too
+    pub fn with_recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Loads only files with these extensions, compared case-insensitively
+    pub fn with_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.extensions = extensions
+            .into_iter()
+            .map(|extension| extension.as_ref().trim_start_matches('.').to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Skips files that cannot be read or decoded with a warning, or fails on the first one
+    pub fn with_skip_undecodable(mut self, skip_undecodable: bool) -> Self {
+        self.skip_undecodable = skip_undecodable;
+        self
+    }
+
+    /// Stops after `limit` images were loaded
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+/// Loads the images of a directory as vectors
+///
+/// Files are visited in path order, so the same directory always loads in the
+/// same order. Each vector records its path under `METADATA_SOURCE`, its file
+/// size under `METADATA_FILE_SIZE` and its modification time under
+/// `METADATA_MODIFIED_AT`.
+///
+/// # Arguments
+/// * `path` - The directory to load
+/// * `options` - Which files to load and how to handle undecodable ones
+///
+/// # Returns
+/// The vectors, or an error if the directory cannot be walked or, unless skipped, a file cannot be decoded
+pub fn load_image_directory(path: impl AsRef<Path>, options: &ImageDirectoryOptions) -> Result<Vec<Vector<DynamicImage>>, Error> {
+    stream_image_directory(path, options)?.collect()
+}
+
+/// Walks a directory like `load_image_directory`, decoding each image only when it is reached
+///
+/// The file list is gathered up front, so only one decoded image is held at a
+/// time. When files are not skipped, the first undecodable one yields an error
+/// and ends the stream.
+///
+/// # Arguments
+/// * `path` - The directory to load
+/// * `options` - Which files to load and how to handle undecodable ones
+///
+/// # Returns
+/// An iterator over the vectors, or an error if the directory cannot be walked
+pub fn stream_image_directory(path: impl AsRef<Path>, options: &ImageDirectoryOptions) -> Result<ImageDirectoryStream, Error> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    collect_image_paths(path.as_ref(), options, &mut paths)?;
+    paths.sort();
+
+    Ok(ImageDirectoryStream {
+        paths: paths.into_iter(),
+        skip_undecodable: options.skip_undecodable,
+        remaining: options.limit,
+    })
+}
+
+/// Lists the images of a directory like `load_image_directory`, without decoding any
+///
+/// Each vector holds only the path and size of its file, and the image is
+/// decoded when it is vectorized, so a batch of thousands of images needs
+/// memory for the items in flight only. Files are not opened here, so
+/// undecodable ones fail when they are vectorized, whatever
+/// `skip_undecodable` says.
+///
+/// # Arguments
+/// * `path` - The directory to list
+/// * `options` - Which files to list
+///
+/// # Returns
+/// The vectors, or an error if the directory cannot be walked
+pub fn list_image_directory(path: impl AsRef<Path>, options: &ImageDirectoryOptions) -> Result<Vec<Vector<ImageFile>>, Error> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    collect_image_paths(path.as_ref(), options, &mut paths)?;
+    paths.sort();
+
+    paths
+        .into_iter()
+        .take(options.limit.unwrap_or(usize::MAX))
+        .map(Vector::from_image_path)
+        .collect()
+}
+
+/// An image file on disk, read and decoded only when it is vectorized
+///
+/// Only the path and file size are held, so `Vector<ImageFile>` stays small
+/// however large the image is. The decoded pixels live while the requests of
+/// the item are built and are dropped once the image is encoded.
+///
+/// # Fields
+/// * `path` - Where the image file is
+/// * `file_size` - The size of the file in bytes when it was listed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageFile {
+    path: PathBuf,
+    file_size: u64,
+}
+
+impl ImageFile {
+    /// Refers to an image file without reading it
+    ///
+    /// # Arguments
+    /// * `path` - The path of the image file
+    ///
+    /// # Returns
+    /// The image file, or an error if it does not exist or is not a file
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path: PathBuf = path.into();
+        let metadata: std::fs::Metadata = std::fs::metadata(&path)
+            .map_err(|e| Error::msg(format!("Failed to read image {}: {}", path.display(), e)))?;
+        if !metadata.is_file() {
+            return Err(Error::msg(format!("Image {} is not a file", path.display())));
+        }
+
+        Ok(Self { path, file_size: metadata.len() })
+    }
+
+    /// Returns the path of the image file
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the size of the file in bytes when it was listed
+    pub fn get_file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    /// Reads and decodes the image
+    ///
+    /// # Returns
+    /// The image as stored and its EXIF orientation, or an error if it cannot be read or decoded
+    pub fn load(&self) -> Result<(DynamicImage, u8), Error> {
+        let bytes: Vec<u8> = std::fs::read(&self.path)
+            .map_err(|e| Error::msg(format!("Failed to read image {}: {}", self.path.display(), e)))?;
+        decode_image_with_orientation(&bytes).map_err(|e| Error::msg(format!("{}: {}", self.path.display(), e)))
+    }
+}
+
+/// The images of a directory, decoded one at a time
+#[derive(Debug)]
+pub struct ImageDirectoryStream {
+    paths: std::vec::IntoIter<PathBuf>,
+    skip_undecodable: bool,
+    remaining: Option<usize>,
+}
+
+impl Iterator for ImageDirectoryStream {
+    type Item = Result<Vector<DynamicImage>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == Some(0) {
+            return None;
+        }
+
+        for path in self.paths.by_ref() {
+            match load_image_file(&path) {
+                Ok(vector) => {
+                    if let Some(remaining) = self.remaining.as_mut() {
+                        *remaining -= 1;
+                    }
+                    return Some(Ok(vector));
+                }
+                Err(e) if self.skip_undecodable => log::warn!("Skipping {}: {}", path.display(), e),
+                Err(e) => {
+                    self.remaining = Some(0);
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Adds the image files under a directory, descending into subdirectories when recursive.
+fn collect_image_paths(directory: &Path, options: &ImageDirectoryOptions, paths: &mut Vec<PathBuf>) -> Result<(), Error> {
+    let entries = std::fs::read_dir(directory)
+        .map_err(|e| Error::msg(format!("Failed to read directory {}: {}", directory.display(), e)))?;
+
+    for entry in entries {
+        let path: PathBuf = entry
+            .map_err(|e| Error::msg(format!("Failed to read directory {}: {}", directory.display(), e)))?
+            .path();
+        if path.is_dir() {
+            if options.recursive {
+                collect_image_paths(&path, options, paths)?;
+            }
+            continue;
+        }
+
+        let extension: Option<String> = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase);
+        if extension.is_some_and(|extension| options.extensions.contains(&extension)) {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Decodes one image file and records its size and modification time.
+fn load_image_file(path: &Path) -> Result<Vector<DynamicImage>, Error> {
+    let bytes: Vec<u8> = std::fs::read(path)
+        .map_err(|e| Error::msg(format!("Failed to read image {}: {}", path.display(), e)))?;
+    let mut vector: Vector<DynamicImage> = Vector::from_bytes(&bytes)
+        .map_err(|e| Error::msg(format!("{}: {}", path.display(), e)))?;
+    record_file_metadata(&mut vector, path, bytes.len() as u64)?;
+
+    Ok(vector)
+}
+
+/// Records the path, size and modification time of the file a vector was loaded from.
+pub(crate) fn record_file_metadata<T>(vector: &mut Vector<T>, path: &Path, file_size: u64) -> Result<(), Error> {
+    vector.set_metadata(METADATA_SOURCE, path.display().to_string());
+    vector.set_metadata(METADATA_FILE_SIZE, file_size.to_string());
+
+    let metadata: std::fs::Metadata = std::fs::metadata(path)
+        .map_err(|e| Error::msg(format!("Failed to read image {}: {}", path.display(), e)))?;
+    if let Some(modified_at) = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+    {
+        vector.set_metadata(METADATA_MODIFIED_AT, modified_at.as_secs().to_string());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,241 @@
+use std::io::Read;
+
+use flate2::read::ZlibDecoder;
+use serde::{Deserialize, Serialize};
+
+/// The file format of a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DocumentFormat {
+    Pdf,
+}
+
+/// Why no text could be extracted from a document
+///
+/// Documents without a text layer, such as scans, can still be rendered to
+/// images and sent through the image pipeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DocumentError {
+    /// The document is well formed but contains no extractable text
+    NoTextLayer,
+    /// The document is encrypted
+    Encrypted,
+    /// The document cannot be parsed
+    Corrupt(String),
+}
+
+impl std::fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DocumentError::NoTextLayer => write!(f, "The document has no text layer"),
+            DocumentError::Encrypted => write!(f, "The document is encrypted"),
+            DocumentError::Corrupt(reason) => write!(f, "The document is corrupt: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for DocumentError {}
+
+/// Extracts the plain text of a document
+///
+/// Only text drawn with the standard PDF text operators is found; text in
+/// fonts with custom encodings may come out garbled. Whitespace is normalized
+/// to single spaces within lines and empty lines are dropped.
+///
+/// # Arguments
+/// * `bytes` - The document file contents
+/// * `format` - The file format of `bytes`
+///
+/// # Returns
+/// The text, or a `DocumentError` saying why there is none
+pub fn extract_text(bytes: &[u8], format: DocumentFormat) -> Result<String, DocumentError> {
+    let text: String = match format {
+        DocumentFormat::Pdf => extract_pdf_text(bytes)?,
+    };
+    let text: String = normalize_whitespace(&text);
+    if text.is_empty() {
+        return Err(DocumentError::NoTextLayer);
+    }
+
+    Ok(text)
+}
+
+/// Collapses runs of whitespace within lines and drops empty lines.
+fn normalize_whitespace(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<&str>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Returns the position of the first occurrence of `needle` at or after `from`.
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|position| position + from)
+}
+
+/// Extracts the text of every content stream of a PDF.
+fn extract_pdf_text(bytes: &[u8]) -> Result<String, DocumentError> {
+    if !bytes.starts_with(b"%PDF-") {
+        return Err(DocumentError::Corrupt("missing %PDF header".to_string()));
+    }
+    if find(bytes, b"endobj", 0).is_none() {
+        return Err(DocumentError::Corrupt("no objects found".to_string()));
+    }
+    if find(bytes, b"/Encrypt", 0).is_some() {
+        return Err(DocumentError::Encrypted);
+    }
+
+    let mut text: String = String::new();
+    let mut position: usize = 0;
+    while let Some(start) = find(bytes, b"stream", position) {
+        // `endstream` also contains `stream`
+        if bytes[..start].ends_with(b"end") {
+            position = start + 6;
+            continue;
+        }
+        let dictionary_start: usize = bytes[..start]
+            .windows(3)
+            .rposition(|window| window == b"obj")
+            .unwrap_or(0);
+        let dictionary: &[u8] = &bytes[dictionary_start..start];
+
+        let mut data_start: usize = start + 6;
+        if bytes.get(data_start) == Some(&b'\r') {
+            data_start += 1;
+        }
+        if bytes.get(data_start) == Some(&b'\n') {
+            data_start += 1;
+        }
+        let data_end: usize = find(bytes, b"endstream", data_start)
+            .ok_or_else(|| DocumentError::Corrupt("unterminated stream".to_string()))?;
+        position = data_end + 9;
+
+        let is_flate: bool = find(dictionary, b"/FlateDecode", 0).is_some();
+        let is_other_filter: bool = find(dictionary, b"/Filter", 0).is_some() && !is_flate;
+        let is_binary: bool = [b"/Image".as_slice(), b"/XRef", b"/ObjStm", b"/FontFile"]
+            .iter()
+            .any(|name| find(dictionary, name, 0).is_some());
+        if is_other_filter || is_binary {
+            continue;
+        }
+
+        let data: &[u8] = &bytes[data_start..data_end];
+        if is_flate {
+            let mut inflated: Vec<u8> = Vec::new();
+            // Streams that fail to inflate are skipped like unsupported ones
+            if ZlibDecoder::new(data).read_to_end(&mut inflated).is_ok() {
+                extract_content_text(&inflated, &mut text);
+            }
+        } else {
+            extract_content_text(data, &mut text);
+        }
+    }
+
+    Ok(text)
+}
+
+/// Appends the strings drawn between `BT` and `ET` in a content stream.
+fn extract_content_text(content: &[u8], text: &mut String) {
+    let mut in_text: bool = false;
+    let mut index: usize = 0;
+    let mut token: Vec<u8> = Vec::new();
+    while index < content.len() {
+        let byte: u8 = content[index];
+        match byte {
+            b'(' if in_text => {
+                let (string, end) = read_literal_string(content, index + 1);
+                text.push_str(&String::from_utf8_lossy(&string));
+                index = end;
+                continue;
+            }
+            // Large negative kerning in a TJ array usually stands for a space
+            b'-' if in_text && content.get(index + 1).is_some_and(u8::is_ascii_digit) => {
+                let end: usize = content[index + 1..]
+                    .iter()
+                    .position(|byte| !byte.is_ascii_digit() && *byte != b'.')
+                    .map_or(content.len(), |position| position + index + 1);
+                if std::str::from_utf8(&content[index..end])
+                    .ok()
+                    .and_then(|number| number.parse::<f32>().ok())
+                    .is_some_and(|kerning| kerning < -200.0)
+                {
+                    text.push(' ');
+                }
+                index = end;
+                continue;
+            }
+            _ if byte.is_ascii_whitespace() || b"[]<>/".contains(&byte) => {
+                match token.as_slice() {
+                    b"BT" => in_text = true,
+                    b"ET" => {
+                        in_text = false;
+                        text.push('\n');
+                    }
+                    b"Td" | b"TD" | b"T*" | b"'" | b"\"" if in_text => text.push('\n'),
+                    b"Tj" | b"TJ" if in_text => text.push(' '),
+                    _ => {}
+                }
+                token.clear();
+            }
+            _ => token.push(byte),
+        }
+        index += 1;
+    }
+}
+
+/// Reads a literal string whose opening parenthesis precedes `start`.
+///
+/// Returns the unescaped bytes and the position after the closing parenthesis.
+fn read_literal_string(content: &[u8], start: usize) -> (Vec<u8>, usize) {
+    let mut string: Vec<u8> = Vec::new();
+    let mut depth: usize = 1;
+    let mut index: usize = start;
+    while index < content.len() {
+        let byte: u8 = content[index];
+        match byte {
+            b'\\' => {
+                index += 1;
+                match content.get(index) {
+                    Some(b'n') => string.push(b'\n'),
+                    Some(b'r') => string.push(b'\r'),
+                    Some(b't') => string.push(b'\t'),
+                    Some(digit @ b'0'..=b'7') => {
+                        let mut value: u32 = (digit - b'0') as u32;
+                        for _ in 0..2 {
+                            match content.get(index + 1) {
+                                Some(next @ b'0'..=b'7') => {
+                                    value = value * 8 + (next - b'0') as u32;
+                                    index += 1;
+                                }
+                                _ => break,
+                            }
+                        }
+                        string.push(value as u8);
+                    }
+                    Some(b'\r') | Some(b'\n') => {}
+                    Some(other) => string.push(*other),
+                    None => break,
+                }
+            }
+            b'(' => {
+                depth += 1;
+                string.push(byte);
+            }
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return (string, index + 1);
+                }
+                string.push(byte);
+            }
+            _ => string.push(byte),
+        }
+        index += 1;
+    }
+
+    (string, index)
+}
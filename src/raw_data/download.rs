@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use image::DynamicImage;
+use reqwest::header::CONTENT_TYPE;
+
+use crate::error::DimError;
+
+/// Limits on downloading an image
+///
+/// # Fields
+/// * `max_bytes` - The largest download accepted, 20 MiB by default
+/// * `timeout` - The time allowed for the whole download, 30 seconds by default
+/// * `max_redirects` - How many redirects are followed, 5 by default
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageDownloadOptions {
+    max_bytes: u64,
+    timeout: Duration,
+    max_redirects: usize,
+}
+
+impl Default for ImageDownloadOptions {
+    fn default() -> Self {
+        Self {
+            max_bytes: 20 * 1024 * 1024,
+            timeout: Duration::from_secs(30),
+            max_redirects: 5,
+        }
+    }
+}
+
+impl ImageDownloadOptions {
+    /// Creates options with the default limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rejects downloads larger than `max_bytes`, before decoding them
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Bounds the whole download, from connecting to reading the last byte
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Follows at most `max_redirects` redirects, none when 0
+    pub fn with_max_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+}
+
+/// Downloads and decodes an image
+///
+/// Responses whose content type is neither `image/*` nor
+/// `application/octet-stream` are rejected, as are responses larger than the
+/// size limit, whether declared in `Content-Length` or found while reading.
+///
+/// # Arguments
+/// * `url` - Where to download the image from
+/// * `options` - The size, time and redirect limits
+///
+/// # Returns
+/// The decoded image, or an error naming the URL and what went wrong,
+/// `DimError::Timeout` when the download took too long
+pub async fn load_image_from_url(url: &str, options: &ImageDownloadOptions) -> Result<DynamicImage, DimError> {
+    let redirect: reqwest::redirect::Policy = match options.max_redirects {
+        0 => reqwest::redirect::Policy::none(),
+        max_redirects => reqwest::redirect::Policy::limited(max_redirects),
+    };
+    let client: reqwest::Client = reqwest::Client::builder()
+        .timeout(options.timeout)
+        .redirect(redirect)
+        .build()
+        .map_err(|e| DimError::msg(format!("Failed to build HTTP client: {}", e)))?;
+
+    let mut response: reqwest::Response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| download_error(url, e))?;
+    // Redirects beyond the limit come back as they are
+    if response.status().is_redirection() {
+        return Err(DimError::msg(format!(
+            "Failed to download image from {}: redirected more than {} times",
+            url, options.max_redirects
+        )));
+    }
+
+    let content_type: String = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if !content_type.starts_with("image/") && !content_type.starts_with("application/octet-stream") {
+        return Err(DimError::msg(format!(
+            "Refusing to decode {} as an image, its content type is {:?}",
+            url, content_type
+        )));
+    }
+    if let Some(length) = response.content_length().filter(|length| *length > options.max_bytes) {
+        return Err(DimError::msg(format!(
+            "Image at {} is {} bytes, above the limit of {} bytes",
+            url, length, options.max_bytes
+        )));
+    }
+
+    let mut bytes: Vec<u8> = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| download_error(url, e))?
+    {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() as u64 > options.max_bytes {
+            return Err(DimError::msg(format!(
+                "Image at {} is above the limit of {} bytes",
+                url, options.max_bytes
+            )));
+        }
+    }
+
+    image::load_from_memory(&bytes).map_err(|e| DimError::msg(format!("Failed to decode image from {}: {}", url, e)))
+}
+
+/// Wraps a failed download, keeping timeouts apart from other failures.
+fn download_error(url: &str, error: reqwest::Error) -> DimError {
+    let message: String = format!("Failed to download image from {}: {}", url, error);
+    match error.is_timeout() {
+        true => DimError::Timeout { message },
+        false => DimError::msg(message),
+    }
+}
@@ -0,0 +1,186 @@
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, DynamicImage, Frames};
+
+use crate::raw_data::{sample_frames_evenly, VideoFrame};
+
+/// Where the video to extract frames from is read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VideoSource {
+    /// A video file on disk
+    Path(PathBuf),
+    /// The contents of a video file
+    Bytes(Vec<u8>),
+}
+
+impl From<&Path> for VideoSource {
+    fn from(path: &Path) -> Self {
+        VideoSource::Path(path.to_path_buf())
+    }
+}
+
+impl From<PathBuf> for VideoSource {
+    fn from(path: PathBuf) -> Self {
+        VideoSource::Path(path)
+    }
+}
+
+impl From<&[u8]> for VideoSource {
+    fn from(bytes: &[u8]) -> Self {
+        VideoSource::Bytes(bytes.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for VideoSource {
+    fn from(bytes: Vec<u8>) -> Self {
+        VideoSource::Bytes(bytes)
+    }
+}
+
+/// Which frames of a video are extracted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameStrategy {
+    /// The frame shown at 0, N, 2N, ... seconds
+    EveryNSeconds(f32),
+    /// Evenly spaced frames, always including the first and last
+    FixedCount(usize),
+    /// Every frame stored whole rather than as a change to an earlier one
+    Keyframes,
+}
+
+/// Why no frames could be extracted from a video
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VideoError {
+    /// The file is a known video container whose codec cannot be decoded here
+    UnsupportedCodec(String),
+    /// The file is not a recognized video or cannot be decoded
+    Corrupt(String),
+    /// The strategy asks for something impossible, such as zero frames
+    InvalidStrategy(String),
+    /// The file cannot be read
+    Io(String),
+}
+
+impl std::fmt::Display for VideoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VideoError::UnsupportedCodec(format) => write!(
+                f,
+                "Cannot decode {} video, decode it with a tool such as ffmpeg and pass the frames instead",
+                format
+            ),
+            VideoError::Corrupt(reason) => write!(f, "The video is corrupt: {}", reason),
+            VideoError::InvalidStrategy(reason) => write!(f, "Invalid frame strategy: {}", reason),
+            VideoError::Io(reason) => write!(f, "Failed to read the video: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for VideoError {}
+
+/// Extracts frames from a video
+///
+/// Animated GIF, PNG (APNG) and WebP files are decoded; every frame of them
+/// is stored whole, so `Keyframes` returns all frames. Other containers such
+/// as MP4, WebM, MKV, MOV and AVI are recognized but their codecs are not
+/// decoded, and yield `VideoError::UnsupportedCodec`. The frames can be passed
+/// to `Vector::from_image`, `Vector::from_images` or, as `VideoFrame`s, to
+/// `vectorize_video_concurrently`.
+///
+/// # Arguments
+/// * `source` - A path or the bytes of the video
+/// * `strategy` - Which frames to extract
+///
+/// # Returns
+/// The frames in playback order with their position in the video, or a `VideoError` saying why there are none
+pub fn extract_frames(source: impl Into<VideoSource>, strategy: FrameStrategy) -> Result<Vec<(Duration, DynamicImage)>, VideoError> {
+    let bytes: Vec<u8> = match source.into() {
+        VideoSource::Path(path) => std::fs::read(&path).map_err(|e| VideoError::Io(format!("{}: {}", path.display(), e)))?,
+        VideoSource::Bytes(bytes) => bytes,
+    };
+    let (frames, duration): (Vec<(Duration, DynamicImage)>, Duration) = decode_frames(&bytes)?;
+
+    match strategy {
+        FrameStrategy::EveryNSeconds(seconds) => {
+            if !seconds.is_finite() || seconds <= 0.0 {
+                return Err(VideoError::InvalidStrategy(format!("the interval must be positive, got {}", seconds)));
+            }
+            let interval: Duration = Duration::from_secs_f32(seconds);
+            let mut sampled: Vec<(Duration, DynamicImage)> = Vec::new();
+            let mut position: Duration = Duration::ZERO;
+            while position < duration || sampled.is_empty() {
+                // The frame on screen is the last one that started at or before the position
+                let shown: usize = frames.partition_point(|(start, _)| *start <= position).max(1) - 1;
+                sampled.push((position, frames[shown].1.clone()));
+                position += interval;
+            }
+            Ok(sampled)
+        }
+        FrameStrategy::FixedCount(0) => Err(VideoError::InvalidStrategy("at least one frame must be extracted".to_string())),
+        FrameStrategy::FixedCount(count) => {
+            let frames: Vec<VideoFrame> = frames
+                .into_iter()
+                .map(|(start, image)| VideoFrame { timestamp: start.as_secs_f32(), image })
+                .collect();
+            Ok(sample_frames_evenly(frames, count)
+                .into_iter()
+                .map(|frame| (Duration::from_secs_f32(frame.timestamp), frame.image))
+                .collect())
+        }
+        FrameStrategy::Keyframes => Ok(frames),
+    }
+}
+
+/// Decodes every frame with its start time, and the total duration.
+fn decode_frames(bytes: &[u8]) -> Result<(Vec<(Duration, DynamicImage)>, Duration), VideoError> {
+    let corrupt = |e: image::ImageError| VideoError::Corrupt(e.to_string());
+    let frames: Frames = if bytes.starts_with(b"GIF8") {
+        GifDecoder::new(Cursor::new(bytes)).map_err(corrupt)?.into_frames()
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        PngDecoder::new(Cursor::new(bytes)).map_err(corrupt)?.apng().map_err(corrupt)?.into_frames()
+    } else if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        WebPDecoder::new(Cursor::new(bytes)).map_err(corrupt)?.into_frames()
+    } else {
+        return Err(match container_name(bytes) {
+            Some(container) => VideoError::UnsupportedCodec(container.to_string()),
+            None => VideoError::Corrupt("not a recognized video format".to_string()),
+        });
+    };
+
+    let mut decoded: Vec<(Duration, DynamicImage)> = Vec::new();
+    let mut position: Duration = Duration::ZERO;
+    for frame in frames {
+        let frame: image::Frame = frame.map_err(corrupt)?;
+        let (numerator, denominator): (u32, u32) = frame.delay().numer_denom_ms();
+        let delay: Duration = Duration::from_secs_f64(numerator as f64 / denominator.max(1) as f64 / 1000.0);
+        decoded.push((position, DynamicImage::ImageRgba8(frame.into_buffer())));
+        position += delay;
+    }
+    if decoded.is_empty() {
+        return Err(VideoError::Corrupt("the video has no frames".to_string()));
+    }
+
+    Ok((decoded, position))
+}
+
+/// Names the video container of a file from its signature, if it is a known one.
+fn container_name(bytes: &[u8]) -> Option<&'static str> {
+    match bytes.get(4..8) {
+        Some(b"ftyp") if bytes.get(8..10) == Some(b"qt") => return Some("MOV"),
+        Some(b"ftyp") => return Some("MP4"),
+        _ => {}
+    }
+    if bytes.starts_with(b"\x1a\x45\xdf\xa3") {
+        return Some("WebM/MKV");
+    }
+    if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"AVI ") {
+        return Some("AVI");
+    }
+
+    None
+}
@@ -0,0 +1,205 @@
+/// Elements whose content is never text, such as scripts
+const SKIPPED_ELEMENTS: [&str; 8] = ["script", "style", "noscript", "template", "svg", "head", "iframe", "canvas"];
+/// Elements holding page chrome rather than content
+const BOILERPLATE_ELEMENTS: [&str; 6] = ["nav", "header", "footer", "aside", "form", "button"];
+/// Elements whose content may contain `<` without starting a tag
+const RAW_TEXT_ELEMENTS: [&str; 2] = ["script", "style"];
+/// Elements that start a new line
+const BLOCK_ELEMENTS: [&str; 28] = [
+    "address", "article", "blockquote", "br", "dd", "div", "dl", "dt", "figcaption", "figure",
+    "h1", "h2", "h3", "h4", "h5", "h6", "hr", "li", "main", "ol", "p", "pre", "section",
+    "table", "td", "th", "tr", "ul",
+];
+
+/// A piece of an HTML document
+enum Token<'a> {
+    Text(&'a str),
+    Open { name: String, self_closing: bool },
+    Close(String),
+}
+
+/// Extracts the readable text of an HTML page
+///
+/// Scripts, styles and page chrome such as navigation, headers, footers and
+/// forms are dropped. When the page has an `<article>`, or else a `<main>`,
+/// only its content is kept. Entities are decoded, block elements start new
+/// lines, whitespace within lines is collapsed and empty lines are dropped.
+///
+/// # Arguments
+/// * `html` - The HTML source of the page
+///
+/// # Returns
+/// The readable text, empty when the page has none
+pub fn extract_readable_text(html: &str) -> String {
+    let tokens: Vec<Token> = tokenize(html);
+    let root: Option<&str> = ["article", "main"].into_iter().find(|root| {
+        tokens
+            .iter()
+            .any(|token| matches!(token, Token::Open { name, .. } if name == root))
+    });
+
+    let mut text: String = String::new();
+    let mut root_depth: usize = 0;
+    let mut skip_depth: usize = 0;
+    for token in &tokens {
+        match token {
+            Token::Open { name, self_closing } => {
+                if !self_closing {
+                    if root == Some(name.as_str()) {
+                        root_depth += 1;
+                    }
+                    if is_skipped(name) {
+                        skip_depth += 1;
+                    }
+                }
+                if BLOCK_ELEMENTS.contains(&name.as_str()) {
+                    text.push('\n');
+                }
+            }
+            Token::Close(name) => {
+                if root == Some(name.as_str()) {
+                    root_depth = root_depth.saturating_sub(1);
+                }
+                if is_skipped(name) {
+                    skip_depth = skip_depth.saturating_sub(1);
+                }
+                if BLOCK_ELEMENTS.contains(&name.as_str()) {
+                    text.push('\n');
+                }
+            }
+            Token::Text(content) => {
+                if skip_depth == 0 && (root.is_none() || root_depth > 0) {
+                    // Line breaks in the source are plain whitespace in HTML
+                    text.push_str(&decode_entities(content).replace(['\n', '\r'], " "));
+                }
+            }
+        }
+    }
+
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<&str>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Returns whether the content of an element is left out.
+fn is_skipped(name: &str) -> bool {
+    SKIPPED_ELEMENTS.contains(&name) || BOILERPLATE_ELEMENTS.contains(&name)
+}
+
+/// Splits HTML into text and tags, dropping comments, doctypes and the content of raw text elements.
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+    // ASCII lowercasing keeps byte offsets, so positions in `lower` apply to `html`
+    let lower: String = html.to_ascii_lowercase();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut position: usize = 0;
+    while position < html.len() {
+        let Some(start) = html[position..].find('<').map(|offset| position + offset) else {
+            tokens.push(Token::Text(&html[position..]));
+            break;
+        };
+        if start > position {
+            tokens.push(Token::Text(&html[position..start]));
+        }
+        if lower[start..].starts_with("<!--") {
+            position = lower[start + 4..].find("-->").map_or(html.len(), |end| start + 4 + end + 3);
+            continue;
+        }
+        // A `<` not starting a tag, as in `a < b`, is text
+        let starts_tag: bool = html[start + 1..]
+            .chars()
+            .next()
+            .is_some_and(|next| next.is_ascii_alphabetic() || matches!(next, '/' | '!' | '?'));
+        let end: Option<usize> = html[start..].find('>').map(|offset| start + offset);
+        let Some(end) = end.filter(|_| starts_tag) else {
+            tokens.push(Token::Text(&html[start..start + 1]));
+            position = start + 1;
+            continue;
+        };
+        position = end + 1;
+
+        let inner: &str = &lower[start + 1..end];
+        let name: String = inner
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|character| character.is_ascii_alphanumeric())
+            .collect();
+        if name.is_empty() {
+            continue;
+        }
+        if inner.starts_with('/') {
+            tokens.push(Token::Close(name));
+            continue;
+        }
+
+        let self_closing: bool = inner.ends_with('/');
+        if !self_closing && RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+            let close: String = format!("</{}", name);
+            position = lower[position..].find(&close).map_or(html.len(), |offset| position + offset);
+        }
+        tokens.push(Token::Open { name, self_closing });
+    }
+
+    tokens
+}
+
+/// Decodes named and numeric character references, leaving unknown ones as they are.
+fn decode_entities(text: &str) -> String {
+    let mut decoded: String = String::with_capacity(text.len());
+    let mut rest: &str = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let character: Option<(char, usize)> = rest
+            .char_indices()
+            .take(12)
+            .find(|(_, character)| *character == ';')
+            .and_then(|(end, _)| decode_entity(&rest[1..end]).map(|character| (character, end + 1)));
+        match character {
+            Some((character, length)) => {
+                decoded.push(character);
+                rest = &rest[length..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    decoded
+}
+
+/// Decodes the name of one character reference, without `&` and `;`.
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code: u32 = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+
+    Some(match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "hellip" => '…',
+        "mdash" => '—',
+        "ndash" => '–',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "euro" => '€',
+        _ => return None,
+    })
+}
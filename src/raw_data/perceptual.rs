@@ -0,0 +1,83 @@
+use std::fmt;
+
+use image::imageops::FilterType;
+use image::{DynamicImage, GrayImage};
+use serde::{Deserialize, Serialize};
+
+use crate::vector::{Vector, VectorOperations};
+
+/// A 64-bit perceptual hash of an image
+///
+/// Unlike a byte or pixel hash, re-saved, recompressed or slightly edited
+/// copies of an image hash within a small Hamming distance of each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ImageHash(pub u64);
+
+impl ImageHash {
+    /// Counts the bits on which two hashes differ
+    ///
+    /// # Arguments
+    /// * `other` - The hash to compare with
+    ///
+    /// # Returns
+    /// The Hamming distance, 0 for identical hashes and at most 64
+    pub fn hamming_distance(&self, other: &ImageHash) -> u32 {
+        (self.0 ^ other.0).count_ones()
+    }
+}
+
+impl fmt::Display for ImageHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Computes the difference hash (dHash) of an image
+///
+/// The image is shrunk to 9x8 grayscale pixels and each bit records whether a
+/// pixel is brighter than its right neighbour, so the hash survives resizing,
+/// recompression and brightness changes. Use `image_sha256` for strict identity.
+///
+/// # Arguments
+/// * `image` - The image to hash
+///
+/// # Returns
+/// The 64-bit hash
+pub fn perceptual_hash(image: &DynamicImage) -> ImageHash {
+    let pixels: GrayImage = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let mut hash: u64 = 0;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if pixels[(x, y)][0] > pixels[(x + 1, y)][0] {
+                hash |= 1;
+            }
+        }
+    }
+
+    ImageHash(hash)
+}
+
+/// Finds every pair of images whose perceptual hashes are within a distance
+///
+/// # Arguments
+/// * `vectors` - The image vectors to compare
+/// * `max_distance` - The largest Hamming distance still counted as a duplicate
+///
+/// # Returns
+/// `(i, j, distance)` triples with `i < j`, ordered by `i` then `j`
+pub fn find_near_duplicates(vectors: &[Vector<DynamicImage>], max_distance: u32) -> Vec<(usize, usize, u32)> {
+    let hashes: Vec<ImageHash> = vectors.iter().map(|vector| perceptual_hash(vector.get_data())).collect();
+
+    let mut pairs: Vec<(usize, usize, u32)> = Vec::new();
+    for (i, first) in hashes.iter().enumerate() {
+        for (j, second) in hashes.iter().enumerate().skip(i + 1) {
+            let distance: u32 = first.hamming_distance(second);
+            if distance <= max_distance {
+                pairs.push((i, j, distance));
+            }
+        }
+    }
+
+    pairs
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Writes JSON records out as `key: value` lines for the LLM
+///
+/// Keys are sorted and nested objects are flattened into dotted keys, so the
+/// same record always renders the same way whatever its key order.
+///
+/// # Fields
+/// * `include` - The only fields to render, all fields when `None`
+/// * `exclude` - Fields never rendered, even when included
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordRenderer {
+    include: Option<Vec<String>>,
+    exclude: Vec<String>,
+}
+
+impl RecordRenderer {
+    /// Creates a renderer that renders every field
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders only the given fields
+    ///
+    /// # Arguments
+    /// * `fields` - Top-level keys, or dotted keys of nested fields such as `dimensions.width`
+    ///
+    /// # Returns
+    /// The renderer restricted to `fields`
+    pub fn include_fields(mut self, fields: &[&str]) -> Self {
+        self.include = Some(fields.iter().map(|field| field.to_string()).collect());
+        self
+    }
+
+    /// Never renders the given fields
+    ///
+    /// # Arguments
+    /// * `fields` - Top-level keys, or dotted keys of nested fields such as `dimensions.width`
+    ///
+    /// # Returns
+    /// The renderer with `fields` left out
+    pub fn exclude_fields(mut self, fields: &[&str]) -> Self {
+        self.exclude.extend(fields.iter().map(|field| field.to_string()));
+        self
+    }
+
+    /// Renders a record as sorted `key: value` lines
+    ///
+    /// Strings are written without quotes, arrays and other values as compact
+    /// JSON. A record that is not an object is rendered as a single `value` line.
+    ///
+    /// # Arguments
+    /// * `record` - The record to render
+    ///
+    /// # Returns
+    /// The rendered record
+    pub fn render(&self, record: &Value) -> String {
+        let mut lines: Vec<(String, String)> = Vec::new();
+        match record {
+            Value::Object(_) => self.flatten("", record, &mut lines),
+            _ => lines.push(("value".to_string(), render_value(record))),
+        }
+        lines.sort();
+
+        lines
+            .into_iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Describes the field selection, for the fingerprint of rendered records
+    pub fn describe(&self) -> String {
+        format!("include={:?};exclude={:?}", self.include, self.exclude)
+    }
+
+    /// Collects the selected leaves of an object under dotted keys.
+    fn flatten(&self, prefix: &str, value: &Value, lines: &mut Vec<(String, String)>) {
+        let Value::Object(map) = value else {
+            if self.selects(prefix) {
+                lines.push((prefix.to_string(), render_value(value)));
+            }
+            return;
+        };
+        for (key, value) in map {
+            let path: String = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+            if !self.exclude.contains(&path) {
+                self.flatten(&path, value, lines);
+            }
+        }
+    }
+
+    /// Returns whether a field, or the object containing it, is included.
+    fn selects(&self, path: &str) -> bool {
+        match &self.include {
+            None => true,
+            Some(include) => include
+                .iter()
+                .any(|field| path == field || path.starts_with(&format!("{}.", field))),
+        }
+    }
+}
+
+/// Writes strings without quotes and anything else as compact JSON.
+fn render_value(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        _ => value.to_string(),
+    }
+}
@@ -0,0 +1,234 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use anyhow::{Error, Result};
+use serde_json::Value;
+
+use crate::vector::{Vector, METADATA_ID, METADATA_SOURCE};
+
+/// The vectors of a dataset, each with the line it starts on
+type NumberedRecords = Box<dyn Iterator<Item = (usize, Result<Vector<String>, Error>)> + Send>;
+
+/// How malformed lines and rows of a text dataset are handled
+///
+/// # Fields
+/// * `skip_malformed` - Whether malformed lines are skipped with a warning instead of failing, false by default
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextDatasetOptions {
+    skip_malformed: bool,
+}
+
+impl TextDatasetOptions {
+    /// Creates options failing on the first malformed line
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Skips malformed lines with a warning, or fails on the first one
+    pub fn with_skip_malformed(mut self, skip_malformed: bool) -> Self {
+        self.skip_malformed = skip_malformed;
+        self
+    }
+}
+
+/// Loads the texts of a JSONL file as vectors, one per line
+///
+/// The identifier, when present, is stored under `METADATA_ID` and
+/// `path:line` under `METADATA_SOURCE`. Blank lines are ignored.
+///
+/// # Arguments
+/// * `path` - The file to read
+/// * `text_field` - The field holding the text, which must be a string
+/// * `id_field` - The field holding the identifier, a string or number
+/// * `options` - Whether malformed lines are skipped
+///
+/// # Returns
+/// The vectors, or an error naming the line of the first malformed one unless skipped
+pub fn load_texts_jsonl(
+    path: impl AsRef<Path>,
+    text_field: &str,
+    id_field: Option<&str>,
+    options: &TextDatasetOptions,
+) -> Result<Vec<Vector<String>>, Error> {
+    read_texts_jsonl(path, text_field, id_field, options)?.collect()
+}
+
+/// Streams the texts of a JSONL file like `load_texts_jsonl`, one line at a time
+///
+/// # Arguments
+/// * `path` - The file to read
+/// * `text_field` - The field holding the text, which must be a string
+/// * `id_field` - The field holding the identifier, a string or number
+/// * `options` - Whether malformed lines are skipped
+///
+/// # Returns
+/// An iterator over the vectors, or an error if the file cannot be opened
+pub fn read_texts_jsonl(
+    path: impl AsRef<Path>,
+    text_field: &str,
+    id_field: Option<&str>,
+    options: &TextDatasetOptions,
+) -> Result<TextDatasetReader, Error> {
+    let path: &Path = path.as_ref();
+    let file: File = File::open(path).map_err(|e| Error::msg(format!("Failed to open {}: {}", path.display(), e)))?;
+    let source: String = path.display().to_string();
+    let text_field: String = text_field.to_string();
+    let id_field: Option<String> = id_field.map(str::to_string);
+
+    let records = BufReader::new(file)
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|(_, line)| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+        .map(move |(line_number, line)| {
+            let record = line
+                .map_err(Error::from)
+                .and_then(|line| parse_text_line(&line, &text_field, id_field.as_deref(), &source, line_number));
+            (line_number, record)
+        });
+
+    Ok(TextDatasetReader {
+        records: Box::new(records),
+        skip_malformed: options.skip_malformed,
+        finished: false,
+    })
+}
+
+/// Loads the texts of a CSV file with a header row as vectors, one per row
+///
+/// The identifier, when present and not empty, is stored under `METADATA_ID`
+/// and `path:line` under `METADATA_SOURCE`.
+///
+/// # Arguments
+/// * `path` - The file to read
+/// * `text_column` - The header of the column holding the text
+/// * `id_column` - The header of the column holding the identifier
+/// * `options` - Whether malformed rows are skipped
+///
+/// # Returns
+/// The vectors, or an error naming the line of the first malformed row unless skipped
+pub fn load_texts_csv(
+    path: impl AsRef<Path>,
+    text_column: &str,
+    id_column: Option<&str>,
+    options: &TextDatasetOptions,
+) -> Result<Vec<Vector<String>>, Error> {
+    read_texts_csv(path, text_column, id_column, options)?.collect()
+}
+
+/// Streams the texts of a CSV file like `load_texts_csv`, one row at a time
+///
+/// # Arguments
+/// * `path` - The file to read
+/// * `text_column` - The header of the column holding the text
+/// * `id_column` - The header of the column holding the identifier
+/// * `options` - Whether malformed rows are skipped
+///
+/// # Returns
+/// An iterator over the vectors, or an error if the file cannot be opened or a column is missing
+pub fn read_texts_csv(
+    path: impl AsRef<Path>,
+    text_column: &str,
+    id_column: Option<&str>,
+    options: &TextDatasetOptions,
+) -> Result<TextDatasetReader, Error> {
+    let path: &Path = path.as_ref();
+    let mut reader = csv::Reader::from_path(path).map_err(|e| Error::msg(format!("Failed to open {}: {}", path.display(), e)))?;
+    let header: Vec<String> = reader.headers()?.iter().map(str::to_string).collect();
+    let column_index = |column: &str| {
+        header
+            .iter()
+            .position(|name| name == column)
+            .ok_or_else(|| Error::msg(format!("{} has no column '{}'", path.display(), column)))
+    };
+    let text_index: usize = column_index(text_column)?;
+    let id_index: Option<usize> = id_column.map(column_index).transpose()?;
+    let source: String = path.display().to_string();
+
+    let records = reader.into_records().map(move |record| match record {
+        Ok(record) => {
+            let line_number: usize = record.position().map_or(0, |position| position.line() as usize);
+            let id: Option<String> = id_index
+                .and_then(|index| record.get(index))
+                .filter(|id| !id.is_empty())
+                .map(str::to_string);
+            let vector = record
+                .get(text_index)
+                .ok_or_else(|| Error::msg(format!("column '{}' is missing", header[text_index])))
+                .map(|text| text_vector(text, id, &source, line_number));
+            (line_number, vector)
+        }
+        Err(e) => {
+            let line_number: usize = e.position().map_or(0, |position| position.line() as usize);
+            (line_number, Err(Error::from(e)))
+        }
+    });
+
+    Ok(TextDatasetReader {
+        records: Box::new(records),
+        skip_malformed: options.skip_malformed,
+        finished: false,
+    })
+}
+
+/// Streaming iterator over the texts of a JSONL or CSV file
+///
+/// When malformed lines are not skipped, the first one yields an error naming
+/// its line and ends the stream.
+pub struct TextDatasetReader {
+    records: NumberedRecords,
+    skip_malformed: bool,
+    finished: bool,
+}
+
+impl Iterator for TextDatasetReader {
+    type Item = Result<Vector<String>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        for (line_number, record) in self.records.by_ref() {
+            match record {
+                Ok(vector) => return Some(Ok(vector)),
+                Err(e) if self.skip_malformed => log::warn!("Skipping malformed line {}: {}", line_number, e),
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(Error::msg(format!("line {}: {}", line_number, e))));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Reads the text and identifier of one JSONL line into a vector.
+pub(crate) fn parse_text_line(line: &str, text_field: &str, id_field: Option<&str>, source: &str, line_number: usize) -> Result<Vector<String>, Error> {
+    let record: Value = serde_json::from_str(line)?;
+    let text: &str = record
+        .get(text_field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error::msg(format!("field '{}' is missing or not a string", text_field)))?;
+    let id: Option<String> = match id_field.and_then(|id_field| record.get(id_field)) {
+        None | Some(Value::Null) => None,
+        Some(Value::String(id)) => Some(id.clone()),
+        Some(id @ Value::Number(_)) => Some(id.to_string()),
+        Some(_) => return Err(Error::msg(format!("field '{}' is not a string or number", id_field.unwrap_or_default()))),
+    };
+
+    Ok(text_vector(text, id, source, line_number))
+}
+
+/// Builds a text vector recording its identifier and where it was read from.
+fn text_vector(text: &str, id: Option<String>, source: &str, line_number: usize) -> Vector<String> {
+    let mut vector: Vector<String> = Vector::from_text(text.to_string());
+    if let Some(id) = id {
+        vector.set_metadata(METADATA_ID, id);
+    }
+    vector.set_metadata(METADATA_SOURCE, format!("{}:{}", source, line_number));
+
+    vector
+}
@@ -0,0 +1,44 @@
+use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+use tiktoken_rs::CoreBPE;
+
+/// Model name prefixes of OpenAI models too new for the tokenizer table, counted with `o200k`
+const OPENAI_MODEL_PREFIXES: [&str; 6] = ["gpt-", "chatgpt-", "o1", "o3", "o4", "text-embedding-"];
+
+/// Counts how many tokens a model reads a text as
+///
+/// OpenAI models are counted exactly with their tiktoken byte-pair encoding,
+/// `cl100k_base` or `o200k_base` for current models; OpenAI models the table
+/// does not know yet use `o200k_base`. Other models fall back to one token
+/// per four characters.
+///
+/// # Arguments
+/// * `text` - The text to count
+/// * `model` - The model the text is sent to, optionally behind a provider prefix such as `openai/`
+///
+/// # Returns
+/// The number of tokens
+pub fn count_tokens(text: &str, model: &str) -> usize {
+    match openai_encoding(model) {
+        Some(encoding) => encoding.encode_with_special_tokens(text).len(),
+        None => text.chars().count().div_ceil(4),
+    }
+}
+
+/// Finds the byte-pair encoding of an OpenAI model.
+fn openai_encoding(model: &str) -> Option<&'static CoreBPE> {
+    let model: String = model.to_lowercase();
+    let model: &str = model.rsplit('/').next().unwrap_or_default();
+    let tokenizer: Tokenizer = match get_tokenizer(model) {
+        Some(tokenizer) => tokenizer,
+        None if OPENAI_MODEL_PREFIXES.iter().any(|prefix| model.starts_with(prefix)) => Tokenizer::O200kBase,
+        None => return None,
+    };
+
+    Some(match tokenizer {
+        Tokenizer::O200kBase => tiktoken_rs::o200k_base_singleton(),
+        Tokenizer::Cl100kBase => tiktoken_rs::cl100k_base_singleton(),
+        Tokenizer::P50kBase => tiktoken_rs::p50k_base_singleton(),
+        Tokenizer::P50kEdit => tiktoken_rs::p50k_edit_singleton(),
+        Tokenizer::R50kBase | Tokenizer::Gpt2 => tiktoken_rs::r50k_base_singleton(),
+    })
+}
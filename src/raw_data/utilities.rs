@@ -0,0 +1,239 @@
+#[cfg(feature = "image")]
+use anyhow::{Error, Result};
+#[cfg(feature = "image")]
+use base64::prelude::*;
+#[cfg(feature = "image")]
+use image::codecs::jpeg::JpegEncoder;
+#[cfg(feature = "image")]
+use image::codecs::png::PngEncoder;
+#[cfg(feature = "image")]
+use image::codecs::webp::WebPEncoder;
+#[cfg(feature = "image")]
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// The tokens every image costs, and all a low-detail image costs
+const IMAGE_BASE_TOKENS: u64 = 85;
+/// The tokens of each 512-pixel tile of a high-detail image
+const IMAGE_TILE_TOKENS: u64 = 170;
+
+/// The format images are encoded in before they are sent to a model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImageEncoding {
+    /// Lossless, best for screenshots, diagrams and text
+    Png,
+    /// Lossy with a quality from 1 to 100, typically 5 to 10 times smaller than PNG for photos
+    Jpeg { quality: u8 },
+    /// Lossless WebP, the only WebP encoding the `image` crate provides
+    WebP,
+}
+
+impl Default for ImageEncoding {
+    fn default() -> Self {
+        Self::Jpeg { quality: 85 }
+    }
+}
+
+impl ImageEncoding {
+    /// Returns the MIME type of images in this encoding
+    pub fn mime(&self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg { .. } => "image/jpeg",
+            Self::WebP => "image/webp",
+        }
+    }
+}
+
+/// How closely a vision model looks at each image, which sets its token cost
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImageDetail {
+    /// A fixed low-resolution view at a fraction of the tokens, enough for colors or overall style
+    Low,
+    /// The full image in tiles, for fine details and small text
+    #[default]
+    High,
+    /// The model picks the detail from the image size
+    Auto,
+}
+
+impl ImageDetail {
+    /// Estimates the prompt tokens of an image as OpenAI counts them
+    ///
+    /// `Auto` is counted like `High`, the most it can cost.
+    ///
+    /// # Arguments
+    /// * `width` - The width of the image as sent
+    /// * `height` - The height of the image as sent
+    ///
+    /// # Returns
+    /// The approximate number of tokens
+    pub fn approx_tokens(&self, width: u32, height: u32) -> u64 {
+        if *self == ImageDetail::Low || width == 0 || height == 0 {
+            return IMAGE_BASE_TOKENS;
+        }
+
+        // The image is fitted into 2048x2048, then its short side is cut to 768
+        let (mut width, mut height): (f64, f64) = (width as f64, height as f64);
+        let fit: f64 = (2048.0 / width.max(height)).min(1.0);
+        (width, height) = (width * fit, height * fit);
+        let shrink: f64 = (768.0 / width.min(height)).min(1.0);
+        (width, height) = (width * shrink, height * shrink);
+        let tiles: u64 = ((width / 512.0).ceil() * (height / 512.0).ceil()) as u64;
+
+        IMAGE_BASE_TOKENS + IMAGE_TILE_TOKENS * tiles
+    }
+}
+
+impl From<ImageDetail> for async_openai::types::ImageDetail {
+    fn from(detail: ImageDetail) -> Self {
+        match detail {
+            ImageDetail::Low => async_openai::types::ImageDetail::Low,
+            ImageDetail::High => async_openai::types::ImageDetail::High,
+            ImageDetail::Auto => async_openai::types::ImageDetail::Auto,
+        }
+    }
+}
+
+/// Converts a DynamicImage to a base64-encoded PNG string
+///
+/// # Arguments
+/// * `image` - The image to encode
+///
+/// # Returns
+/// The base64 encoding of the PNG bytes
+#[cfg(feature = "image")]
+pub fn dynamic_image_to_base64(image: &DynamicImage) -> Result<String, Error> {
+    let mut raw_image_bytes: Vec<u8> = Vec::new();
+    image.write_to(
+        &mut std::io::Cursor::new(&mut raw_image_bytes),
+        image::ImageFormat::Png,
+    )?;
+    let base64_image: String = BASE64_STANDARD.encode(raw_image_bytes);
+
+    Ok(base64_image)
+}
+
+/// Encodes a DynamicImage in the given format
+///
+/// JPEG has no alpha channel, so transparent images are flattened to RGB first.
+///
+/// # Arguments
+/// * `image` - The image to encode
+/// * `encoding` - The format to encode it in
+///
+/// # Returns
+/// The encoded bytes
+#[cfg(feature = "image")]
+pub fn encode_dynamic_image(image: &DynamicImage, encoding: ImageEncoding) -> Result<Vec<u8>, Error> {
+    let mut raw_image_bytes: Vec<u8> = Vec::new();
+    let cursor = std::io::Cursor::new(&mut raw_image_bytes);
+    match encoding {
+        ImageEncoding::Png => image.write_with_encoder(PngEncoder::new(cursor))?,
+        ImageEncoding::Jpeg { quality } => {
+            DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(JpegEncoder::new_with_quality(cursor, quality))?
+        }
+        ImageEncoding::WebP => DynamicImage::ImageRgba8(image.to_rgba8()).write_with_encoder(WebPEncoder::new_lossless(cursor))?,
+    }
+
+    Ok(raw_image_bytes)
+}
+
+/// Encodes a DynamicImage as a data URL whose MIME type matches the encoding
+///
+/// # Arguments
+/// * `image` - The image to encode
+/// * `encoding` - The format to encode it in
+///
+/// # Returns
+/// A `data:<mime>;base64,...` URL
+#[cfg(feature = "image")]
+pub fn dynamic_image_to_data_url(image: &DynamicImage, encoding: ImageEncoding) -> Result<String, Error> {
+    let raw_image_bytes: Vec<u8> = encode_dynamic_image(image, encoding)?;
+
+    Ok(format!("data:{};base64,{}", encoding.mime(), BASE64_STANDARD.encode(raw_image_bytes)))
+}
+
+/// Decodes a base64-encoded image back into a DynamicImage
+///
+/// Any format supported by the `image` crate is accepted; the format is
+/// detected from the decoded bytes.
+///
+/// # Arguments
+/// * `base64_image` - The base64 encoding of the image bytes
+///
+/// # Returns
+/// The decoded image
+#[cfg(feature = "image")]
+pub fn base64_to_dynamic_image(base64_image: &str) -> Result<DynamicImage, Error> {
+    let raw_image_bytes: Vec<u8> = BASE64_STANDARD.decode(base64_image)?;
+    let image: DynamicImage = image::load_from_memory(&raw_image_bytes)?;
+
+    Ok(image)
+}
+
+/// Computes a SHA-256 over the decoded pixels of an image
+///
+/// Two images hash equally exactly when they have the same dimensions and
+/// identical RGBA pixels, regardless of the file format they came from.
+///
+/// # Arguments
+/// * `image` - The image to hash
+///
+/// # Returns
+/// The hash as a lowercase hex string
+#[cfg(feature = "image")]
+pub fn image_sha256(image: &DynamicImage) -> String {
+    let pixels = image.to_rgba8();
+    let mut hasher = Sha256::new();
+    hasher.update(pixels.width().to_le_bytes());
+    hasher.update(pixels.height().to_le_bytes());
+    hasher.update(pixels.as_raw());
+
+    hex::encode(hasher.finalize())
+}
+
+/// Computes a SHA-256 over a text
+///
+/// # Arguments
+/// * `text` - The text to hash
+///
+/// # Returns
+/// The hash as a lowercase hex string
+pub fn text_sha256(text: &str) -> String {
+    bytes_sha256(text.as_bytes())
+}
+
+/// Computes a SHA-256 over raw bytes, such as an encoded audio file
+///
+/// # Arguments
+/// * `bytes` - The bytes to hash
+///
+/// # Returns
+/// The hash as a lowercase hex string
+pub fn bytes_sha256(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Serde adapter storing a DynamicImage as a base64 PNG string
+///
+/// Use it on your own image fields with
+/// `#[serde(with = "dim_rs::raw_data::utilities::base64_image")]`.
+#[cfg(feature = "image")]
+pub mod base64_image {
+    use image::DynamicImage;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::{base64_to_dynamic_image, dynamic_image_to_base64};
+
+    pub fn serialize<S: Serializer>(image: &DynamicImage, serializer: S) -> Result<S::Ok, S::Error> {
+        let encoded: String = dynamic_image_to_base64(image).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&encoded)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DynamicImage, D::Error> {
+        let encoded: String = String::deserialize(deserializer)?;
+        base64_to_dynamic_image(&encoded).map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,87 @@
+//! Tasks, clocks and timers that work natively and, with the `wasm` feature, on wasm32.
+
+use std::future::Future;
+use std::time::Duration;
+
+use crate::error::DimError;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::{Instant, SystemTime, UNIX_EPOCH};
+#[cfg(target_arch = "wasm32")]
+pub use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Starts running a future as a task of its own
+///
+/// Natively the future is spawned on the tokio runtime, so it runs in
+/// parallel with the others. wasm32 has a single thread and no tokio
+/// runtime, so there the future is returned as is and runs concurrently
+/// with the futures it is awaited with.
+///
+/// # Arguments
+/// * `future` - The work of the task
+///
+/// # Returns
+/// A future of the output, or of the error that stopped the task
+#[cfg(not(target_arch = "wasm32"))]
+pub fn spawn<F>(future: F) -> impl Future<Output = Result<F::Output, DimError>> + Send
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let handle: tokio::task::JoinHandle<F::Output> = tokio::spawn(future);
+
+    async move {
+        handle.await.map_err(|e| match e.is_cancelled() {
+            true => DimError::Cancelled,
+            false => DimError::msg(format!("Vectorization task failed: {}", e)),
+        })
+    }
+}
+
+/// Starts running a future as a task of its own, which on wasm32 is the future itself
+#[cfg(target_arch = "wasm32")]
+pub fn spawn<F>(future: F) -> impl Future<Output = Result<F::Output, DimError>> + Send
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    async move { Ok(future.await) }
+}
+
+/// Runs a future that is not `Send`, such as a `fetch` request, on the JavaScript event loop
+///
+/// The output comes back over a channel, so the returned future is `Send`
+/// and meets the bounds of `ChatBackend`.
+///
+/// # Arguments
+/// * `future` - The work to run
+///
+/// # Returns
+/// A future of the output
+#[cfg(target_arch = "wasm32")]
+pub fn run_local<F>(future: F) -> impl Future<Output = F::Output> + Send
+where
+    F: Future + 'static,
+    F::Output: Send + 'static,
+{
+    let (sender, receiver) = futures::channel::oneshot::channel::<F::Output>();
+    wasm_bindgen_futures::spawn_local(async move {
+        let _ = sender.send(future.await);
+    });
+
+    async move { receiver.await.expect("The local task stopped before it finished") }
+}
+
+/// Waits for a duration, on the tokio timer natively and a JavaScript timer on wasm32
+///
+/// The JavaScript timer runs through `run_local`, so the wait is `Send` on
+/// every target and can be awaited inside spawned tasks.
+///
+/// # Arguments
+/// * `duration` - How long to wait
+pub async fn sleep(duration: Duration) {
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(duration).await;
+    #[cfg(target_arch = "wasm32")]
+    run_local(gloo_timers::future::sleep(duration)).await;
+}
@@ -0,0 +1,366 @@
+//! An HTTP service exposing vectorization to clients in other languages.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use axum::extract::{DefaultBodyLimit, FromRequest, Multipart, Request, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use base64::Engine;
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::error::DimError;
+use crate::llm::{verify_model, ChatBackend, ModelInfo};
+use crate::prompt::PromptSet;
+use crate::vector::{Vector, VectorOperations};
+use crate::vectorization::report::VectorizationReport;
+use crate::vectorization::usage::UsageReport;
+use crate::vectorization::vectorizable::Vectorizable;
+use crate::vectorization::vectorizer::Vectorizer;
+
+/// The largest request body accepted by default, enough for most photos
+const DEFAULT_MAX_BODY_BYTES: usize = 10 * 1024 * 1024;
+
+/// Limits and prompt sets of the service
+///
+/// # Fields
+/// * `max_concurrent_requests` - The vectorizations run at once, the vectorizer's `max_concurrency` by default; others wait
+/// * `max_body_bytes` - The largest request body accepted, 10 MiB by default
+/// * `prompt_sets` - Prompt sets requests can name instead of the vectorizer's
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerOptions {
+    max_concurrent_requests: Option<usize>,
+    max_body_bytes: usize,
+    prompt_sets: Vec<(String, PromptSet)>,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrent_requests: None,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            prompt_sets: Vec::new(),
+        }
+    }
+}
+
+impl ServerOptions {
+    /// Creates options with the default limits and no named prompt sets
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs at most `max_concurrent_requests` vectorizations at once
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.max_concurrent_requests = Some(max_concurrent_requests);
+        self
+    }
+
+    /// Rejects request bodies larger than `max_body_bytes` with 413
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    /// Lets requests choose `prompts` by naming them in their `prompt_set` field
+    pub fn with_prompt_set(mut self, name: impl Into<String>, prompts: PromptSet) -> Self {
+        self.prompt_sets.push((name.into(), prompts));
+        self
+    }
+}
+
+/// The body of `POST /vectorize/text`
+///
+/// # Fields
+/// * `text` - The text to vectorize
+/// * `prompt_set` - The name of a prompt set of the `ServerOptions`, the vectorizer's prompts when absent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextRequest {
+    pub text: String,
+    #[serde(default)]
+    pub prompt_set: Option<String>,
+}
+
+/// The JSON body of `POST /vectorize/image`
+///
+/// Multipart requests send the same fields as parts, the image as its file contents.
+///
+/// # Fields
+/// * `image` - The image file, base64 encoded, optionally as a `data:` URL
+/// * `prompt_set` - The name of a prompt set of the `ServerOptions`, the vectorizer's prompts when absent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageRequest {
+    pub image: String,
+    #[serde(default)]
+    pub prompt_set: Option<String>,
+}
+
+/// The answer to a vectorization request
+///
+/// # Fields
+/// * `vector` - The values of the vector
+/// * `labels` - The label of each dimension
+/// * `fingerprint` - The fingerprint of the prompts and model
+/// * `usage` - The tokens the vectorization consumed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VectorResponse {
+    pub vector: Vec<f32>,
+    pub labels: Vec<String>,
+    pub fingerprint: Option<String>,
+    pub usage: UsageReport,
+}
+
+impl VectorResponse {
+    /// Collects the answer from a vectorized vector and its report.
+    fn new<T>(vector: &Vector<T>, report: VectorizationReport) -> Self {
+        Self {
+            vector: vector.get_vector(),
+            labels: vector.get_labels().to_vec(),
+            fingerprint: vector.get_fingerprint().map(str::to_string),
+            usage: report.usage,
+        }
+    }
+}
+
+/// A failed request, answered with its status and `{"error": message}`
+#[derive(Debug)]
+struct ServerError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ServerError {
+    /// Creates an error for an invalid request.
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self { status: StatusCode::BAD_REQUEST, message: message.into() }
+    }
+}
+
+impl From<DimError> for ServerError {
+    fn from(error: DimError) -> Self {
+        Self { status: status_for(&error), message: error.to_string() }
+    }
+}
+
+impl IntoResponse for ServerError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+/// Returns the HTTP status the service answers a failed vectorization with
+///
+/// Rate limits of the LLM API are passed through as 429 and its timeouts
+/// become 504. Other failures of the API or its answers, including answers
+/// failing validation, become 502, and internal errors become 500. Invalid
+/// requests are answered with 400 before anything is vectorized.
+///
+/// # Arguments
+/// * `error` - The error of the vectorization
+///
+/// # Returns
+/// The status code
+pub fn status_for(error: &DimError) -> StatusCode {
+    match error {
+        DimError::ApiError { status: Some(429), .. } => StatusCode::TOO_MANY_REQUESTS,
+        DimError::ApiError { .. }
+        | DimError::Unauthorized { .. }
+        | DimError::ModelNotFound { .. }
+        | DimError::ParseFailed { .. }
+        | DimError::ValidationFailed { .. }
+        | DimError::DimensionalityMismatch { .. } => StatusCode::BAD_GATEWAY,
+        DimError::Timeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+        DimError::Cancelled => StatusCode::SERVICE_UNAVAILABLE,
+        DimError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// What every handler shares.
+struct AppState<B> {
+    vectorizer: Vectorizer<B>,
+    prompt_sets: HashMap<String, Vectorizer<B>>,
+    permits: Semaphore,
+}
+
+impl<B: ChatBackend> AppState<B> {
+    /// Finds the vectorizer of a named prompt set, the default one when unnamed.
+    fn vectorizer(&self, prompt_set: Option<&str>) -> Result<&Vectorizer<B>, ServerError> {
+        match prompt_set {
+            Some(name) => self
+                .prompt_sets
+                .get(name)
+                .ok_or_else(|| ServerError::bad_request(format!("Unknown prompt set {:?}", name))),
+            None => Ok(&self.vectorizer),
+        }
+    }
+
+    /// Vectorizes one item once a permit is free.
+    async fn vectorize<T: Vectorizable>(&self, prompt_set: Option<&str>, mut vector: Vector<T>) -> Result<VectorResponse, ServerError> {
+        let vectorizer: &Vectorizer<B> = self.vectorizer(prompt_set)?;
+        let _permit: SemaphorePermit<'_> = self.permits.acquire().await.map_err(|_| DimError::Cancelled)?;
+        let report: VectorizationReport = vectorizer.vectorize(&mut vector).await?;
+
+        Ok(VectorResponse::new(&vector, report))
+    }
+}
+
+/// Builds the service with the default `ServerOptions`
+///
+/// The routes are:
+///
+/// * `POST /vectorize/text` - A `TextRequest`, answered with a `VectorResponse`
+/// * `POST /vectorize/image` - An `ImageRequest` or its multipart form, answered with a `VectorResponse`
+/// * `GET /healthz` - Checks that the endpoint serves every model of the prompts
+///
+/// Errors are answered as `{"error": message}` with the status of `status_for`.
+///
+/// # Arguments
+/// * `vectorizer` - The client, prompts and parameters of every request
+///
+/// # Returns
+/// The router, to serve with `axum::serve` or nest in a larger app
+pub fn router<B: ChatBackend>(vectorizer: Vectorizer<B>) -> Router {
+    build_router(vectorizer, ServerOptions::new(), HashMap::new())
+}
+
+/// Builds the service like `router`, with custom limits and named prompt sets
+///
+/// # Arguments
+/// * `vectorizer` - The client, default prompts and parameters of every request
+/// * `options` - The limits and the prompt sets requests can name
+///
+/// # Returns
+/// The router, or an error if a named prompt set is invalid
+pub fn router_with_options<B: ChatBackend>(vectorizer: Vectorizer<B>, options: ServerOptions) -> Result<Router, Error> {
+    let mut prompt_sets: HashMap<String, Vectorizer<B>> = HashMap::new();
+    for (name, prompts) in &options.prompt_sets {
+        let named: Vectorizer<B> = Vectorizer::builder()
+            .shared_client(vectorizer.get_client().clone())
+            .prompts(prompts.clone())
+            .model_parameters(vectorizer.get_model_parameters().clone())
+            .max_concurrency(vectorizer.get_max_concurrency())
+            .build()
+            .map_err(|e| Error::msg(format!("Invalid prompt set {:?}: {}", name, e)))?;
+        prompt_sets.insert(name.clone(), named);
+    }
+
+    Ok(build_router(vectorizer, options, prompt_sets))
+}
+
+/// Assembles the routes and limits around the shared state.
+fn build_router<B: ChatBackend>(vectorizer: Vectorizer<B>, options: ServerOptions, prompt_sets: HashMap<String, Vectorizer<B>>) -> Router {
+    let permits: usize = options.max_concurrent_requests.unwrap_or(vectorizer.get_max_concurrency()).max(1);
+    let state: Arc<AppState<B>> = Arc::new(AppState {
+        vectorizer,
+        prompt_sets,
+        permits: Semaphore::new(permits),
+    });
+
+    Router::new()
+        .route("/vectorize/text", post(vectorize_text::<B>))
+        .route("/vectorize/image", post(vectorize_image::<B>))
+        .route("/healthz", get(healthz::<B>))
+        .layer(DefaultBodyLimit::max(options.max_body_bytes))
+        .with_state(state)
+}
+
+/// Handles `POST /vectorize/text`.
+async fn vectorize_text<B: ChatBackend>(
+    State(state): State<Arc<AppState<B>>>,
+    Json(request): Json<TextRequest>,
+) -> Result<Json<VectorResponse>, ServerError> {
+    let vector: Vector<String> = Vector::from_text(request.text);
+
+    Ok(Json(state.vectorize(request.prompt_set.as_deref(), vector).await?))
+}
+
+/// Handles `POST /vectorize/image`, reading a multipart form or a JSON body.
+async fn vectorize_image<B: ChatBackend>(
+    State(state): State<Arc<AppState<B>>>,
+    request: Request,
+) -> Result<Json<VectorResponse>, ServerError> {
+    let multipart: bool = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("multipart/form-data"));
+    let (bytes, prompt_set): (Vec<u8>, Option<String>) = match multipart {
+        true => read_multipart_image(request).await?,
+        false => read_json_image(request).await?,
+    };
+    let vector: Vector<DynamicImage> =
+        Vector::from_bytes(&bytes).map_err(|e| ServerError::bad_request(format!("Invalid image: {}", e)))?;
+
+    Ok(Json(state.vectorize(prompt_set.as_deref(), vector).await?))
+}
+
+/// Reads the `image` and `prompt_set` parts of a multipart form.
+async fn read_multipart_image(request: Request) -> Result<(Vec<u8>, Option<String>), ServerError> {
+    let mut multipart: Multipart = Multipart::from_request(request, &())
+        .await
+        .map_err(|rejection| ServerError { status: rejection.status(), message: rejection.body_text() })?;
+    let (mut image, mut prompt_set): (Option<Vec<u8>>, Option<String>) = (None, None);
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ServerError { status: e.status(), message: e.body_text() })?
+    {
+        match field.name() {
+            Some("image") => {
+                let bytes = field.bytes().await.map_err(|e| ServerError { status: e.status(), message: e.body_text() })?;
+                image = Some(bytes.to_vec());
+            }
+            Some("prompt_set") => {
+                prompt_set = Some(field.text().await.map_err(|e| ServerError { status: e.status(), message: e.body_text() })?);
+            }
+            _ => {}
+        }
+    }
+
+    let image: Vec<u8> = image.ok_or_else(|| ServerError::bad_request("The form has no image part"))?;
+
+    Ok((image, prompt_set))
+}
+
+/// Reads and decodes the base64 image of a JSON `ImageRequest`.
+async fn read_json_image(request: Request) -> Result<(Vec<u8>, Option<String>), ServerError> {
+    let Json(request): Json<ImageRequest> = Json::from_request(request, &())
+        .await
+        .map_err(|rejection| ServerError { status: rejection.status(), message: rejection.body_text() })?;
+    let encoded: &str = match request.image.split_once(";base64,") {
+        Some((prefix, encoded)) if prefix.starts_with("data:") => encoded,
+        _ => request.image.as_str(),
+    };
+    let image: Vec<u8> = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| ServerError::bad_request(format!("Invalid base64 image: {}", e)))?;
+
+    Ok((image, request.prompt_set))
+}
+
+/// Handles `GET /healthz`, checking every model the prompts are sent to.
+async fn healthz<B: ChatBackend>(State(state): State<Arc<AppState<B>>>) -> Result<Json<Value>, ServerError> {
+    let mut models: Vec<String> = Vec::new();
+    for vectorizer in std::iter::once(&state.vectorizer).chain(state.prompt_sets.values()) {
+        let parameters = vectorizer.get_model_parameters();
+        for prompt in vectorizer.get_prompts().get_prompts() {
+            let model: String = parameters.with_override(prompt.get_model_override()).get_model();
+            if !models.contains(&model) {
+                models.push(model);
+            }
+        }
+    }
+
+    let mut verified: Vec<ModelInfo> = Vec::new();
+    for model in &models {
+        verified.push(verify_model(state.vectorizer.get_client().as_ref(), model).await?);
+    }
+
+    Ok(Json(json!({ "status": "ok", "models": verified })))
+}
@@ -0,0 +1,174 @@
+//! Metrics of the vectorization requests, recorded with the `metrics` facade behind the `metrics` feature.
+//!
+//! Install any `metrics` recorder, e.g. a Prometheus exporter, to collect them.
+//! Without the feature every recording compiles to nothing. Labels carry the
+//! model and the prompt's dimension labels, never the text being vectorized.
+
+use std::time::Duration;
+
+#[cfg(feature = "metrics")]
+use metrics::Label;
+
+use crate::prompt::Prompt;
+use crate::vectorization::usage::TokenUsage;
+
+/// Counter of the chat requests sent, labeled by `model`, `prompt` and `status`
+pub const METRIC_REQUESTS: &str = "dim_requests_total";
+/// Counter of the retries of a prompt, labeled by `model`, `prompt` and `reason`
+pub const METRIC_RETRIES: &str = "dim_retries_total";
+/// Counter of the answers rejected by validation, labeled by `model` and `prompt`
+pub const METRIC_VALIDATION_FAILURES: &str = "dim_validation_failures_total";
+/// Counter of the tokens consumed, labeled by `model` and `kind`, prompt or completion
+pub const METRIC_TOKENS: &str = "dim_tokens_total";
+/// Histogram of the seconds one chat request took, labeled by `model` and `prompt`
+pub const METRIC_REQUEST_DURATION: &str = "dim_request_duration_seconds";
+/// Histogram of the requests it took to get a valid answer for a prompt, labeled by `model` and `prompt`
+pub const METRIC_ATTEMPTS: &str = "dim_attempts_per_dimension";
+/// Gauge of the chat requests awaiting an answer, labeled by `model`
+pub const METRIC_IN_FLIGHT: &str = "dim_requests_in_flight";
+
+/// Registers the units and descriptions of dim's metrics with the installed recorder
+///
+/// Optional: the metrics are recorded either way, this only adds help texts
+/// and units to exporters that show them.
+#[cfg(feature = "metrics")]
+pub fn describe_metrics() {
+    use metrics::{describe_counter, describe_gauge, describe_histogram, Unit};
+
+    describe_counter!(METRIC_REQUESTS, Unit::Count, "Chat requests sent to the LLM");
+    describe_counter!(METRIC_RETRIES, Unit::Count, "Retries of a prompt after an error or a rejected answer");
+    describe_counter!(METRIC_VALIDATION_FAILURES, Unit::Count, "Answers rejected by validation");
+    describe_counter!(METRIC_TOKENS, Unit::Count, "Tokens consumed");
+    describe_histogram!(METRIC_REQUEST_DURATION, Unit::Seconds, "Duration of one chat request");
+    describe_histogram!(METRIC_ATTEMPTS, Unit::Count, "Requests needed for a valid answer to a prompt");
+    describe_gauge!(METRIC_IN_FLIGHT, Unit::Count, "Chat requests awaiting an answer");
+}
+
+/// Why a prompt was retried, kept to a fixed set so the label stays low-cardinality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RetryReason {
+    FormatRejected,
+    RequestFailed,
+    EmptyAnswer,
+    ParseFailed,
+    ValidationFailed,
+}
+
+impl RetryReason {
+    /// Returns the value of the `reason` label.
+    #[cfg(feature = "metrics")]
+    fn as_str(&self) -> &'static str {
+        match self {
+            RetryReason::FormatRejected => "format_rejected",
+            RetryReason::RequestFailed => "request_failed",
+            RetryReason::EmptyAnswer => "empty_answer",
+            RetryReason::ParseFailed => "parse_failed",
+            RetryReason::ValidationFailed => "validation_failed",
+        }
+    }
+}
+
+/// The labels of the metrics of one prompt, empty without the `metrics` feature.
+#[derive(Debug, Clone)]
+pub(crate) struct PromptMetrics {
+    #[cfg(feature = "metrics")]
+    model: String,
+    #[cfg(feature = "metrics")]
+    prompt: String,
+}
+
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+impl PromptMetrics {
+    /// Labels a prompt by its dimension labels, or by its position when they are unknown.
+    pub(crate) fn new(model: &str, prompt: &Prompt, prompt_index: usize) -> Self {
+        #[cfg(feature = "metrics")]
+        {
+            let labels: Vec<String> = prompt.get_labels();
+            let prompt: String = match labels.is_empty() {
+                true => format!("prompt_{}", prompt_index),
+                false => labels.join(","),
+            };
+            Self { model: model.to_string(), prompt }
+        }
+        #[cfg(not(feature = "metrics"))]
+        Self {}
+    }
+
+    /// Counts a request as in flight until the returned guard is dropped.
+    pub(crate) fn in_flight(&self) -> InFlight {
+        #[cfg(feature = "metrics")]
+        {
+            let gauge: metrics::Gauge = metrics::gauge!(METRIC_IN_FLIGHT, "model" => self.model.clone());
+            gauge.increment(1.0);
+            InFlight { gauge }
+        }
+        #[cfg(not(feature = "metrics"))]
+        InFlight {}
+    }
+
+    /// Records a finished request and how long it took.
+    pub(crate) fn record_request(&self, latency: Duration, succeeded: bool) {
+        #[cfg(feature = "metrics")]
+        {
+            let mut labels: Vec<Label> = self.labels();
+            labels.push(Label::new("status", if succeeded { "ok" } else { "error" }));
+            metrics::counter!(METRIC_REQUESTS, labels).increment(1);
+            metrics::histogram!(METRIC_REQUEST_DURATION, self.labels()).record(latency.as_secs_f64());
+        }
+    }
+
+    /// Records a retry of the prompt.
+    pub(crate) fn record_retry(&self, reason: RetryReason) {
+        #[cfg(feature = "metrics")]
+        {
+            let mut labels: Vec<Label> = self.labels();
+            labels.push(Label::new("reason", reason.as_str()));
+            metrics::counter!(METRIC_RETRIES, labels).increment(1);
+            if reason == RetryReason::ValidationFailed {
+                metrics::counter!(METRIC_VALIDATION_FAILURES, self.labels()).increment(1);
+            }
+        }
+    }
+
+    /// Records the tokens of an answered request.
+    pub(crate) fn record_usage(&self, usage: &TokenUsage) {
+        #[cfg(feature = "metrics")]
+        record_usage(&self.model, usage);
+    }
+
+    /// Records the requests it took to get a valid answer.
+    pub(crate) fn record_attempts(&self, attempts: u64) {
+        #[cfg(feature = "metrics")]
+        metrics::histogram!(METRIC_ATTEMPTS, self.labels()).record(attempts as f64);
+    }
+
+    /// Returns the `model` and `prompt` labels.
+    #[cfg(feature = "metrics")]
+    fn labels(&self) -> Vec<Label> {
+        vec![Label::new("model", self.model.clone()), Label::new("prompt", self.prompt.clone())]
+    }
+}
+
+/// Records the tokens consumed by a model outside of chat requests, e.g. by embeddings.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_usage(model: &str, usage: &TokenUsage) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!(METRIC_TOKENS, "model" => model.to_string(), "kind" => "prompt").increment(usage.prompt_tokens);
+        metrics::counter!(METRIC_TOKENS, "model" => model.to_string(), "kind" => "completion").increment(usage.completion_tokens);
+    }
+}
+
+/// Keeps a request counted as in flight, until dropped.
+#[derive(Debug)]
+pub(crate) struct InFlight {
+    #[cfg(feature = "metrics")]
+    gauge: metrics::Gauge,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        #[cfg(feature = "metrics")]
+        self.gauge.decrement(1.0);
+    }
+}
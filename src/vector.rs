@@ -1,4 +1,73 @@
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+use anyhow::{Error, Result};
+#[cfg(feature = "image")]
+use image::DynamicImage;
 use serde::{Serialize, Deserialize};
+
+use crate::error::DimError;
+use crate::prompt::combine_fingerprints;
+#[cfg(feature = "document")]
+use crate::raw_data::document::{extract_text, DocumentError, DocumentFormat};
+use crate::raw_data::{AudioData, AudioFormat, ChatTurn, RemoteImage, VideoData, VideoFormat};
+#[cfg(feature = "image")]
+use crate::raw_data::directory::record_file_metadata;
+#[cfg(feature = "image")]
+use crate::raw_data::{decode_image_with_orientation, load_image_from_url, EncodedImage, ImageDownloadOptions, ImageFile};
+
+pub mod binary;
+pub mod diff;
+pub mod explain;
+pub mod io;
+pub mod metrics;
+#[cfg(feature = "ndarray")]
+pub mod ndarray;
+pub mod precision;
+pub mod quantization;
+#[cfg(feature = "image")]
+pub mod serialization;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod stats;
+
+pub use precision::{Precision, Scalar};
+
+/// Metadata key holding the caller's identifier for the item
+pub const METADATA_ID: &str = "id";
+/// Metadata key holding where the data came from, such as a file path or URL
+pub const METADATA_SOURCE: &str = "source";
+/// Metadata key holding the size in bytes of the file the data was loaded from
+pub const METADATA_FILE_SIZE: &str = "file_size";
+/// Metadata key holding when the file the data was loaded from was last modified, in seconds since the Unix epoch
+pub const METADATA_MODIFIED_AT: &str = "modified_at";
+/// Metadata key holding the model that produced the vector
+pub const METADATA_MODEL: &str = "model";
+/// Metadata key holding, as a JSON array, the model behind each dimension when prompts were routed to different models
+pub const METADATA_DIMENSION_MODELS: &str = "dimension_models";
+/// Metadata key holding when the vector was produced, in seconds since the Unix epoch
+pub const METADATA_VECTORIZED_AT: &str = "vectorized_at";
+/// Metadata key holding the transcript audio was scored from
+pub const METADATA_TRANSCRIPT: &str = "transcript";
+/// Metadata key holding the EXIF orientation, from 2 to 8, of an image whose file asked for one
+pub const METADATA_EXIF_ORIENTATION: &str = "exif_orientation";
+/// Metadata key holding the `WIDTHxHEIGHT` of images before they were downscaled, comma separated
+pub const METADATA_ORIGINAL_SIZE: &str = "original_size";
+/// Metadata key holding the `WIDTHxHEIGHT` images were sent to the model at, comma separated
+pub const METADATA_SENT_SIZE: &str = "sent_size";
+/// Metadata key holding the programming language of source code
+pub const METADATA_LANGUAGE: &str = "language";
+/// Metadata key holding the size in bytes of the source the stored data was extracted from, such as a page's HTML
+pub const METADATA_ORIGINAL_LENGTH: &str = "original_length";
+/// Metadata key holding the estimated tokens of a text that was cut to `max_input_tokens` before it was sent
+pub const METADATA_TRUNCATED_FROM: &str = "truncated_from";
+/// Metadata key holding how many prompts were answered from a cache instead of the LLM
+pub const METADATA_CACHE_HITS: &str = "cache_hits";
+/// Metadata key holding, as a JSON array, the vector of every chunk of a long text
+pub const METADATA_CHUNK_VECTORS: &str = "chunk_vectors";
+/// Metadata key holding, as a JSON array, the indices of the prompts that failed, their dimensions left NaN
+pub const METADATA_FAILED_PROMPTS: &str = "failed_prompts";
+
 /// The type of data that is being vectorized. This enum represents the different
 /// types of data that can be processed and vectorized in the system.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -11,27 +80,68 @@ pub enum DataType {
     Audio,
     /// Video data type for processing video files and motion picture data
     Video,
+    /// An image paired with text, such as a photo and its caption, scored together
+    ImageWithText,
+    /// A conversation of role-tagged messages, scored as a whole
+    Conversation,
+    /// A structured JSON record, such as a product row
+    Record,
+    /// Source code, sent fenced and labeled with its language
+    Code,
 }
 
 /// A vector that contains a vectorized data and the original data. This struct pairs
 /// the original data with its vector representation and type information.
+///
+/// Values are stored as `f32` unless `S` says otherwise; see `Vector64`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Vector<T> {
+#[serde(bound(serialize = "T: Serialize, S: Scalar", deserialize = "T: Deserialize<'de>, S: Scalar"))]
+pub struct Vector<T, S = f32> {
     /// The vector representation of the data as floating point values
-    vector: Vec<f32>,
+    vector: Vec<S>,
     /// The original data being vectorized
     data: T,
     /// The type of the data being stored
     data_type: DataType,
+    /// Fingerprint of the prompts and model that produced the vector
+    #[serde(default)]
+    fingerprint: Option<String>,
+    /// Labels naming each dimension, either empty or as long as the vector
+    #[serde(default)]
+    labels: Vec<String>,
+    /// Free-form provenance such as ids, source paths and the producing model
+    #[serde(default)]
+    metadata: BTreeMap<String, String>,
+    /// The number of dimensions the vector must have once vectorized, if declared
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expected_dims: Option<usize>,
+    /// The precision of the values, recorded when serialized
+    #[serde(
+        default,
+        serialize_with = "precision::serialize_precision",
+        deserialize_with = "precision::deserialize_precision"
+    )]
+    precision: PhantomData<S>,
 }
 
+/// A `Vector` storing its values as `f64`
+///
+/// Convert with `Vector64::from` or `Vector::cast`. Vectorizing into a
+/// `Vector64` keeps the parsed scores at full precision.
+pub type Vector64<T> = Vector<T, f64>;
+
 /// Shared behaviors between `Vector` types. This trait defines the common operations
 /// that can be performed on vectorized data regardless of the underlying data type.
-pub trait VectorOperations<T> {
+pub trait VectorOperations<T, S: Scalar = f32> {
     /// Get the vector representation of the data
     /// 
-    /// Returns a clone of the internal vector of f32 values
-    fn get_vector(&self) -> Vec<f32>;
+    /// Returns a clone of the internal vector of values
+    fn get_vector(&self) -> Vec<S>;
+
+    /// Borrow the vector representation of the data
+    ///
+    /// Returns the internal values without cloning them
+    fn as_slice(&self) -> &[S];
 
     /// Get a reference to the original data
     ///
@@ -47,21 +157,125 @@ pub trait VectorOperations<T> {
     ///
     /// Returns the length of the vector representation
     fn get_dimensionality(&self) -> usize {
-        self.get_vector().len()
+        self.as_slice().len()
     }
 
     /// Write a new vector to the vector field
     ///
+    /// Any dimension labels are cleared, since they can no longer be assumed
+    /// to describe the new values. Use `overwrite_vector_with_labels` to keep
+    /// the vector labeled or to enforce `Vector::with_expected_dims`, which is
+    /// not checked here.
+    ///
     /// # Arguments
     /// * `vector` - The new vector to replace the existing one
-    fn overwrite_vector(&mut self, vector: Vec<f32>);
+    fn overwrite_vector(&mut self, vector: Vec<S>);
+
+    /// Write a new vector together with the labels of its dimensions
+    ///
+    /// Every vectorization function writes its result with this method, so a
+    /// declared `Vector::with_expected_dims` is enforced here.
+    ///
+    /// # Arguments
+    /// * `vector` - The new vector to replace the existing one
+    /// * `labels` - One label per dimension
+    ///
+    /// # Returns
+    /// An error if the number of labels differs from the vector length, or
+    /// `DimError::DimensionalityMismatch` if the length differs from the declared one
+    fn overwrite_vector_with_labels(&mut self, vector: Vec<S>, labels: Vec<String>) -> Result<(), Error>;
+
+    /// Get the labels naming each dimension
+    ///
+    /// Returns an empty slice if the dimensions are unlabeled
+    fn get_labels(&self) -> &[String];
+
+    /// Get the vector paired with the label of each dimension
+    ///
+    /// Unlabeled dimensions are named `dim_0`, `dim_1`, ...
+    fn get_labeled_vector(&self) -> Vec<(String, S)> {
+        let labels: &[String] = self.get_labels();
+        self.as_slice()
+            .iter()
+            .copied()
+            .enumerate()
+            .map(|(index, value)| {
+                let label: String = labels
+                    .get(index)
+                    .cloned()
+                    .unwrap_or_else(|| format!("dim_{}", index));
+                (label, value)
+            })
+            .collect()
+    }
+
+    /// Get the value of the dimension with the given label
+    ///
+    /// # Arguments
+    /// * `label` - The label to look up, e.g. "sentiment_score"
+    ///
+    /// # Returns
+    /// The value of the first dimension carrying the label, if any
+    fn get_by_label(&self, label: &str) -> Option<S> {
+        let index: usize = self.get_labels().iter().position(|candidate| candidate == label)?;
+        self.as_slice().get(index).copied()
+    }
+
+    /// Cosine similarity between this vector and another
+    ///
+    /// # Arguments
+    /// * `other` - The vector to compare against
+    ///
+    /// # Returns
+    /// The similarity in [-1, 1], or an error on empty or mismatched vectors.
+    /// A zero vector has a similarity of 0.0 to everything.
+    fn cosine_similarity<U>(&self, other: &impl VectorOperations<U, S>) -> Result<S, Error> {
+        metrics::cosine_similarity(self.as_slice(), other.as_slice())
+    }
+
+    /// Dot product between this vector and another
+    ///
+    /// # Arguments
+    /// * `other` - The vector to compare against
+    ///
+    /// # Returns
+    /// The dot product, or an error on empty or mismatched vectors
+    fn dot<U>(&self, other: &impl VectorOperations<U, S>) -> Result<S, Error> {
+        metrics::dot(self.as_slice(), other.as_slice())
+    }
+
+    /// Euclidean distance between this vector and another
+    ///
+    /// # Arguments
+    /// * `other` - The vector to compare against
+    ///
+    /// # Returns
+    /// The distance, or an error on empty or mismatched vectors
+    fn euclidean_distance<U>(&self, other: &impl VectorOperations<U, S>) -> Result<S, Error> {
+        metrics::euclidean_distance(self.as_slice(), other.as_slice())
+    }
+
+    /// Get the fingerprint of the prompts and model that produced the vector
+    ///
+    /// Returns `None` if the vector has not been vectorized yet
+    fn get_fingerprint(&self) -> Option<&str>;
+
+    /// Record the fingerprint of the prompts and model that produced the vector
+    ///
+    /// # Arguments
+    /// * `fingerprint` - The fingerprint, usually from `prompt::compute_fingerprint`
+    fn set_fingerprint(&mut self, fingerprint: String);
 }
 
-impl<T> VectorOperations<T> for Vector<T> {
-    fn get_vector(&self) -> Vec<f32> {
+impl<T, S: Scalar> VectorOperations<T, S> for Vector<T, S> {
+    fn get_vector(&self) -> Vec<S> {
         self.vector.clone()
     }
 
+    fn as_slice(&self) -> &[S] {
+        &self.vector
+    }
+
     fn get_data_type(&self) -> DataType {
         self.data_type
     }
@@ -70,12 +284,391 @@ impl<T> VectorOperations<T> for Vector<T> {
         &self.data
     }
 
-    fn overwrite_vector(&mut self, vector: Vec<f32>) {
+    fn overwrite_vector(&mut self, vector: Vec<S>) {
+        self.vector = vector;
+        self.labels.clear();
+    }
+
+    fn overwrite_vector_with_labels(&mut self, vector: Vec<S>, labels: Vec<String>) -> Result<(), Error> {
+        if let Some(expected) = self.expected_dims.filter(|expected| *expected != vector.len()) {
+            return Err(Error::new(DimError::DimensionalityMismatch { expected, actual: vector.len() }));
+        }
+        if vector.len() != labels.len() {
+            return Err(Error::msg(format!(
+                "Got {} labels for a vector of {} dimensions",
+                labels.len(),
+                vector.len()
+            )));
+        }
         self.vector = vector;
+        self.labels = labels;
+
+        Ok(())
+    }
+
+    fn get_labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    fn get_fingerprint(&self) -> Option<&str> {
+        self.fingerprint.as_deref()
+    }
+
+    fn set_fingerprint(&mut self, fingerprint: String) {
+        self.fingerprint = Some(fingerprint);
+    }
+}
+
+impl<T, S: Scalar> Vector<T, S> {
+    /// Create an unvectorized vector holding `data` of the given type
+    fn with_data(data: T, data_type: DataType) -> Self {
+        Self {
+            vector: vec![],
+            data,
+            data_type,
+            fingerprint: None,
+            labels: vec![],
+            metadata: BTreeMap::new(),
+            expected_dims: None,
+            precision: PhantomData,
+        }
+    }
+
+    /// Declare how many dimensions the vector must have once vectorized
+    ///
+    /// A vectorization producing another number of dimensions then fails with
+    /// `DimError::DimensionalityMismatch` and leaves the vector untouched.
+    /// `PromptSet::total_dims` gives the dimensions a prompt set declares.
+    /// Without a declaration, any length is accepted.
+    ///
+    /// # Arguments
+    /// * `dims` - The required number of dimensions
+    ///
+    /// # Returns
+    /// The vector with the expectation declared
+    pub fn with_expected_dims(mut self, dims: usize) -> Self {
+        self.expected_dims = Some(dims);
+        self
+    }
+
+    /// Get the number of dimensions the vector must have, if declared
+    pub fn get_expected_dims(&self) -> Option<usize> {
+        self.expected_dims
+    }
+
+    /// Raises a declared dimensionality by `dims`, for dimensions appended after vectorization.
+    pub(crate) fn grow_expected_dims(&mut self, dims: usize) {
+        self.expected_dims = self.expected_dims.map(|expected| expected + dims);
+    }
+
+    /// Convert the vector to another precision, keeping its data, labels and metadata
+    ///
+    /// Narrowing to `f32` rounds every value to the nearest `f32`.
+    ///
+    /// # Returns
+    /// The vector with its values stored as `R`
+    pub fn cast<R: Scalar>(self) -> Vector<T, R> {
+        Vector {
+            vector: self.vector.into_iter().map(|value| R::from_f64(value.to_f64())).collect(),
+            data: self.data,
+            data_type: self.data_type,
+            fingerprint: self.fingerprint,
+            labels: self.labels,
+            metadata: self.metadata,
+            expected_dims: self.expected_dims,
+            precision: PhantomData,
+        }
+    }
+
+    /// Get the precision the values are stored in
+    pub fn get_precision(&self) -> Precision {
+        S::PRECISION
+    }
+
+    /// Set a metadata entry, replacing any previous value
+    ///
+    /// See the `METADATA_*` constants for the keys the crate fills in itself.
+    ///
+    /// # Arguments
+    /// * `key` - The metadata key
+    /// * `value` - The value to store
+    pub fn set_metadata(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.metadata.insert(key.into(), value.into());
+    }
+
+    /// Get a metadata entry
+    ///
+    /// # Arguments
+    /// * `key` - The metadata key
+    ///
+    /// # Returns
+    /// The stored value, if any
+    pub fn get_metadata(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(String::as_str)
+    }
+
+    /// Remove a metadata entry
+    ///
+    /// # Arguments
+    /// * `key` - The metadata key
+    ///
+    /// # Returns
+    /// The removed value, if any
+    pub fn remove_metadata(&mut self, key: &str) -> Option<String> {
+        self.metadata.remove(key)
+    }
+
+    /// Get all metadata entries, ordered by key
+    pub fn get_metadata_map(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    /// Get the caller's identifier for the item, stored under `METADATA_ID`
+    pub fn get_id(&self) -> Option<&str> {
+        self.get_metadata(METADATA_ID)
+    }
+
+    /// Scale the vector to unit length in place
+    ///
+    /// A zero (or empty) vector has no direction and cannot be normalized, so
+    /// it is left unchanged and an error is returned. Labels are preserved.
+    ///
+    /// # Returns
+    /// An error if the vector's L2 norm is 0
+    pub fn normalize_l2(&mut self) -> Result<(), Error> {
+        self.vector = self.normalized_l2()?;
+        Ok(())
+    }
+
+    /// Return a unit-length copy of the vector, leaving it untouched
+    ///
+    /// # Returns
+    /// The normalized values, or an error if the vector's L2 norm is 0
+    pub fn normalized_l2(&self) -> Result<Vec<S>, Error> {
+        let norm: S = metrics::l2_norm(&self.vector);
+        if norm == S::default() {
+            return Err(Error::msg("Cannot L2-normalize a zero vector"));
+        }
+
+        Ok(self.vector.iter().map(|value| *value / norm).collect())
+    }
+
+    /// Rescale the vector from the `[min, max]` score range to `[0, 1]` in place
+    ///
+    /// Use the prompts' declared scale as bounds so vectors stay comparable
+    /// across items. Values outside the bounds are mapped outside `[0, 1]`
+    /// rather than clamped. Labels are preserved.
+    ///
+    /// # Arguments
+    /// * `min` - The value mapped to 0.0
+    /// * `max` - The value mapped to 1.0
+    ///
+    /// # Returns
+    /// An error if `max` is not greater than `min`
+    pub fn normalize_min_max(&mut self, min: S, max: S) -> Result<(), Error> {
+        self.vector = self.normalized_min_max(min, max)?;
+        Ok(())
+    }
+
+    /// Return a copy rescaled from `[min, max]` to `[0, 1]`, leaving the vector untouched
+    ///
+    /// # Arguments
+    /// * `min` - The value mapped to 0.0
+    /// * `max` - The value mapped to 1.0
+    ///
+    /// # Returns
+    /// The rescaled values, or an error if `max` is not greater than `min`
+    pub fn normalized_min_max(&self, min: S, max: S) -> Result<Vec<S>, Error> {
+        if min.is_nan() || max.is_nan() || max <= min {
+            return Err(Error::msg(format!(
+                "Invalid min-max bounds: {} must be below {}",
+                min, max
+            )));
+        }
+
+        Ok(self.vector.iter().map(|value| (*value - min) / (max - min)).collect())
+    }
+
+    /// Check whether two vectors were produced by the same prompts and model
+    ///
+    /// Vectors are compatible when their fingerprints are equal. Two vectors
+    /// without a fingerprint (e.g. built by hand) are considered compatible,
+    /// while a fingerprinted vector is never compatible with one lacking it.
+    ///
+    /// # Arguments
+    /// * `other` - The vector to compare against
+    ///
+    /// # Returns
+    /// True if the two vectors can be meaningfully compared
+    pub fn compatible_with<U, R>(&self, other: &Vector<U, R>) -> bool {
+        self.fingerprint == other.fingerprint
+    }
+}
+
+impl<T: PartialEq, S: Scalar> Vector<T, S> {
+    /// Append another vector of the same data to this one
+    ///
+    /// Useful to join the results of separate prompt sets (e.g. "style" and
+    /// "content") run over the same item. Values and labels are appended; when
+    /// only one side is labeled the other side gets `dim_<index>` placeholders.
+    /// Metadata keys missing here are copied from `other`, and the fingerprints
+    /// are combined so the result is only compatible with vectors concatenated
+    /// from the same runs in the same order.
+    ///
+    /// # Arguments
+    /// * `other` - The vector to append
+    ///
+    /// # Returns
+    /// An error, leaving this vector untouched, if the data differs or a label
+    /// would appear twice
+    pub fn concat(&mut self, other: &Vector<T, S>) -> Result<(), Error> {
+        if self.data != other.data {
+            return Err(Error::msg("Cannot concatenate vectors of different data"));
+        }
+
+        let labels: Vec<String> = if self.labels.is_empty() && other.labels.is_empty() {
+            Vec::new()
+        } else {
+            let mut labels: Vec<String> = placeholder_labels(&self.labels, self.vector.len(), 0);
+            labels.extend(placeholder_labels(&other.labels, other.vector.len(), self.vector.len()));
+            for (index, label) in labels.iter().enumerate() {
+                if labels[..index].contains(label) {
+                    return Err(Error::msg(format!(
+                        "Cannot concatenate vectors: label '{}' appears in both",
+                        label
+                    )));
+                }
+            }
+            labels
+        };
+
+        self.fingerprint = match (&self.fingerprint, &other.fingerprint) {
+            (None, None) => None,
+            (left, right) => Some(combine_fingerprints(
+                "concat",
+                &[left.as_deref().unwrap_or_default(), right.as_deref().unwrap_or_default()],
+            )),
+        };
+        self.vector.extend_from_slice(&other.vector);
+        self.labels = labels;
+        self.expected_dims = self.expected_dims.zip(other.expected_dims).map(|(left, right)| left + right);
+        for (key, value) in &other.metadata {
+            self.metadata.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+
+        Ok(())
     }
 }
 
-impl<DynamicImage> Vector<DynamicImage> {
+impl<T: Clone, S: Scalar> Vector<T, S> {
+    /// Select the named dimensions, in the given order
+    ///
+    /// # Arguments
+    /// * `labels` - The labels of the dimensions to keep
+    ///
+    /// # Returns
+    /// The projected vector, or an error if the vector is unlabeled or a label
+    /// is unknown or repeated
+    pub fn project(&self, labels: &[&str]) -> Result<Vector<T, S>, Error> {
+        if self.labels.is_empty() {
+            return Err(Error::msg("Cannot project an unlabeled vector by labels"));
+        }
+        let indices: Vec<usize> = labels
+            .iter()
+            .map(|label| {
+                self.labels
+                    .iter()
+                    .position(|existing| existing == label)
+                    .ok_or_else(|| Error::msg(format!("Unknown dimension label '{}'", label)))
+            })
+            .collect::<Result<Vec<usize>, Error>>()?;
+
+        self.project_dims(&indices)
+    }
+
+    /// Select dimensions by index, in the given order
+    ///
+    /// The projection keeps the matching labels and metadata, and gets a
+    /// fingerprint derived from the original and the selected indices, so it is
+    /// never mistaken for a full vector.
+    ///
+    /// # Arguments
+    /// * `indices` - The indices of the dimensions to keep
+    ///
+    /// # Returns
+    /// The projected vector, or an error if an index is out of range or repeated
+    pub fn project_dims(&self, indices: &[usize]) -> Result<Vector<T, S>, Error> {
+        for (position, index) in indices.iter().enumerate() {
+            if *index >= self.vector.len() {
+                return Err(Error::msg(format!(
+                    "Dimension {} is out of range for a vector of {} dimensions",
+                    index,
+                    self.vector.len()
+                )));
+            }
+            if indices[..position].contains(index) {
+                return Err(Error::msg(format!("Dimension {} is selected twice", index)));
+            }
+        }
+
+        let selection: String = indices
+            .iter()
+            .map(|index| index.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+        let mut projected: Vector<T, S> = self.clone();
+        projected.vector = indices.iter().map(|index| self.vector[*index]).collect();
+        projected.expected_dims = None;
+        if !self.labels.is_empty() {
+            projected.labels = indices.iter().map(|index| self.labels[*index].clone()).collect();
+        }
+        projected.fingerprint = Some(combine_fingerprints(
+            "project",
+            &[self.fingerprint.as_deref().unwrap_or_default(), &selection],
+        ));
+
+        Ok(projected)
+    }
+}
+
+/// Concatenates several vectors of the same data into one
+///
+/// # Arguments
+/// * `vectors` - The vectors in the order their dimensions should appear
+///
+/// # Returns
+/// The combined vector, or an error if the slice is empty, the data differs or a
+/// label appears twice
+pub fn concat_all<T: PartialEq + Clone>(vectors: &[Vector<T>]) -> Result<Vector<T>, Error> {
+    let (first, rest) = vectors
+        .split_first()
+        .ok_or_else(|| Error::msg("Cannot concatenate an empty list of vectors"))?;
+
+    let mut combined: Vector<T> = first.clone();
+    for vector in rest {
+        combined.concat(vector)?;
+    }
+
+    Ok(combined)
+}
+
+impl<T> From<Vector<T>> for Vector64<T> {
+    fn from(vector: Vector<T>) -> Self {
+        vector.cast()
+    }
+}
+
+/// Returns `labels`, or `dim_<offset + index>` placeholders when there are none
+fn placeholder_labels(labels: &[String], dimensionality: usize, offset: usize) -> Vec<String> {
+    if labels.is_empty() {
+        (0..dimensionality).map(|index| format!("dim_{}", offset + index)).collect()
+    } else {
+        labels.to_vec()
+    }
+}
+
+#[cfg(feature = "image")]
+impl Vector<DynamicImage> {
     /// Initialize a new vector from image data
     ///
     /// # Arguments
@@ -84,15 +677,170 @@ impl<DynamicImage> Vector<DynamicImage> {
     /// # Returns
     /// A new Vector instance containing the image data
     pub fn from_image(data: DynamicImage) -> Self {
-        Self {
-            vector: vec![],
-            data,
-            data_type: DataType::Image,
+        Self::with_data(data, DataType::Image)
+    }
+
+    /// Initialize a new vector from an encoded image file
+    ///
+    /// An EXIF orientation other than upright is stored under
+    /// `METADATA_EXIF_ORIENTATION` and applied when the image is vectorized.
+    ///
+    /// # Arguments
+    /// * `bytes` - The image file contents
+    ///
+    /// # Returns
+    /// A new Vector instance containing the image as stored, or an error if it cannot be decoded
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let (image, orientation) = decode_image_with_orientation(bytes)?;
+        let mut vector: Self = Self::from_image(image);
+        if orientation != 1 {
+            vector.set_metadata(METADATA_EXIF_ORIENTATION, orientation.to_string());
         }
+
+        Ok(vector)
+    }
+
+    /// Initialize a new vector from an image file on disk, decoding it right away
+    ///
+    /// Same as `from_bytes`, and the path is stored under `METADATA_SOURCE`.
+    /// The decoded image stays in memory with the vector.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the image file
+    ///
+    /// # Returns
+    /// A new Vector instance containing the image, or an error if it cannot be read or decoded
+    #[deprecated(note = "use `Vector::from_image_path`, which decodes the image only when it is vectorized")]
+    pub fn from_image_path_decoded(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+        let path: &std::path::Path = path.as_ref();
+        let bytes: Vec<u8> = std::fs::read(path)
+            .map_err(|e| Error::msg(format!("Failed to read image {}: {}", path.display(), e)))?;
+        let mut vector: Self = Self::from_bytes(&bytes)?;
+        vector.set_metadata(METADATA_SOURCE, path.display().to_string());
+
+        Ok(vector)
+    }
+
+    /// Initialize a new vector from an image downloaded from a URL
+    ///
+    /// The default `ImageDownloadOptions` apply; use `load_image_from_url`
+    /// for other limits. The URL is stored under `METADATA_SOURCE`.
+    ///
+    /// # Arguments
+    /// * `url` - Where to download the image from
+    ///
+    /// # Returns
+    /// A new Vector instance containing the image, or an error if it cannot be downloaded or decoded
+    pub async fn from_image_url(url: &str) -> Result<Self, Error> {
+        let image: DynamicImage = load_image_from_url(url, &ImageDownloadOptions::default()).await?;
+        let mut vector: Self = Self::from_image(image);
+        vector.set_metadata(METADATA_SOURCE, url);
+
+        Ok(vector)
+    }
+}
+
+#[cfg(feature = "image")]
+impl Vector<EncodedImage> {
+    /// Initialize a new vector from an image that is already base64-encoded
+    ///
+    /// The encoding is sent to the model as it is, without decoding the image.
+    ///
+    /// # Arguments
+    /// * `data` - The base64 encoding of the image file
+    /// * `mime` - The MIME type of the image, e.g. `image/jpeg`
+    ///
+    /// # Returns
+    /// A new Vector instance containing the image, or an error for invalid
+    /// base64, an unsupported MIME type or data of another type
+    pub fn from_image_base64(data: String, mime: &str) -> Result<Self, Error> {
+        Ok(Self::with_data(EncodedImage::from_base64(data, mime)?, DataType::Image))
+    }
+
+    /// Initialize a new vector from an encoded image file, without decoding it
+    ///
+    /// # Arguments
+    /// * `bytes` - The image file contents, in PNG, JPEG, GIF or WebP
+    ///
+    /// # Returns
+    /// A new Vector instance containing the image, or an error for other formats
+    pub fn from_image_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self::with_data(EncodedImage::from_bytes(bytes)?, DataType::Image))
     }
 }
 
-impl<String> Vector<String> {
+#[cfg(feature = "image")]
+impl Vector<ImageFile> {
+    /// Initialize a new vector from an image file on disk, decoding it only when it is vectorized
+    ///
+    /// The file is not read, so a batch of many images holds only their paths.
+    /// Each image is decoded on a blocking thread while its requests are built
+    /// and dropped once it is encoded. The path, file size and modification
+    /// time are stored like `load_image_directory` does.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the image file
+    ///
+    /// # Returns
+    /// A new Vector instance referring to the image, or an error if the file does not exist
+    pub fn from_image_path(path: impl Into<std::path::PathBuf>) -> Result<Self, Error> {
+        let mut vector: Self = Self::with_data(ImageFile::new(path)?, DataType::Image);
+        let image_file: ImageFile = vector.get_data().clone();
+        record_file_metadata(&mut vector, image_file.get_path(), image_file.get_file_size())?;
+
+        Ok(vector)
+    }
+}
+
+impl Vector<RemoteImage> {
+    /// Initialize a new vector from an image hosted at a public https URL
+    ///
+    /// The URL is sent to the model, which downloads the image itself, so
+    /// resizing, re-encoding and EXIF orientation are not applied.
+    ///
+    /// # Arguments
+    /// * `url` - The https URL of the image
+    ///
+    /// # Returns
+    /// A new Vector instance containing the image, or an error if `url` is not a valid https URL
+    pub fn from_image_remote(url: impl Into<String>) -> Result<Self, Error> {
+        let mut vector: Self = Self::with_data(RemoteImage::new(url)?, DataType::Image);
+        vector.set_metadata(METADATA_SOURCE, vector.get_data().get_url().to_string());
+
+        Ok(vector)
+    }
+}
+
+#[cfg(feature = "image")]
+impl Vector<Vec<DynamicImage>> {
+    /// Initialize a new vector from several images of one item, such as the photos of a listing
+    ///
+    /// # Arguments
+    /// * `images` - The images, in the order they are sent to the LLM
+    ///
+    /// # Returns
+    /// A new Vector instance containing the images
+    pub fn from_images(images: Vec<DynamicImage>) -> Self {
+        Self::with_data(images, DataType::Image)
+    }
+}
+
+#[cfg(feature = "image")]
+impl Vector<(DynamicImage, String)> {
+    /// Initialize a new vector from an image and its text, such as a listing photo and its title
+    ///
+    /// # Arguments
+    /// * `image` - The image
+    /// * `text` - The text shown to the LLM alongside the image
+    ///
+    /// # Returns
+    /// A new Vector instance containing the pair
+    pub fn from_image_with_text(image: DynamicImage, text: String) -> Self {
+        Self::with_data((image, text), DataType::ImageWithText)
+    }
+}
+
+impl Vector<String> {
     /// Initialize a new vector from text data
     ///
     /// # Arguments
@@ -101,10 +849,123 @@ impl<String> Vector<String> {
     /// # Returns 
     /// A new Vector instance containing the text data
     pub fn from_text(data: String) -> Self {
-        Self {
-            vector: vec![],
-            data,
-            data_type: DataType::Text,
+        Self::with_data(data, DataType::Text)
+    }
+}
+
+#[cfg(feature = "document")]
+impl Vector<String> {
+    /// Initialize a new vector from the text of a document
+    ///
+    /// # Arguments
+    /// * `bytes` - The document file contents
+    /// * `format` - The file format of `bytes`
+    ///
+    /// # Returns
+    /// A new Vector instance containing the extracted text, or a `DocumentError`
+    /// telling documents without a text layer apart from unreadable ones
+    pub fn from_document(bytes: &[u8], format: DocumentFormat) -> Result<Self, DocumentError> {
+        Ok(Self::from_text(extract_text(bytes, format)?))
+    }
+}
+
+#[cfg(feature = "html")]
+impl Vector<String> {
+    /// Initialize a new vector from the readable text of an HTML page
+    ///
+    /// The size of the HTML is stored under `METADATA_ORIGINAL_LENGTH` and the
+    /// URL, when given, under `METADATA_SOURCE`.
+    ///
+    /// # Arguments
+    /// * `html` - The HTML source of the page
+    /// * `url` - Where the page was fetched from, if known
+    ///
+    /// # Returns
+    /// A new Vector instance containing the text extracted by `extract_readable_text`
+    pub fn from_html(html: &str, url: Option<&str>) -> Self {
+        let mut vector: Self = Self::from_text(crate::raw_data::extract_readable_text(html));
+        vector.set_metadata(METADATA_ORIGINAL_LENGTH, html.len().to_string());
+        if let Some(url) = url {
+            vector.set_metadata(METADATA_SOURCE, url);
         }
+
+        vector
+    }
+}
+
+impl Vector<String> {
+    /// Initialize a new vector from source code
+    ///
+    /// The code is sent to the LLM in a fence headed by its language, which is
+    /// stored under `METADATA_LANGUAGE`. Long files are chunked at blank lines
+    /// where possible, so functions tend to stay whole.
+    ///
+    /// # Arguments
+    /// * `source` - The source code
+    /// * `language` - The programming language, such as `Rust`, if known
+    ///
+    /// # Returns
+    /// A new Vector instance containing the code
+    pub fn from_code(source: String, language: Option<String>) -> Self {
+        let mut vector: Self = Self::with_data(source, DataType::Code);
+        if let Some(language) = language {
+            vector.set_metadata(METADATA_LANGUAGE, language);
+        }
+
+        vector
+    }
+}
+
+impl Vector<AudioData> {
+    /// Initialize a new vector from audio data
+    ///
+    /// # Arguments
+    /// * `bytes` - The encoded audio file contents
+    /// * `format` - The encoding of `bytes`
+    ///
+    /// # Returns
+    /// A new Vector instance containing the audio data
+    pub fn from_audio(bytes: Vec<u8>, format: AudioFormat) -> Self {
+        Self::with_data(AudioData::new(bytes, format), DataType::Audio)
+    }
+}
+
+impl Vector<Vec<ChatTurn>> {
+    /// Initialize a new vector from a conversation, such as a support chat
+    ///
+    /// # Arguments
+    /// * `turns` - The messages in the order they were written
+    ///
+    /// # Returns
+    /// A new Vector instance containing the conversation
+    pub fn from_conversation(turns: Vec<ChatTurn>) -> Self {
+        Self::with_data(turns, DataType::Conversation)
+    }
+}
+
+impl Vector<serde_json::Value> {
+    /// Initialize a new vector from a JSON record, such as a product row
+    ///
+    /// # Arguments
+    /// * `record` - The record, usually a JSON object
+    ///
+    /// # Returns
+    /// A new Vector instance containing the record
+    pub fn from_record(record: serde_json::Value) -> Self {
+        Self::with_data(record, DataType::Record)
+    }
+}
+
+impl Vector<VideoData> {
+    /// Initialize a new vector from video data
+    ///
+    /// # Arguments
+    /// * `bytes` - The encoded video file contents
+    /// * `format` - The container format of `bytes`
+    ///
+    /// # Returns
+    /// A new Vector instance containing the video data
+    pub fn from_video(bytes: Vec<u8>, format: VideoFormat) -> Self {
+        Self::with_data(VideoData::new(bytes, format), DataType::Video)
     }
 }
@@ -0,0 +1,111 @@
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::vector::Vector;
+
+/// A vector with one bit per dimension, packed into 64-bit words
+///
+/// Bit `i` is stored in word `i / 64` at position `i % 64`; unused bits of the
+/// last word are always 0.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BitVector {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVector {
+    /// Packs a sequence of bits
+    pub fn from_bits(bits: &[bool]) -> Self {
+        let mut words: Vec<u64> = vec![0; bits.len().div_ceil(64)];
+        for (index, bit) in bits.iter().enumerate() {
+            if *bit {
+                words[index / 64] |= 1 << (index % 64);
+            }
+        }
+
+        Self { words, len: bits.len() }
+    }
+
+    /// Returns the bit at `index`
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+
+        Some(self.words[index / 64] >> (index % 64) & 1 == 1)
+    }
+
+    /// Returns the number of dimensions
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether there are no dimensions
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the packed words
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Returns the number of set bits
+    pub fn count_ones(&self) -> u32 {
+        self.words.iter().map(|word| word.count_ones()).sum()
+    }
+}
+
+/// Counts the dimensions on which two bit vectors differ
+///
+/// # Arguments
+/// * `a` - The first bit vector
+/// * `b` - The second bit vector
+///
+/// # Returns
+/// The Hamming distance, or an error if the lengths differ
+pub fn hamming_distance(a: &BitVector, b: &BitVector) -> Result<u32, Error> {
+    if a.len != b.len {
+        return Err(Error::msg(format!(
+            "Dimensionality mismatch: {} vs {}",
+            a.len, b.len
+        )));
+    }
+
+    Ok(a.words.iter().zip(&b.words).map(|(x, y)| (x ^ y).count_ones()).sum())
+}
+
+/// Sets a bit for every value strictly above its threshold
+pub(crate) fn binarize_values(values: &[f32], thresholds: &[f32]) -> Result<BitVector, Error> {
+    if values.len() != thresholds.len() {
+        return Err(Error::msg(format!(
+            "Expected {} thresholds, got {}",
+            values.len(),
+            thresholds.len()
+        )));
+    }
+
+    let bits: Vec<bool> = values
+        .iter()
+        .zip(thresholds)
+        .map(|(value, threshold)| value > threshold)
+        .collect();
+
+    Ok(BitVector::from_bits(&bits))
+}
+
+impl<T> Vector<T> {
+    /// Binarize each dimension against a threshold
+    ///
+    /// Use `DimensionStats::median` or `DimensionStats::mean` as thresholds to
+    /// split each dimension at the dataset's center.
+    ///
+    /// # Arguments
+    /// * `thresholds` - One threshold per dimension; values above it become 1
+    ///
+    /// # Returns
+    /// The packed bits, or an error if the thresholds do not match the dimensionality
+    pub fn binarize(&self, thresholds: &[f32]) -> Result<BitVector, Error> {
+        binarize_values(&self.vector, thresholds)
+    }
+}
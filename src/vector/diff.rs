@@ -0,0 +1,125 @@
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::vector::{Vector, VectorOperations};
+
+/// A dimension on which two vectors differ by more than the tolerance
+///
+/// # Fields
+/// * `index` - The dimension index
+/// * `label` - The dimension label, `dim_<index>` when unlabeled
+/// * `left` - The value in the vector `compare` was called on
+/// * `right` - The value in the other vector
+/// * `abs_diff` - The absolute difference
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DimensionDiff {
+    pub index: usize,
+    pub label: String,
+    pub left: f32,
+    pub right: f32,
+    pub abs_diff: f32,
+}
+
+/// The element-wise differences between two vectors
+///
+/// # Fields
+/// * `max_abs_diff` - The largest absolute difference over all dimensions
+/// * `differences` - The dimensions outside the tolerance, in index order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VectorDiff {
+    pub max_abs_diff: f32,
+    pub differences: Vec<DimensionDiff>,
+}
+
+impl VectorDiff {
+    /// Returns whether every dimension is within the tolerance
+    pub fn is_within_tolerance(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+impl std::fmt::Display for VectorDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "max abs diff: {}", self.max_abs_diff)?;
+        for difference in &self.differences {
+            writeln!(
+                f,
+                "  {}: {} vs {} (diff {})",
+                difference.label, difference.left, difference.right, difference.abs_diff
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether two values are equal within an absolute or relative tolerance
+fn within_tolerance(a: f32, b: f32, abs_tol: f32, rel_tol: f32) -> bool {
+    (a - b).abs() <= abs_tol.max(rel_tol * a.abs().max(b.abs()))
+}
+
+impl<T> Vector<T> {
+    /// Check whether two vectors are equal within a tolerance
+    ///
+    /// Two values match when `|a - b| <= max(abs_tol, rel_tol * max(|a|, |b|))`.
+    ///
+    /// # Arguments
+    /// * `other` - The vector to compare against
+    /// * `abs_tol` - The absolute tolerance
+    /// * `rel_tol` - The tolerance relative to the larger magnitude
+    ///
+    /// # Returns
+    /// True if the lengths match and every dimension is within tolerance
+    pub fn approx_eq<U>(&self, other: &Vector<U>, abs_tol: f32, rel_tol: f32) -> bool {
+        self.vector.len() == other.vector.len()
+            && self
+                .vector
+                .iter()
+                .zip(&other.vector)
+                .all(|(a, b)| within_tolerance(*a, *b, abs_tol, rel_tol))
+    }
+
+    /// List the dimensions on which two vectors differ beyond a tolerance
+    ///
+    /// Labels are taken from this vector, falling back to the other's.
+    ///
+    /// # Arguments
+    /// * `other` - The vector to compare against
+    /// * `abs_tol` - The absolute tolerance
+    /// * `rel_tol` - The tolerance relative to the larger magnitude
+    ///
+    /// # Returns
+    /// The differences, or an error if the dimensionalities differ
+    pub fn compare<U>(&self, other: &Vector<U>, abs_tol: f32, rel_tol: f32) -> Result<VectorDiff, Error> {
+        if self.vector.len() != other.vector.len() {
+            return Err(Error::msg(format!(
+                "Dimensionality mismatch: {} vs {}",
+                self.vector.len(),
+                other.vector.len()
+            )));
+        }
+
+        let labels: Vec<String> = if self.labels.is_empty() {
+            other.get_labeled_vector().into_iter().map(|(label, _)| label).collect()
+        } else {
+            self.labels.clone()
+        };
+        let mut max_abs_diff: f32 = 0.0;
+        let mut differences: Vec<DimensionDiff> = Vec::new();
+        for (index, (left, right)) in self.vector.iter().zip(&other.vector).enumerate() {
+            let abs_diff: f32 = (left - right).abs();
+            max_abs_diff = max_abs_diff.max(abs_diff);
+            if !within_tolerance(*left, *right, abs_tol, rel_tol) {
+                differences.push(DimensionDiff {
+                    index,
+                    label: labels[index].clone(),
+                    left: *left,
+                    right: *right,
+                    abs_diff,
+                });
+            }
+        }
+
+        Ok(VectorDiff { max_abs_diff, differences })
+    }
+}
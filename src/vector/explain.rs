@@ -0,0 +1,104 @@
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::vector::metrics::{l2_norm, Metric};
+use crate::vector::{Vector, VectorOperations};
+
+/// How much one dimension adds to the score of two vectors
+///
+/// # Fields
+/// * `index` - The dimension index
+/// * `label` - The dimension label, `dim_<index>` when unlabeled
+/// * `left` - The value in the first vector
+/// * `right` - The value in the second vector
+/// * `contribution` - The share of the score due to this dimension
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DimensionContribution {
+    pub index: usize,
+    pub label: String,
+    pub left: f32,
+    pub right: f32,
+    pub contribution: f32,
+}
+
+/// Why two vectors score as they do, dimension by dimension
+///
+/// The contributions add up to the score. With `Cosine` and `Dot`, a positive
+/// contribution is agreement and a negative one disagreement; with
+/// `Euclidean`, every contribution is distance, so the largest are the
+/// dimensions that disagree most.
+///
+/// # Fields
+/// * `metric` - The metric the vectors were compared with
+/// * `score` - The overall score, as `Metric::score` computes it
+/// * `contributions` - Every dimension, the largest contribution in magnitude first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimilarityExplanation {
+    pub metric: Metric,
+    pub score: f32,
+    pub contributions: Vec<DimensionContribution>,
+}
+
+impl std::fmt::Display for SimilarityExplanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:?}: {}", self.metric, self.score)?;
+        for contribution in &self.contributions {
+            writeln!(
+                f,
+                "  {}: {:+} ({} vs {})",
+                contribution.label, contribution.contribution, contribution.left, contribution.right
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Breaks the score of two vectors down into the contribution of each dimension
+///
+/// With `Dot`, each dimension contributes the product of its values; with
+/// `Cosine`, that product over the product of the norms; with `Euclidean`, the
+/// squared difference over the distance. Either way the contributions add up
+/// to the score. Labels are taken from the first vector, falling back to the
+/// second's.
+///
+/// # Arguments
+/// * `a` - The first vector, e.g. a query
+/// * `b` - The second vector, e.g. a search hit
+/// * `metric` - How the vectors are compared
+///
+/// # Returns
+/// The explanation, or an error on empty or mismatched vectors
+pub fn explain_similarity<T, U>(a: &Vector<T>, b: &Vector<U>, metric: Metric) -> Result<SimilarityExplanation, Error> {
+    let labeled: &[String] = if a.get_labels().is_empty() { b.get_labels() } else { a.get_labels() };
+    let (left, right): (&[f32], &[f32]) = (a.as_slice(), b.as_slice());
+    let score: f32 = metric.score(left, right)?;
+
+    let scale: f32 = match metric {
+        Metric::Dot => 1.0,
+        Metric::Cosine => l2_norm(left) * l2_norm(right),
+        Metric::Euclidean => score,
+    };
+    let mut contributions: Vec<DimensionContribution> = left
+        .iter()
+        .zip(right)
+        .enumerate()
+        .map(|(index, (x, y))| {
+            let term: f32 = match metric {
+                Metric::Dot | Metric::Cosine => x * y,
+                Metric::Euclidean => (x - y) * (x - y),
+            };
+            DimensionContribution {
+                index,
+                label: labeled.get(index).cloned().unwrap_or_else(|| format!("dim_{}", index)),
+                left: *x,
+                right: *y,
+                // Identical or zero vectors have nothing to share out
+                contribution: if scale == 0.0 { 0.0 } else { term / scale },
+            }
+        })
+        .collect();
+    contributions.sort_by(|a, b| b.contribution.abs().total_cmp(&a.contribution.abs()));
+
+    Ok(SimilarityExplanation { metric, score, contributions })
+}
@@ -0,0 +1,322 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use anyhow::{Error, Result};
+#[cfg(feature = "image")]
+use image::DynamicImage;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::raw_data::VectorData;
+#[cfg(feature = "image")]
+use crate::vector::serialization::SerializableImageVector;
+use crate::vector::{Vector, VectorOperations, METADATA_ID};
+
+/// Whether image payloads are written along with image vectors
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImagePayload {
+    /// Write the image as a base64 PNG so the vector can be fully restored
+    Embedded,
+    /// Write only a SHA-256 of the pixels, keeping files small
+    HashOnly,
+}
+
+/// Saves vectors to a JSONL file, one JSON object per line
+///
+/// Any serializable record works, such as `Vector<String>`, `Vector<AudioData>`
+/// or `SerializableImageVector`. Use `save_image_jsonl` for `Vector<DynamicImage>`.
+///
+/// # Arguments
+/// * `path` - The file to create or truncate
+/// * `vectors` - The records to write
+///
+/// # Returns
+/// An error if the file cannot be written or a record cannot be serialized
+pub fn save_jsonl<V: Serialize>(path: impl AsRef<Path>, vectors: &[V]) -> Result<(), Error> {
+    let mut writer: BufWriter<File> = BufWriter::new(File::create(path)?);
+    for vector in vectors {
+        serde_json::to_writer(&mut writer, vector)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Loads all vectors from a JSONL file
+///
+/// # Arguments
+/// * `path` - The file to read
+///
+/// # Returns
+/// The records in file order, or an error naming the first malformed line
+pub fn load_jsonl<V: DeserializeOwned>(path: impl AsRef<Path>) -> Result<Vec<V>, Error> {
+    read_jsonl(path)?.collect()
+}
+
+/// Opens a JSONL file for streaming, one record at a time
+///
+/// Only the current line is held in memory, so large files can be processed
+/// without loading them fully. Blank lines are skipped.
+///
+/// # Arguments
+/// * `path` - The file to read
+///
+/// # Returns
+/// An iterator over the records, each an error naming its line if malformed
+pub fn read_jsonl<V: DeserializeOwned>(path: impl AsRef<Path>) -> Result<JsonlReader<V>, Error> {
+    let file: File = File::open(path)?;
+
+    Ok(JsonlReader {
+        lines: BufReader::new(file).lines(),
+        line_number: 0,
+        marker: PhantomData,
+    })
+}
+
+/// Streaming iterator over the records of a JSONL file
+pub struct JsonlReader<V> {
+    lines: Lines<BufReader<File>>,
+    line_number: usize,
+    marker: PhantomData<V>,
+}
+
+impl<V: DeserializeOwned> Iterator for JsonlReader<V> {
+    type Item = Result<V, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line: String = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(Error::msg(format!("line {}: {}", self.line_number + 1, e)))),
+            };
+            self.line_number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            return Some(
+                serde_json::from_str::<V>(&line)
+                    .map_err(|e| Error::msg(format!("line {}: {}", self.line_number, e))),
+            );
+        }
+    }
+}
+
+/// Saves image vectors to a JSONL file
+///
+/// # Arguments
+/// * `path` - The file to create or truncate
+/// * `vectors` - The image vectors to write
+/// * `payload` - Whether to embed the images or only their pixel hash
+///
+/// # Returns
+/// An error if the file cannot be written or an image cannot be encoded
+#[cfg(feature = "image")]
+pub fn save_image_jsonl(
+    path: impl AsRef<Path>,
+    vectors: &[Vector<DynamicImage>],
+    payload: ImagePayload,
+) -> Result<(), Error> {
+    let records: Vec<SerializableImageVector> = vectors
+        .iter()
+        .map(|vector| match payload {
+            ImagePayload::Embedded => SerializableImageVector::try_from(vector),
+            ImagePayload::HashOnly => Ok(SerializableImageVector::vector_only(vector)),
+        })
+        .collect::<Result<_, Error>>()?;
+
+    save_jsonl(path, &records)
+}
+
+/// Loads image vectors saved with `ImagePayload::Embedded`
+///
+/// # Arguments
+/// * `path` - The file to read
+///
+/// # Returns
+/// The image vectors, or an error naming the first malformed line or the first
+/// line without an embedded image
+#[cfg(feature = "image")]
+pub fn load_image_jsonl(path: impl AsRef<Path>) -> Result<Vec<Vector<DynamicImage>>, Error> {
+    read_jsonl::<SerializableImageVector>(path)?
+        .enumerate()
+        .map(|(index, record)| {
+            Vector::try_from(record?).map_err(|e| Error::msg(format!("record {}: {}", index + 1, e)))
+        })
+        .collect()
+}
+
+/// Configuration for CSV export and import
+///
+/// # Fields
+/// * `id_column` - Header of the identifier column
+/// * `id_metadata_key` - Metadata key the identifier is read from and written to
+/// * `text_column` - Header of the raw text column; None to leave the text out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    pub id_column: String,
+    pub id_metadata_key: String,
+    pub text_column: Option<String>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            id_column: "id".to_string(),
+            id_metadata_key: METADATA_ID.to_string(),
+            text_column: None,
+        }
+    }
+}
+
+/// Exports vectors to a CSV file with one row per item and one column per dimension
+///
+/// The header holds the identifier column, the optional text column, then the
+/// dimension labels of the first vector (or `dim_0..dim_n` when unlabeled).
+/// Text containing commas, quotes or newlines is quoted per RFC 4180.
+///
+/// # Arguments
+/// * `path` - The file to create or truncate
+/// * `vectors` - The vectors to export
+/// * `options` - Column configuration
+///
+/// # Returns
+/// An error before anything is written if the vectors disagree on dimensionality
+/// or labels, or an error if the file cannot be written
+pub fn export_csv<T: VectorData>(
+    path: impl AsRef<Path>,
+    vectors: &[Vector<T>],
+    options: &CsvOptions,
+) -> Result<(), Error> {
+    let labels: Vec<String> = consistent_labels(vectors)?;
+
+    let mut writer = csv::Writer::from_path(path)?;
+    let mut header: Vec<String> = vec![options.id_column.clone()];
+    if let Some(text_column) = &options.text_column {
+        header.push(text_column.clone());
+    }
+    header.extend(labels);
+    writer.write_record(&header)?;
+
+    for vector in vectors {
+        let mut record: Vec<String> = vec![
+            vector.get_metadata(&options.id_metadata_key).unwrap_or_default().to_string(),
+        ];
+        if options.text_column.is_some() {
+            record.push(vector.get_data().as_text().unwrap_or_default().to_string());
+        }
+        record.extend(vector.as_slice().iter().map(|value| value.to_string()));
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Imports the numeric part of a CSV file written by `export_csv`
+///
+/// Every column other than the identifier and text columns is read as a
+/// dimension, labeled by its header. The text column, when configured and
+/// present, becomes the vector's data; otherwise the data is empty.
+///
+/// # Arguments
+/// * `path` - The file to read
+/// * `options` - Column configuration
+///
+/// # Returns
+/// The text vectors, or an error naming the first row with a non-numeric value
+pub fn import_csv(path: impl AsRef<Path>, options: &CsvOptions) -> Result<Vec<Vector<String>>, Error> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let header: Vec<String> = reader.headers()?.iter().map(str::to_string).collect();
+    let id_index: Option<usize> = header.iter().position(|column| *column == options.id_column);
+    let text_index: Option<usize> = options
+        .text_column
+        .as_ref()
+        .and_then(|text_column| header.iter().position(|column| column == text_column));
+    let dimension_indices: Vec<usize> = (0..header.len())
+        .filter(|index| Some(*index) != id_index && Some(*index) != text_index)
+        .collect();
+    let labels: Vec<String> = dimension_indices.iter().map(|index| header[*index].clone()).collect();
+
+    let mut vectors: Vec<Vector<String>> = Vec::new();
+    for (row, record) in reader.records().enumerate() {
+        // Row 1 is the header
+        let record = record.map_err(|e| Error::msg(format!("row {}: {}", row + 2, e)))?;
+        let values: Vec<f32> = dimension_indices
+            .iter()
+            .map(|index| {
+                let field: &str = record.get(*index).unwrap_or_default();
+                field.trim().parse::<f32>().map_err(|e| {
+                    Error::msg(format!("row {}, column '{}': {}: '{}'", row + 2, header[*index], e, field))
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        let text: String = text_index
+            .and_then(|index| record.get(index))
+            .unwrap_or_default()
+            .to_string();
+        let mut vector: Vector<String> = Vector::from_text(text);
+        vector.overwrite_vector_with_labels(values, labels.clone())?;
+        if let Some(id) = id_index.and_then(|index| record.get(index)).filter(|id| !id.is_empty()) {
+            vector.set_metadata(options.id_metadata_key.clone(), id);
+        }
+        vectors.push(vector);
+    }
+
+    Ok(vectors)
+}
+
+/// Returns the shared dimension labels of a collection, validating consistency
+///
+/// Unlabeled collections get `dim_0..dim_n`. Vectors may be unlabeled as long
+/// as all labeled vectors agree.
+pub(crate) fn consistent_labels<T>(vectors: &[Vector<T>]) -> Result<Vec<String>, Error> {
+    let dimensionality: usize = match vectors.first() {
+        Some(first) => first.get_dimensionality(),
+        None => return Ok(Vec::new()),
+    };
+
+    let mut labels: Option<&[String]> = None;
+    let mut positions: BTreeMap<&str, usize> = BTreeMap::new();
+    for (index, vector) in vectors.iter().enumerate() {
+        if vector.get_dimensionality() != dimensionality {
+            return Err(Error::msg(format!(
+                "Inconsistent dimensionality: vector {} has {} dimensions, expected {}",
+                index,
+                vector.get_dimensionality(),
+                dimensionality
+            )));
+        }
+        if vector.get_labels().is_empty() {
+            continue;
+        }
+        match labels {
+            None => labels = Some(vector.get_labels()),
+            Some(existing) if existing != vector.get_labels() => {
+                return Err(Error::msg(format!(
+                    "Inconsistent labels: vector {} is labeled differently from the others",
+                    index
+                )));
+            }
+            _ => {}
+        }
+    }
+
+    match labels {
+        Some(labels) => {
+            for (index, label) in labels.iter().enumerate() {
+                if positions.insert(label, index).is_some() {
+                    return Err(Error::msg(format!("Duplicate dimension label '{}'", label)));
+                }
+            }
+            Ok(labels.to_vec())
+        }
+        None => Ok((0..dimensionality).map(|index| format!("dim_{}", index)).collect()),
+    }
+}
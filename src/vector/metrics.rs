@@ -0,0 +1,128 @@
+use std::cmp::Ordering;
+
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::vector::Scalar;
+
+/// Ensures two vectors can be compared element-wise
+fn check_dimensions<S: Scalar>(a: &[S], b: &[S]) -> Result<(), Error> {
+    if a.is_empty() || b.is_empty() {
+        return Err(Error::msg("Cannot compare empty vectors"));
+    }
+    if a.len() != b.len() {
+        return Err(Error::msg(format!(
+            "Dimensionality mismatch: {} vs {}",
+            a.len(),
+            b.len()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Computes the dot product of two vectors
+///
+/// # Arguments
+/// * `a` - The first vector
+/// * `b` - The second vector
+///
+/// # Returns
+/// The dot product, or an error on empty or mismatched vectors
+pub fn dot<S: Scalar>(a: &[S], b: &[S]) -> Result<S, Error> {
+    check_dimensions(a, b)?;
+
+    Ok(a.iter().zip(b).map(|(x, y)| *x * *y).sum())
+}
+
+/// Computes the cosine similarity of two vectors
+///
+/// A zero vector has no direction, so its similarity to any vector is
+/// defined as 0.0 rather than an error.
+///
+/// # Arguments
+/// * `a` - The first vector
+/// * `b` - The second vector
+///
+/// # Returns
+/// The similarity in [-1, 1], or an error on empty or mismatched vectors
+pub fn cosine_similarity<S: Scalar>(a: &[S], b: &[S]) -> Result<S, Error> {
+    let product: S = dot(a, b)?;
+    let norms: S = l2_norm(a) * l2_norm(b);
+    if norms == S::default() {
+        return Ok(S::default());
+    }
+
+    Ok(product / norms)
+}
+
+/// Computes the euclidean distance between two vectors
+///
+/// # Arguments
+/// * `a` - The first vector
+/// * `b` - The second vector
+///
+/// # Returns
+/// The distance, or an error on empty or mismatched vectors
+pub fn euclidean_distance<S: Scalar>(a: &[S], b: &[S]) -> Result<S, Error> {
+    check_dimensions(a, b)?;
+
+    Ok(a.iter().zip(b).map(|(x, y)| (*x - *y) * (*x - *y)).sum::<S>().sqrt())
+}
+
+/// Computes the L2 norm (length) of a vector
+pub fn l2_norm<S: Scalar>(vector: &[S]) -> S {
+    vector.iter().map(|x| *x * *x).sum::<S>().sqrt()
+}
+
+/// The measure used to compare vectors in a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Metric {
+    /// Cosine similarity, higher is closer
+    Cosine,
+    /// Dot product, higher is closer
+    Dot,
+    /// Euclidean distance, lower is closer
+    Euclidean,
+}
+
+impl Metric {
+    /// Scores two vectors with this metric
+    ///
+    /// # Arguments
+    /// * `a` - The first vector
+    /// * `b` - The second vector
+    ///
+    /// # Returns
+    /// The score, or an error on empty or mismatched vectors
+    pub fn score<S: Scalar>(&self, a: &[S], b: &[S]) -> Result<S, Error> {
+        match self {
+            Metric::Cosine => cosine_similarity(a, b),
+            Metric::Dot => dot(a, b),
+            Metric::Euclidean => euclidean_distance(a, b),
+        }
+    }
+
+    /// Returns whether a higher score means the vectors are closer
+    pub fn higher_is_closer(&self) -> bool {
+        !matches!(self, Metric::Euclidean)
+    }
+
+    /// Returns whether a score is at least as close as `threshold`
+    pub fn within(&self, score: f32, threshold: f32) -> bool {
+        if self.higher_is_closer() {
+            score >= threshold
+        } else {
+            score <= threshold
+        }
+    }
+
+    /// Orders two scores so that the closer one comes first
+    pub fn closest_first(&self, a: f32, b: f32) -> Ordering {
+        if self.higher_is_closer() {
+            b.total_cmp(&a)
+        } else {
+            a.total_cmp(&b)
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use anyhow::{Error, Result};
+use ndarray::{Array2, ArrayView1};
+
+use crate::vector::{Scalar, Vector};
+
+impl<T, S: Scalar> Vector<T, S> {
+    /// Views the values as a 1-D array, without copying
+    pub fn as_array1(&self) -> ArrayView1<'_, S> {
+        ArrayView1::from(self.vector.as_slice())
+    }
+}
+
+/// Copies the values of vectors into a matrix with one row per item and one column per dimension
+///
+/// # Arguments
+/// * `vectors` - The vectors, one row each
+///
+/// # Returns
+/// The items × dims matrix, or an error if the vectors disagree on dimensionality
+pub fn to_array2<T>(vectors: &[Vector<T>]) -> Result<Array2<f32>, Error> {
+    let dimensionality: usize = vectors.first().map_or(0, |first| first.vector.len());
+    let mut values: Vec<f32> = Vec::with_capacity(vectors.len() * dimensionality);
+    for (index, vector) in vectors.iter().enumerate() {
+        if vector.vector.len() != dimensionality {
+            return Err(Error::msg(format!(
+                "Inconsistent dimensionality: vector {} has {} dimensions, expected {}",
+                index,
+                vector.vector.len(),
+                dimensionality
+            )));
+        }
+        values.extend_from_slice(&vector.vector);
+    }
+
+    Array2::from_shape_vec((vectors.len(), dimensionality), values)
+        .map_err(|e| Error::msg(format!("Failed to build the matrix: {}", e)))
+}
+
+/// Writes the rows of a matrix back into the vectors, e.g. after transforming the output of `to_array2`
+///
+/// Only the values change; labels, metadata and fingerprints are kept.
+///
+/// # Arguments
+/// * `vectors` - The vectors to update, one per row
+/// * `array` - The new values, items × dims
+///
+/// # Returns
+/// An error, leaving every vector untouched, if the matrix does not have one
+/// row per vector and one column per dimension of each
+pub fn update_from_array2<T>(vectors: &mut [Vector<T>], array: &Array2<f32>) -> Result<(), Error> {
+    let (rows, columns) = array.dim();
+    if rows != vectors.len() {
+        return Err(Error::msg(format!("The matrix has {} rows for {} vectors", rows, vectors.len())));
+    }
+    if let Some((index, vector)) = vectors.iter().enumerate().find(|(_, vector)| vector.vector.len() != columns) {
+        return Err(Error::msg(format!(
+            "The matrix has {} columns, but vector {} has {} dimensions",
+            columns,
+            index,
+            vector.vector.len()
+        )));
+    }
+
+    for (vector, row) in vectors.iter_mut().zip(array.rows()) {
+        vector.vector = row.to_vec();
+    }
+
+    Ok(())
+}
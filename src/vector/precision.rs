@@ -0,0 +1,145 @@
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display};
+use std::iter::Sum;
+use std::marker::PhantomData;
+use std::ops::{Add, Div, Mul, Sub};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The floating point type a vector stores its values in
+///
+/// Serialized vectors record it, so values written as `F64` are never read
+/// back into an `f32` vector by mistake. Vectors serialized without it were
+/// written as `F32`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Precision {
+    /// 32-bit floats, the default
+    #[default]
+    F32,
+    /// 64-bit floats, for pipelines that cannot afford the narrowing
+    F64,
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for f32 {}
+
+    impl Sealed for f64 {}
+}
+
+/// The value types a `Vector` can store, `f32` or `f64`
+///
+/// Sealed, so the two precisions are the only implementations.
+pub trait Scalar:
+    sealed::Sealed
+    + Copy
+    + Default
+    + PartialOrd
+    + Debug
+    + Display
+    + Send
+    + Sync
+    + Serialize
+    + DeserializeOwned
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Sum
+    + 'static
+{
+    /// The precision of the type, recorded when a vector is serialized
+    const PRECISION: Precision;
+
+    /// Converts from `f64`, rounding to the nearest `f32` when narrower
+    fn from_f64(value: f64) -> Self;
+
+    /// Converts to `f64` without loss
+    fn to_f64(self) -> f64;
+
+    /// Returns the square root
+    fn sqrt(self) -> Self;
+
+    /// Returns whether the value is NaN
+    fn is_nan(self) -> bool;
+
+    /// Returns the larger value, ignoring NaN
+    fn max(self, other: Self) -> Self;
+
+    /// Orders two values totally, NaN included
+    fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+impl Scalar for f32 {
+    const PRECISION: Precision = Precision::F32;
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn sqrt(self) -> Self {
+        f32::sqrt(self)
+    }
+
+    fn is_nan(self) -> bool {
+        f32::is_nan(self)
+    }
+
+    fn max(self, other: Self) -> Self {
+        f32::max(self, other)
+    }
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f32::total_cmp(self, other)
+    }
+}
+
+impl Scalar for f64 {
+    const PRECISION: Precision = Precision::F64;
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn sqrt(self) -> Self {
+        f64::sqrt(self)
+    }
+
+    fn is_nan(self) -> bool {
+        f64::is_nan(self)
+    }
+
+    fn max(self, other: Self) -> Self {
+        f64::max(self, other)
+    }
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+}
+
+/// Writes the precision of a vector's values in place of its marker.
+pub(crate) fn serialize_precision<S: Scalar, Z: Serializer>(_: &PhantomData<S>, serializer: Z) -> Result<Z::Ok, Z::Error> {
+    S::PRECISION.serialize(serializer)
+}
+
+/// Reads a recorded precision, refusing `f64` values for an `f32` vector.
+pub(crate) fn deserialize_precision<'de, S: Scalar, D: Deserializer<'de>>(deserializer: D) -> Result<PhantomData<S>, D::Error> {
+    match Precision::deserialize(deserializer)? {
+        Precision::F64 if S::PRECISION == Precision::F32 => Err(serde::de::Error::custom(
+            "The vector was written with f64 precision; load it as a Vector64",
+        )),
+        _ => Ok(PhantomData),
+    }
+}
@@ -0,0 +1,160 @@
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::raw_data::base64_bytes;
+use crate::vector::Vector;
+
+/// The number of steps between the lowest and highest quantized value
+const LEVELS: f32 = u8::MAX as f32;
+
+/// A vector stored with one byte per dimension
+///
+/// Each value is mapped linearly from `[min, max]` onto `0..=255`, so the
+/// round-trip error is at most half a step, `(max - min) / 510`. The bytes
+/// serialize as a base64 string to keep JSONL files small.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuantizedVector {
+    #[serde(with = "base64_bytes")]
+    values: Vec<u8>,
+    min: f32,
+    max: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fingerprint: Option<String>,
+}
+
+impl<T> Vector<T> {
+    /// Quantize the vector to one byte per dimension
+    ///
+    /// Use the prompts' declared scale as bounds. Values outside the bounds are
+    /// clamped to them.
+    ///
+    /// # Arguments
+    /// * `min` - The value mapped to 0
+    /// * `max` - The value mapped to 255
+    ///
+    /// # Returns
+    /// The quantized vector, or an error if `max` is not greater than `min`
+    pub fn quantize(&self, min: f32, max: f32) -> Result<QuantizedVector, Error> {
+        if min.is_nan() || max.is_nan() || max <= min {
+            return Err(Error::msg(format!(
+                "Invalid quantization bounds: {} must be below {}",
+                min, max
+            )));
+        }
+
+        let values: Vec<u8> = self
+            .vector
+            .iter()
+            .map(|value| ((value.clamp(min, max) - min) / (max - min) * LEVELS).round() as u8)
+            .collect();
+
+        Ok(QuantizedVector {
+            values,
+            min,
+            max,
+            fingerprint: self.fingerprint.clone(),
+        })
+    }
+}
+
+impl QuantizedVector {
+    /// Returns the quantized bytes
+    pub fn get_values(&self) -> &[u8] {
+        &self.values
+    }
+
+    /// Returns the `(min, max)` bounds the bytes are scaled to
+    pub fn get_scale(&self) -> (f32, f32) {
+        (self.min, self.max)
+    }
+
+    /// Returns the fingerprint of the vector that was quantized
+    pub fn get_fingerprint(&self) -> Option<&str> {
+        self.fingerprint.as_deref()
+    }
+
+    /// Returns the number of dimensions
+    pub fn get_dimensionality(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Converts the bytes back to approximate float values
+    pub fn dequantize(&self) -> Vec<f32> {
+        self.values
+            .iter()
+            .map(|value| self.min + *value as f32 * self.step())
+            .collect()
+    }
+
+    /// Computes the dot product of the approximate values without dequantizing
+    ///
+    /// The bytes are multiplied as integers and the result is rescaled, so
+    /// vectors with different bounds can still be compared.
+    ///
+    /// # Arguments
+    /// * `other` - The vector to compare against
+    ///
+    /// # Returns
+    /// The dot product, or an error on empty or mismatched vectors
+    pub fn dot(&self, other: &QuantizedVector) -> Result<f32, Error> {
+        if self.values.is_empty() || other.values.is_empty() {
+            return Err(Error::msg("Cannot compare empty vectors"));
+        }
+        if self.values.len() != other.values.len() {
+            return Err(Error::msg(format!(
+                "Dimensionality mismatch: {} vs {}",
+                self.values.len(),
+                other.values.len()
+            )));
+        }
+
+        // sum((a + sa*qa) * (b + sb*qb)) expanded into integer sums
+        let (sum_self, sum_other, sum_product): (u64, u64, u64) = self
+            .values
+            .iter()
+            .zip(&other.values)
+            .fold((0, 0, 0), |(sum_self, sum_other, sum_product), (a, b)| {
+                (
+                    sum_self + *a as u64,
+                    sum_other + *b as u64,
+                    sum_product + *a as u64 * *b as u64,
+                )
+            });
+        let count: f32 = self.values.len() as f32;
+
+        Ok(count * self.min * other.min
+            + self.min * other.step() * sum_other as f32
+            + other.min * self.step() * sum_self as f32
+            + self.step() * other.step() * sum_product as f32)
+    }
+
+    /// Computes the cosine similarity of the approximate values
+    ///
+    /// # Returns
+    /// The similarity in [-1, 1], 0.0 if either vector is zero, or an error on
+    /// empty or mismatched vectors
+    pub fn cosine_similarity(&self, other: &QuantizedVector) -> Result<f32, Error> {
+        let product: f32 = self.dot(other)?;
+        let norms: f32 = (self.dot(self)? * other.dot(other)?).sqrt();
+        if norms == 0.0 {
+            return Ok(0.0);
+        }
+
+        Ok((product / norms).clamp(-1.0, 1.0))
+    }
+
+    /// Computes the euclidean distance between the approximate values
+    ///
+    /// # Returns
+    /// The distance, or an error on empty or mismatched vectors
+    pub fn euclidean_distance(&self, other: &QuantizedVector) -> Result<f32, Error> {
+        let squared: f32 = self.dot(self)? + other.dot(other)? - 2.0 * self.dot(other)?;
+
+        Ok(squared.max(0.0).sqrt())
+    }
+
+    /// The value of one quantization step
+    fn step(&self) -> f32 {
+        (self.max - self.min) / LEVELS
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+use anyhow::{Error, Result};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+use crate::raw_data::utilities::{base64_to_dynamic_image, dynamic_image_to_base64, image_sha256};
+use crate::vector::{DataType, Vector};
+
+/// A serializable mirror of `Vector<DynamicImage>`
+///
+/// `DynamicImage` has no serde support, so image vectors are converted to this
+/// type before being written and converted back after being read. The image is
+/// stored as a base64 PNG string, which is lossless, or dropped entirely in
+/// vector-only mode for people who only need the numbers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerializableImageVector {
+    /// The vector representation of the image
+    pub vector: Vec<f32>,
+    /// The image as a base64 PNG string, or None in vector-only mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// SHA-256 of the image pixels, to identify the image without its payload
+    pub image_sha256: String,
+    /// The type of the data, always `DataType::Image`
+    pub data_type: DataType,
+    /// Fingerprint of the prompts and model that produced the vector
+    #[serde(default)]
+    pub fingerprint: Option<String>,
+    /// Labels naming each dimension
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Free-form provenance such as ids and source paths
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl SerializableImageVector {
+    /// Mirror an image vector without its image payload
+    ///
+    /// The result serializes to just the numbers, labels, metadata and a pixel
+    /// hash, but it cannot be converted back into a `Vector<DynamicImage>`.
+    ///
+    /// # Arguments
+    /// * `vector` - The image vector to mirror
+    ///
+    /// # Returns
+    /// The vector-only mirror
+    pub fn vector_only(vector: &Vector<DynamicImage>) -> Self {
+        Self {
+            vector: vector.vector.clone(),
+            image: None,
+            image_sha256: image_sha256(&vector.data),
+            data_type: vector.data_type,
+            fingerprint: vector.fingerprint.clone(),
+            labels: vector.labels.clone(),
+            metadata: vector.metadata.clone(),
+        }
+    }
+}
+
+impl TryFrom<&Vector<DynamicImage>> for SerializableImageVector {
+    type Error = Error;
+
+    fn try_from(vector: &Vector<DynamicImage>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            image: Some(dynamic_image_to_base64(&vector.data)?),
+            ..Self::vector_only(vector)
+        })
+    }
+}
+
+impl TryFrom<SerializableImageVector> for Vector<DynamicImage> {
+    type Error = Error;
+
+    fn try_from(serializable: SerializableImageVector) -> Result<Self, Self::Error> {
+        let encoded: String = serializable.image.ok_or_else(|| {
+            Error::msg("Cannot restore an image vector that was serialized without its image")
+        })?;
+
+        Ok(Self {
+            vector: serializable.vector,
+            data: base64_to_dynamic_image(&encoded)?,
+            data_type: serializable.data_type,
+            fingerprint: serializable.fingerprint,
+            labels: serializable.labels,
+            metadata: serializable.metadata,
+            expected_dims: None,
+            precision: PhantomData,
+        })
+    }
+}
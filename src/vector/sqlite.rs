@@ -0,0 +1,251 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use anyhow::{Error, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::collection::filter::Filter;
+use crate::raw_data::VectorData;
+use crate::vector::{DataType, Vector, METADATA_ID, METADATA_MODEL};
+
+/// The schema migrations, applied in order; the position of the last applied one is the `user_version`
+const MIGRATIONS: [&str; 1] = ["
+    CREATE TABLE vectors (
+        row_id INTEGER PRIMARY KEY,
+        item_id TEXT UNIQUE,
+        data_type TEXT NOT NULL,
+        data TEXT,
+        vector BLOB NOT NULL,
+        labels TEXT NOT NULL,
+        metadata TEXT NOT NULL,
+        fingerprint TEXT,
+        model TEXT
+    );
+    CREATE INDEX vectors_fingerprint ON vectors (fingerprint);
+    CREATE INDEX vectors_model ON vectors (model);
+"];
+
+/// The columns read back into a vector, in order
+const COLUMNS: &str = "row_id, data_type, data, vector, labels, metadata, fingerprint";
+
+/// How many rows `all` reads at a time
+const PAGE_SIZE: i64 = 256;
+
+/// Vectors persisted in a SQLite database
+///
+/// Each vector is a row holding its values as a BLOB of little-endian f32s,
+/// its labels and metadata as JSON, and its fingerprint and model in indexed
+/// columns. Items with a `METADATA_ID` are upserted by it; items without one
+/// are always added. Text is stored with the vector; other data, such as
+/// images, is not, and comes back as an empty text.
+#[derive(Debug)]
+pub struct SqliteVectorStore {
+    connection: Connection,
+}
+
+impl SqliteVectorStore {
+    /// Opens or creates a store, bringing its schema up to date
+    ///
+    /// # Arguments
+    /// * `path` - The database file
+    ///
+    /// # Returns
+    /// The store, or an error if the database cannot be opened or migrated
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut connection: Connection = Connection::open(path.as_ref())
+            .map_err(|e| Error::msg(format!("Failed to open {}: {}", path.as_ref().display(), e)))?;
+        migrate(&mut connection)?;
+
+        Ok(Self { connection })
+    }
+
+    /// Stores a vector, replacing the stored one with the same `METADATA_ID`
+    ///
+    /// # Arguments
+    /// * `vector` - The vector to store
+    ///
+    /// # Returns
+    /// An error if the row cannot be written
+    pub fn insert<T: VectorData>(&self, vector: &Vector<T>) -> Result<(), Error> {
+        let values: Vec<u8> = vector.vector.iter().flat_map(|value| value.to_le_bytes()).collect();
+        self.connection.execute(
+            "INSERT INTO vectors (item_id, data_type, data, vector, labels, metadata, fingerprint, model)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT (item_id) DO UPDATE SET
+                data_type = excluded.data_type,
+                data = excluded.data,
+                vector = excluded.vector,
+                labels = excluded.labels,
+                metadata = excluded.metadata,
+                fingerprint = excluded.fingerprint,
+                model = excluded.model",
+            params![
+                vector.metadata.get(METADATA_ID),
+                serde_json::to_string(&vector.data_type)?,
+                vector.data.as_text(),
+                values,
+                serde_json::to_string(&vector.labels)?,
+                serde_json::to_string(&vector.metadata)?,
+                vector.fingerprint,
+                vector.metadata.get(METADATA_MODEL),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns the vector stored under a `METADATA_ID`
+    ///
+    /// # Arguments
+    /// * `id` - The identifier of the item
+    ///
+    /// # Returns
+    /// The vector, None if there is none, or an error if the row cannot be read
+    pub fn get(&self, id: &str) -> Result<Option<Vector<String>>, Error> {
+        self.connection
+            .query_row(&format!("SELECT {} FROM vectors WHERE item_id = ?1", COLUMNS), params![id], read_row)
+            .optional()?
+            .map(|(_, vector)| vector)
+            .transpose()
+    }
+
+    /// Returns the stored vectors meeting every condition of a filter, in insertion order
+    ///
+    /// # Arguments
+    /// * `filter` - The conditions on data type and metadata
+    ///
+    /// # Returns
+    /// The matching vectors, or an error if a row cannot be read
+    pub fn query(&self, filter: &Filter) -> Result<Vec<Vector<String>>, Error> {
+        let mut matches: Vec<Vector<String>> = Vec::new();
+        for vector in self.all() {
+            let vector: Vector<String> = vector?;
+            if filter.matches(&vector) {
+                matches.push(vector);
+            }
+        }
+
+        Ok(matches)
+    }
+
+    /// Streams every stored vector in insertion order, a page of rows at a time
+    pub fn all(&self) -> SqliteVectors<'_> {
+        SqliteVectors {
+            store: self,
+            last_row_id: 0,
+            page: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    /// Returns the number of stored vectors
+    pub fn len(&self) -> Result<usize, Error> {
+        let count: i64 = self.connection.query_row("SELECT COUNT(*) FROM vectors", [], |row| row.get(0))?;
+
+        Ok(count as usize)
+    }
+
+    /// Returns whether no vector is stored
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Reads the rows after `last_row_id`, with their row ids.
+    fn page(&self, last_row_id: i64) -> Result<Vec<(i64, Vector<String>)>, Error> {
+        let mut statement = self.connection.prepare_cached(&format!(
+            "SELECT {} FROM vectors WHERE row_id > ?1 ORDER BY row_id LIMIT ?2",
+            COLUMNS
+        ))?;
+        let rows = statement.query_map(params![last_row_id, PAGE_SIZE], read_row)?;
+
+        rows.map(|row| {
+            let (row_id, vector) = row?;
+            Ok((row_id, vector?))
+        })
+        .collect()
+    }
+}
+
+/// Streaming iterator over the vectors of a `SqliteVectorStore`
+pub struct SqliteVectors<'a> {
+    store: &'a SqliteVectorStore,
+    last_row_id: i64,
+    page: VecDeque<Vector<String>>,
+    finished: bool,
+}
+
+impl Iterator for SqliteVectors<'_> {
+    type Item = Result<Vector<String>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.page.is_empty() && !self.finished {
+            match self.store.page(self.last_row_id) {
+                Ok(rows) => {
+                    self.finished = (rows.len() as i64) < PAGE_SIZE;
+                    if let Some((row_id, _)) = rows.last() {
+                        self.last_row_id = *row_id;
+                    }
+                    self.page.extend(rows.into_iter().map(|(_, vector)| vector));
+                }
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        self.page.pop_front().map(Ok)
+    }
+}
+
+/// Applies the migrations the database has not seen yet, in one transaction.
+fn migrate(connection: &mut Connection) -> Result<(), Error> {
+    let version: usize = connection.query_row("PRAGMA user_version", [], |row| row.get::<_, i64>(0))? as usize;
+    if version > MIGRATIONS.len() {
+        return Err(Error::msg(format!(
+            "The database has schema version {}, newer than the {} this version of dim supports",
+            version,
+            MIGRATIONS.len()
+        )));
+    }
+
+    let transaction = connection.transaction()?;
+    for migration in &MIGRATIONS[version..] {
+        transaction.execute_batch(migration)?;
+    }
+    transaction.pragma_update(None, "user_version", MIGRATIONS.len() as i64)?;
+    transaction.commit()?;
+
+    Ok(())
+}
+
+/// Reads a row selected with `COLUMNS`; decoding errors are kept apart from SQLite ones.
+fn read_row(row: &Row) -> rusqlite::Result<(i64, Result<Vector<String>, Error>)> {
+    let row_id: i64 = row.get(0)?;
+    let data_type: String = row.get(1)?;
+    let data: Option<String> = row.get(2)?;
+    let values: Vec<u8> = row.get(3)?;
+    let labels: String = row.get(4)?;
+    let metadata: String = row.get(5)?;
+    let fingerprint: Option<String> = row.get(6)?;
+
+    let decode = || -> Result<Vector<String>, serde_json::Error> {
+        Ok(Vector {
+            vector: values
+                .chunks_exact(4)
+                .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .collect(),
+            data: data.unwrap_or_default(),
+            data_type: serde_json::from_str::<DataType>(&data_type)?,
+            fingerprint,
+            labels: serde_json::from_str::<Vec<String>>(&labels)?,
+            metadata: serde_json::from_str::<BTreeMap<String, String>>(&metadata)?,
+            expected_dims: None,
+            precision: PhantomData,
+        })
+    };
+    let vector: Result<Vector<String>, Error> = decode().map_err(|e| Error::msg(format!("Row {} is corrupt: {}", row_id, e)));
+
+    Ok((row_id, vector))
+}
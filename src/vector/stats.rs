@@ -0,0 +1,322 @@
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::vector::io::consistent_labels;
+use crate::vector::{Vector, VectorOperations};
+
+/// Per-dimension statistics of a set of vectors
+///
+/// Computed once over a dataset and stored alongside it, so that new queries
+/// can be standardized exactly like the indexed vectors.
+///
+/// # Fields
+/// * `labels` - The dimension labels, empty when the vectors are unlabeled
+/// * `fingerprint` - The fingerprint of the vectors the statistics describe
+/// * `count` - The number of vectors the statistics were computed over
+/// * `mean` - The mean of each dimension
+/// * `std` - The population standard deviation of each dimension
+/// * `min` - The smallest value of each dimension
+/// * `max` - The largest value of each dimension
+/// * `median` - The median of each dimension, a robust threshold for `Vector::binarize`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DimensionStats {
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub fingerprint: Option<String>,
+    pub count: usize,
+    pub mean: Vec<f32>,
+    pub std: Vec<f32>,
+    pub min: Vec<f32>,
+    pub max: Vec<f32>,
+    #[serde(default)]
+    pub median: Vec<f32>,
+}
+
+impl DimensionStats {
+    /// Computes the statistics of a set of vectors
+    ///
+    /// Dimensions with zero variance are logged as warnings; standardizing
+    /// maps them to 0.
+    ///
+    /// # Arguments
+    /// * `vectors` - The vectors, all of the same dimensionality and labels
+    ///
+    /// # Returns
+    /// The statistics, or an error if the set is empty or inconsistent
+    pub fn compute<T>(vectors: &[Vector<T>]) -> Result<Self, Error> {
+        let first: &Vector<T> = vectors
+            .first()
+            .ok_or_else(|| Error::msg("Cannot compute statistics of an empty set of vectors"))?;
+        let labels: Vec<String> = consistent_labels(vectors)?;
+        let labeled: bool = vectors.iter().any(|vector| !vector.get_labels().is_empty());
+
+        let dimensionality: usize = first.get_dimensionality();
+        let count: f32 = vectors.len() as f32;
+        let mut mean: Vec<f32> = vec![0.0; dimensionality];
+        let mut min: Vec<f32> = vec![f32::INFINITY; dimensionality];
+        let mut max: Vec<f32> = vec![f32::NEG_INFINITY; dimensionality];
+        for vector in vectors {
+            for (index, value) in vector.as_slice().iter().enumerate() {
+                mean[index] += value / count;
+                min[index] = min[index].min(*value);
+                max[index] = max[index].max(*value);
+            }
+        }
+
+        let mut variance: Vec<f32> = vec![0.0; dimensionality];
+        for vector in vectors {
+            for (index, value) in vector.as_slice().iter().enumerate() {
+                variance[index] += (value - mean[index]) * (value - mean[index]) / count;
+            }
+        }
+        let std: Vec<f32> = variance.into_iter().map(f32::sqrt).collect();
+        for (index, deviation) in std.iter().enumerate() {
+            if *deviation == 0.0 {
+                log::warn!("Dimension {} has zero variance and will standardize to 0", labels[index]);
+            }
+        }
+
+        let median: Vec<f32> = (0..dimensionality)
+            .map(|index| {
+                let mut column: Vec<f32> = vectors.iter().map(|vector| vector.as_slice()[index]).collect();
+                column.sort_by(f32::total_cmp);
+                let middle: usize = column.len() / 2;
+                if column.len().is_multiple_of(2) {
+                    (column[middle - 1] + column[middle]) / 2.0
+                } else {
+                    column[middle]
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            labels: if labeled { labels } else { Vec::new() },
+            fingerprint: first.get_fingerprint().map(str::to_string),
+            count: vectors.len(),
+            mean,
+            std,
+            min,
+            max,
+            median,
+        })
+    }
+
+    /// Returns the number of dimensions described
+    pub fn get_dimensionality(&self) -> usize {
+        self.mean.len()
+    }
+
+    /// Returns the indices of dimensions with zero variance
+    pub fn zero_variance_dimensions(&self) -> Vec<usize> {
+        self.std
+            .iter()
+            .enumerate()
+            .filter(|(_, deviation)| **deviation == 0.0)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Standardizes a single vector in place with these statistics
+    ///
+    /// Each value becomes `(x - mean) / std`, or 0 for zero variance dimensions.
+    /// Labels and metadata are kept.
+    ///
+    /// # Arguments
+    /// * `vector` - The vector to standardize, such as a new query
+    ///
+    /// # Returns
+    /// An error, leaving the vector untouched, if it does not match the statistics
+    pub fn apply_to<T>(&self, vector: &mut Vector<T>) -> Result<(), Error> {
+        self.check(vector)?;
+        for (index, value) in vector.vector.iter_mut().enumerate() {
+            *value = if self.std[index] == 0.0 {
+                0.0
+            } else {
+                (*value - self.mean[index]) / self.std[index]
+            };
+        }
+
+        Ok(())
+    }
+
+    /// Ensures a vector has the dimensions, labels and fingerprint these statistics describe
+    fn check<T>(&self, vector: &Vector<T>) -> Result<(), Error> {
+        if vector.get_dimensionality() != self.get_dimensionality() {
+            return Err(Error::msg(format!(
+                "Dimensionality mismatch: statistics have {}, vector has {}",
+                self.get_dimensionality(),
+                vector.get_dimensionality()
+            )));
+        }
+        if !self.labels.is_empty() && !vector.get_labels().is_empty() && self.labels != vector.get_labels() {
+            return Err(Error::msg("Label mismatch: the vector is labeled differently from the statistics"));
+        }
+        if self.fingerprint.as_deref() != vector.get_fingerprint() {
+            return Err(Error::msg(format!(
+                "Fingerprint mismatch: statistics have {:?}, vector has {:?}",
+                self.fingerprint,
+                vector.get_fingerprint()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Standardizes a set of vectors in place with previously computed statistics
+///
+/// # Arguments
+/// * `vectors` - The vectors to standardize
+/// * `stats` - The statistics to apply, usually computed over the same vectors
+///
+/// # Returns
+/// An error, leaving every vector untouched, if any vector does not match the statistics
+pub fn standardize<T>(vectors: &mut [Vector<T>], stats: &DimensionStats) -> Result<(), Error> {
+    for vector in vectors.iter() {
+        stats.check(vector)?;
+    }
+    for vector in vectors.iter_mut() {
+        stats.apply_to(vector)?;
+    }
+
+    Ok(())
+}
+
+/// Two dimensions whose values move together
+///
+/// # Fields
+/// * `first` / `second` - The dimension indices, `first < second`
+/// * `first_label` / `second_label` - The dimension labels, `dim_<index>` when unlabeled
+/// * `correlation` - The Pearson correlation coefficient
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorrelatedPair {
+    pub first: usize,
+    pub second: usize,
+    pub first_label: String,
+    pub second_label: String,
+    pub correlation: f32,
+}
+
+/// A dimension that barely varies and so adds little information
+///
+/// # Fields
+/// * `index` - The dimension index
+/// * `label` - The dimension label, `dim_<index>` when unlabeled
+/// * `variance` - The population variance of the dimension
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NearConstantDimension {
+    pub index: usize,
+    pub label: String,
+    pub variance: f32,
+}
+
+/// Redundancy analysis of the dimensions of a set of vectors
+///
+/// # Fields
+/// * `labels` - The dimension labels, `dim_<index>` when unlabeled
+/// * `correlations` - The Pearson correlation matrix between dimensions
+/// * `correlated_pairs` - Pairs whose |r| exceeds the threshold, strongest first
+/// * `near_constant` - Dimensions whose variance is below epsilon, candidates for removal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorrelationReport {
+    pub labels: Vec<String>,
+    pub correlations: Vec<Vec<f32>>,
+    pub correlated_pairs: Vec<CorrelatedPair>,
+    pub near_constant: Vec<NearConstantDimension>,
+}
+
+impl std::fmt::Display for CorrelationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Correlated dimensions:")?;
+        if self.correlated_pairs.is_empty() {
+            writeln!(f, "  none")?;
+        }
+        for pair in &self.correlated_pairs {
+            writeln!(f, "  {} ~ {}: r = {:.3}", pair.first_label, pair.second_label, pair.correlation)?;
+        }
+        writeln!(f, "Near-constant dimensions:")?;
+        if self.near_constant.is_empty() {
+            writeln!(f, "  none")?;
+        }
+        for dimension in &self.near_constant {
+            writeln!(f, "  {}: variance = {:.6}", dimension.label, dimension.variance)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds redundant dimensions in a set of vectors
+///
+/// Correlations involving a constant dimension are undefined and reported as 0;
+/// such dimensions show up as near-constant instead.
+///
+/// # Arguments
+/// * `vectors` - The vectors, all of the same dimensionality and labels
+/// * `threshold` - Pairs with |r| above this are reported, e.g. 0.9
+/// * `epsilon` - Dimensions with variance below this are reported
+///
+/// # Returns
+/// The report, or an error if the set is empty or inconsistent
+pub fn correlation_report<T>(vectors: &[Vector<T>], threshold: f32, epsilon: f32) -> Result<CorrelationReport, Error> {
+    let stats: DimensionStats = DimensionStats::compute(vectors)?;
+    let labels: Vec<String> = consistent_labels(vectors)?;
+    let dimensionality: usize = stats.get_dimensionality();
+    let count: f32 = vectors.len() as f32;
+
+    let mut covariance: Vec<Vec<f32>> = vec![vec![0.0; dimensionality]; dimensionality];
+    for vector in vectors {
+        let values: &[f32] = vector.as_slice();
+        for i in 0..dimensionality {
+            for j in i..dimensionality {
+                covariance[i][j] += (values[i] - stats.mean[i]) * (values[j] - stats.mean[j]) / count;
+            }
+        }
+    }
+
+    let mut correlations: Vec<Vec<f32>> = vec![vec![0.0; dimensionality]; dimensionality];
+    let mut correlated_pairs: Vec<CorrelatedPair> = Vec::new();
+    for i in 0..dimensionality {
+        for j in i..dimensionality {
+            let deviations: f32 = stats.std[i] * stats.std[j];
+            let correlation: f32 = if deviations == 0.0 {
+                0.0
+            } else {
+                (covariance[i][j] / deviations).clamp(-1.0, 1.0)
+            };
+            correlations[i][j] = correlation;
+            correlations[j][i] = correlation;
+            if i != j && correlation.abs() > threshold {
+                correlated_pairs.push(CorrelatedPair {
+                    first: i,
+                    second: j,
+                    first_label: labels[i].clone(),
+                    second_label: labels[j].clone(),
+                    correlation,
+                });
+            }
+        }
+    }
+    correlated_pairs.sort_by(|a, b| b.correlation.abs().total_cmp(&a.correlation.abs()));
+
+    let near_constant: Vec<NearConstantDimension> = stats
+        .std
+        .iter()
+        .enumerate()
+        .map(|(index, deviation)| (index, deviation * deviation))
+        .filter(|(_, variance)| *variance < epsilon)
+        .map(|(index, variance)| NearConstantDimension {
+            index,
+            label: labels[index].clone(),
+            variance,
+        })
+        .collect();
+
+    Ok(CorrelationReport {
+        labels,
+        correlations,
+        correlated_pairs,
+        near_constant,
+    })
+}
@@ -0,0 +1,104 @@
+use anyhow::Result;
+use async_openai::types::{AudioInput, CreateTranscriptionRequest, CreateTranscriptionRequestArgs};
+
+use crate::error::DimError;
+use crate::llm::{ChatBackend, TranscriptionBackend};
+use crate::prompt::IntoPrompts;
+use crate::raw_data::utilities::bytes_sha256;
+use crate::raw_data::AudioData;
+use crate::vector::{Vector, VectorOperations, METADATA_TRANSCRIPT};
+use crate::vectorization::report::VectorizationReport;
+use crate::vectorization::{adopt_vectorization, observe_item, vectorize_item, ModelParameters};
+
+/// Transcribes audio, retrying failed and timed out attempts a bounded number of times.
+///
+/// When every attempt fails, the error of the last one is returned, so a
+/// timeout or a rejected API key keeps its `DimError` variant.
+async fn transcribe_audio<B>(client: &B, audio: &AudioData, model_parameters: &ModelParameters) -> Result<String, DimError>
+where
+    B: TranscriptionBackend,
+{
+    let attempts: usize = model_parameters.get_transcription_attempts();
+    let mut last_error: DimError = DimError::msg("No transcription was attempted");
+    for attempt in 1..=attempts {
+        let request: CreateTranscriptionRequest = CreateTranscriptionRequestArgs::default()
+            .file(AudioInput::from_vec_u8(
+                format!("audio.{}", audio.get_format().extension()),
+                audio.get_bytes().to_vec(),
+            ))
+            .model(model_parameters.get_transcription_model())
+            .build()
+            .map_err(|e| DimError::msg(e.to_string()))?;
+
+        match tokio::time::timeout(model_parameters.get_transcription_timeout(), client.transcribe(request)).await {
+            Ok(Ok(transcript)) if !transcript.trim().is_empty() => return Ok(transcript),
+            Ok(Ok(_)) => last_error = DimError::msg("the transcript is empty"),
+            Ok(Err(e)) => last_error = DimError::from(e),
+            Err(_) => {
+                last_error = DimError::Timeout {
+                    message: format!("Transcription timed out after {:?}", model_parameters.get_transcription_timeout()),
+                }
+            }
+        }
+        log::warn!("Transcription attempt {} of {} failed: {}", attempt, attempts, last_error);
+    }
+
+    Err(last_error.context(&format!("Transcription failed after {} attempts", attempts)))
+}
+
+/// Concurrently vectorizes audio by transcribing it and scoring the transcript.
+///
+/// The transcript is stored under `METADATA_TRANSCRIPT`; the vector, labels,
+/// fingerprint and provenance are those of the transcript's vectorization.
+///
+/// # Arguments
+/// * `prompts` - The prompts, e.g. a `Vec<String>`, `&[Prompt]` or `&PromptSet`, written for text
+/// * `vector` - A mutable reference to the Vector struct containing the audio
+/// * `client` - The OpenAI API client, or any other backend that can chat and transcribe
+/// * `model_parameters` - The chat parameters, including the transcription model
+///
+/// # Returns
+/// * `Result<VectorizationReport, DimError>` - How each prompt went and the tokens consumed on success,
+///   DimError when the transcription fails
+pub async fn vectorize_audio_concurrently<B, P>(
+    prompts: P,
+    vector: &mut Vector<AudioData>,
+    client: B,
+    model_parameters: ModelParameters,
+) -> Result<VectorizationReport, DimError>
+where
+    B: ChatBackend + TranscriptionBackend,
+    P: IntoPrompts,
+{
+    observe_item(
+        model_parameters.get_progress_observer(),
+        model_parameters.get_item_index(),
+        vectorize_audio(prompts, vector, client, model_parameters),
+    )
+    .await
+}
+
+/// Transcribes audio and vectorizes the transcript.
+async fn vectorize_audio<B, P>(
+    prompts: P,
+    vector: &mut Vector<AudioData>,
+    client: B,
+    model_parameters: ModelParameters,
+) -> Result<VectorizationReport, DimError>
+where
+    B: ChatBackend + TranscriptionBackend,
+    P: IntoPrompts,
+{
+    let transcript: String = transcribe_audio(&client, vector.get_data(), &model_parameters).await?;
+    vector.set_metadata(METADATA_TRANSCRIPT, transcript.clone());
+
+    let mut text_vector: Vector<String> = Vector::from_text(transcript);
+    let mut report: VectorizationReport = vectorize_item(prompts, &mut text_vector, client, model_parameters).await?;
+    if report.input_hash.is_some() {
+        report.input_hash = Some(bytes_sha256(vector.get_data().get_bytes()));
+    }
+
+    adopt_vectorization(vector, &text_vector)?;
+
+    Ok(report)
+}
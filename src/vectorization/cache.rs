@@ -0,0 +1,365 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "redis")]
+pub mod redis;
+
+use crate::raw_data::utilities::text_sha256;
+
+/// Tells apart the temporary files of concurrent writers in one process
+static WRITE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// The most independently locked parts of a `MemoryCache`
+const MEMORY_CACHE_SHARDS: usize = 16;
+
+/// The fewest results per shard, so small caches evict in close to exact LRU order
+const MEMORY_SHARD_MIN_CAPACITY: usize = 64;
+
+/// What a cached result depends on
+///
+/// # Fields
+/// * `data_hash` - The SHA-256 of the text or images exactly as they are sent
+/// * `fingerprint` - The fingerprint of the prompt alone
+/// * `model` - The model the prompt is sent to
+/// * `extraction_mode` - How scores are requested and computed, e.g. `JsonObject/Parsed`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CacheKey {
+    pub data_hash: String,
+    pub fingerprint: String,
+    pub model: String,
+    pub extraction_mode: String,
+}
+
+impl CacheKey {
+    /// Returns a SHA-256 over all parts of the key, usable as a file name
+    pub fn digest(&self) -> String {
+        text_sha256(&format!("{}\n{}\n{}\n{}", self.data_hash, self.fingerprint, self.model, self.extraction_mode))
+    }
+}
+
+/// The accepted scores of one prompt for one input
+///
+/// # Fields
+/// * `values` - The scores, after combining samples
+/// * `keys` - The JSON key path each score was read from
+/// * `samples` - The accepted scores of every sample, when the prompt was sampled more than once
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedResult {
+    pub values: Vec<f32>,
+    pub keys: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub samples: Vec<Vec<f32>>,
+}
+
+/// How much a cache holds
+///
+/// # Fields
+/// * `entries` - The number of cached results
+/// * `bytes` - Their size on disk, or approximately in memory for `MemoryCache`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub bytes: u64,
+}
+
+/// A store of prompt results, consulted before every request
+///
+/// Set one with `ModelParametersBuilder::cache`. Prompts whose result is
+/// cached are not sent; accepted results of the others are written back.
+pub trait VectorizationCache: std::fmt::Debug + Send + Sync {
+    /// Returns the cached result for a key, if there is one
+    fn get(&self, key: &CacheKey) -> Result<Option<CachedResult>, Error>;
+
+    /// Returns the cached results for several keys, in key order
+    ///
+    /// Every prompt of an item is looked up with one call. The default calls
+    /// `get` for each key; remote caches override it to save round trips.
+    fn get_many(&self, keys: &[CacheKey]) -> Result<Vec<Option<CachedResult>>, Error> {
+        keys.iter().map(|key| self.get(key)).collect()
+    }
+
+    /// Stores the result for a key, replacing any earlier one
+    fn put(&self, key: &CacheKey, result: &CachedResult) -> Result<(), Error>;
+
+    /// Removes every cached result
+    fn clear(&self) -> Result<(), Error>;
+
+    /// Counts the cached results and their size
+    fn stats(&self) -> Result<CacheStats, Error>;
+}
+
+/// One cache file, holding its key to guard against digest collisions
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    key: CacheKey,
+    result: CachedResult,
+}
+
+/// A `VectorizationCache` keeping one JSON file per key in a directory
+///
+/// Entries are written to a temporary file and renamed into place, so
+/// concurrent writers of the same key leave one complete entry, never a mix.
+#[derive(Debug, Clone)]
+pub struct FileCache {
+    directory: PathBuf,
+}
+
+impl FileCache {
+    /// Opens a cache directory, creating it if it does not exist
+    ///
+    /// # Arguments
+    /// * `directory` - Where the cache files are kept
+    ///
+    /// # Returns
+    /// The cache, or an error if the directory cannot be created
+    pub fn new(directory: impl AsRef<Path>) -> Result<Self, Error> {
+        let directory: PathBuf = directory.as_ref().to_path_buf();
+        std::fs::create_dir_all(&directory)
+            .map_err(|e| Error::msg(format!("Failed to create cache directory {}: {}", directory.display(), e)))?;
+
+        Ok(Self { directory })
+    }
+
+    /// Returns the directory the cache files are kept in
+    pub fn get_directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Returns the file of a key.
+    fn entry_path(&self, key: &CacheKey) -> PathBuf {
+        self.directory.join(format!("{}.json", key.digest()))
+    }
+
+    /// Returns the paths of all cache files.
+    fn entry_paths(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for entry in std::fs::read_dir(&self.directory)? {
+            let path: PathBuf = entry?.path();
+            if path.extension().is_some_and(|extension| extension == "json") {
+                paths.push(path);
+            }
+        }
+
+        Ok(paths)
+    }
+}
+
+impl VectorizationCache for FileCache {
+    fn get(&self, key: &CacheKey) -> Result<Option<CachedResult>, Error> {
+        let path: PathBuf = self.entry_path(key);
+        let file: File = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(Error::msg(format!("Failed to read cache entry {}: {}", path.display(), e))),
+        };
+
+        match serde_json::from_reader::<_, CacheEntry>(BufReader::new(file)) {
+            Ok(entry) if entry.key == *key => Ok(Some(entry.result)),
+            Ok(_) => Ok(None),
+            Err(e) => {
+                log::warn!("Ignoring unreadable cache entry {}: {}", path.display(), e);
+                Ok(None)
+            }
+        }
+    }
+
+    fn put(&self, key: &CacheKey, result: &CachedResult) -> Result<(), Error> {
+        let path: PathBuf = self.entry_path(key);
+        let temporary: PathBuf = self.directory.join(format!(
+            "{}.{}-{}.tmp",
+            key.digest(),
+            std::process::id(),
+            WRITE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let entry: CacheEntry = CacheEntry { key: key.clone(), result: result.clone() };
+
+        let mut file: File = File::create(&temporary)?;
+        file.write_all(&serde_json::to_vec(&entry)?)?;
+        file.sync_all()?;
+        std::fs::rename(&temporary, &path).map_err(|e| {
+            let _ = std::fs::remove_file(&temporary);
+            Error::msg(format!("Failed to write cache entry {}: {}", path.display(), e))
+        })
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        for path in self.entry_paths()? {
+            std::fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+
+    fn stats(&self) -> Result<CacheStats, Error> {
+        let mut stats: CacheStats = CacheStats::default();
+        for path in self.entry_paths()? {
+            stats.entries += 1;
+            stats.bytes += std::fs::metadata(path)?.len();
+        }
+
+        Ok(stats)
+    }
+}
+
+/// A bounded `VectorizationCache` kept in memory, evicting the least recently used results
+///
+/// Share one through an `Arc` across calls, e.g. to skip duplicate texts of a
+/// dataset. Large caches spread keys over independently locked shards, each
+/// evicting on its own, so concurrent prompts rarely wait on each other. It is orthogonal to `cache`: with both
+/// set, results are looked up here first.
+#[derive(Debug)]
+pub struct MemoryCache {
+    shards: Vec<Mutex<MemoryShard>>,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// The entries of one shard and their order of use
+#[derive(Debug, Default)]
+struct MemoryShard {
+    entries: HashMap<String, MemoryEntry>,
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+/// A cached result with the key it was stored under and when it was last used
+#[derive(Debug)]
+struct MemoryEntry {
+    key: CacheKey,
+    result: CachedResult,
+    last_used: u64,
+}
+
+impl MemoryCache {
+    /// Creates a cache holding at most `capacity` results, at least 1
+    pub fn new(capacity: usize) -> Self {
+        let capacity: usize = capacity.max(1);
+        let shards: usize = (capacity / MEMORY_SHARD_MIN_CAPACITY).clamp(1, MEMORY_CACHE_SHARDS);
+
+        Self {
+            shards: (0..shards).map(|_| Mutex::new(MemoryShard::default())).collect(),
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the most results the cache holds
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns how many lookups found a result
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Returns how many lookups found nothing
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Returns the shard of a digest and how many entries it may hold.
+    fn shard(&self, digest: &str) -> (&Mutex<MemoryShard>, usize) {
+        let index: usize = usize::from_str_radix(&digest[..8], 16).unwrap_or_default() % self.shards.len();
+        let capacity: usize = self.capacity / self.shards.len() + usize::from(index < self.capacity % self.shards.len());
+
+        (&self.shards[index], capacity)
+    }
+}
+
+impl MemoryShard {
+    /// Marks an entry as the most recently used.
+    fn touch(&mut self, digest: &str) {
+        self.tick += 1;
+        let tick: u64 = self.tick;
+        if let Some(entry) = self.entries.get_mut(digest) {
+            self.recency.remove(&entry.last_used);
+            entry.last_used = tick;
+            self.recency.insert(tick, digest.to_string());
+        }
+    }
+}
+
+impl VectorizationCache for MemoryCache {
+    fn get(&self, key: &CacheKey) -> Result<Option<CachedResult>, Error> {
+        let digest: String = key.digest();
+        let (shard, _) = self.shard(&digest);
+        let mut shard = shard.lock().map_err(|_| Error::msg("Memory cache lock poisoned"))?;
+
+        let result: Option<CachedResult> = shard
+            .entries
+            .get(&digest)
+            .filter(|entry| entry.key == *key)
+            .map(|entry| entry.result.clone());
+        match result {
+            Some(_) => {
+                shard.touch(&digest);
+                self.hits.fetch_add(1, Ordering::Relaxed);
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn put(&self, key: &CacheKey, result: &CachedResult) -> Result<(), Error> {
+        let digest: String = key.digest();
+        let (shard, capacity) = self.shard(&digest);
+        let mut shard = shard.lock().map_err(|_| Error::msg("Memory cache lock poisoned"))?;
+
+        if let Some(previous) = shard.entries.remove(&digest) {
+            shard.recency.remove(&previous.last_used);
+        }
+        while shard.entries.len() >= capacity {
+            let Some((_, evicted)) = shard.recency.pop_first() else {
+                break;
+            };
+            shard.entries.remove(&evicted);
+        }
+        shard.entries.insert(digest.clone(), MemoryEntry { key: key.clone(), result: result.clone(), last_used: 0 });
+        shard.touch(&digest);
+
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        for shard in &self.shards {
+            let mut shard = shard.lock().map_err(|_| Error::msg("Memory cache lock poisoned"))?;
+            shard.entries.clear();
+            shard.recency.clear();
+        }
+
+        Ok(())
+    }
+
+    fn stats(&self) -> Result<CacheStats, Error> {
+        let mut stats: CacheStats = CacheStats::default();
+        for shard in &self.shards {
+            let shard = shard.lock().map_err(|_| Error::msg("Memory cache lock poisoned"))?;
+            stats.entries += shard.entries.len();
+            stats.bytes += shard
+                .entries
+                .values()
+                .map(|entry| {
+                    let result: &CachedResult = &entry.result;
+                    (result.values.len() * 4
+                        + result.keys.iter().map(String::len).sum::<usize>()
+                        + result.samples.iter().map(|sample| sample.len() * 4).sum::<usize>()) as u64
+                })
+                .sum::<u64>();
+        }
+
+        Ok(stats)
+    }
+}
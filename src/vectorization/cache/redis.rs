@@ -0,0 +1,261 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{Error, Result};
+use redis::{Client, Connection, Pipeline, RedisResult};
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::Instant;
+use crate::vectorization::cache::{CacheKey, CacheStats, CachedResult, VectorizationCache};
+
+/// The prefix of the keys a `RedisCache` writes by default
+const DEFAULT_PREFIX: &str = "dim:cache:";
+
+/// How long connecting, reading or writing may take before Redis counts as unreachable
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long an unreachable Redis is not retried, so every prompt does not wait for a timeout
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// How many keys `clear` and `stats` scan at a time
+const SCAN_COUNT: usize = 1000;
+
+/// One cached value, holding its key to guard against digest collisions
+///
+/// A struct of its own because bincode cannot skip fields the way the JSON of
+/// `CachedResult` does.
+#[derive(Serialize, Deserialize)]
+struct RedisEntry {
+    key: CacheKey,
+    values: Vec<f32>,
+    keys: Vec<String>,
+    samples: Vec<Vec<f32>>,
+}
+
+/// A `VectorizationCache` shared by every worker through Redis
+///
+/// Keys are the digest of the `CacheKey` under a prefix, values the result in
+/// bincode, optionally expiring after a TTL. All prompts of an item are looked
+/// up in one pipelined round trip. When Redis is unreachable, lookups are
+/// misses and writes are dropped, both logged, so vectorization goes on
+/// without the cache; reconnecting is tried again after a few seconds.
+#[derive(Debug)]
+pub struct RedisCache {
+    client: Client,
+    prefix: String,
+    ttl: Option<Duration>,
+    timeout: Duration,
+    connection: Mutex<RedisConnection>,
+}
+
+/// The open connection, or when connecting last failed
+#[derive(Default)]
+struct RedisConnection {
+    connection: Option<Connection>,
+    failed_at: Option<Instant>,
+}
+
+impl std::fmt::Debug for RedisConnection {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("RedisConnection")
+            .field("connected", &self.connection.is_some())
+            .field("failed_at", &self.failed_at)
+            .finish()
+    }
+}
+
+impl RedisCache {
+    /// Creates a cache on a Redis server, connecting on first use
+    ///
+    /// # Arguments
+    /// * `url` - The server, e.g. "redis://127.0.0.1:6379/0"
+    ///
+    /// # Returns
+    /// The cache, or an error if the URL is invalid
+    pub fn new(url: &str) -> Result<Self, Error> {
+        let client: Client = Client::open(url).map_err(|e| Error::msg(format!("Invalid Redis URL {}: {}", url, e)))?;
+
+        Ok(Self {
+            client,
+            prefix: DEFAULT_PREFIX.to_string(),
+            ttl: None,
+            timeout: DEFAULT_TIMEOUT,
+            connection: Mutex::new(RedisConnection::default()),
+        })
+    }
+
+    /// Expires every result written from now on after `ttl`, or never by default
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Writes keys under another prefix, e.g. to keep the results of two projects apart
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Sets how long connecting, reading or writing may take, 1 second by default
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn get_prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn get_ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// Returns the Redis key of a cache key.
+    fn redis_key(&self, key: &CacheKey) -> String {
+        format!("{}{}", self.prefix, key.digest())
+    }
+
+    /// Runs a command on the connection, connecting first if needed.
+    ///
+    /// The connection is dropped after an error so the next command reconnects.
+    fn with_connection<T>(&self, command: impl FnOnce(&mut Connection) -> RedisResult<T>) -> Result<T, Error> {
+        let mut state = self.connection.lock().map_err(|_| Error::msg("Redis cache lock poisoned"))?;
+        if state.connection.is_none() {
+            if let Some(failed_at) = state.failed_at.filter(|failed_at| failed_at.elapsed() < RECONNECT_DELAY) {
+                return Err(Error::msg(format!("Redis was unreachable {:?} ago", failed_at.elapsed())));
+            }
+            match self.connect() {
+                Ok(connection) => {
+                    state.connection = Some(connection);
+                    state.failed_at = None;
+                }
+                Err(e) => {
+                    state.failed_at = Some(Instant::now());
+                    return Err(Error::msg(format!("Failed to connect to Redis: {}", e)));
+                }
+            }
+        }
+
+        let connection: &mut Connection = state.connection.as_mut().expect("connected above");
+        command(connection).map_err(|e| {
+            state.connection = None;
+            Error::msg(format!("Redis command failed: {}", e))
+        })
+    }
+
+    /// Opens a connection with the timeouts set.
+    fn connect(&self) -> RedisResult<Connection> {
+        let connection: Connection = self.client.get_connection_with_timeout(self.timeout)?;
+        connection.set_read_timeout(Some(self.timeout))?;
+        connection.set_write_timeout(Some(self.timeout))?;
+
+        Ok(connection)
+    }
+
+    /// Returns every key under the prefix.
+    fn scan_keys(&self) -> Result<Vec<String>, Error> {
+        let pattern: String = format!("{}*", self.prefix);
+        self.with_connection(|connection| {
+            let mut keys: Vec<String> = Vec::new();
+            let mut cursor: u64 = 0;
+            loop {
+                let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(&pattern)
+                    .arg("COUNT")
+                    .arg(SCAN_COUNT)
+                    .query(connection)?;
+                keys.extend(batch);
+                if next == 0 {
+                    return Ok(keys);
+                }
+                cursor = next;
+            }
+        })
+    }
+}
+
+/// Decodes a value, treating undecodable ones and digest collisions as misses.
+fn decode(key: &CacheKey, bytes: Option<Vec<u8>>) -> Option<CachedResult> {
+    let entry: RedisEntry = match bincode::deserialize(&bytes?) {
+        Ok(entry) => entry,
+        Err(e) => {
+            log::warn!("Ignoring unreadable Redis cache entry {}: {}", key.digest(), e);
+            return None;
+        }
+    };
+
+    (entry.key == *key).then_some(CachedResult {
+        values: entry.values,
+        keys: entry.keys,
+        samples: entry.samples,
+    })
+}
+
+impl VectorizationCache for RedisCache {
+    fn get(&self, key: &CacheKey) -> Result<Option<CachedResult>, Error> {
+        Ok(self.get_many(std::slice::from_ref(key))?.pop().flatten())
+    }
+
+    fn get_many(&self, keys: &[CacheKey]) -> Result<Vec<Option<CachedResult>>, Error> {
+        let mut pipeline: Pipeline = redis::pipe();
+        for key in keys {
+            pipeline.get(self.redis_key(key));
+        }
+
+        match self.with_connection(|connection| pipeline.query::<Vec<Option<Vec<u8>>>>(connection)) {
+            Ok(values) => Ok(keys.iter().zip(values).map(|(key, bytes)| decode(key, bytes)).collect()),
+            Err(e) => {
+                log::warn!("Treating {} Redis cache lookups as misses: {}", keys.len(), e);
+                Ok(keys.iter().map(|_| None).collect())
+            }
+        }
+    }
+
+    fn put(&self, key: &CacheKey, result: &CachedResult) -> Result<(), Error> {
+        let entry: RedisEntry = RedisEntry {
+            key: key.clone(),
+            values: result.values.clone(),
+            keys: result.keys.clone(),
+            samples: result.samples.clone(),
+        };
+        let bytes: Vec<u8> = bincode::serialize(&entry).map_err(|e| Error::msg(format!("Failed to encode a cache entry: {}", e)))?;
+        let redis_key: String = self.redis_key(key);
+
+        let written: Result<(), Error> = self.with_connection(|connection| match self.ttl {
+            Some(ttl) => redis::cmd("SET").arg(&redis_key).arg(&bytes).arg("PX").arg(ttl.as_millis().max(1) as u64).query(connection),
+            None => redis::cmd("SET").arg(&redis_key).arg(&bytes).query(connection),
+        });
+        if let Err(e) = written {
+            log::warn!("Dropping a Redis cache write: {}", e);
+        }
+
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        for batch in self.scan_keys()?.chunks(SCAN_COUNT) {
+            self.with_connection(|connection| redis::cmd("DEL").arg(batch).query::<()>(connection))?;
+        }
+
+        Ok(())
+    }
+
+    fn stats(&self) -> Result<CacheStats, Error> {
+        let keys: Vec<String> = self.scan_keys()?;
+        let mut stats: CacheStats = CacheStats::default();
+        for batch in keys.chunks(SCAN_COUNT) {
+            let mut pipeline: Pipeline = redis::pipe();
+            for key in batch {
+                pipeline.cmd("STRLEN").arg(key);
+            }
+            let lengths: Vec<u64> = self.with_connection(|connection| pipeline.query(connection))?;
+            stats.entries += lengths.iter().filter(|length| **length > 0).count();
+            stats.bytes += lengths.iter().sum::<u64>();
+        }
+
+        Ok(stats)
+    }
+}
@@ -0,0 +1,118 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// Which raw LLM answers are kept in the run report
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CaptureMode {
+    /// Keep no answers
+    #[default]
+    Off,
+    /// Keep the answer each prompt's scores were taken from
+    Accepted,
+    /// Keep the accepted answer and every attempt rejected before it
+    All,
+}
+
+impl CaptureMode {
+    /// Returns whether an answer with this verdict is kept
+    pub fn keeps(&self, accepted: bool) -> bool {
+        match self {
+            CaptureMode::Off => false,
+            CaptureMode::Accepted => accepted,
+            CaptureMode::All => true,
+        }
+    }
+}
+
+impl std::str::FromStr for CaptureMode {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "off" => Ok(CaptureMode::Off),
+            "accepted" => Ok(CaptureMode::Accepted),
+            "all" => Ok(CaptureMode::All),
+            _ => Err(Error::msg(format!(
+                "Unknown capture mode '{}', expected off, accepted or all",
+                value
+            ))),
+        }
+    }
+}
+
+/// One raw answer of the LLM, as it was received
+///
+/// # Fields
+/// * `sample` - The sample of the prompt the answer belongs to, 0 unless sampled more than once
+/// * `attempt` - The answered request of that sample, starting at 1
+/// * `accepted` - Whether the scores were taken from this answer
+/// * `raw` - The message content, or the tool call arguments in tool call mode
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapturedResponse {
+    #[serde(default)]
+    pub sample: usize,
+    pub attempt: u64,
+    pub accepted: bool,
+    pub raw: String,
+}
+
+/// A destination for captured answers, such as an audit log
+///
+/// Pass it to `VectorizationReport::record_responses` after each item.
+pub trait ResponseSink {
+    /// Records one answer
+    ///
+    /// # Arguments
+    /// * `item_id` - The SHA-256 of the vectorized text or image, never the payload itself
+    /// * `prompt_index` - The position of the prompt in the call
+    /// * `response` - The captured answer
+    fn record(&self, item_id: &str, prompt_index: usize, response: &CapturedResponse) -> Result<(), Error>;
+}
+
+/// A `ResponseSink` appending one JSON object per answer to a file
+///
+/// Lines look like `{"item_id": "...", "prompt_index": 0, "sample": 0, "attempt": 1, "accepted": true, "raw": "..."}`.
+#[derive(Debug)]
+pub struct JsonlAuditSink {
+    file: Mutex<File>,
+}
+
+impl JsonlAuditSink {
+    /// Opens an audit file for appending, creating it if needed
+    ///
+    /// # Arguments
+    /// * `path` - The file to append to
+    ///
+    /// # Returns
+    /// The sink, or an error if the file cannot be opened
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file: File = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self { file: Mutex::new(file) })
+    }
+}
+
+impl ResponseSink for JsonlAuditSink {
+    fn record(&self, item_id: &str, prompt_index: usize, response: &CapturedResponse) -> Result<(), Error> {
+        let mut line: String = serde_json::json!({
+            "item_id": item_id,
+            "prompt_index": prompt_index,
+            "sample": response.sample,
+            "attempt": response.attempt,
+            "accepted": response.accepted,
+            "raw": response.raw,
+        })
+        .to_string();
+        line.push('\n');
+
+        let mut file = self.file.lock().map_err(|_| Error::msg("Audit file lock poisoned"))?;
+        file.write_all(line.as_bytes())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,259 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+
+use crate::error::DimError;
+use crate::llm::ChatBackend;
+use crate::prompt::{combine_fingerprints, Prompt};
+use crate::vector::{DataType, Scalar, Vector, VectorOperations, METADATA_CHUNK_VECTORS, METADATA_DIMENSION_MODELS, METADATA_LANGUAGE};
+use crate::vectorization::report::{ChunkReport, VectorizationReport};
+use crate::vectorization::{record_failed_prompts, vectorize_whole_text, ModelParameters};
+
+/// Splits text into the tokens chunk sizes and overlaps are counted in
+///
+/// Implement this to chunk by the tokens of your model's tokenizer instead of characters.
+pub trait Tokenizer: std::fmt::Debug + Send + Sync {
+    /// Returns the byte range of every token of `text`, in order and without gaps between chunks
+    fn token_spans(&self, text: &str) -> Vec<Range<usize>>;
+}
+
+/// Counts every character as one token, the default tokenizer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CharTokenizer;
+
+impl Tokenizer for CharTokenizer {
+    fn token_spans(&self, text: &str) -> Vec<Range<usize>> {
+        text.char_indices()
+            .map(|(start, character)| start..start + character.len_utf8())
+            .collect()
+    }
+}
+
+/// How the scores of a long text's chunks are combined into one per dimension
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChunkAggregation {
+    /// The average score of all chunks
+    #[default]
+    Mean,
+    /// The highest score of any chunk, for attributes present anywhere in the text
+    Max,
+    /// The average score weighted by each chunk's number of tokens
+    LengthWeightedMean,
+}
+
+impl ChunkAggregation {
+    /// Combines the scores of one dimension
+    ///
+    /// # Arguments
+    /// * `scores` - The score of every chunk
+    /// * `lengths` - The number of tokens of every chunk, in the same order
+    ///
+    /// # Returns
+    /// The combined score, or NaN when there are no scores
+    pub fn aggregate<S: Scalar>(&self, scores: &[S], lengths: &[usize]) -> S {
+        match self {
+            ChunkAggregation::Mean => scores.iter().copied().sum::<S>() / S::from_f64(scores.len() as f64),
+            ChunkAggregation::Max => scores.iter().copied().fold(S::from_f64(f64::NAN), S::max),
+            ChunkAggregation::LengthWeightedMean => {
+                let total: usize = lengths.iter().sum();
+                scores
+                    .iter()
+                    .zip(lengths)
+                    .map(|(score, length)| *score * S::from_f64(*length as f64))
+                    .sum::<S>()
+                    / S::from_f64(total as f64)
+            }
+        }
+    }
+}
+
+/// Splits a text into the chunks a chunked vectorization would score
+///
+/// Texts of at most `chunk_size` tokens, or any text when no chunk size is
+/// set, come back as a single chunk.
+///
+/// # Arguments
+/// * `text` - The text to split
+/// * `model_parameters` - The chunk size, overlap and tokenizer to use
+///
+/// # Returns
+/// The chunks in order, each overlapping the previous one by `chunk_overlap` tokens
+pub fn split_into_chunks<'a>(text: &'a str, model_parameters: &ModelParameters) -> Vec<&'a str> {
+    chunk_ranges(text, model_parameters, false)
+        .into_iter()
+        .map(|(range, _)| &text[range])
+        .collect()
+}
+
+/// Returns the byte range and token count of every chunk of a text.
+///
+/// With `at_blank_lines`, chunks end at the last blank line in their second
+/// half when there is one, which keeps the functions of source code whole.
+fn chunk_ranges(text: &str, model_parameters: &ModelParameters, at_blank_lines: bool) -> Vec<(Range<usize>, usize)> {
+    let Some(chunk_size) = model_parameters.get_chunk_size() else {
+        return vec![(0..text.len(), 0)];
+    };
+    let spans: Vec<Range<usize>> = model_parameters.get_chunk_tokenizer().token_spans(text);
+    if spans.len() <= chunk_size {
+        return vec![(0..text.len(), spans.len())];
+    }
+    let overlap: usize = model_parameters.get_chunk_overlap();
+
+    let mut chunks: Vec<(Range<usize>, usize)> = Vec::new();
+    let mut start: usize = 0;
+    loop {
+        let mut end: usize = (start + chunk_size).min(spans.len());
+        if at_blank_lines && end < spans.len() {
+            if let Some(boundary) = (start + chunk_size / 2 + 1..=end)
+                .rev()
+                .find(|token| follows_blank_line(&text[..spans[*token].start]))
+            {
+                end = boundary;
+            }
+        }
+        chunks.push((spans[start].start..spans[end - 1].end, end - start));
+        if end == spans.len() {
+            return chunks;
+        }
+        start = end.saturating_sub(overlap).max(start + 1);
+    }
+}
+
+/// Returns whether text ends with an empty or whitespace-only line.
+fn follows_blank_line(text: &str) -> bool {
+    text.strip_suffix('\n')
+        .is_some_and(|text| text.trim_end_matches([' ', '\t', '\r']).ends_with('\n'))
+}
+
+/// Vectorizes a text chunk by chunk when it is longer than the chunk size.
+///
+/// Returns `None` when the text fits in one chunk, so the caller vectorizes it whole.
+pub(crate) async fn vectorize_chunks<B, S>(
+    prompts: &[Prompt],
+    vector: &mut Vector<String, S>,
+    client: &Arc<B>,
+    model_parameters: &ModelParameters,
+) -> Option<Result<VectorizationReport, DimError>>
+where
+    B: ChatBackend,
+    S: Scalar,
+{
+    let is_code: bool = vector.get_data_type() == DataType::Code;
+    let chunks: Vec<(Range<usize>, usize)> = chunk_ranges(vector.get_data(), model_parameters, is_code);
+    if chunks.len() < 2 {
+        return None;
+    }
+
+    Some(vectorize_chunk_ranges(prompts, vector, chunks, client, model_parameters).await)
+}
+
+/// A chunk's byte range and tokens, with its vector and report.
+type ScoredChunk<S> = (Range<usize>, usize, Vector<String, S>, VectorizationReport);
+
+/// Vectorizes every chunk concurrently and combines them per dimension.
+async fn vectorize_chunk_ranges<B, S>(
+    prompts: &[Prompt],
+    vector: &mut Vector<String, S>,
+    chunks: Vec<(Range<usize>, usize)>,
+    client: &Arc<B>,
+    model_parameters: &ModelParameters,
+) -> Result<VectorizationReport, DimError>
+where
+    B: ChatBackend,
+    S: Scalar,
+{
+    let text: &str = vector.get_data();
+    let language: Option<String> = vector.get_metadata(METADATA_LANGUAGE).map(str::to_string);
+    let is_code: bool = vector.get_data_type() == DataType::Code;
+    let tasks = chunks.into_iter().map(|(range, tokens)| {
+        // Chunks of code are framed like the whole file would be
+        let chunk: String = text[range.clone()].to_string();
+        let mut chunk_vector: Vector<String, S> = match is_code {
+            true => Vector::from_code(chunk, language.clone()).cast(),
+            false => Vector::from_text(chunk).cast(),
+        };
+        let prompts: Vec<Prompt> = prompts.to_vec();
+        let client: Arc<B> = client.clone();
+        let model_parameters: ModelParameters = model_parameters.clone();
+        async move {
+            let report: Result<VectorizationReport, DimError> =
+                vectorize_whole_text(prompts, &mut chunk_vector, client, model_parameters).await;
+            (range, tokens, chunk_vector, report)
+        }
+    });
+    let results = join_all(tasks).await;
+
+    // Chunks labeled differently cannot be combined by position
+    let mut chunk_vectors: Vec<ScoredChunk<S>> = Vec::new();
+    for (range, tokens, chunk_vector, report) in results {
+        chunk_vectors.push((range, tokens, chunk_vector, report?));
+    }
+    let reference: &Vector<String, S> = chunk_vectors
+        .iter()
+        .min_by_key(|(_, _, _, report)| report.failed_prompts().len())
+        .map(|(_, _, chunk_vector, _)| chunk_vector)
+        .expect("at least two chunks");
+    let labels: Vec<String> = reference.get_labels().to_vec();
+    let fingerprint: Option<String> = reference.get_fingerprint().map(str::to_string);
+    let metadata = reference.get_metadata_map().clone();
+
+    let mut combined: VectorizationReport = VectorizationReport::default();
+    let mut included_values: Vec<Vec<S>> = Vec::new();
+    for (range, tokens, chunk_vector, report) in chunk_vectors {
+        let included: bool = chunk_vector.get_labels() == labels.as_slice();
+        if included {
+            included_values.push(chunk_vector.get_vector());
+        } else {
+            log::warn!("Leaving out the chunk at bytes {:?}, its dimensions are labeled differently", range);
+        }
+        combined.merge_prompts(&report);
+        combined.chunks.push(ChunkReport {
+            start: range.start,
+            end: range.end,
+            tokens,
+            values: chunk_vector.as_slice().iter().map(|value| value.to_f64() as f32).collect(),
+            labels: chunk_vector.get_labels().to_vec(),
+            included,
+            report,
+        });
+    }
+
+    let included: Vec<&ChunkReport> = combined.chunks.iter().filter(|chunk| chunk.included).collect();
+    let lengths: Vec<usize> = included.iter().map(|chunk| chunk.tokens).collect();
+    let values: Vec<S> = (0..labels.len())
+        .map(|dimension| {
+            let scores: Vec<S> = included_values.iter().map(|chunk_values| chunk_values[dimension]).collect();
+            // A prompt that failed for any chunk leaves its dimensions NaN
+            match scores.iter().any(|score| score.is_nan()) {
+                true => S::from_f64(f64::NAN),
+                false => model_parameters.get_chunk_aggregation().aggregate(&scores, &lengths),
+            }
+        })
+        .collect();
+
+    vector.overwrite_vector_with_labels(values, labels)?;
+    if let Some(fingerprint) = fingerprint {
+        let chunking: String = format!(
+            "{:?}:{:?}:{}:{:?}",
+            model_parameters.get_chunk_tokenizer(),
+            model_parameters.get_chunk_size(),
+            model_parameters.get_chunk_overlap(),
+            model_parameters.get_chunk_aggregation(),
+        );
+        vector.set_fingerprint(combine_fingerprints("chunked", &[&fingerprint, &chunking]));
+    }
+    vector.remove_metadata(METADATA_DIMENSION_MODELS);
+    for (key, value) in metadata {
+        vector.set_metadata(key, value);
+    }
+    record_failed_prompts(vector, &combined);
+    if model_parameters.get_keep_chunk_vectors() {
+        let chunk_values: Vec<&Vec<f32>> = combined.chunks.iter().map(|chunk| &chunk.values).collect();
+        vector.set_metadata(METADATA_CHUNK_VECTORS, serde_json::to_string(&chunk_values).map_err(Error::from)?);
+    }
+
+    Ok(combined)
+}
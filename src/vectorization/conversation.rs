@@ -0,0 +1,69 @@
+use anyhow::{Error, Result};
+
+use crate::error::DimError;
+use crate::llm::ChatBackend;
+use crate::prompt::IntoPrompts;
+use crate::raw_data::utilities::text_sha256;
+use crate::raw_data::{render_conversation, ChatTurn};
+use crate::vector::{Vector, VectorOperations};
+use crate::vectorization::report::VectorizationReport;
+use crate::vectorization::{adopt_vectorization, observe_item, vectorize_item, ModelParameters};
+
+/// Concurrently vectorizes a conversation with multiple prompts.
+///
+/// The turns are rendered with `ModelParametersBuilder::conversation_format`
+/// and scored as text, so long conversations are chunked like long texts.
+///
+/// # Arguments
+/// * `prompts` - The prompts, e.g. a `Vec<String>`, `&[Prompt]` or `&PromptSet`, written for conversations
+/// * `vector` - A mutable reference to the Vector struct containing the conversation
+/// * `client` - The OpenAI API client, or any other `ChatBackend`
+/// * `model_parameters` - The parameters of the vectorization
+///
+/// # Returns
+/// * `Result<VectorizationReport, DimError>` - How each prompt went and the tokens consumed on success,
+///   DimError when the conversation is empty or cannot be vectorized
+pub async fn vectorize_conversation_concurrently<B, P>(
+    prompts: P,
+    vector: &mut Vector<Vec<ChatTurn>>,
+    client: B,
+    model_parameters: ModelParameters,
+) -> Result<VectorizationReport, DimError>
+where
+    B: ChatBackend,
+    P: IntoPrompts,
+{
+    observe_item(
+        model_parameters.get_progress_observer(),
+        model_parameters.get_item_index(),
+        vectorize_conversation(prompts, vector, client, model_parameters),
+    )
+    .await
+}
+
+/// Renders a conversation and vectorizes the rendering.
+async fn vectorize_conversation<B, P>(
+    prompts: P,
+    vector: &mut Vector<Vec<ChatTurn>>,
+    client: B,
+    model_parameters: ModelParameters,
+) -> Result<VectorizationReport, DimError>
+where
+    B: ChatBackend,
+    P: IntoPrompts,
+{
+    if vector.get_data().is_empty() {
+        return Err(DimError::msg("Cannot vectorize a conversation without turns"));
+    }
+    let rendered: String = render_conversation(vector.get_data(), model_parameters.get_conversation_format());
+
+    let mut text_vector: Vector<String> = Vector::from_text(rendered);
+    let mut report: VectorizationReport = vectorize_item(prompts, &mut text_vector, client, model_parameters).await?;
+    if report.input_hash.is_some() {
+        report.input_hash = Some(text_sha256(&serde_json::to_string(vector.get_data()).map_err(Error::from)?));
+    }
+
+    adopt_vectorization(vector, &text_vector)?;
+
+    Ok(report)
+}
@@ -0,0 +1,80 @@
+use anyhow::Result;
+
+use crate::error::DimError;
+use crate::llm::ChatBackend;
+use crate::prompt::IntoPrompts;
+use crate::vector::{Vector, VectorOperations};
+use crate::vectorization::report::VectorizationReport;
+use crate::vectorization::{adopt_vectorization, observe_item, vectorize_item, ModelParameters};
+
+/// Cuts a text to at most `budget` characters, at the last whitespace if there is one.
+fn truncate_to_budget(text: &str, budget: usize) -> &str {
+    let Some((end, _)) = text.char_indices().nth(budget) else {
+        return text;
+    };
+    let cut: &str = &text[..end];
+
+    cut.rfind(char::is_whitespace).map_or(cut, |position| cut[..position].trim_end())
+}
+
+/// Concurrently vectorizes the text of a document, such as one from `Vector::from_document`.
+///
+/// Documents within `ModelParametersBuilder::document_char_budget` are
+/// vectorized like any text. Longer ones are scored on their beginning, cut
+/// to the budget; the vector keeps the full text.
+///
+/// # Arguments
+/// * `prompts` - The prompts, e.g. a `Vec<String>`, `&[Prompt]` or `&PromptSet`, to process concurrently
+/// * `vector` - A mutable reference to the Vector struct containing the document text
+/// * `client` - The OpenAI API client, or any other `ChatBackend`
+/// * `model_parameters` - The parameters, including the character budget
+///
+/// # Returns
+/// * `Result<VectorizationReport, DimError>` - How each prompt went and the tokens consumed on success, DimError on failure
+pub async fn vectorize_document_concurrently<B, P>(
+    prompts: P,
+    vector: &mut Vector<String>,
+    client: B,
+    model_parameters: ModelParameters,
+) -> Result<VectorizationReport, DimError>
+where
+    B: ChatBackend,
+    P: IntoPrompts,
+{
+    observe_item(
+        model_parameters.get_progress_observer(),
+        model_parameters.get_item_index(),
+        vectorize_document(prompts, vector, client, model_parameters),
+    )
+    .await
+}
+
+/// Vectorizes a document whole, or its beginning when it is over the character budget.
+async fn vectorize_document<B, P>(
+    prompts: P,
+    vector: &mut Vector<String>,
+    client: B,
+    model_parameters: ModelParameters,
+) -> Result<VectorizationReport, DimError>
+where
+    B: ChatBackend,
+    P: IntoPrompts,
+{
+    let budget: usize = model_parameters.get_document_char_budget();
+    let text: &str = truncate_to_budget(vector.get_data(), budget);
+    if text.len() == vector.get_data().len() {
+        return vectorize_item(prompts, vector, client, model_parameters).await;
+    }
+    log::warn!(
+        "The document has {} characters, scoring the first {} only",
+        vector.get_data().chars().count(),
+        text.chars().count()
+    );
+
+    let mut truncated: Vector<String> = Vector::from_text(text.to_string());
+    let report: VectorizationReport = vectorize_item(prompts, &mut truncated, client, model_parameters).await?;
+
+    adopt_vectorization(vector, &truncated)?;
+
+    Ok(report)
+}
@@ -0,0 +1,202 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_openai::types::{CreateEmbeddingRequest, CreateEmbeddingRequestArgs, CreateEmbeddingResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::error::DimError;
+use crate::llm::{ChatBackend, EmbeddingBackend};
+use crate::prompt::{combine_fingerprints, IntoPrompts};
+use crate::telemetry::record_usage;
+use crate::vector::metrics::l2_norm;
+use crate::vector::{Vector, VectorOperations};
+use crate::vectorization::report::VectorizationReport;
+use crate::vectorization::usage::TokenUsage;
+use crate::vectorization::{dimension_models, observe_item, record_provenance, vectorize_item, ModelParameters};
+
+/// The prefix of the labels of embedding dimensions, `emb_0`, `emb_1`, ...
+pub const EMBEDDING_LABEL_PREFIX: &str = "emb_";
+
+/// The embedding appended to the scored dimensions of a hybrid vector
+///
+/// # Fields
+/// * `model` - The embedding model, e.g. "text-embedding-3-small"
+/// * `normalize` - Whether the embedding is scaled to unit length, on by default
+/// * `weight` - The factor the embedding is multiplied by after normalizing, 1.0 by default
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingOptions {
+    model: String,
+    normalize: bool,
+    weight: f32,
+}
+
+impl EmbeddingOptions {
+    /// Creates options for an embedding model, normalized and unweighted
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            normalize: true,
+            weight: 1.0,
+        }
+    }
+
+    /// Scales the embedding to unit length before weighting, or keeps it as the API returned it
+    pub fn with_normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Multiplies the embedding, e.g. by less than 1.0 so the scored dimensions dominate distances
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn get_model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn get_normalize(&self) -> bool {
+        self.normalize
+    }
+
+    pub fn get_weight(&self) -> f32 {
+        self.weight
+    }
+
+    /// Describes everything that changes the embedding's values, for the fingerprint.
+    fn describe(&self) -> String {
+        format!("{}|normalize={}|weight={}", self.model, self.normalize, self.weight)
+    }
+}
+
+impl From<&str> for EmbeddingOptions {
+    fn from(model: &str) -> Self {
+        Self::new(model)
+    }
+}
+
+impl From<String> for EmbeddingOptions {
+    fn from(model: String) -> Self {
+        Self::new(model)
+    }
+}
+
+/// Returns where the embedding block of a hybrid vector starts, from its labels
+///
+/// # Arguments
+/// * `labels` - The labels of the vector
+///
+/// # Returns
+/// The index of the first embedding dimension, or None when the vector has no
+/// embedding block at its end
+pub fn embedding_offset(labels: &[String]) -> Option<usize> {
+    let offset: usize = labels.iter().position(|label| label.starts_with(EMBEDDING_LABEL_PREFIX))?;
+
+    labels[offset..]
+        .iter()
+        .all(|label| label.starts_with(EMBEDDING_LABEL_PREFIX))
+        .then_some(offset)
+}
+
+/// Scores a text with prompts and appends an embedding of it from the `/embeddings` endpoint
+///
+/// The embedding follows the scored dimensions, labeled `emb_0..emb_n`,
+/// optionally normalized and weighted. The fingerprint combines that of the
+/// prompts with the embedding model and options, so hybrid vectors are never
+/// compared with pure ones or with hybrids of another embedding. The tokens of
+/// the embedding are added to the report's total usage.
+///
+/// # Arguments
+/// * `prompts` - The prompts, e.g. a `Vec<String>`, `&[Prompt]` or `&PromptSet`
+/// * `vector` - A mutable reference to the Vector struct containing the text
+/// * `client` - The OpenAI API client, or any other backend that can chat and embed
+/// * `model_parameters` - The chat parameters of the prompts
+/// * `embedding_model` - The embedding model, or `EmbeddingOptions` to normalize or weight it
+///
+/// # Returns
+/// * `Result<VectorizationReport, DimError>` - How each prompt went and the tokens consumed on success,
+///   DimError when the embedding or the scoring fails, leaving the vector unchanged
+pub async fn vectorize_string_hybrid<B, P>(
+    prompts: P,
+    vector: &mut Vector<String>,
+    client: B,
+    model_parameters: ModelParameters,
+    embedding_model: impl Into<EmbeddingOptions>,
+) -> Result<VectorizationReport, DimError>
+where
+    B: ChatBackend + EmbeddingBackend,
+    P: IntoPrompts,
+{
+    let options: EmbeddingOptions = embedding_model.into();
+    observe_item(
+        model_parameters.get_progress_observer(),
+        model_parameters.get_item_index(),
+        vectorize_hybrid(prompts, vector, client, model_parameters, options),
+    )
+    .await
+}
+
+/// Embeds a text, then scores it and appends the embedding to the scores.
+async fn vectorize_hybrid<B, P>(
+    prompts: P,
+    vector: &mut Vector<String>,
+    client: B,
+    model_parameters: ModelParameters,
+    options: EmbeddingOptions,
+) -> Result<VectorizationReport, DimError>
+where
+    B: ChatBackend + EmbeddingBackend,
+    P: IntoPrompts,
+{
+    let client: Arc<B> = Arc::new(client);
+    let (embedding, usage): (Vec<f32>, TokenUsage) = embed_text(client.as_ref(), vector.get_data(), &options).await?;
+
+    let mut report: VectorizationReport = vectorize_item(prompts, vector, client, model_parameters.clone()).await?;
+    report.usage.total += usage;
+
+    let mut models: Vec<String> = dimension_models(vector, &model_parameters.get_model());
+    models.extend(vec![options.model.clone(); embedding.len()]);
+    let (mut values, mut labels): (Vec<f32>, Vec<String>) = vector.get_labeled_vector().into_iter().map(|(label, value)| (value, label)).unzip();
+    labels.extend((0..embedding.len()).map(|index| format!("{}{}", EMBEDDING_LABEL_PREFIX, index)));
+    values.extend(embedding);
+    vector.grow_expected_dims(values.len() - vector.get_dimensionality());
+    vector.overwrite_vector_with_labels(values, labels)?;
+    let fingerprint: String = combine_fingerprints("hybrid", &[vector.get_fingerprint().unwrap_or_default(), &options.describe()]);
+    vector.set_fingerprint(fingerprint);
+    record_provenance(vector, &model_parameters.get_model(), &models);
+
+    Ok(report)
+}
+
+/// Requests the embedding of a text, normalized and weighted as the options say.
+async fn embed_text<B: EmbeddingBackend>(client: &B, text: &str, options: &EmbeddingOptions) -> Result<(Vec<f32>, TokenUsage), DimError> {
+    let request: CreateEmbeddingRequest = CreateEmbeddingRequestArgs::default()
+        .model(options.model.clone())
+        .input(text)
+        .build()
+        .map_err(|e| DimError::msg(e.to_string()))?;
+    let response: CreateEmbeddingResponse = client.create_embeddings(request).await?;
+    let mut embedding: Vec<f32> = response
+        .data
+        .into_iter()
+        .next()
+        .map(|embedding| embedding.embedding)
+        .filter(|embedding| !embedding.is_empty())
+        .ok_or_else(|| DimError::msg(format!("The embedding model {} returned no embedding", options.model)))?;
+
+    let norm: f32 = l2_norm(&embedding);
+    let scale: f32 = match options.normalize && norm > 0.0 {
+        true => options.weight / norm,
+        false => options.weight,
+    };
+    embedding.iter_mut().for_each(|value| *value *= scale);
+    let usage: TokenUsage = TokenUsage {
+        prompt_tokens: response.usage.prompt_tokens as u64,
+        completion_tokens: 0,
+        total_tokens: response.usage.total_tokens as u64,
+    };
+    record_usage(&options.model, &usage);
+
+    Ok((embedding, usage))
+}
@@ -0,0 +1,259 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::prompt::{compute_fingerprint, PromptSet, SampleAggregation};
+use crate::raw_data::ImageDetail;
+use crate::runtime::{SystemTime, UNIX_EPOCH};
+use crate::vectorization::{ExtractionMode, ModelParameters};
+
+/// The settings of a run that shape its vectors
+///
+/// Modes without a serialized form are recorded by their debug representation.
+///
+/// # Fields
+/// * `temperature` - The sampling temperature
+/// * `seed` - The fixed seed, None when every request got a random one
+/// * `max_tokens` - The answer length limit, if any
+/// * `top_p` - The nucleus sampling threshold, if any
+/// * `extraction_mode` - How scores were requested and read
+/// * `scoring_mode` - How scores were computed from the answers
+/// * `samples_per_prompt` - How often each prompt was sampled
+/// * `sample_aggregation` - How samples were combined
+/// * `rotate_seed` - Whether each sample and retry got its own seed
+/// * `chunk_size` - The chunk length of long texts, if chunked
+/// * `chunk_overlap` - The overlap of consecutive chunks
+/// * `max_input_tokens` - The token limit texts were cut to, if any
+/// * `input_truncation` - Which part of a long text was kept
+/// * `max_image_dimension` - The longest side images were downscaled to, if any
+/// * `image_encoding` - How images were encoded before sending
+/// * `image_detail` - How closely the model looked at images
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestParameters {
+    pub temperature: f32,
+    pub seed: Option<i64>,
+    pub max_tokens: Option<u32>,
+    pub top_p: Option<f32>,
+    pub extraction_mode: ExtractionMode,
+    pub scoring_mode: String,
+    pub samples_per_prompt: usize,
+    pub sample_aggregation: SampleAggregation,
+    pub rotate_seed: bool,
+    pub chunk_size: Option<usize>,
+    pub chunk_overlap: usize,
+    pub max_input_tokens: Option<usize>,
+    pub input_truncation: String,
+    pub max_image_dimension: Option<u32>,
+    pub image_encoding: String,
+    #[serde(default)]
+    pub image_detail: ImageDetail,
+}
+
+impl From<&ModelParameters> for ManifestParameters {
+    fn from(parameters: &ModelParameters) -> Self {
+        Self {
+            temperature: parameters.get_temperature(),
+            seed: parameters.seed,
+            max_tokens: parameters.get_max_tokens(),
+            top_p: parameters.get_top_p(),
+            extraction_mode: parameters.get_extraction_mode(),
+            scoring_mode: format!("{:?}", parameters.get_scoring_mode()),
+            samples_per_prompt: parameters.get_samples_per_prompt(),
+            sample_aggregation: parameters.get_sample_aggregation().clone(),
+            rotate_seed: parameters.get_rotate_seed(),
+            chunk_size: parameters.get_chunk_size(),
+            chunk_overlap: parameters.get_chunk_overlap(),
+            max_input_tokens: parameters.get_max_input_tokens(),
+            input_truncation: format!("{:?}", parameters.get_input_truncation()),
+            max_image_dimension: parameters.get_max_image_dimension(),
+            image_encoding: format!("{:?}", parameters.get_image_encoding()),
+            image_detail: parameters.get_image_detail(),
+        }
+    }
+}
+
+/// How a set of vectors was made, written next to them to check later runs against
+///
+/// The manifest holds the prompts and settings, never the vectorized inputs.
+///
+/// # Fields
+/// * `crate_version` - The version of this crate that made the vectors
+/// * `model` - The default model of the run
+/// * `parameters` - The settings that shape the vectors
+/// * `prompts` - The instruction of every prompt, in dimension order
+/// * `fingerprint` - The fingerprint of the prompts and model, as set on every vector
+/// * `started_at` - When the run started, in seconds since the Unix epoch
+/// * `finished_at` - When the last item was recorded, in seconds since the Unix epoch
+/// * `items` - How many items were vectorized, including failed ones
+/// * `failures` - How many items failed to vectorize
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunManifest {
+    pub crate_version: String,
+    pub model: String,
+    pub parameters: ManifestParameters,
+    pub prompts: Vec<String>,
+    pub fingerprint: String,
+    pub started_at: u64,
+    pub finished_at: u64,
+    pub items: usize,
+    pub failures: usize,
+}
+
+impl RunManifest {
+    /// Starts the manifest of a run
+    ///
+    /// # Arguments
+    /// * `prompts` - The prompts of the run
+    /// * `model_parameters` - The parameters of the run
+    ///
+    /// # Returns
+    /// A manifest without items, started now
+    pub fn new(prompts: &PromptSet, model_parameters: &ModelParameters) -> Self {
+        let now: u64 = unix_now();
+
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            model: model_parameters.get_model(),
+            parameters: ManifestParameters::from(model_parameters),
+            prompts: prompts.get_prompts().iter().map(|prompt| prompt.get_instruction()).collect(),
+            fingerprint: compute_fingerprint(prompts.get_prompts(), &model_parameters.get_model()),
+            started_at: now,
+            finished_at: now,
+            items: 0,
+            failures: 0,
+        }
+    }
+
+    /// Counts one vectorized item
+    ///
+    /// # Arguments
+    /// * `succeeded` - Whether the item was vectorized
+    pub fn record_item(&mut self, succeeded: bool) {
+        self.items += 1;
+        if !succeeded {
+            self.failures += 1;
+        }
+        self.finished_at = unix_now();
+    }
+
+    /// Lists how a prompt set and parameters differ from the run in ways that change the vectors
+    ///
+    /// # Arguments
+    /// * `prompts` - The prompts of the new run
+    /// * `model_parameters` - The parameters of the new run
+    ///
+    /// # Returns
+    /// One description per difference, empty when results can be mixed
+    pub fn mismatches(&self, prompts: &PromptSet, model_parameters: &ModelParameters) -> Vec<String> {
+        let other: RunManifest = RunManifest::new(prompts, model_parameters);
+        let mut mismatches: Vec<String> = Vec::new();
+
+        if other.fingerprint != self.fingerprint {
+            mismatches.push(format!("fingerprint {} differs from {}", other.fingerprint, self.fingerprint));
+        }
+        if other.parameters.extraction_mode != self.parameters.extraction_mode {
+            mismatches.push(format!(
+                "extraction mode {:?} differs from {:?}",
+                other.parameters.extraction_mode, self.parameters.extraction_mode
+            ));
+        }
+        if other.parameters.scoring_mode != self.parameters.scoring_mode {
+            mismatches.push(format!(
+                "scoring mode {} differs from {}",
+                other.parameters.scoring_mode, self.parameters.scoring_mode
+            ));
+        }
+        if other.parameters.samples_per_prompt != self.parameters.samples_per_prompt
+            || other.parameters.sample_aggregation != self.parameters.sample_aggregation
+        {
+            mismatches.push(format!(
+                "{} samples combined by {:?} differ from {} combined by {:?}",
+                other.parameters.samples_per_prompt,
+                other.parameters.sample_aggregation,
+                self.parameters.samples_per_prompt,
+                self.parameters.sample_aggregation
+            ));
+        }
+
+        mismatches
+    }
+
+    /// Checks whether a new run may add its vectors to this run's
+    ///
+    /// # Arguments
+    /// * `prompts` - The prompts of the new run
+    /// * `model_parameters` - The parameters of the new run
+    ///
+    /// # Returns
+    /// True if the prompts, model and scoring settings match
+    pub fn compatible_with(&self, prompts: &PromptSet, model_parameters: &ModelParameters) -> bool {
+        self.mismatches(prompts, model_parameters).is_empty()
+    }
+
+    /// Writes the manifest as pretty-printed JSON
+    ///
+    /// # Arguments
+    /// * `path` - The file to create or truncate
+    ///
+    /// # Returns
+    /// An error if the file cannot be written
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        std::fs::write(path.as_ref(), serde_json::to_string_pretty(self)?)
+            .map_err(|e| Error::msg(format!("Failed to write manifest {}: {}", path.as_ref().display(), e)))
+    }
+
+    /// Writes the manifest next to a file of exported vectors
+    ///
+    /// # Arguments
+    /// * `data_path` - The exported vectors, e.g. `vectors.jsonl`
+    ///
+    /// # Returns
+    /// The path written, e.g. `vectors.manifest.json`, or an error if it cannot be written
+    pub fn save_sidecar(&self, data_path: impl AsRef<Path>) -> Result<PathBuf, Error> {
+        let path: PathBuf = sidecar_path(data_path);
+        self.save(&path)?;
+
+        Ok(path)
+    }
+}
+
+/// Returns where the manifest of a file of exported vectors is kept
+///
+/// # Arguments
+/// * `data_path` - The exported vectors, e.g. `out/vectors.jsonl`
+///
+/// # Returns
+/// The sidecar path, e.g. `out/vectors.manifest.json`
+pub fn sidecar_path(data_path: impl AsRef<Path>) -> PathBuf {
+    let data_path: &Path = data_path.as_ref();
+    let stem: String = data_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    data_path.with_file_name(format!("{}.manifest.json", stem))
+}
+
+/// Reads a manifest written by `RunManifest::save`
+///
+/// # Arguments
+/// * `path` - The manifest file
+///
+/// # Returns
+/// The manifest, or an error if the file cannot be read or parsed
+pub fn load_manifest(path: impl AsRef<Path>) -> Result<RunManifest, Error> {
+    let text: String = std::fs::read_to_string(path.as_ref())
+        .map_err(|e| Error::msg(format!("Failed to read manifest {}: {}", path.as_ref().display(), e)))?;
+
+    serde_json::from_str(&text)
+        .map_err(|e| Error::msg(format!("Failed to parse manifest {}: {}", path.as_ref().display(), e)))
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,214 @@
+use anyhow::{Error, Result};
+use async_openai::types::{ChatCompletionRequestMessage, CreateChatCompletionRequest};
+#[cfg(feature = "image")]
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::prompt::{compute_fingerprint, IntoPrompts, Prompt};
+use crate::raw_data::ImageDetail;
+use crate::vector::{Scalar, Vector};
+use crate::vectorization::truncation::estimate_tokens;
+use crate::vectorization::vectorizable::{RequestInput, Vectorizable};
+use crate::vectorization::{build_chat_request, AnswerFormat, ImageUrls, ModelParameters};
+
+/// Data URLs longer than this are elided from planned requests
+const ELIDED_DATA_URL_LENGTH: usize = 64;
+
+/// A request a vectorization would send, built without sending it
+///
+/// # Fields
+/// * `prompt_index` - The position of the prompt in the call
+/// * `sample` - The sample of the prompt, 0 unless sampled more than once
+/// * `model` - The model the request is sent to
+/// * `request` - The serialized `CreateChatCompletionRequest`, with image data URLs elided
+/// * `payload_bytes` - The size of the serialized request before elision
+/// * `approx_prompt_tokens` - The text of the messages as `estimate_tokens` counts it for the model, images excluded
+/// * `approx_image_tokens` - The images as OpenAI counts them at the request's `ImageDetail`; images
+///   of unknown size, such as URLs and encoded images, count their base cost only
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedRequest {
+    pub prompt_index: usize,
+    pub sample: usize,
+    pub model: String,
+    pub request: Value,
+    pub payload_bytes: usize,
+    pub approx_prompt_tokens: u64,
+    #[serde(default)]
+    pub approx_image_tokens: u64,
+}
+
+/// Everything a vectorization would send, for inspection before an expensive run
+///
+/// Validation retries are not known in advance, so the totals are lower bounds.
+///
+/// # Fields
+/// * `requests` - One entry per prompt and sample, in the order they are spawned
+/// * `fingerprint` - The fingerprint the vector would get
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VectorizationPlan {
+    pub requests: Vec<PlannedRequest>,
+    pub fingerprint: String,
+}
+
+impl VectorizationPlan {
+    /// Returns the number of requests sent when no answer is rejected
+    pub fn total_requests(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Returns the approximate prompt tokens of all requests, images excluded
+    pub fn approx_prompt_tokens(&self) -> u64 {
+        self.requests.iter().map(|request| request.approx_prompt_tokens).sum()
+    }
+
+    /// Returns the approximate image tokens of all requests
+    pub fn approx_image_tokens(&self) -> u64 {
+        self.requests.iter().map(|request| request.approx_image_tokens).sum()
+    }
+
+    /// Returns the size of all serialized requests, images included
+    pub fn payload_bytes(&self) -> usize {
+        self.requests.iter().map(|request| request.payload_bytes).sum()
+    }
+}
+
+/// Builds the requests `vectorize_string_concurrently` would send, without sending any
+///
+/// # Arguments
+/// * `prompts` - The prompts that would be processed, e.g. a `Vec<String>`, `&[Prompt]` or `&PromptSet`
+/// * `vector` - The Vector containing the text
+/// * `model_parameters` - The parameters the call would use
+///
+/// # Returns
+/// The plan, or an error if a request cannot be built
+pub fn plan_string_vectorization<P: IntoPrompts>(
+    prompts: P,
+    vector: &Vector<String>,
+    model_parameters: &ModelParameters,
+) -> Result<VectorizationPlan, Error> {
+    plan_vectorization(prompts, vector, model_parameters)
+}
+
+/// Builds the requests `vectorize_image_concurrently` would send, without sending any
+///
+/// # Arguments
+/// * `prompts` - The prompts that would be processed, e.g. a `Vec<String>`, `&[Prompt]` or `&PromptSet`
+/// * `vector` - The Vector containing the image
+/// * `model_parameters` - The parameters the call would use
+///
+/// # Returns
+/// The plan, or an error if a request cannot be built
+#[cfg(feature = "image")]
+pub fn plan_image_vectorization<P: IntoPrompts>(
+    prompts: P,
+    vector: &Vector<DynamicImage>,
+    model_parameters: &ModelParameters,
+) -> Result<VectorizationPlan, Error> {
+    plan_vectorization(prompts, vector, model_parameters)
+}
+
+/// Builds the requests `vectorize_concurrently` would send, without sending any
+///
+/// Texts are planned whole, even when `chunk_size` would split them.
+///
+/// # Arguments
+/// * `prompts` - The prompts that would be processed, e.g. a `Vec<String>`, `&[Prompt]` or `&PromptSet`
+/// * `vector` - The Vector containing the data
+/// * `model_parameters` - The parameters the call would use
+///
+/// # Returns
+/// The plan, or an error if a request cannot be built
+pub fn plan_vectorization<T, P, S>(
+    prompts: P,
+    vector: &Vector<T, S>,
+    model_parameters: &ModelParameters,
+) -> Result<VectorizationPlan, Error>
+where
+    T: Vectorizable,
+    P: IntoPrompts,
+    S: Scalar,
+{
+    let input: RequestInput = T::request_input(vector, model_parameters)?;
+    let prompts: Vec<Prompt> = prompts.into_prompts();
+    let mut plan: VectorizationPlan = VectorizationPlan {
+        requests: Vec::new(),
+        fingerprint: compute_fingerprint(&prompts, &model_parameters.get_model()),
+    };
+
+    for (prompt_index, prompt) in prompts.iter().enumerate() {
+        let parameters: ModelParameters = model_parameters.with_override(prompt.get_model_override());
+        let messages: Vec<ChatCompletionRequestMessage> = input.build_messages(prompt, parameters.get_image_detail())?;
+        let approx_image_tokens: u64 = image_tokens(&input, parameters.get_image_detail());
+        let answer_format: AnswerFormat = AnswerFormat::for_prompt(prompt, &parameters);
+
+        for sample in 0..model_parameters.get_samples_per_prompt() {
+            let sample_parameters: ModelParameters = parameters.for_sample(sample);
+            let request: CreateChatCompletionRequest = build_chat_request(messages.clone(), &sample_parameters, &answer_format)
+                .map_err(|e| Error::msg(e.to_string()))?;
+            let mut request: Value = serde_json::to_value(&request)?;
+            let payload_bytes: usize = request.to_string().len();
+            elide_data_urls(&mut request);
+            let approx_prompt_tokens: u64 = estimate_tokens(&message_text(&request["messages"]), &sample_parameters.get_model()) as u64;
+
+            plan.requests.push(PlannedRequest {
+                prompt_index,
+                sample,
+                model: sample_parameters.get_model(),
+                request,
+                payload_bytes,
+                approx_prompt_tokens,
+                approx_image_tokens,
+            });
+        }
+    }
+
+    Ok(plan)
+}
+
+/// Estimates the tokens of the images of a request, counting images of unknown size at `ImageDetail::Low`.
+fn image_tokens(input: &RequestInput, image_detail: ImageDetail) -> u64 {
+    let image_urls: &ImageUrls = &input.image_urls;
+    let unsized_images: usize = image_urls.urls.len().saturating_sub(image_urls.sent_sizes.len());
+    let sized: u64 = image_urls
+        .sent_sizes
+        .iter()
+        .map(|(width, height)| image_detail.approx_tokens(*width, *height))
+        .sum();
+
+    sized + unsized_images as u64 * ImageDetail::Low.approx_tokens(0, 0)
+}
+
+/// Replaces long `data:` URLs, such as base64 images, with their size.
+fn elide_data_urls(value: &mut Value) {
+    match value {
+        Value::String(text) if text.starts_with("data:") && text.len() > ELIDED_DATA_URL_LENGTH => {
+            let header: &str = text.split(',').next().unwrap_or_default();
+            *text = format!("{},<{} bytes elided>", header, text.len());
+        }
+        Value::Array(values) => values.iter_mut().for_each(elide_data_urls),
+        Value::Object(map) => map.values_mut().for_each(elide_data_urls),
+        _ => {}
+    }
+}
+
+/// Joins the text contents of messages, leaving out elided images.
+fn message_text(messages: &Value) -> String {
+    match messages {
+        Value::String(text) if !text.starts_with("data:") => text.clone(),
+        Value::Array(values) => values
+            .iter()
+            .map(message_text)
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<String>>()
+            .join("\n"),
+        Value::Object(map) => map
+            .iter()
+            .filter(|(key, _)| key.as_str() != "role" && key.as_str() != "type")
+            .map(|(_, value)| message_text(value))
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<String>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
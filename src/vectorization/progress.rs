@@ -0,0 +1,103 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::Duration;
+
+use crate::error::DimError;
+
+/// Receives progress of a vectorization run, e.g. to drive a progress bar
+///
+/// Set one with `ModelParametersBuilder::progress_observer`. Methods are called
+/// from the tasks sending the requests, so they should return quickly; hand
+/// the work to another thread, as `ChannelProgress` does, if it is slow. Every
+/// method does nothing by default.
+pub trait ProgressObserver: std::fmt::Debug + Send + Sync {
+    /// Called when a sample of a prompt is accepted or answered from a cache
+    ///
+    /// # Arguments
+    /// * `item_index` - The position of the item in the batch, 0 outside of one
+    /// * `prompt_index` - The position of the prompt in the call
+    /// * `attempts` - The requests answered until the sample was accepted, 0 when cached
+    fn on_prompt_complete(&self, _item_index: usize, _prompt_index: usize, _attempts: u64) {}
+
+    /// Called when an item is vectorized
+    ///
+    /// # Arguments
+    /// * `item_index` - The position of the item in the batch, 0 outside of one
+    /// * `duration` - The time the item took
+    fn on_item_complete(&self, _item_index: usize, _duration: Duration) {}
+
+    /// Called when a request failed or its answer was rejected, before it is sent again
+    ///
+    /// # Arguments
+    /// * `item_index` - The position of the item in the batch, 0 outside of one
+    /// * `prompt_index` - The position of the prompt in the call
+    /// * `attempt` - The requests answered so far for the sample
+    /// * `reason` - Why the request is sent again
+    fn on_retry(&self, _item_index: usize, _prompt_index: usize, _attempt: u64, _reason: &str) {}
+
+    /// Called when a prompt or a whole item fails
+    ///
+    /// # Arguments
+    /// * `item_index` - The position of the item in the batch, 0 outside of one
+    /// * `prompt_index` - The prompt that failed, or None when the item failed
+    /// * `error` - Why it failed
+    fn on_error(&self, _item_index: usize, _prompt_index: Option<usize>, _error: &DimError) {}
+}
+
+/// A `ProgressObserver` that ignores every event
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoopProgress;
+
+impl ProgressObserver for NoopProgress {}
+
+/// One call of a `ProgressObserver`, as sent by `ChannelProgress`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// A sample of a prompt was accepted or answered from a cache
+    PromptComplete { item_index: usize, prompt_index: usize, attempts: u64 },
+    /// An item was vectorized
+    ItemComplete { item_index: usize, duration: Duration },
+    /// A request is sent again
+    Retry { item_index: usize, prompt_index: usize, attempt: u64, reason: String },
+    /// A prompt, or the whole item when `prompt_index` is None, failed
+    Error { item_index: usize, prompt_index: Option<usize>, message: String },
+}
+
+/// A `ProgressObserver` sending every event to a channel
+///
+/// Sending never blocks. Read the events from another thread or task to update
+/// a progress bar or UI; events sent after the receiver is dropped are discarded.
+#[derive(Debug, Clone)]
+pub struct ChannelProgress {
+    sender: Sender<ProgressEvent>,
+}
+
+impl ChannelProgress {
+    /// Creates an observer and the receiving end of its channel
+    pub fn new() -> (Self, Receiver<ProgressEvent>) {
+        let (sender, receiver) = channel();
+        (Self { sender }, receiver)
+    }
+
+    /// Sends an event, ignoring a dropped receiver.
+    fn send(&self, event: ProgressEvent) {
+        let _ = self.sender.send(event);
+    }
+}
+
+impl ProgressObserver for ChannelProgress {
+    fn on_prompt_complete(&self, item_index: usize, prompt_index: usize, attempts: u64) {
+        self.send(ProgressEvent::PromptComplete { item_index, prompt_index, attempts });
+    }
+
+    fn on_item_complete(&self, item_index: usize, duration: Duration) {
+        self.send(ProgressEvent::ItemComplete { item_index, duration });
+    }
+
+    fn on_retry(&self, item_index: usize, prompt_index: usize, attempt: u64, reason: &str) {
+        self.send(ProgressEvent::Retry { item_index, prompt_index, attempt, reason: reason.to_string() });
+    }
+
+    fn on_error(&self, item_index: usize, prompt_index: Option<usize>, error: &DimError) {
+        self.send(ProgressEvent::Error { item_index, prompt_index, message: error.to_string() });
+    }
+}
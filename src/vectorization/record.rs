@@ -0,0 +1,73 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::error::DimError;
+use crate::llm::ChatBackend;
+use crate::prompt::{combine_fingerprints, IntoPrompts};
+use crate::raw_data::record::RecordRenderer;
+use crate::vector::{Vector, VectorOperations};
+use crate::vectorization::report::VectorizationReport;
+use crate::vectorization::{adopt_vectorization, observe_item, vectorize_item, ModelParameters};
+
+/// Concurrently vectorizes a JSON record, such as a product row, with multiple prompts.
+///
+/// The record is rendered as sorted `key: value` lines by `renderer` and scored
+/// as text. The fingerprint covers the field selection, so records are only
+/// comparable when rendered with the same fields.
+///
+/// # Arguments
+/// * `prompts` - The prompts, e.g. a `Vec<String>`, `&[Prompt]` or `&PromptSet`, written for records
+/// * `vector` - A mutable reference to the Vector struct containing the record
+/// * `renderer` - Which fields to render
+/// * `client` - The OpenAI API client, or any other `ChatBackend`
+/// * `model_parameters` - The parameters of the vectorization
+///
+/// # Returns
+/// * `Result<VectorizationReport, DimError>` - How each prompt went and the tokens consumed on success,
+///   DimError when no field is rendered or the record cannot be vectorized
+pub async fn vectorize_record_concurrently<B, P>(
+    prompts: P,
+    vector: &mut Vector<Value>,
+    renderer: &RecordRenderer,
+    client: B,
+    model_parameters: ModelParameters,
+) -> Result<VectorizationReport, DimError>
+where
+    B: ChatBackend,
+    P: IntoPrompts,
+{
+    observe_item(
+        model_parameters.get_progress_observer(),
+        model_parameters.get_item_index(),
+        vectorize_record(prompts, vector, renderer, client, model_parameters),
+    )
+    .await
+}
+
+/// Renders a record and vectorizes the rendering.
+async fn vectorize_record<B, P>(
+    prompts: P,
+    vector: &mut Vector<Value>,
+    renderer: &RecordRenderer,
+    client: B,
+    model_parameters: ModelParameters,
+) -> Result<VectorizationReport, DimError>
+where
+    B: ChatBackend,
+    P: IntoPrompts,
+{
+    let rendered: String = renderer.render(vector.get_data());
+    if rendered.is_empty() {
+        return Err(DimError::msg("Cannot vectorize a record without any rendered field"));
+    }
+
+    let mut text_vector: Vector<String> = Vector::from_text(rendered);
+    let report: VectorizationReport = vectorize_item(prompts, &mut text_vector, client, model_parameters).await?;
+
+    adopt_vectorization(vector, &text_vector)?;
+    if let Some(fingerprint) = text_vector.get_fingerprint() {
+        vector.set_fingerprint(combine_fingerprints("record", &[fingerprint, &renderer.describe()]));
+    }
+
+    Ok(report)
+}
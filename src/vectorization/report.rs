@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use anyhow::{Error, Result};
+
+use crate::error::DimError;
+use crate::vectorization::capture::{CapturedResponse, ResponseSink};
+use crate::vectorization::usage::{TokenUsage, UsageReport};
+
+/// How one prompt of a vectorization went
+///
+/// # Fields
+/// * `prompt_index` - The position of the prompt in the call
+/// * `model` - The model the prompt was sent to
+/// * `succeeded` - Whether the prompt contributed its dimensions to the vector
+/// * `error` - Why the prompt failed, its dimensions being left NaN
+/// * `logprob_fallback` - Whether expected-value scoring was requested but the
+///   provider returned no usable logprobs, so the parsed score was kept
+/// * `samples` - The accepted scores of every sample, when the prompt was sampled more than once
+/// * `responses` - The raw answers kept by the capture mode, in the order they were received
+/// * `accepted_attempts` - Which attempt and seed produced each accepted answer, one per sample
+/// * `cached` - Whether the scores were found in the cache, so no request was sent
+/// * `keys` - The JSON key path each accepted score was read from
+/// * `usage` - The tokens of all answered requests, including rejected ones
+/// * `retries` - The requests sent again after a failure or a rejected answer
+/// * `latency_ms` - The time until the answer was accepted, the slowest sample's when sampled more than once
+/// * `endpoints` - The answered requests per endpoint, e.g. per API base of a `FailoverClient`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptReport {
+    pub prompt_index: usize,
+    pub model: String,
+    pub succeeded: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub logprob_fallback: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub samples: Vec<Vec<f32>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub responses: Vec<CapturedResponse>,
+    #[serde(default)]
+    pub accepted_attempts: Vec<AcceptedAttempt>,
+    #[serde(default)]
+    pub cached: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<String>,
+    #[serde(default)]
+    pub usage: TokenUsage,
+    #[serde(default)]
+    pub retries: u64,
+    #[serde(default)]
+    pub latency_ms: u64,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub endpoints: BTreeMap<String, u64>,
+}
+
+/// The attempt an accepted answer came from
+///
+/// # Fields
+/// * `sample` - The sample of the prompt, 0 unless sampled more than once
+/// * `attempt` - The answered request of that sample, starting at 1
+/// * `seed` - The seed sent with that request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AcceptedAttempt {
+    pub sample: usize,
+    pub attempt: u64,
+    pub seed: Option<i64>,
+}
+
+impl PromptReport {
+    /// Returns the population standard deviation of each dimension across samples
+    ///
+    /// Empty unless the prompt was sampled more than once.
+    pub fn sample_spread(&self) -> Vec<f32> {
+        let Some(first) = self.samples.first() else {
+            return Vec::new();
+        };
+        let count: f32 = self.samples.len() as f32;
+
+        (0..first.len())
+            .map(|dimension| {
+                let mean: f32 = self.samples.iter().map(|sample| sample[dimension]).sum::<f32>() / count;
+                let variance: f32 = self
+                    .samples
+                    .iter()
+                    .map(|sample| (sample[dimension] - mean) * (sample[dimension] - mean))
+                    .sum::<f32>()
+                    / count;
+                variance.sqrt()
+            })
+            .collect()
+    }
+}
+
+/// What happened during one vectorization call
+///
+/// # Fields
+/// * `prompts` - One entry per prompt, in prompt order
+/// * `usage` - The tokens consumed, including retried requests
+/// * `input_hash` - The SHA-256 of the vectorized text or image when answers were
+///   captured, so the answers can be traced back without storing the input
+/// * `frames` - The vector of each frame, for videos
+/// * `chunks` - The vector of each chunk, for texts vectorized in chunks
+/// * `wall_time_ms` - The time the whole call took
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VectorizationReport {
+    pub prompts: Vec<PromptReport>,
+    pub usage: UsageReport,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frames: Vec<FrameReport>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chunks: Vec<ChunkReport>,
+    #[serde(default)]
+    pub wall_time_ms: u64,
+}
+
+/// How one frame of a video was scored
+///
+/// # Fields
+/// * `timestamp` - The position of the frame in the video, in seconds
+/// * `values` - The vector of the frame
+/// * `labels` - The label of each value
+/// * `included` - Whether the frame was combined into the video's vector; frames
+///   labeled differently from the others are left out
+/// * `report` - The report of the frame's vectorization
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrameReport {
+    pub timestamp: f32,
+    pub values: Vec<f32>,
+    pub labels: Vec<String>,
+    pub included: bool,
+    pub report: VectorizationReport,
+}
+
+/// How one chunk of a long text was scored
+///
+/// # Fields
+/// * `start` - The byte offset of the chunk in the text
+/// * `end` - The byte offset after the chunk
+/// * `tokens` - The number of tokens of the chunk, its weight in a length-weighted mean
+/// * `values` - The vector of the chunk
+/// * `labels` - The label of each value
+/// * `included` - Whether the chunk was combined into the text's vector; chunks
+///   labeled differently from the others are left out
+/// * `report` - The report of the chunk's vectorization
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkReport {
+    pub start: usize,
+    pub end: usize,
+    pub tokens: usize,
+    pub values: Vec<f32>,
+    pub labels: Vec<String>,
+    pub included: bool,
+    pub report: VectorizationReport,
+}
+
+impl VectorizationReport {
+    /// Returns how many prompts were answered from the cache
+    pub fn cache_hits(&self) -> usize {
+        self.prompts.iter().filter(|prompt| prompt.cached).count()
+    }
+
+    /// Returns the answered requests of every prompt, including rejected ones
+    pub fn total_requests(&self) -> u64 {
+        self.usage.get_requests()
+    }
+
+    /// Returns how many answered requests each endpoint served, across every prompt
+    pub fn endpoint_requests(&self) -> BTreeMap<String, u64> {
+        let mut endpoints: BTreeMap<String, u64> = BTreeMap::new();
+        for (endpoint, requests) in self.prompts.iter().flat_map(|prompt| &prompt.endpoints) {
+            *endpoints.entry(endpoint.clone()).or_default() += requests;
+        }
+
+        endpoints
+    }
+
+    /// Returns the requests sent again after a failure or a rejected answer
+    pub fn total_retries(&self) -> u64 {
+        self.prompts.iter().map(|prompt| prompt.retries).sum()
+    }
+
+    /// Returns how many chunks the text was vectorized in, 1 when it was vectorized whole
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len().max(1)
+    }
+
+    /// Adds the usage and prompt outcomes of a part, such as a frame or chunk, to this report
+    ///
+    /// A prompt succeeds only if it succeeded for every part; samples and
+    /// responses stay in the part's own report.
+    pub(crate) fn merge_prompts(&mut self, part: &VectorizationReport) {
+        self.usage.merge(&part.usage);
+        for prompt in &part.prompts {
+            match self.prompts.iter_mut().find(|existing| existing.prompt_index == prompt.prompt_index) {
+                Some(existing) => {
+                    existing.succeeded &= prompt.succeeded;
+                    if existing.error.is_none() {
+                        existing.error = prompt.error.clone();
+                    }
+                    existing.logprob_fallback |= prompt.logprob_fallback;
+                    existing.cached &= prompt.cached;
+                    existing.usage += prompt.usage;
+                    existing.retries += prompt.retries;
+                    existing.latency_ms = existing.latency_ms.max(prompt.latency_ms);
+                    for (endpoint, requests) in &prompt.endpoints {
+                        *existing.endpoints.entry(endpoint.clone()).or_default() += requests;
+                    }
+                }
+                None => self.prompts.push(PromptReport {
+                    prompt_index: prompt.prompt_index,
+                    model: prompt.model.clone(),
+                    succeeded: prompt.succeeded,
+                    error: prompt.error.clone(),
+                    logprob_fallback: prompt.logprob_fallback,
+                    samples: Vec::new(),
+                    responses: Vec::new(),
+                    accepted_attempts: Vec::new(),
+                    cached: prompt.cached,
+                    keys: prompt.keys.clone(),
+                    usage: prompt.usage,
+                    retries: prompt.retries,
+                    latency_ms: prompt.latency_ms,
+                    endpoints: prompt.endpoints.clone(),
+                }),
+            }
+        }
+    }
+
+    /// Returns the indices of the prompts that failed, their dimensions left NaN
+    pub fn failed_prompts(&self) -> Vec<usize> {
+        self.prompts
+            .iter()
+            .filter(|prompt| !prompt.succeeded)
+            .map(|prompt| prompt.prompt_index)
+            .collect()
+    }
+
+    /// Fails with the indices of the prompts that failed, for callers that only keep complete vectors
+    ///
+    /// # Returns
+    /// An error naming the failed prompts and the first failure's reason, if any prompt failed
+    pub fn ensure_complete(&self) -> Result<(), DimError> {
+        let failed: Vec<usize> = self.failed_prompts();
+        if failed.is_empty() {
+            return Ok(());
+        }
+        let reason: &str = self.prompts.iter().find_map(|prompt| prompt.error.as_deref()).unwrap_or("unknown error");
+
+        Err(DimError::msg(format!("Prompts {:?} failed, the first with: {}", failed, reason)))
+    }
+
+    /// Sends every captured answer to a sink, such as a `JsonlAuditSink`
+    ///
+    /// # Arguments
+    /// * `sink` - The destination of the answers
+    ///
+    /// # Returns
+    /// The first error of the sink, if any
+    pub fn record_responses(&self, sink: &dyn ResponseSink) -> Result<(), Error> {
+        let item_id: &str = self.input_hash.as_deref().unwrap_or_default();
+        for prompt in &self.prompts {
+            for response in &prompt.responses {
+                sink.record(item_id, prompt.prompt_index, response)?;
+            }
+        }
+
+        Ok(())
+    }
+}
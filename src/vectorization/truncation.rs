@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+use crate::vectorization::ModelParameters;
+
+/// Put between the head and the tail of a text cut by `InputTruncation::HeadAndTail`
+const TRUNCATION_MARKER: &str = "\n[...]\n";
+
+/// Which part of a text longer than `max_input_tokens` is sent to the model
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputTruncation {
+    /// The beginning of the text
+    #[default]
+    Head,
+    /// The beginning and the end of the text, joined by a `[...]` marker
+    HeadAndTail,
+}
+
+/// Estimates the tokens of a text for a model
+///
+/// Uses `count_tokens` with the `tokens` feature, and one token per four
+/// characters without it.
+pub fn estimate_tokens(text: &str, model: &str) -> usize {
+    #[cfg(feature = "tokens")]
+    return crate::raw_data::tokens::count_tokens(text, model);
+    #[cfg(not(feature = "tokens"))]
+    {
+        let _ = model;
+        text.chars().count().div_ceil(4)
+    }
+}
+
+/// Cuts a text to `max_input_tokens`, if it is set and the text is longer.
+///
+/// Returns the text to send and the estimated tokens of the full text.
+pub(crate) fn truncate_input(text: &str, model_parameters: &ModelParameters) -> Option<(String, usize)> {
+    let max_tokens: usize = model_parameters.get_max_input_tokens()?;
+    let model: String = model_parameters.get_model();
+    let tokens: usize = estimate_tokens(text, &model);
+    if tokens <= max_tokens {
+        return None;
+    }
+
+    let boundaries: Vec<usize> = text
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(text.len()))
+        .collect();
+    let truncated: String = match model_parameters.get_input_truncation() {
+        InputTruncation::Head => text[..longest_prefix(text, &boundaries, max_tokens, &model)].trim_end().to_string(),
+        InputTruncation::HeadAndTail => {
+            let budget: usize = max_tokens.saturating_sub(estimate_tokens(TRUNCATION_MARKER, &model));
+            let head: &str = &text[..longest_prefix(text, &boundaries, budget.div_ceil(2), &model)];
+            let tail: &str = &text[longest_suffix(text, &boundaries, budget / 2, &model)..];
+            format!("{}{}{}", head.trim_end(), TRUNCATION_MARKER, tail.trim_start())
+        }
+    };
+
+    Some((truncated, tokens))
+}
+
+/// Finds the end of the longest prefix within `budget` tokens.
+fn longest_prefix(text: &str, boundaries: &[usize], budget: usize, model: &str) -> usize {
+    let (mut low, mut high): (usize, usize) = (0, boundaries.len() - 1);
+    while low < high {
+        let middle: usize = (low + high).div_ceil(2);
+        if estimate_tokens(&text[..boundaries[middle]], model) <= budget {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+
+    boundaries[low]
+}
+
+/// Finds the start of the longest suffix within `budget` tokens.
+fn longest_suffix(text: &str, boundaries: &[usize], budget: usize, model: &str) -> usize {
+    let (mut low, mut high): (usize, usize) = (0, boundaries.len() - 1);
+    while low < high {
+        let middle: usize = (low + high) / 2;
+        if estimate_tokens(&text[boundaries[middle]..], model) <= budget {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+
+    boundaries[low]
+}
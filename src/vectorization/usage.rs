@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+use std::ops::AddAssign;
+
+use anyhow::{Error, Result};
+use async_openai::types::CompletionUsage;
+use serde::{Deserialize, Serialize};
+
+/// Token counts reported by the API
+///
+/// # Fields
+/// * `prompt_tokens` - Tokens sent, including few-shot examples and images
+/// * `completion_tokens` - Tokens generated
+/// * `total_tokens` - The sum reported by the API
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+}
+
+impl From<&CompletionUsage> for TokenUsage {
+    fn from(usage: &CompletionUsage) -> Self {
+        Self {
+            prompt_tokens: usage.prompt_tokens as u64,
+            completion_tokens: usage.completion_tokens as u64,
+            total_tokens: usage.total_tokens as u64,
+        }
+    }
+}
+
+impl AddAssign for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+    }
+}
+
+/// The tokens one prompt consumed
+///
+/// # Fields
+/// * `prompt_index` - The position of the prompt in the call
+/// * `model` - The model the prompt was sent to
+/// * `requests` - The answered requests, including attempts rejected by validation
+/// * `usage` - The tokens of all those requests
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptUsage {
+    pub prompt_index: usize,
+    pub model: String,
+    pub requests: u64,
+    pub usage: TokenUsage,
+}
+
+/// The tokens a vectorization consumed, per prompt and in total
+///
+/// Returned by the `vectorize_*` functions for one item. Merge the reports of
+/// several items to account for a whole run.
+///
+/// # Fields
+/// * `prompts` - The usage of each prompt, in prompt order
+/// * `total` - The usage of every prompt together
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageReport {
+    pub prompts: Vec<PromptUsage>,
+    pub total: TokenUsage,
+}
+
+impl UsageReport {
+    /// Adds the usage of one prompt to the report
+    pub fn record(&mut self, prompt_usage: PromptUsage) {
+        self.total += prompt_usage.usage;
+        self.prompts.push(prompt_usage);
+    }
+
+    /// Adds another report, such as that of the next item, to this one
+    ///
+    /// Prompts are matched by index and model, so the report of a whole run
+    /// still lists one entry per prompt.
+    pub fn merge(&mut self, other: &UsageReport) {
+        for prompt_usage in &other.prompts {
+            let existing: Option<&mut PromptUsage> = self.prompts.iter_mut().find(|existing| {
+                existing.prompt_index == prompt_usage.prompt_index && existing.model == prompt_usage.model
+            });
+            match existing {
+                Some(existing) => {
+                    existing.requests += prompt_usage.requests;
+                    existing.usage += prompt_usage.usage;
+                }
+                None => self.prompts.push(prompt_usage.clone()),
+            }
+        }
+        self.total += other.total;
+    }
+
+    /// Returns the number of answered requests, including retried ones
+    pub fn get_requests(&self) -> u64 {
+        self.prompts.iter().map(|prompt_usage| prompt_usage.requests).sum()
+    }
+
+    /// Returns the usage of each model
+    pub fn get_usage_by_model(&self) -> BTreeMap<String, TokenUsage> {
+        let mut by_model: BTreeMap<String, TokenUsage> = BTreeMap::new();
+        for prompt_usage in &self.prompts {
+            *by_model.entry(prompt_usage.model.clone()).or_default() += prompt_usage.usage;
+        }
+
+        by_model
+    }
+
+    /// Estimates the cost of the tokens in the report
+    ///
+    /// # Arguments
+    /// * `prices` - The price of every model in the report
+    ///
+    /// # Returns
+    /// The estimated cost in the price table's currency, or an error naming a model without a price
+    pub fn estimate_cost(&self, prices: &PriceTable) -> Result<f64, Error> {
+        self.get_usage_by_model()
+            .iter()
+            .map(|(model, usage)| {
+                prices
+                    .estimate(model, usage)
+                    .ok_or_else(|| Error::msg(format!("No price for model '{}'", model)))
+            })
+            .sum()
+    }
+}
+
+/// The price of one model, per 1,000 tokens
+///
+/// # Fields
+/// * `input_per_1k` - The price of 1,000 prompt tokens
+/// * `output_per_1k` - The price of 1,000 completion tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input_per_1k: f64,
+    pub output_per_1k: f64,
+}
+
+/// Prices of the models used in a run, for cost estimates
+///
+/// No prices are built in since they change often; copy them from the
+/// provider's pricing page.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PriceTable {
+    prices: BTreeMap<String, ModelPrice>,
+}
+
+impl PriceTable {
+    /// Creates an empty price table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the price of a model
+    ///
+    /// # Arguments
+    /// * `model` - The model name, as sent in requests
+    /// * `input_per_1k` - The price of 1,000 prompt tokens
+    /// * `output_per_1k` - The price of 1,000 completion tokens
+    ///
+    /// # Returns
+    /// The table with the price applied
+    pub fn with_price(mut self, model: impl Into<String>, input_per_1k: f64, output_per_1k: f64) -> Self {
+        self.prices.insert(model.into(), ModelPrice { input_per_1k, output_per_1k });
+        self
+    }
+
+    /// Returns the price of a model, if known
+    pub fn get(&self, model: &str) -> Option<&ModelPrice> {
+        self.prices.get(model)
+    }
+
+    /// Estimates the cost of some tokens of a model
+    ///
+    /// # Returns
+    /// The estimated cost, or `None` if the model has no price
+    pub fn estimate(&self, model: &str, usage: &TokenUsage) -> Option<f64> {
+        self.get(model).map(|price| {
+            usage.prompt_tokens as f64 / 1000.0 * price.input_per_1k
+                + usage.completion_tokens as f64 / 1000.0 * price.output_per_1k
+        })
+    }
+}
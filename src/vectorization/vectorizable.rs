@@ -0,0 +1,262 @@
+#[cfg(feature = "image")]
+use std::borrow::Cow;
+use std::future::Future;
+
+use anyhow::{Error, Result};
+use async_openai::types::ChatCompletionRequestMessage;
+#[cfg(feature = "image")]
+use image::DynamicImage;
+
+use crate::prompt::Prompt;
+use crate::raw_data::{ImageDetail, RemoteImage};
+#[cfg(feature = "image")]
+use crate::raw_data::utilities::image_sha256;
+use crate::raw_data::utilities::text_sha256;
+#[cfg(feature = "image")]
+use crate::raw_data::{apply_exif_orientation, EncodedImage, ImageFile};
+use crate::vector::{Scalar, Vector, VectorOperations, METADATA_TRUNCATED_FROM};
+use crate::vectorization::capture::CaptureMode;
+use crate::vectorization::{build_image_messages, build_text_messages, request_text, ImageUrls, ModelParameters};
+#[cfg(feature = "image")]
+use crate::vectorization::{encode_image_urls, upright_image};
+
+/// Data that `vectorize_concurrently` can send to an LLM
+///
+/// Implementations only turn a vector's data into the text and images every
+/// request carries; sending, retrying, validating and combining the answers
+/// are shared by all of them.
+pub trait Vectorizable: Sized {
+    /// Builds what every request for a vector carries
+    ///
+    /// # Arguments
+    /// * `vector` - The vector holding the data
+    /// * `model_parameters` - The parameters of the call, e.g. the image size limit
+    ///
+    /// # Returns
+    /// The request input, or an error if the data cannot be sent
+    fn request_input<S: Scalar>(vector: &Vector<Self, S>, model_parameters: &ModelParameters) -> Result<RequestInput, Error>;
+
+    /// Builds the request input when vectorizing, where blocking work can be moved off the async workers
+    ///
+    /// Defaults to `request_input`. Data that must be read and decoded first,
+    /// such as an `ImageFile`, does that on a blocking thread.
+    ///
+    /// # Arguments
+    /// * `vector` - The vector holding the data
+    /// * `model_parameters` - The parameters of the call, e.g. the image size limit
+    ///
+    /// # Returns
+    /// The request input, or an error if the data cannot be sent
+    fn prepare_input<S: Scalar>(vector: &Vector<Self, S>, model_parameters: &ModelParameters) -> impl Future<Output = Result<RequestInput, Error>> + Send {
+        std::future::ready(Self::request_input(vector, model_parameters))
+    }
+
+    /// Returns the vector as a text vector, for data that is split into chunks when long
+    fn as_text_vector<S: Scalar>(_vector: &mut Vector<Self, S>) -> Option<&mut Vector<String, S>> {
+        None
+    }
+}
+
+/// The text and images sent with every prompt for one vector
+#[derive(Debug, Clone)]
+pub struct RequestInput {
+    pub(crate) image_urls: ImageUrls,
+    pub(crate) text: Option<String>,
+    pub(crate) input_hash: Option<String>,
+    pub(crate) original_tokens: Option<usize>,
+}
+
+impl RequestInput {
+    /// Sends a text
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            image_urls: ImageUrls::unresized(Vec::new()),
+            text: Some(text.into()),
+            input_hash: None,
+            original_tokens: None,
+        }
+    }
+
+    /// Sends images, given as URLs or data URLs, with an optional text
+    pub fn images(urls: Vec<String>, text: Option<String>) -> Self {
+        Self {
+            image_urls: ImageUrls::unresized(urls),
+            text,
+            input_hash: None,
+            original_tokens: None,
+        }
+    }
+
+    /// Records a hash of the input in the report when answers are captured
+    pub fn with_input_hash(mut self, input_hash: impl Into<String>) -> Self {
+        self.input_hash = Some(input_hash.into());
+        self
+    }
+
+    /// Builds the messages that ask for one prompt's scores of the input, sending images at `image_detail`.
+    pub(crate) fn build_messages(&self, prompt: &Prompt, image_detail: ImageDetail) -> Result<Vec<ChatCompletionRequestMessage>, Error> {
+        if self.image_urls.urls.is_empty() {
+            build_text_messages(self.text.as_deref().unwrap_or_default(), prompt)
+        } else {
+            build_image_messages(&self.image_urls.urls, self.text.as_deref(), prompt, image_detail)
+        }
+    }
+
+    /// Returns the SHA-256 the cache keys the input by.
+    pub(crate) fn data_hash(&self) -> String {
+        match (self.image_urls.urls.is_empty(), &self.text) {
+            (true, text) => text_sha256(text.as_deref().unwrap_or_default()),
+            (false, text) => text_sha256(&format!("{}\n{}", self.image_urls.urls.join("\n"), text.as_deref().unwrap_or_default())),
+        }
+    }
+
+    /// Records how the input was shrunk before sending, removing stale notes.
+    pub(crate) fn record_adjustments<T, S: Scalar>(&self, vector: &mut Vector<T, S>) {
+        self.image_urls.record_sizes(vector);
+        match self.original_tokens {
+            Some(tokens) => vector.set_metadata(METADATA_TRUNCATED_FROM, tokens.to_string()),
+            None => {
+                vector.remove_metadata(METADATA_TRUNCATED_FROM);
+            }
+        }
+    }
+}
+
+/// Returns whether answers are captured, so the input hash is reported.
+fn captures(model_parameters: &ModelParameters) -> bool {
+    model_parameters.get_capture_mode() != CaptureMode::Off
+}
+
+impl Vectorizable for String {
+    fn request_input<S: Scalar>(vector: &Vector<Self, S>, model_parameters: &ModelParameters) -> Result<RequestInput, Error> {
+        let (text, original_tokens): (String, Option<usize>) = request_text(vector, model_parameters);
+        let mut input: RequestInput = RequestInput::text(text);
+        input.original_tokens = original_tokens;
+        if captures(model_parameters) {
+            input = input.with_input_hash(text_sha256(vector.get_data()));
+        }
+
+        Ok(input)
+    }
+
+    fn as_text_vector<S: Scalar>(vector: &mut Vector<Self, S>) -> Option<&mut Vector<String, S>> {
+        Some(vector)
+    }
+}
+
+#[cfg(feature = "image")]
+impl Vectorizable for DynamicImage {
+    fn request_input<S: Scalar>(vector: &Vector<Self, S>, model_parameters: &ModelParameters) -> Result<RequestInput, Error> {
+        let image: Cow<DynamicImage> = upright_image(vector, model_parameters);
+        let mut input: RequestInput = RequestInput {
+            image_urls: encode_image_urls(std::slice::from_ref(image.as_ref()), model_parameters)?,
+            text: None,
+            input_hash: None,
+            original_tokens: None,
+        };
+        if captures(model_parameters) {
+            input = input.with_input_hash(image_sha256(vector.get_data()));
+        }
+
+        Ok(input)
+    }
+}
+
+#[cfg(feature = "image")]
+impl Vectorizable for EncodedImage {
+    fn request_input<S: Scalar>(vector: &Vector<Self, S>, model_parameters: &ModelParameters) -> Result<RequestInput, Error> {
+        let mut input: RequestInput = RequestInput::images(vec![vector.get_data().to_data_url()], None);
+        if captures(model_parameters) {
+            input = input.with_input_hash(text_sha256(vector.get_data().get_data()));
+        }
+
+        Ok(input)
+    }
+}
+
+#[cfg(feature = "image")]
+impl Vectorizable for ImageFile {
+    fn request_input<S: Scalar>(vector: &Vector<Self, S>, model_parameters: &ModelParameters) -> Result<RequestInput, Error> {
+        image_file_input(vector.get_data(), model_parameters)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn prepare_input<S: Scalar>(vector: &Vector<Self, S>, model_parameters: &ModelParameters) -> impl Future<Output = Result<RequestInput, Error>> + Send {
+        let image_file: ImageFile = vector.get_data().clone();
+        let model_parameters: ModelParameters = model_parameters.clone();
+
+        async move {
+            tokio::task::spawn_blocking(move || image_file_input(&image_file, &model_parameters))
+                .await
+                .map_err(|e| Error::msg(format!("Failed to decode image: {}", e)))?
+        }
+    }
+}
+
+/// Reads and decodes an image file, then encodes it for the requests; the decoded image is dropped afterwards.
+#[cfg(feature = "image")]
+fn image_file_input(image_file: &ImageFile, model_parameters: &ModelParameters) -> Result<RequestInput, Error> {
+    let (mut image, orientation): (DynamicImage, u8) = image_file.load()?;
+    let input_hash: Option<String> = captures(model_parameters).then(|| image_sha256(&image));
+    if model_parameters.get_apply_exif_orientation() {
+        apply_exif_orientation(&mut image, orientation);
+    }
+    let input: RequestInput = RequestInput {
+        image_urls: encode_image_urls(std::slice::from_ref(&image), model_parameters)?,
+        text: None,
+        input_hash,
+        original_tokens: None,
+    };
+
+    Ok(input)
+}
+
+impl Vectorizable for RemoteImage {
+    fn request_input<S: Scalar>(vector: &Vector<Self, S>, model_parameters: &ModelParameters) -> Result<RequestInput, Error> {
+        let mut input: RequestInput = RequestInput::images(vec![vector.get_data().get_url().to_string()], None);
+        if captures(model_parameters) {
+            input = input.with_input_hash(text_sha256(vector.get_data().get_url()));
+        }
+
+        Ok(input)
+    }
+}
+
+#[cfg(feature = "image")]
+impl Vectorizable for Vec<DynamicImage> {
+    fn request_input<S: Scalar>(vector: &Vector<Self, S>, model_parameters: &ModelParameters) -> Result<RequestInput, Error> {
+        if vector.get_data().is_empty() {
+            return Err(Error::msg("Cannot vectorize an item without images"));
+        }
+        let mut input: RequestInput = RequestInput {
+            image_urls: encode_image_urls(vector.get_data(), model_parameters)?,
+            text: None,
+            input_hash: None,
+            original_tokens: None,
+        };
+        if captures(model_parameters) {
+            let hashes: Vec<String> = vector.get_data().iter().map(image_sha256).collect();
+            input = input.with_input_hash(text_sha256(&hashes.join(",")));
+        }
+
+        Ok(input)
+    }
+}
+
+#[cfg(feature = "image")]
+impl Vectorizable for (DynamicImage, String) {
+    fn request_input<S: Scalar>(vector: &Vector<Self, S>, model_parameters: &ModelParameters) -> Result<RequestInput, Error> {
+        let (image, text) = vector.get_data();
+        let mut input: RequestInput = RequestInput {
+            image_urls: encode_image_urls(std::slice::from_ref(image), model_parameters)?,
+            text: Some(text.clone()),
+            input_hash: None,
+            original_tokens: None,
+        };
+        if captures(model_parameters) {
+            input = input.with_input_hash(text_sha256(&format!("{},{}", image_sha256(image), text_sha256(text))));
+        }
+
+        Ok(input)
+    }
+}
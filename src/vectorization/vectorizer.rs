@@ -0,0 +1,202 @@
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use futures::stream::{self, StreamExt};
+#[cfg(feature = "image")]
+use image::DynamicImage;
+
+use crate::error::DimError;
+use crate::llm::ChatBackend;
+use crate::prompt::{Prompt, PromptSet};
+use crate::vector::{Scalar, Vector};
+use crate::vectorization::report::VectorizationReport;
+use crate::vectorization::vectorizable::Vectorizable;
+use crate::vectorization::{vectorize_concurrently, ModelParameters};
+
+/// How many items `Vectorizer::vectorize_batch` processes at once by default
+const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// A client, prompts and parameters bundled for vectorizing many items
+///
+/// The client and prompts are shared behind `Arc`s, so cloning a vectorizer
+/// or handing it to several tasks is cheap. Build one with `Vectorizer::builder`.
+#[derive(Debug)]
+pub struct Vectorizer<B> {
+    client: Arc<B>,
+    prompts: Arc<PromptSet>,
+    model_parameters: ModelParameters,
+    max_concurrency: usize,
+}
+
+impl<B> Clone for Vectorizer<B> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            prompts: self.prompts.clone(),
+            model_parameters: self.model_parameters.clone(),
+            max_concurrency: self.max_concurrency,
+        }
+    }
+}
+
+impl<B: ChatBackend> Vectorizer<B> {
+    /// Starts building a vectorizer
+    pub fn builder() -> VectorizerBuilder<B> {
+        VectorizerBuilder {
+            client: None,
+            prompts: None,
+            model_parameters: None,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+        }
+    }
+
+    /// Returns the backend requests are sent to
+    pub fn get_client(&self) -> &Arc<B> {
+        &self.client
+    }
+
+    /// Returns the prompts every item is vectorized with
+    pub fn get_prompts(&self) -> &PromptSet {
+        &self.prompts
+    }
+
+    /// Returns the parameters of every request
+    pub fn get_model_parameters(&self) -> &ModelParameters {
+        &self.model_parameters
+    }
+
+    /// Returns how many items a batch processes at once
+    pub fn get_max_concurrency(&self) -> usize {
+        self.max_concurrency
+    }
+
+    /// Vectorizes any supported data with every prompt, as `vectorize_concurrently` does
+    ///
+    /// # Arguments
+    /// * `vector` - The Vector containing the data
+    ///
+    /// # Returns
+    /// How each prompt went and the tokens consumed, or an error
+    pub async fn vectorize<T: Vectorizable, S: Scalar>(&self, vector: &mut Vector<T, S>) -> Result<VectorizationReport, DimError> {
+        vectorize_concurrently(self.prompt_list(), vector, self.client.clone(), self.model_parameters.clone()).await
+    }
+
+    /// Vectorizes a text with every prompt, as `vectorize_string_concurrently` does
+    ///
+    /// # Arguments
+    /// * `vector` - The Vector containing the text
+    ///
+    /// # Returns
+    /// How each prompt went and the tokens consumed, or an error
+    pub async fn vectorize_text(&self, vector: &mut Vector<String>) -> Result<VectorizationReport, DimError> {
+        self.vectorize(vector).await
+    }
+
+    /// Vectorizes an image with every prompt, as `vectorize_image_concurrently` does
+    ///
+    /// # Arguments
+    /// * `vector` - The Vector containing the image
+    ///
+    /// # Returns
+    /// How each prompt went and the tokens consumed, or an error
+    #[cfg(feature = "image")]
+    pub async fn vectorize_image(&self, vector: &mut Vector<DynamicImage>) -> Result<VectorizationReport, DimError> {
+        self.vectorize(vector).await
+    }
+
+    /// Vectorizes many texts, images or other supported data, at most `max_concurrency` at a time
+    ///
+    /// A failed item does not stop the others. A progress observer is told the
+    /// position of each item in `vectors`.
+    ///
+    /// # Arguments
+    /// * `vectors` - The Vectors containing the data
+    ///
+    /// # Returns
+    /// The report or error of every item, in input order
+    pub async fn vectorize_batch<T: Vectorizable, S: Scalar>(&self, vectors: &mut [Vector<T, S>]) -> Vec<Result<VectorizationReport, DimError>> {
+        stream::iter(vectors.iter_mut().enumerate().map(|(item_index, vector)| self.vectorize_item(vector, item_index)))
+            .buffered(self.max_concurrency)
+            .collect()
+            .await
+    }
+
+    /// Vectorizes one item of a batch, telling a progress observer its position.
+    pub(crate) async fn vectorize_item<T: Vectorizable, S: Scalar>(&self, vector: &mut Vector<T, S>, item_index: usize) -> Result<VectorizationReport, DimError> {
+        vectorize_concurrently(self.prompt_list(), vector, self.client.clone(), self.model_parameters.for_item(item_index)).await
+    }
+
+    /// Returns the prompts in the form the vectorization functions take.
+    fn prompt_list(&self) -> Vec<Prompt> {
+        self.prompts.get_prompts().to_vec()
+    }
+}
+
+/// Builds a `Vectorizer` with chained setters and validates it
+#[derive(Debug)]
+pub struct VectorizerBuilder<B> {
+    client: Option<Arc<B>>,
+    prompts: Option<PromptSet>,
+    model_parameters: Option<ModelParameters>,
+    max_concurrency: usize,
+}
+
+impl<B: ChatBackend> VectorizerBuilder<B> {
+    /// Sends requests through `client`, e.g. an async-openai `Client` or a `FailoverClient`
+    pub fn client(mut self, client: B) -> Self {
+        self.client = Some(Arc::new(client));
+        self
+    }
+
+    /// Sends requests through a client shared with other code
+    pub fn shared_client(mut self, client: Arc<B>) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Vectorizes every item with `prompts`, whose order defines the dimensions
+    pub fn prompts(mut self, prompts: PromptSet) -> Self {
+        self.prompts = Some(prompts);
+        self
+    }
+
+    /// Uses `model_parameters` for every request
+    pub fn model_parameters(mut self, model_parameters: ModelParameters) -> Self {
+        self.model_parameters = Some(model_parameters);
+        self
+    }
+
+    /// Processes at most `max_concurrency` items of a batch at once, 4 by default
+    ///
+    /// Each item still sends its prompts concurrently.
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency;
+        self
+    }
+
+    /// Validates the settings and builds the vectorizer
+    ///
+    /// # Returns
+    /// The vectorizer, or an error naming a missing client, prompts or
+    /// parameters, an empty prompt set or a zero concurrency
+    pub fn build(self) -> Result<Vectorizer<B>, Error> {
+        let client: Arc<B> = self.client.ok_or_else(|| Error::msg("Invalid vectorizer: no client was set"))?;
+        let prompts: PromptSet = self.prompts.ok_or_else(|| Error::msg("Invalid vectorizer: no prompts were set"))?;
+        let model_parameters: ModelParameters = self
+            .model_parameters
+            .ok_or_else(|| Error::msg("Invalid vectorizer: no model parameters were set"))?;
+        if prompts.is_empty() {
+            return Err(Error::msg("Invalid vectorizer: the prompt set is empty"));
+        }
+        if self.max_concurrency == 0 {
+            return Err(Error::msg("Invalid vectorizer: max_concurrency must be at least 1"));
+        }
+
+        Ok(Vectorizer {
+            client,
+            prompts: Arc::new(prompts),
+            model_parameters,
+            max_concurrency: self.max_concurrency,
+        })
+    }
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use futures::future::join_all;
+use image::DynamicImage;
+
+use crate::error::DimError;
+use crate::llm::ChatBackend;
+use crate::prompt::{combine_fingerprints, IntoPrompts, Prompt};
+use crate::raw_data::{sample_frames_evenly, VideoData, VideoFrame};
+use crate::vector::{Vector, VectorOperations, METADATA_DIMENSION_MODELS};
+use crate::vectorization::report::{FrameReport, VectorizationReport};
+use crate::vectorization::{observe_item, record_failed_prompts, vectorize_item, ModelParameters};
+
+/// Concurrently vectorizes a video from its decoded frames.
+///
+/// Evenly spaced frames, as many as `ModelParametersBuilder::video_frames`,
+/// are vectorized with the image path and combined per dimension with the
+/// frame aggregation. All frames are sent at once, so a call keeps
+/// `frames * prompts * samples_per_prompt` requests in flight. The vector of
+/// every frame is kept in the report.
+///
+/// # Arguments
+/// * `prompts` - The prompts, e.g. a `Vec<String>`, `&[Prompt]` or `&PromptSet`, written for images
+/// * `vector` - A mutable reference to the Vector struct containing the video
+/// * `frames` - The decoded frames of the video, in playback order
+/// * `client` - The OpenAI API client, or any other `ChatBackend`
+/// * `model_parameters` - The parameters of every frame's vectorization
+///
+/// # Returns
+/// * `Result<VectorizationReport, DimError>` - The combined report with one entry per frame on success,
+///   DimError when there are no frames or a frame cannot be vectorized
+pub async fn vectorize_video_concurrently<B, P>(
+    prompts: P,
+    vector: &mut Vector<VideoData>,
+    frames: Vec<VideoFrame>,
+    client: B,
+    model_parameters: ModelParameters,
+) -> Result<VectorizationReport, DimError>
+where
+    B: ChatBackend,
+    P: IntoPrompts,
+{
+    observe_item(
+        model_parameters.get_progress_observer(),
+        model_parameters.get_item_index(),
+        vectorize_video(prompts, vector, frames, client, model_parameters),
+    )
+    .await
+}
+
+/// Vectorizes evenly spaced frames of a video and combines them per dimension.
+async fn vectorize_video<B, P>(
+    prompts: P,
+    vector: &mut Vector<VideoData>,
+    frames: Vec<VideoFrame>,
+    client: B,
+    model_parameters: ModelParameters,
+) -> Result<VectorizationReport, DimError>
+where
+    B: ChatBackend,
+    P: IntoPrompts,
+{
+    let frames: Vec<VideoFrame> = sample_frames_evenly(frames, model_parameters.get_video_frames());
+    if frames.is_empty() {
+        return Err(DimError::msg("Cannot vectorize a video without frames"));
+    }
+
+    let prompts: Vec<Prompt> = prompts.into_prompts();
+    let shared_client: Arc<B> = Arc::new(client);
+    let tasks = frames.into_iter().map(|frame| {
+        let prompts: Vec<Prompt> = prompts.clone();
+        let client: Arc<B> = shared_client.clone();
+        let model_parameters: ModelParameters = model_parameters.clone();
+        async move {
+            let mut frame_vector: Vector<DynamicImage> = Vector::from_image(frame.image);
+            let report: Result<VectorizationReport, DimError> =
+                vectorize_item(prompts, &mut frame_vector, client, model_parameters).await;
+            (frame.timestamp, frame_vector, report)
+        }
+    });
+    let results = join_all(tasks).await;
+
+    // Frames labeled differently cannot be combined by position
+    let mut frame_vectors: Vec<(f32, Vector<DynamicImage>, VectorizationReport)> = Vec::new();
+    for (timestamp, frame_vector, report) in results {
+        frame_vectors.push((timestamp, frame_vector, report?));
+    }
+    let reference: &Vector<DynamicImage> = frame_vectors
+        .iter()
+        .min_by_key(|(_, _, report)| report.failed_prompts().len())
+        .map(|(_, frame_vector, _)| frame_vector)
+        .expect("at least one frame");
+    let labels: Vec<String> = reference.get_labels().to_vec();
+    let fingerprint: Option<String> = reference.get_fingerprint().map(str::to_string);
+    let metadata = reference.get_metadata_map().clone();
+
+    let mut combined: VectorizationReport = VectorizationReport::default();
+    for (timestamp, frame_vector, report) in frame_vectors {
+        let included: bool = frame_vector.get_labels() == labels.as_slice();
+        if !included {
+            log::warn!("Leaving out the frame at {}s, its dimensions are labeled differently", timestamp);
+        }
+        combined.merge_prompts(&report);
+        combined.frames.push(FrameReport {
+            timestamp,
+            values: frame_vector.get_vector(),
+            labels: frame_vector.get_labels().to_vec(),
+            included,
+            report,
+        });
+    }
+
+    let included: Vec<&FrameReport> = combined.frames.iter().filter(|frame| frame.included).collect();
+    let values: Vec<f32> = (0..labels.len())
+        .map(|dimension| {
+            let scores: Vec<f32> = included.iter().map(|frame| frame.values[dimension]).collect();
+            // A prompt that failed for any frame leaves its dimensions NaN
+            match scores.iter().any(|score| score.is_nan()) {
+                true => f32::NAN,
+                false => model_parameters.get_frame_aggregation().aggregate(&scores),
+            }
+        })
+        .collect();
+
+    vector.overwrite_vector_with_labels(values, labels)?;
+    if let Some(fingerprint) = fingerprint {
+        let aggregation: String = format!("{:?}", model_parameters.get_frame_aggregation());
+        vector.set_fingerprint(combine_fingerprints("video", &[&fingerprint, &aggregation]));
+    }
+    vector.remove_metadata(METADATA_DIMENSION_MODELS);
+    for (key, value) in metadata {
+        vector.set_metadata(key, value);
+    }
+    record_failed_prompts(vector, &combined);
+
+    Ok(combined)
+}
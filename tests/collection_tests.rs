@@ -0,0 +1,273 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use dim_rs::collection::filter::{Filter, FilteredSearch};
+    use dim_rs::collection::search::{search_text, SearchHit};
+    use dim_rs::collection::similarity::{similar_pairs, similarity_matrix, SimilarityMatrix};
+    use dim_rs::collection::{BinaryIndex, VectorCollection};
+    use dim_rs::llm::testing::MockBackend;
+    use dim_rs::prelude::*;
+    use dim_rs::vector::explain::SimilarityExplanation;
+    use dim_rs::vector::io::save_jsonl;
+    use dim_rs::vector::metrics::Metric;
+    use dim_rs::vector::METADATA_ID;
+    use dim_rs::vectorization::manifest::RunManifest;
+    use dim_rs::vectorization::ModelParameters;
+
+    fn item(id: &str, values: Vec<f32>, fingerprint: &str) -> Vector<String> {
+        let mut vector: Vector<String> = Vector::from_text(id.to_string());
+        vector.overwrite_vector(values);
+        vector.set_fingerprint(fingerprint.to_string());
+        vector.set_metadata(METADATA_ID, id);
+        vector
+    }
+
+    fn collection() -> VectorCollection<String> {
+        VectorCollection::from_vectors(vec![
+            item("east", vec![1.0, 0.0], "fp"),
+            item("north", vec![0.0, 1.0], "fp"),
+            item("north-east", vec![2.0, 2.0], "fp"),
+            item("west", vec![-1.0, 0.0], "fp"),
+        ]).unwrap()
+    }
+
+    fn ids(collection: &VectorCollection<String>, results: &[(usize, f32)]) -> Vec<String> {
+        results
+            .iter()
+            .map(|(index, _)| collection.get(*index).unwrap().get_id().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_search_ranking() {
+        let collection: VectorCollection<String> = collection();
+        let query: [f32; 2] = [1.0, 0.2];
+
+        // Cosine ignores magnitude
+        let results = collection.search(&query, 2, Metric::Cosine).unwrap();
+        assert_eq!(ids(&collection, &results), vec!["east", "north-east"]);
+        assert!(results[0].1 > results[1].1);
+
+        // Dot rewards magnitude
+        let results = collection.search(&query, 1, Metric::Dot).unwrap();
+        assert_eq!(ids(&collection, &results), vec!["north-east"]);
+        assert!((results[0].1 - 2.4).abs() < 1e-6);
+
+        // Euclidean ranks smallest distance first
+        let results = collection.search(&query, 4, Metric::Euclidean).unwrap();
+        assert_eq!(ids(&collection, &results), vec!["east", "north", "west", "north-east"]);
+        assert!(results.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+    }
+
+    #[test]
+    fn test_search_k_larger_than_collection() {
+        let collection: VectorCollection<String> = collection();
+        assert_eq!(collection.search(&[0.0, 1.0], 100, Metric::Cosine).unwrap().len(), 4);
+        assert!(VectorCollection::<String>::new().search(&[0.0, 1.0], 3, Metric::Cosine).unwrap().is_empty());
+
+        // Queries must match the collection's dimensionality
+        assert!(collection.search(&[1.0], 1, Metric::Cosine).is_err());
+    }
+
+    #[test]
+    fn test_push_enforces_consistency() {
+        let mut collection: VectorCollection<String> = collection();
+        assert!(collection.push(item("up", vec![0.0, 0.0, 1.0], "fp")).is_err());
+        assert!(collection.push(item("other model", vec![1.0, 1.0], "other")).is_err());
+        assert!(collection.search_vector(&item("query", vec![1.0, 1.0], "other"), 1, Metric::Cosine).is_err());
+        assert_eq!(collection.len(), 4);
+
+        // Mixing can be explicitly allowed
+        let mut mixed: VectorCollection<String> = collection.with_mixed_fingerprints(true);
+        mixed.push(item("other model", vec![1.0, 1.0], "other")).unwrap();
+        assert_eq!(mixed.len(), 5);
+    }
+
+    fn naive(vectors: &[Vector<String>], metric: Metric) -> Vec<Vec<f32>> {
+        vectors
+            .iter()
+            .map(|a| vectors.iter().map(|b| metric.score(a.as_slice(), b.as_slice()).unwrap()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_similarity_matrix_matches_naive() {
+        let collection: VectorCollection<String> = collection();
+        for metric in [Metric::Cosine, Metric::Dot, Metric::Euclidean] {
+            let matrix: SimilarityMatrix = similarity_matrix(collection.get_vectors(), metric).unwrap();
+            assert_eq!(matrix.size(), 4);
+            assert_eq!(matrix.to_rows(), naive(collection.get_vectors(), metric));
+            assert_eq!(matrix.get(1, 2), matrix.get(2, 1));
+            assert_eq!(collection.similarity_matrix(metric).unwrap(), matrix);
+        }
+
+        // Inconsistent inputs are rejected
+        let mixed: Vec<Vector<String>> = vec![item("a", vec![1.0, 0.0], "fp"), item("b", vec![1.0], "fp")];
+        assert!(similarity_matrix(&mixed, Metric::Cosine).is_err());
+        let mixed: Vec<Vector<String>> = vec![item("a", vec![1.0, 0.0], "fp"), item("b", vec![1.0, 0.0], "other")];
+        assert!(similarity_matrix(&mixed, Metric::Cosine).is_err());
+    }
+
+    #[test]
+    fn test_similar_pairs() {
+        let collection: VectorCollection<String> = collection();
+
+        // east~north-east and north~north-east are 45° apart
+        let pairs = similar_pairs(collection.get_vectors(), Metric::Cosine, 0.7).unwrap();
+        let indices: Vec<(usize, usize)> = pairs.iter().map(|(i, j, _)| (*i, *j)).collect();
+        assert_eq!(indices, vec![(0, 2), (1, 2)]);
+        assert!(pairs.iter().all(|(_, _, score)| *score >= 0.7));
+
+        // Euclidean keeps pairs closer than the threshold
+        let pairs = collection.similar_pairs(Metric::Euclidean, 1.5).unwrap();
+        let indices: Vec<(usize, usize)> = pairs.iter().map(|(i, j, _)| (*i, *j)).collect();
+        assert_eq!(indices, vec![(0, 1), (1, 3)]);
+    }
+
+    #[test]
+    fn test_filtered_search() {
+        let mut collection: VectorCollection<String> = VectorCollection::new();
+        for (id, values, category) in [
+            ("parka", vec![1.0, 0.1], "outerwear"),
+            ("sandal", vec![1.0, 0.0], "footwear"),
+            ("raincoat", vec![0.9, 0.5], "outerwear"),
+            ("scarf", vec![0.0, 1.0], "accessories"),
+            ("trench", vec![0.2, 1.0], "outerwear"),
+        ] {
+            let mut vector: Vector<String> = item(id, values, "fp");
+            vector.set_metadata("category", category);
+            collection.push(vector).unwrap();
+        }
+
+        // Only outerwear is ranked; the closer sandal is skipped
+        let filter: Filter = Filter::new().data_type(DataType::Text).metadata_eq("category", "outerwear");
+        let search: FilteredSearch = collection.search_filtered(&[1.0, 0.0], 2, Metric::Cosine, &filter).unwrap();
+        assert_eq!(search.matched, 3);
+        assert_eq!(ids(&collection, &search.results), vec!["parka", "raincoat"]);
+
+        // No candidates for another data type
+        let search: FilteredSearch = collection
+            .search_filtered(&[1.0, 0.0], 2, Metric::Cosine, &Filter::new().data_type(DataType::Image))
+            .unwrap();
+        assert_eq!(search.matched, 0);
+        assert!(search.results.is_empty());
+
+        // Closures work as predicates
+        let search: FilteredSearch = collection
+            .search_where(&[1.0, 0.0], 5, Metric::Cosine, |vector| vector.get_id() != Some("parka"))
+            .unwrap();
+        assert_eq!(search.matched, 4);
+        assert_eq!(ids(&collection, &search.results)[0], "sandal");
+    }
+
+    #[test]
+    fn test_two_stage_search() {
+        let collection: VectorCollection<String> = collection();
+        let index: BinaryIndex = collection.binary_index(&[0.5, 0.5]).unwrap();
+
+        // The Hamming stage keeps east (10) and north-east (11) for a query near east
+        let results = collection.search_two_stage(&[1.0, 0.2], &index, 2, 2).unwrap();
+        assert_eq!(ids(&collection, &results), vec!["east", "north-east"]);
+
+        // A full shortlist reproduces the exact cosine ranking
+        let exact = collection.search(&[1.0, 0.2], 4, Metric::Cosine).unwrap();
+        assert_eq!(collection.search_two_stage(&[1.0, 0.2], &index, 4, 4).unwrap(), exact);
+
+        // A stale index is rejected
+        let mut grown: VectorCollection<String> = collection.clone();
+        grown.push(item("south", vec![0.0, -1.0], "fp")).unwrap();
+        assert!(grown.search_two_stage(&[1.0, 0.2], &index, 2, 2).is_err());
+    }
+
+    #[test]
+    fn test_load_with_manifest() {
+        let prompts: PromptSet = PromptSet::from_attributes(&["warmth", "size"], (1.0, 9.0)).unwrap();
+        let parameters: ModelParameters = ModelParameters::builder().model("mock-model").build().unwrap();
+        let manifest: RunManifest = RunManifest::new(&prompts, &parameters);
+        let fingerprint: String = manifest.fingerprint.clone();
+
+        let directory: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let path: std::path::PathBuf = directory.path().join("vectors.jsonl");
+        save_jsonl(&path, &[item("east", vec![1.0, 0.0], &fingerprint), item("north", vec![0.0, 1.0], &fingerprint)]).unwrap();
+        assert!(VectorCollection::<String>::load_with_manifest(&path).is_err());
+        manifest.save_sidecar(&path).unwrap();
+
+        let collection: VectorCollection<String> = VectorCollection::load_with_manifest(&path).unwrap();
+        assert_eq!(collection.get_fingerprint(), Some(fingerprint.as_str()));
+        assert_eq!(collection.get_manifest(), Some(&manifest));
+        collection.verify_run(&prompts, &parameters).unwrap();
+
+        // Queries made with other prompts are rejected
+        let query: Vector<String> = item("query", vec![1.0, 0.1], "other");
+        let error: String = collection.search_vector(&query, 1, Metric::Cosine).unwrap_err().to_string();
+        assert!(error.contains("Fingerprint mismatch"));
+        let changed: PromptSet = PromptSet::from_attributes(&["warmth", "height"], (1.0, 9.0)).unwrap();
+        assert!(collection.verify_run(&changed, &parameters).is_err());
+
+        // unless they are deliberately compared, with or without a warning
+        let warning: VectorCollection<String> = collection.clone().with_query_mismatch_warnings(true);
+        assert_eq!(ids(&warning, &warning.search_vector(&query, 1, Metric::Cosine).unwrap()), vec!["east"]);
+        warning.verify_run(&changed, &parameters).unwrap();
+        let forced: VectorCollection<String> = collection.with_mixed_fingerprints(true);
+        assert_eq!(ids(&forced, &forced.search_vector(&query, 1, Metric::Cosine).unwrap()), vec!["east"]);
+
+        // Files whose vectors disagree with their manifest are refused
+        save_jsonl(&path, &[item("east", vec![1.0, 0.0], "stale")]).unwrap();
+        assert!(VectorCollection::<String>::load_with_manifest(&path).unwrap_err().to_string().contains("stale"));
+    }
+
+    #[tokio::test]
+    async fn test_search_text() {
+        let backend: Arc<MockBackend> = Arc::new(
+            MockBackend::new()
+                .with_response("calm", "{\"score\": 2}")
+                .with_response("furious", "{\"score\": 9}")
+                .with_response("annoyed", "{\"score\": 7}")
+                .with_fallback("{\"score\": 4}"),
+        );
+        let vectorizer: Vectorizer<MockBackend> = Vectorizer::builder()
+            .shared_client(backend.clone())
+            .prompts(PromptSet::new(vec!["Rate the anger. {'score': 5}"]))
+            .model_parameters(ModelParameters::builder().model("mock-model").build().unwrap())
+            .build()
+            .unwrap();
+
+        let mut vectors: Vec<Vector<String>> = ["A calm note", "A furious rant", "A neutral memo"]
+            .iter()
+            .map(|text| Vector::from_text(text.to_string()))
+            .collect();
+        assert!(vectorizer.vectorize_batch(&mut vectors).await.iter().all(Result::is_ok));
+        let collection: VectorCollection<String> = VectorCollection::from_vectors(vectors).unwrap();
+
+        // The query is vectorized with the same prompts, then ranked by distance
+        let hits: Vec<SearchHit<String>> = search_text("An annoyed reply", &collection, &vectorizer, 2, Metric::Euclidean).await.unwrap();
+        assert_eq!(hits.iter().map(|hit| hit.data.as_str()).collect::<Vec<&str>>(), vec!["A furious rant", "A neutral memo"]);
+        assert_eq!(hits[0].index, 1);
+        assert!((hits[0].score - 2.0).abs() < 1e-6);
+        assert!(hits.iter().all(|hit| hit.explanation.is_none()));
+        assert_eq!(backend.get_requests().len(), 4);
+
+        // Explanations are attached to the top hits on request
+        let explained: VectorCollection<String> = collection.clone().with_explanations(1);
+        let hits: Vec<SearchHit<String>> = search_text("An annoyed reply", &explained, &vectorizer, 2, Metric::Euclidean).await.unwrap();
+        let explanation: &SimilarityExplanation = hits[0].explanation.as_ref().unwrap();
+        assert_eq!(explanation.contributions[0].label, "score");
+        assert_eq!((explanation.contributions[0].left, explanation.contributions[0].right), (7.0, 9.0));
+        assert!((explanation.contributions[0].contribution - hits[0].score).abs() < 1e-6);
+        assert!(hits[1].explanation.is_none());
+        assert_eq!(explained.explain(&explained.get_vectors()[0], 2, Metric::Euclidean).unwrap().score, 2.0);
+        assert!(explained.explain(&explained.get_vectors()[0], 3, Metric::Euclidean).is_err());
+
+        // Other prompts are refused before any request is sent
+        let other: Vectorizer<MockBackend> = Vectorizer::builder()
+            .shared_client(backend.clone())
+            .prompts(PromptSet::new(vec!["Rate the joy. {'score': 5}"]))
+            .model_parameters(ModelParameters::builder().model("mock-model").build().unwrap())
+            .build()
+            .unwrap();
+        let error: String = search_text("An annoyed reply", &collection, &other, 2, Metric::Euclidean).await.unwrap_err().to_string();
+        assert!(error.contains("Fingerprint mismatch"));
+        assert_eq!(backend.get_requests().len(), 5);
+    }
+}
@@ -0,0 +1,264 @@
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// A request received by the mock server
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// Returns the value of a header, matched case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Parses the body as JSON
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).unwrap()
+    }
+}
+
+/// A canned response of the mock server
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+    pub content_type: String,
+    pub headers: Vec<(String, String)>,
+    pub delay: Option<Duration>,
+}
+
+impl MockResponse {
+    pub fn ok(body: impl Into<String>) -> Self {
+        Self::status(200, body)
+    }
+
+    pub fn status(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: body.into().into_bytes(),
+            content_type: "application/json".to_string(),
+            headers: Vec::new(),
+            delay: None,
+        }
+    }
+
+    pub fn bytes(content_type: &str, body: Vec<u8>) -> Self {
+        Self { content_type: content_type.to_string(), body, ..Self::status(200, "") }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn delayed(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+}
+
+/// A minimal HTTP/1.1 server on localhost that replays canned responses in order,
+/// repeating the last one, and records every request it receives
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockServer {
+    pub async fn start(responses: Vec<MockResponse>) -> Self {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url: String = format!("http://{}", listener.local_addr().unwrap());
+        let requests: Arc<Mutex<Vec<RecordedRequest>>> = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded: Arc<Mutex<Vec<RecordedRequest>>> = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = match listener.accept().await {
+                    Ok(connection) => connection,
+                    Err(_) => return,
+                };
+                let recorded: Arc<Mutex<Vec<RecordedRequest>>> = recorded.clone();
+                let responses: Vec<MockResponse> = responses.clone();
+                tokio::spawn(async move {
+                    serve(stream, recorded, responses).await;
+                });
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// Returns the requests received so far
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn serve(mut stream: TcpStream, recorded: Arc<Mutex<Vec<RecordedRequest>>>, responses: Vec<MockResponse>) {
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut chunk: [u8; 4096] = [0; 4096];
+    let header_end: usize = loop {
+        let read: usize = match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(read) => read,
+        };
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position + 4;
+        }
+    };
+
+    let head: String = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method: String = request_line.next().unwrap_or_default().to_string();
+    let path: String = request_line.next().unwrap_or_default().to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect();
+    let content_length: usize = headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    while buffer.len() < header_end + content_length {
+        let read: usize = match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+    let body: String = String::from_utf8_lossy(&buffer[header_end..]).to_string();
+
+    let response: MockResponse = {
+        let mut recorded = recorded.lock().unwrap();
+        recorded.push(RecordedRequest { method, path, headers, body });
+        responses[(recorded.len() - 1).min(responses.len() - 1)].clone()
+    };
+    if let Some(delay) = response.delay {
+        tokio::time::sleep(delay).await;
+    }
+
+    let headers: String = response
+        .headers
+        .iter()
+        .map(|(name, value)| format!("{}: {}\r\n", name, value))
+        .collect();
+    let head: String = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: {}\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len(),
+        headers
+    );
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(&response.body).await;
+    let _ = stream.shutdown().await;
+}
+
+/// An OpenAI chat completion response whose message has the given content
+pub fn chat_completion(content: &str) -> String {
+    serde_json::json!({
+        "id": "chatcmpl-mock",
+        "object": "chat.completion",
+        "created": 0,
+        "model": "mock-model",
+        "choices": [{
+            "index": 0,
+            "message": { "role": "assistant", "content": content },
+            "finish_reason": "stop"
+        }],
+        "usage": { "prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15 }
+    })
+    .to_string()
+}
+
+/// An OpenAI chat completion response for `{"score": <answer>}` with token logprobs
+///
+/// `alternatives` are the top tokens and their probabilities at the answer position.
+pub fn logprob_completion(answer: &str, alternatives: &[(&str, f32)]) -> String {
+    let token = |token: &str| serde_json::json!({ "token": token, "logprob": 0.0, "bytes": null, "top_logprobs": [] });
+    let top_logprobs: Vec<serde_json::Value> = alternatives
+        .iter()
+        .map(|(token, probability)| serde_json::json!({ "token": token, "logprob": probability.ln(), "bytes": null }))
+        .collect();
+    let content: Vec<serde_json::Value> = vec![
+        token("{\""),
+        token("score"),
+        token("\":"),
+        serde_json::json!({ "token": answer, "logprob": -0.5, "bytes": null, "top_logprobs": top_logprobs }),
+        token("}"),
+    ];
+
+    serde_json::json!({
+        "id": "chatcmpl-mock",
+        "object": "chat.completion",
+        "created": 0,
+        "model": "mock-model",
+        "choices": [{
+            "index": 0,
+            "message": { "role": "assistant", "content": format!("{{\"score\":{}}}", answer) },
+            "logprobs": { "content": content, "refusal": null },
+            "finish_reason": "stop"
+        }],
+        "usage": { "prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15 }
+    })
+    .to_string()
+}
+
+/// An OpenAI chat completion response calling a tool with the given arguments
+pub fn tool_call_completion(name: &str, arguments: &str) -> String {
+    serde_json::json!({
+        "id": "chatcmpl-mock",
+        "object": "chat.completion",
+        "created": 0,
+        "model": "mock-model",
+        "choices": [{
+            "index": 0,
+            "message": {
+                "role": "assistant",
+                "content": null,
+                "tool_calls": [{
+                    "id": "call_mock",
+                    "type": "function",
+                    "function": { "name": name, "arguments": arguments }
+                }]
+            },
+            "finish_reason": "tool_calls"
+        }],
+        "usage": { "prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15 }
+    })
+    .to_string()
+}
+
+/// An OpenAI models listing with the given model names
+pub fn model_list(models: &[&str]) -> String {
+    let data: Vec<serde_json::Value> = models
+        .iter()
+        .map(|model| serde_json::json!({ "id": model, "object": "model", "created": 0, "owned_by": "mock" }))
+        .collect();
+
+    serde_json::json!({ "object": "list", "data": data }).to_string()
+}
+
+/// An OpenAI error body with the given message
+pub fn api_error(message: &str) -> String {
+    serde_json::json!({
+        "error": { "message": message, "type": "invalid_request_error", "param": null, "code": null }
+    })
+    .to_string()
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "document")]
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use async_openai::config::OpenAIConfig;
+    use async_openai::Client;
+    use dim_rs::prelude::*;
+    use dim_rs::raw_data::document::{DocumentError, DocumentFormat};
+    use dim_rs::vectorization::document::vectorize_document_concurrently;
+    use dim_rs::vectorization::ModelParameters;
+
+    use crate::common::{chat_completion, MockResponse, MockServer};
+
+    /// A one-page PDF whose uncompressed content stream draws `content`
+    fn pdf(content: &str) -> Vec<u8> {
+        format!(
+            "%PDF-1.4\n1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n\
+             2 0 obj\n<< /Length {} >>\nstream\n{}\nendstream\nendobj\ntrailer\n<< /Root 1 0 R >>\n%%EOF\n",
+            content.len(),
+            content
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_from_document() {
+        let bytes: Vec<u8> = pdf("BT /F1 12 Tf 72 712 Td (Quarterly   report) Tj 0 -14 Td [(Revenue) -300 (\\(up\\))] TJ ET");
+        let vector: Vector<String> = Vector::from_document(&bytes, DocumentFormat::Pdf).unwrap();
+        assert_eq!(vector.get_data(), "Quarterly report\nRevenue (up)");
+        assert_eq!(vector.get_data_type(), DataType::Text);
+
+        // Scans have no text to extract, which differs from unreadable files
+        let scan: Vec<u8> = pdf("q 612 0 0 792 0 0 cm /Im1 Do Q");
+        assert_eq!(Vector::from_document(&scan, DocumentFormat::Pdf).unwrap_err(), DocumentError::NoTextLayer);
+        assert!(matches!(
+            Vector::from_document(b"not a pdf", DocumentFormat::Pdf).unwrap_err(),
+            DocumentError::Corrupt(_)
+        ));
+        let encrypted: Vec<u8> = [pdf("BT (Secret) Tj ET"), b"<< /Encrypt 5 0 R >>".to_vec()].concat();
+        assert_eq!(Vector::from_document(&encrypted, DocumentFormat::Pdf).unwrap_err(), DocumentError::Encrypted);
+    }
+
+    #[tokio::test]
+    async fn test_document_char_budget() {
+        let server: MockServer = MockServer::start(vec![MockResponse::ok(chat_completion("{\"score\": 5}"))]).await;
+        let client: Client<OpenAIConfig> = Client::with_config(OpenAIConfig::new().with_api_base(&server.url).with_api_key("test-key"));
+        let parameters: ModelParameters = ModelParameters::builder()
+            .model("mock-model")
+            .document_char_budget(12)
+            .build()
+            .unwrap();
+
+        let mut vector: Vector<String> = Vector::from_text("Quarterly report with details".to_string());
+        vectorize_document_concurrently(vec!["Rate it. {'score': 5}"], &mut vector, client, parameters)
+            .await
+            .unwrap();
+        assert_eq!(vector.get_vector(), vec![5.0]);
+        assert_eq!(vector.get_data(), "Quarterly report with details");
+
+        // Cut at the last whitespace within the budget
+        let content: String = server.requests()[0].json()["messages"][0]["content"].as_str().unwrap().to_string();
+        assert!(content.ends_with("Text to analyze: Quarterly"));
+    }
+}
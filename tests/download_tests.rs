@@ -0,0 +1,65 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::time::Duration;
+
+    use dim_rs::prelude::*;
+    use dim_rs::raw_data::{load_image_from_url, ImageDownloadOptions};
+    use dim_rs::vector::METADATA_SOURCE;
+    use image::{DynamicImage, ImageBuffer, ImageFormat, Rgba};
+
+    use crate::common::{MockResponse, MockServer};
+
+    fn png() -> Vec<u8> {
+        let image: DynamicImage = DynamicImage::ImageRgba8(ImageBuffer::from_fn(3, 2, |_, _| Rgba([10, 20, 30, 255])));
+        let mut bytes: Vec<u8> = Vec::new();
+        image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png).unwrap();
+        bytes
+    }
+
+    #[tokio::test]
+    async fn test_from_image_url() {
+        let server: MockServer = MockServer::start(vec![MockResponse::bytes("image/png", png())]).await;
+        let url: String = format!("{}/chair.png", server.url);
+
+        let vector: Vector<DynamicImage> = Vector::from_image_url(&url).await.unwrap();
+        assert_eq!((vector.get_data().width(), vector.get_data().height()), (3, 2));
+        assert_eq!(vector.get_metadata(METADATA_SOURCE), Some(url.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_image_url_redirects() {
+        let target: MockServer = MockServer::start(vec![MockResponse::bytes("image/png", png())]).await;
+        let location: String = format!("{}/chair.png", target.url);
+        let redirect: MockServer = MockServer::start(vec![MockResponse::status(302, "").with_header("Location", &location)]).await;
+
+        let image: DynamicImage = load_image_from_url(&redirect.url, &ImageDownloadOptions::new()).await.unwrap();
+        assert_eq!(image.width(), 3);
+
+        let error: String = load_image_from_url(&redirect.url, &ImageDownloadOptions::new().with_max_redirects(0))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("redirected more than 0 times"));
+    }
+
+    #[tokio::test]
+    async fn test_image_url_limits() {
+        let server: MockServer = MockServer::start(vec![MockResponse::bytes("image/png", png())]).await;
+        let error: String = load_image_from_url(&server.url, &ImageDownloadOptions::new().with_max_bytes(16))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("above the limit of 16 bytes"));
+
+        let server: MockServer = MockServer::start(vec![MockResponse::bytes("text/html", b"<html></html>".to_vec())]).await;
+        let error: String = load_image_from_url(&server.url, &ImageDownloadOptions::new()).await.unwrap_err().to_string();
+        assert!(error.contains("content type is \"text/html\""));
+
+        let server: MockServer = MockServer::start(vec![MockResponse::bytes("image/png", png()).delayed(Duration::from_secs(2))]).await;
+        let options: ImageDownloadOptions = ImageDownloadOptions::new().with_timeout(Duration::from_millis(200));
+        assert!(matches!(load_image_from_url(&server.url, &options).await, Err(DimError::Timeout { .. })));
+    }
+}
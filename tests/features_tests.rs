@@ -0,0 +1,18 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    #[test]
+    fn test_text_only_build() {
+        // Text-only users build without the image crate, so that build has to keep compiling
+        let status = Command::new(env!("CARGO"))
+            .args(["check", "--lib", "--quiet", "--no-default-features", "--features", "text"])
+            .arg("--manifest-path")
+            .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))
+            .arg("--target-dir")
+            .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/target/text-only"))
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+}
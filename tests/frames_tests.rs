@@ -0,0 +1,69 @@
+#![cfg(feature = "video")]
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use dim_rs::raw_data::{extract_frames, FrameStrategy, VideoError};
+    use image::codecs::gif::GifEncoder;
+    use image::{Delay, DynamicImage, Frame, RgbaImage, Rgba};
+
+    const COLORS: [[u8; 4]; 4] = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 255]];
+
+    /// A two second clip of four solid frames, half a second each
+    fn clip() -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut encoder: GifEncoder<&mut Vec<u8>> = GifEncoder::new(&mut bytes);
+            for color in COLORS {
+                let frame: Frame = Frame::from_parts(
+                    RgbaImage::from_pixel(8, 8, Rgba(color)),
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(500, 1),
+                );
+                encoder.encode_frame(frame).unwrap();
+            }
+        }
+        bytes
+    }
+
+    fn color(image: &DynamicImage) -> [u8; 4] {
+        image.to_rgba8()[(4, 4)].0
+    }
+
+    #[test]
+    fn test_extract_frames() {
+        let frames: Vec<(Duration, DynamicImage)> = extract_frames(clip(), FrameStrategy::Keyframes).unwrap();
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[3].0, Duration::from_millis(1500));
+        assert_eq!(color(&frames[1].1), COLORS[1]);
+
+        let frames: Vec<(Duration, DynamicImage)> = extract_frames(clip(), FrameStrategy::EveryNSeconds(0.75)).unwrap();
+        let timestamps: Vec<u128> = frames.iter().map(|(timestamp, _)| timestamp.as_millis()).collect();
+        assert_eq!(timestamps, [0, 750, 1500]);
+        assert_eq!(color(&frames[1].1), COLORS[1]);
+        assert_eq!(color(&frames[2].1), COLORS[3]);
+
+        let frames: Vec<(Duration, DynamicImage)> = extract_frames(clip(), FrameStrategy::FixedCount(2)).unwrap();
+        assert_eq!(frames.iter().map(|(_, image)| color(image)).collect::<Vec<[u8; 4]>>(), [COLORS[0], COLORS[3]]);
+
+        // Files are read from disk too
+        let directory: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let path: std::path::PathBuf = directory.path().join("clip.gif");
+        std::fs::write(&path, clip()).unwrap();
+        assert_eq!(extract_frames(path.as_path(), FrameStrategy::FixedCount(10)).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_extract_frames_errors() {
+        let mp4: Vec<u8> = b"\x00\x00\x00\x18ftypisom\x00\x00\x02\x00".to_vec();
+        assert_eq!(extract_frames(mp4, FrameStrategy::Keyframes).unwrap_err(), VideoError::UnsupportedCodec("MP4".to_string()));
+        assert!(matches!(extract_frames(b"not a video".to_vec(), FrameStrategy::Keyframes), Err(VideoError::Corrupt(_))));
+        let mut truncated: Vec<u8> = clip();
+        truncated.truncate(40);
+        assert!(matches!(extract_frames(truncated, FrameStrategy::Keyframes), Err(VideoError::Corrupt(_))));
+        assert!(matches!(extract_frames(clip(), FrameStrategy::EveryNSeconds(0.0)), Err(VideoError::InvalidStrategy(_))));
+        assert!(matches!(extract_frames(clip(), FrameStrategy::FixedCount(0)), Err(VideoError::InvalidStrategy(_))));
+    }
+}
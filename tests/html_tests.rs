@@ -0,0 +1,43 @@
+#![cfg(feature = "html")]
+
+#[cfg(test)]
+mod tests {
+    use dim_rs::prelude::*;
+    use dim_rs::raw_data::extract_readable_text;
+    use dim_rs::vector::{METADATA_ORIGINAL_LENGTH, METADATA_SOURCE};
+
+    #[test]
+    fn test_entity_decoding() {
+        let text: String = extract_readable_text("<p>Fish &amp; chips &lt;3 &quot;fresh&quot; &#169; &#x263A; &copy;&nbsp;2024 &bogus; a < b</p>");
+        assert_eq!(text, "Fish & chips <3 \"fresh\" © ☺ © 2024 &bogus; a < b");
+    }
+
+    #[test]
+    fn test_nested_tags() {
+        let html: &str = "<div><p>First <b>bold <i>and italic</i></b>\n  text.</p><ul><li>One</li><li>Two<br/>lines</li></ul>\
+                          <script>if (a < b) { document.write('<p>hidden</p>'); }</script><!-- <p>comment</p> --></div>";
+        assert_eq!(extract_readable_text(html), "First bold and italic text.\nOne\nTwo\nlines");
+    }
+
+    #[test]
+    fn test_boilerplate_page() {
+        let html: &str = r#"<!DOCTYPE html>
+            <html><head><title>Shop</title><style>body { color: red; }</style></head>
+            <body>
+                <header><a href="/">Home</a> | <a href="/about">About</a></header>
+                <nav><ul><li>Chairs</li><li>Tables</li><li>Lamps</li><li>Sale</li></ul></nav>
+                <aside>Sign up for our newsletter!</aside>
+                <main><article><h1>Oak chair</h1><p>A solid oak chair.</p></article></main>
+                <footer>&copy; 2024 Shop. All rights reserved.</footer>
+                <script src="tracking.js"></script>
+            </body></html>"#;
+        assert_eq!(extract_readable_text(html), "Oak chair\nA solid oak chair.");
+
+        let vector: Vector<String> = Vector::from_html(html, Some("https://shop.example/oak-chair"));
+        assert_eq!(vector.get_data(), "Oak chair\nA solid oak chair.");
+        assert_eq!(vector.get_metadata(METADATA_ORIGINAL_LENGTH), Some(html.len().to_string().as_str()));
+        assert_eq!(vector.get_metadata(METADATA_SOURCE), Some("https://shop.example/oak-chair"));
+
+        assert_eq!(extract_readable_text("<nav>Menu</nav><footer>Footer</footer>"), "");
+    }
+}
@@ -0,0 +1,153 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use dim_rs::prelude::*;
+    use dim_rs::vector::io::{export_csv, import_csv, load_image_jsonl, load_jsonl, read_jsonl, save_image_jsonl, save_jsonl, CsvOptions, ImagePayload};
+    use dim_rs::vector::serialization::SerializableImageVector;
+    use dim_rs::vector::METADATA_ID;
+    use image::{DynamicImage, ImageBuffer, Rgba};
+
+    fn text_vectors() -> Vec<Vector<String>> {
+        (0..3)
+            .map(|index| {
+                let mut vector: Vector<String> = Vector::from_text(format!("Text number {}, with \"quotes\"", index));
+                vector.overwrite_vector_with_labels(
+                    vec![index as f32, 9.0 - index as f32],
+                    vec!["sentiment_score".to_string(), "formality_score".to_string()],
+                ).unwrap();
+                vector.set_metadata(METADATA_ID, format!("text-{}", index));
+                vector.set_fingerprint("fingerprint".to_string());
+                vector
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_text_jsonl_round_trip() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("vectors.jsonl");
+        let vectors: Vec<Vector<String>> = text_vectors();
+
+        save_jsonl(&path, &vectors).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 3);
+
+        let restored: Vec<Vector<String>> = load_jsonl(&path).unwrap();
+        assert_eq!(restored.len(), vectors.len());
+        for (original, restored) in vectors.iter().zip(&restored) {
+            assert_eq!(restored.get_data(), original.get_data());
+            assert_eq!(restored.get_vector(), original.get_vector());
+            assert_eq!(restored.get_labels(), original.get_labels());
+            assert_eq!(restored.get_metadata_map(), original.get_metadata_map());
+            assert_eq!(restored.get_fingerprint(), original.get_fingerprint());
+            assert_eq!(restored.get_data_type(), DataType::Text);
+        }
+    }
+
+    #[test]
+    fn test_streaming_and_malformed_lines() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("vectors.jsonl");
+        save_jsonl(&path, &text_vectors()).unwrap();
+
+        // Corrupt the file with a blank line and a broken record
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file).unwrap();
+        writeln!(file, "{{\"vector\": [1.0,").unwrap();
+
+        let mut reader = read_jsonl::<Vector<String>>(&path).unwrap();
+        for _ in 0..3 {
+            assert!(reader.next().unwrap().is_ok());
+        }
+        let error: String = reader.next().unwrap().unwrap_err().to_string();
+        assert!(error.starts_with("line 5:"), "{}", error);
+        assert!(reader.next().is_none());
+
+        assert!(load_jsonl::<Vector<String>>(&path).is_err());
+    }
+
+    #[test]
+    fn test_image_jsonl_round_trip() {
+        let directory = tempfile::tempdir().unwrap();
+        let embedded_path = directory.path().join("embedded.jsonl");
+        let hashed_path = directory.path().join("hashed.jsonl");
+
+        let test_image: DynamicImage = DynamicImage::ImageRgba8(
+            ImageBuffer::from_fn(4, 4, |x, y| Rgba([x as u8 * 60, y as u8 * 60, 0, 255]))
+        );
+        let mut vector: Vector<DynamicImage> = Vector::from_image(test_image.clone());
+        vector.overwrite_vector(vec![2.0, 7.0]);
+        let vectors: Vec<Vector<DynamicImage>> = vec![vector];
+
+        // Embedded images restore fully
+        save_image_jsonl(&embedded_path, &vectors, ImagePayload::Embedded).unwrap();
+        let restored: Vec<Vector<DynamicImage>> = load_image_jsonl(&embedded_path).unwrap();
+        assert_eq!(restored[0].get_data().to_rgba8(), test_image.to_rgba8());
+        assert_eq!(restored[0].get_vector(), vec![2.0, 7.0]);
+
+        // Hash-only files keep the numbers but cannot restore the image
+        save_image_jsonl(&hashed_path, &vectors, ImagePayload::HashOnly).unwrap();
+        assert!(std::fs::metadata(&hashed_path).unwrap().len() < std::fs::metadata(&embedded_path).unwrap().len());
+        let records: Vec<SerializableImageVector> = load_jsonl(&hashed_path).unwrap();
+        assert_eq!(records[0].vector, vec![2.0, 7.0]);
+        assert!(records[0].image.is_none());
+        assert!(load_image_jsonl(&hashed_path).is_err());
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("vectors.csv");
+        let vectors: Vec<Vector<String>> = text_vectors();
+        let options: CsvOptions = CsvOptions {
+            text_column: Some("text".to_string()),
+            ..CsvOptions::default()
+        };
+
+        export_csv(&path, &vectors, &options).unwrap();
+        let contents: String = std::fs::read_to_string(&path).unwrap();
+        let mut lines = contents.lines();
+        assert_eq!(lines.next(), Some("id,text,sentiment_score,formality_score"));
+        // Commas and quotes in the text are quoted
+        assert_eq!(lines.next(), Some("text-0,\"Text number 0, with \"\"quotes\"\"\",0,9"));
+
+        let restored: Vec<Vector<String>> = import_csv(&path, &options).unwrap();
+        for (original, restored) in vectors.iter().zip(&restored) {
+            assert_eq!(restored.get_data(), original.get_data());
+            assert_eq!(restored.get_vector(), original.get_vector());
+            assert_eq!(restored.get_labels(), original.get_labels());
+            assert_eq!(restored.get_id(), original.get_id());
+        }
+    }
+
+    #[test]
+    fn test_csv_unlabeled_and_inconsistent() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("vectors.csv");
+
+        let mut first: Vector<String> = Vector::from_text("first".to_string());
+        first.overwrite_vector(vec![1.0, 2.0]);
+        let mut second: Vector<String> = Vector::from_text("second".to_string());
+        second.overwrite_vector(vec![3.0, 4.0]);
+
+        // Unlabeled dimensions fall back to dim_i
+        export_csv(&path, &[first.clone(), second.clone()], &CsvOptions::default()).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().starts_with("id,dim_0,dim_1\n"));
+
+        // Mixed dimensionality fails before writing
+        second.overwrite_vector(vec![3.0]);
+        let other_path = directory.path().join("inconsistent.csv");
+        assert!(export_csv(&other_path, &[first, second], &CsvOptions::default()).is_err());
+        assert!(!other_path.exists());
+    }
+
+    #[test]
+    fn test_csv_import_rejects_non_numeric() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("broken.csv");
+        std::fs::write(&path, "id,score\na,1.5\nb,high\n").unwrap();
+
+        let error: String = import_csv(&path, &CsvOptions::default()).unwrap_err().to_string();
+        assert!(error.contains("row 3"), "{}", error);
+    }
+}
@@ -0,0 +1,232 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use async_openai::config::{AzureConfig, Config, OpenAIConfig};
+    use async_openai::Client;
+    use dim_rs::llm::failover::{EndpointStats, FailoverClient};
+    use dim_rs::llm::*;
+    use dim_rs::vector::Vector;
+    use dim_rs::vectorization::report::VectorizationReport;
+    use dim_rs::vectorization::{vectorize_string_concurrently, ModelParameters};
+    use serial_test::serial;
+
+    use crate::common::{api_error, chat_completion, model_list, MockResponse, MockServer};
+    use async_openai::types::{ChatCompletionRequestUserMessageArgs, CreateChatCompletionRequest, CreateChatCompletionRequestArgs};
+
+    fn authorization<C: Config>(config: &C) -> String {
+        config.headers()["Authorization"].to_str().unwrap().to_string()
+    }
+
+    #[test]
+    #[serial]
+    fn test_api_key_from_env() {
+        std::env::set_var(DEFAULT_API_KEY_ENV, "sk-default");
+        std::env::set_var("GATEWAY_KEY", "sk-gateway");
+        std::env::set_var(API_BASE_ENV, "http://localhost:11434/v1");
+
+        let client: Client<OpenAIConfig> = instantiate_client(None, false).unwrap();
+        assert_eq!(authorization(client.config()), "Bearer sk-default");
+        assert_eq!(client.config().api_base(), "http://localhost:11434/v1");
+
+        // The key variable can be overridden
+        let client: Client<OpenAIConfig> = instantiate_client(Some("GATEWAY_KEY"), false).unwrap();
+        assert_eq!(authorization(client.config()), "Bearer sk-gateway");
+
+        std::env::remove_var(DEFAULT_API_KEY_ENV);
+        std::env::remove_var("GATEWAY_KEY");
+        std::env::remove_var(API_BASE_ENV);
+    }
+
+    #[test]
+    #[serial]
+    fn test_missing_api_key() {
+        std::env::remove_var(DEFAULT_API_KEY_ENV);
+        std::env::remove_var(API_BASE_ENV);
+
+        // Missing keys are an error unless explicitly allowed
+        let error: String = instantiate_client::<OpenAIConfig>(None, false).unwrap_err().to_string();
+        assert!(error.contains(DEFAULT_API_KEY_ENV));
+        let client: Client<OpenAIConfig> = instantiate_client(None, true).unwrap();
+        assert_eq!(client.config().api_base(), "https://api.openai.com/v1");
+    }
+
+    fn clear_azure_env() {
+        for name in [AZURE_API_KEY_ENV, AZURE_ENDPOINT_ENV, AZURE_DEPLOYMENT_ENV, AZURE_API_VERSION_ENV] {
+            std::env::remove_var(name);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_azure_config_from_env() {
+        clear_azure_env();
+        std::env::set_var(AZURE_ENDPOINT_ENV, "https://my-resource.openai.azure.com");
+        std::env::set_var(AZURE_DEPLOYMENT_ENV, "gpt-4o-mini");
+        std::env::set_var(AZURE_API_KEY_ENV, "azure-key");
+
+        let client: Client<AzureConfig> = instantiate_azure_client().unwrap();
+        let config: &AzureConfig = client.config();
+        assert_eq!(config.api_base(), "https://my-resource.openai.azure.com");
+        assert_eq!(config.url("/chat/completions"), "https://my-resource.openai.azure.com/openai/deployments/gpt-4o-mini/chat/completions");
+        assert_eq!(config.query(), vec![("api-version", DEFAULT_AZURE_API_VERSION)]);
+        assert_eq!(config.headers()["api-key"], "azure-key");
+
+        // The API version can be pinned
+        std::env::set_var(AZURE_API_VERSION_ENV, "2024-06-01");
+        let client: Client<AzureConfig> = instantiate_client(None, false).unwrap();
+        assert_eq!(client.config().query(), vec![("api-version", "2024-06-01")]);
+
+        // Missing settings are named in the error
+        std::env::remove_var(AZURE_DEPLOYMENT_ENV);
+        let error: String = instantiate_azure_client().unwrap_err().to_string();
+        assert!(error.contains(AZURE_DEPLOYMENT_ENV));
+        clear_azure_env();
+    }
+
+    fn request() -> CreateChatCompletionRequest {
+        CreateChatCompletionRequestArgs::default()
+            .model("mock-model")
+            .messages(vec![ChatCompletionRequestUserMessageArgs::default().content("hi").build().unwrap().into()])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_invalid_http_options() {
+        assert!(HttpOptions::new().with_proxy("not a url").build_http_client().is_err());
+        assert!(HttpOptions::new().with_header("X Org Token", "secret").build_http_client().is_err());
+        assert!(HttpOptions::new().with_header("X-Org-Token", "line\nbreak").build_http_client().is_err());
+        assert!(HttpOptions::new()
+            .with_proxy("http://proxy.internal:3128")
+            .with_connect_timeout(Duration::from_secs(30))
+            .build_http_client()
+            .is_ok());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_http_options_headers_and_timeout() {
+        let server: MockServer = MockServer::start(vec![
+            MockResponse::ok(chat_completion("{\"score\": 5}")),
+            MockResponse::ok(chat_completion("{\"score\": 5}")).delayed(Duration::from_secs(5)),
+        ])
+        .await;
+        std::env::set_var(API_BASE_ENV, &server.url);
+        let options: HttpOptions = HttpOptions::new()
+            .with_header("X-Org-Token", "org-secret")
+            .with_request_timeout(Duration::from_millis(300));
+        let client: Client<OpenAIConfig> = instantiate_client_with_options(None, true, &options).unwrap();
+        std::env::remove_var(API_BASE_ENV);
+
+        // Default headers are sent with every request
+        client.chat().create(request()).await.unwrap();
+        assert_eq!(server.requests()[0].header("X-Org-Token"), Some("org-secret"));
+        assert_eq!(server.requests()[0].path, "/chat/completions");
+
+        // A slow response hits the request timeout
+        let started: std::time::Instant = std::time::Instant::now();
+        assert!(client.chat().create(request()).await.is_err());
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
+    fn mock_client(api_base: &str) -> Client<OpenAIConfig> {
+        Client::with_config(OpenAIConfig::new().with_api_base(api_base).with_api_key("test-key"))
+    }
+
+    /// An API base nothing listens on
+    fn dead_endpoint() -> String {
+        let listener: std::net::TcpListener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_failover_to_next_endpoint() {
+        assert!(FailoverClient::<OpenAIConfig>::new(Vec::new()).is_err());
+
+        let server: MockServer = MockServer::start(vec![MockResponse::ok(chat_completion("{\"score\": 5}"))]).await;
+        let dead: String = dead_endpoint();
+        let client: FailoverClient<OpenAIConfig> = FailoverClient::new(vec![mock_client(&dead), mock_client(&server.url)]).unwrap();
+
+        // The unreachable primary is skipped, and the healthy endpoint is remembered
+        client.create_chat(request()).await.unwrap();
+        assert_eq!(client.get_preferred(), 1);
+        client.create_chat(request()).await.unwrap();
+        assert_eq!(server.requests().len(), 2);
+        assert_eq!(
+            client.get_endpoint_stats(),
+            vec![
+                EndpointStats { api_base: dead, served: 0, failed: 1 },
+                EndpointStats { api_base: server.url.clone(), served: 2, failed: 0 },
+            ]
+        );
+
+        // The report of a vectorization counts the requests each endpoint served
+        let client: Arc<FailoverClient<OpenAIConfig>> =
+            Arc::new(FailoverClient::new(vec![mock_client(&dead_endpoint()), mock_client(&server.url)]).unwrap());
+        let parameters: ModelParameters = ModelParameters::builder().model("mock-model").build().unwrap();
+        let mut vector: Vector<String> = Vector::from_text("Hello".to_string());
+        let report: VectorizationReport =
+            vectorize_string_concurrently(vec!["Rate it. {'score': 5}", "Rate the tone. {'tone': 5}"], &mut vector, client, parameters)
+                .await
+                .unwrap();
+        assert_eq!(report.endpoint_requests(), BTreeMap::from([(server.url.clone(), 2)]));
+        assert_eq!(report.prompts[1].endpoints, BTreeMap::from([(server.url.clone(), 1)]));
+    }
+
+    #[tokio::test]
+    async fn test_no_failover_on_api_errors() {
+        let unauthorized: MockServer = MockServer::start(vec![MockResponse::status(
+            401,
+            "{\"error\": {\"message\": \"Incorrect API key\", \"type\": \"invalid_request_error\", \"param\": null, \"code\": \"invalid_api_key\"}}",
+        )])
+        .await;
+        let secondary: MockServer = MockServer::start(vec![MockResponse::ok(chat_completion("{\"score\": 5}"))]).await;
+        let client: FailoverClient<OpenAIConfig> =
+            FailoverClient::new(vec![mock_client(&unauthorized.url), mock_client(&secondary.url)]).unwrap();
+
+        // A reachable endpoint rejecting the request would reject it everywhere
+        assert!(client.create_chat(request()).await.is_err());
+        assert!(secondary.requests().is_empty());
+        assert_eq!(client.get_preferred(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_verify_model() {
+        let server: MockServer = MockServer::start(vec![MockResponse::ok(model_list(&["llama3.2", "minicpm-v"]))]).await;
+        let client: Client<OpenAIConfig> = mock_client(&server.url);
+
+        let info: ModelInfo = verify_model(&client, "minicpm-v").await.unwrap();
+        assert_eq!(info, ModelInfo { id: "minicpm-v".to_string(), owned_by: Some("mock".to_string()), listed: true });
+        assert_eq!(server.requests()[0].path, "/models");
+
+        // A typo fails with the endpoint and the available models
+        let error: String = verify_model(&client, "minicpm").await.unwrap_err().to_string();
+        assert_eq!(
+            error,
+            format!("model 'minicpm' not found on {}; available: [llama3.2, minicpm-v]", server.url)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_model_without_listing() {
+        // Servers without a models listing are sent a one-token completion
+        let server: MockServer = MockServer::start(vec![
+            MockResponse::status(404, api_error("Not found")),
+            MockResponse::ok(chat_completion("hi")),
+        ])
+        .await;
+        let client: Client<OpenAIConfig> = mock_client(&server.url);
+
+        let info: ModelInfo = verify_model(&client, "local-model").await.unwrap();
+        assert!(!info.listed);
+        let requests = server.requests();
+        assert_eq!(requests[1].path, "/chat/completions");
+        assert_eq!(requests[1].json()["max_tokens"], 1);
+        assert_eq!(requests[1].json()["model"], "local-model");
+    }
+}
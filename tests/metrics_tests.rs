@@ -0,0 +1,338 @@
+#[cfg(test)]
+mod tests {
+    use dim_rs::prelude::*;
+    use dim_rs::vector::binary::{hamming_distance, BitVector};
+    use dim_rs::vector::explain::{explain_similarity, SimilarityExplanation};
+    use dim_rs::vector::io::{load_jsonl, save_jsonl};
+    use dim_rs::vector::metrics;
+    use dim_rs::vector::metrics::Metric;
+    use dim_rs::vector::quantization::QuantizedVector;
+    use dim_rs::vector::stats::{correlation_report, standardize, CorrelationReport, DimensionStats};
+    use rand::Rng;
+
+    fn text_vector(values: Vec<f32>) -> Vector<String> {
+        let mut vector: Vector<String> = Vector::from_text("metrics".to_string());
+        vector.overwrite_vector(values);
+        vector
+    }
+
+    #[test]
+    fn test_dot() {
+        let a: Vector<String> = text_vector(vec![1.0, 2.0, 3.0]);
+        let b: Vector<String> = text_vector(vec![4.0, 5.0, 6.0]);
+
+        // 1*4 + 2*5 + 3*6 = 32
+        assert_eq!(a.dot(&b).unwrap(), 32.0);
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        let a: Vector<String> = text_vector(vec![1.0, 0.0]);
+        let b: Vector<String> = text_vector(vec![1.0, 1.0]);
+        let c: Vector<String> = text_vector(vec![-2.0, 0.0]);
+
+        // cos(45°) = 1/sqrt(2)
+        assert!((a.cosine_similarity(&b).unwrap() - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+        assert!((a.cosine_similarity(&a).unwrap() - 1.0).abs() < 1e-6);
+        assert!((a.cosine_similarity(&c).unwrap() + 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_cosine_similarity_zero_vector() {
+        let zero: Vector<String> = text_vector(vec![0.0, 0.0]);
+        let b: Vector<String> = text_vector(vec![3.0, 4.0]);
+        assert_eq!(zero.cosine_similarity(&b).unwrap(), 0.0);
+        assert_eq!(zero.cosine_similarity(&zero).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_euclidean_distance() {
+        let a: Vector<String> = text_vector(vec![0.0, 0.0]);
+        let b: Vector<String> = text_vector(vec![3.0, 4.0]);
+
+        // 3-4-5 triangle
+        assert_eq!(a.euclidean_distance(&b).unwrap(), 5.0);
+        assert_eq!(metrics::euclidean_distance(&[1.0], &[1.0]).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_metric_errors() {
+        let a: Vector<String> = text_vector(vec![1.0, 2.0]);
+        let b: Vector<String> = text_vector(vec![1.0, 2.0, 3.0]);
+        let empty: Vector<String> = text_vector(vec![]);
+
+        assert!(a.dot(&b).is_err());
+        assert!(a.cosine_similarity(&b).is_err());
+        assert!(a.euclidean_distance(&b).is_err());
+        assert!(empty.cosine_similarity(&empty).is_err());
+    }
+
+    #[test]
+    fn test_normalize_l2_property() {
+        let mut rng = rand::rng();
+        for _ in 0..100 {
+            let dimensions: usize = rng.random_range(1..32);
+            let values: Vec<f32> = (0..dimensions).map(|_| rng.random_range(0.0..10.0)).collect();
+            if values.iter().all(|value| *value == 0.0) {
+                continue;
+            }
+            let mut vector: Vector<String> = text_vector(values.clone());
+
+            // The copy is unit length and the original is untouched
+            let normalized: Vec<f32> = vector.normalized_l2().unwrap();
+            assert!((metrics::l2_norm(&normalized) - 1.0).abs() < 1e-5);
+            assert_eq!(vector.get_vector(), values);
+
+            // Normalizing in place keeps the direction
+            vector.normalize_l2().unwrap();
+            assert!((metrics::l2_norm(vector.as_slice()) - 1.0).abs() < 1e-5);
+            assert!((metrics::cosine_similarity(vector.as_slice(), &values).unwrap() - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_explain_similarity() {
+        let mut query: Vector<String> = Vector::from_text("query".to_string());
+        let labels: Vec<String> = vec!["formality".to_string(), "politeness".to_string(), "urgency".to_string()];
+        query.overwrite_vector_with_labels(vec![8.0, 7.0, -3.0], labels).unwrap();
+        let hit: Vector<String> = text_vector(vec![9.0, 6.0, 4.0]);
+
+        // Labels come from the query; the largest contributions come first
+        let explanation: SimilarityExplanation = explain_similarity(&query, &hit, Metric::Dot).unwrap();
+        assert_eq!(explanation.score, 72.0 + 42.0 - 12.0);
+        let labeled: Vec<(&str, f32)> = explanation
+            .contributions
+            .iter()
+            .map(|contribution| (contribution.label.as_str(), contribution.contribution))
+            .collect();
+        assert_eq!(labeled, vec![("formality", 72.0), ("politeness", 42.0), ("urgency", -12.0)]);
+        assert_eq!((explanation.contributions[2].left, explanation.contributions[2].right), (-3.0, 4.0));
+
+        // Euclidean contributions rank disagreement, unlabeled dimensions get placeholders
+        let explanation: SimilarityExplanation = explain_similarity(&hit, &text_vector(vec![9.0, 6.0, 0.0]), Metric::Euclidean).unwrap();
+        assert_eq!(explanation.contributions[0].label, "dim_2");
+        assert_eq!(explanation.contributions[0].contribution, 4.0);
+        assert_eq!(explanation.contributions[1].contribution, 0.0);
+
+        // Identical and zero vectors score 0 with nothing to share out
+        let zero: Vector<String> = text_vector(vec![0.0, 0.0, 0.0]);
+        assert!(explain_similarity(&hit, &hit, Metric::Euclidean).unwrap().contributions.iter().all(|contribution| contribution.contribution == 0.0));
+        assert_eq!(explain_similarity(&zero, &hit, Metric::Cosine).unwrap().score, 0.0);
+        assert!(explain_similarity(&hit, &text_vector(vec![1.0]), Metric::Cosine).is_err());
+    }
+
+    #[test]
+    fn test_explanation_sums_to_score() {
+        let mut rng = rand::rng();
+        for metric in [Metric::Cosine, Metric::Dot, Metric::Euclidean] {
+            for _ in 0..100 {
+                let dimensions: usize = rng.random_range(1..32);
+                let a: Vector<String> = text_vector((0..dimensions).map(|_| rng.random_range(-10.0..10.0)).collect());
+                let b: Vector<String> = text_vector((0..dimensions).map(|_| rng.random_range(-10.0..10.0)).collect());
+
+                let explanation: SimilarityExplanation = explain_similarity(&a, &b, metric).unwrap();
+                let total: f32 = explanation.contributions.iter().map(|contribution| contribution.contribution).sum();
+                assert_eq!(explanation.score, metric.score(a.as_slice(), b.as_slice()).unwrap());
+                assert!((total - explanation.score).abs() <= 1e-3 * explanation.score.abs().max(1.0));
+                assert!(explanation.contributions.windows(2).all(|pair| pair[0].contribution.abs() >= pair[1].contribution.abs()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalize_l2_zero_vector() {
+        let mut zero: Vector<String> = text_vector(vec![0.0, 0.0, 0.0]);
+        assert!(zero.normalize_l2().is_err());
+        assert_eq!(zero.get_vector(), vec![0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_normalize_min_max() {
+        let mut vector: Vector<String> = Vector::from_text("scaled".to_string());
+        vector.overwrite_vector_with_labels(
+            vec![1.0, 5.0, 9.0],
+            vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        ).unwrap();
+
+        assert_eq!(vector.normalized_min_max(1.0, 9.0).unwrap(), vec![0.0, 0.5, 1.0]);
+        vector.normalize_min_max(1.0, 9.0).unwrap();
+        assert_eq!(vector.get_vector(), vec![0.0, 0.5, 1.0]);
+
+        // Labels survive normalization
+        assert_eq!(vector.get_by_label("b"), Some(0.5));
+
+        // Inverted bounds are rejected
+        assert!(vector.normalize_min_max(9.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_dimension_stats() {
+        let mut vectors: Vec<Vector<String>> = vec![
+            text_vector(vec![1.0, 10.0, 5.0]),
+            text_vector(vec![3.0, 30.0, 5.0]),
+        ];
+        let stats: DimensionStats = DimensionStats::compute(&vectors).unwrap();
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.mean, vec![2.0, 20.0, 5.0]);
+        assert_eq!(stats.std, vec![1.0, 10.0, 0.0]);
+        assert_eq!(stats.min, vec![1.0, 10.0, 5.0]);
+        assert_eq!(stats.max, vec![3.0, 30.0, 5.0]);
+        assert_eq!(stats.zero_variance_dimensions(), vec![2]);
+
+        // Zero variance dimensions standardize to 0
+        standardize(&mut vectors, &stats).unwrap();
+        assert_eq!(vectors[0].get_vector(), vec![-1.0, -1.0, 0.0]);
+        assert_eq!(vectors[1].get_vector(), vec![1.0, 1.0, 0.0]);
+
+        // Stored statistics standardize new queries the same way
+        let restored: DimensionStats = serde_json::from_str(&serde_json::to_string(&stats).unwrap()).unwrap();
+        let mut query: Vector<String> = text_vector(vec![2.0, 40.0, 7.0]);
+        restored.apply_to(&mut query).unwrap();
+        assert_eq!(query.get_vector(), vec![0.0, 2.0, 0.0]);
+
+        // Mismatched vectors are rejected
+        assert!(stats.apply_to(&mut text_vector(vec![1.0])).is_err());
+        assert!(DimensionStats::compute::<String>(&[]).is_err());
+    }
+
+    #[test]
+    fn test_correlation_report() {
+        // sentiment and intensity move together, formality moves against them, length is constant
+        let labels: Vec<String> = ["sentiment", "intensity", "formality", "length"].iter().map(|label| label.to_string()).collect();
+        let vectors: Vec<Vector<String>> = [1.0, 2.0, 3.0, 4.0]
+            .iter()
+            .zip([0.0, 1.0, 1.0, 0.0])
+            .map(|(x, noise)| {
+                let mut vector: Vector<String> = Vector::from_text("report".to_string());
+                vector.overwrite_vector_with_labels(vec![*x, 2.0 * x + 1.0, 10.0 - x + noise, 5.0], labels.clone()).unwrap();
+                vector
+            })
+            .collect();
+
+        let report: CorrelationReport = correlation_report(&vectors, 0.95, 1e-6).unwrap();
+        assert!((report.correlations[0][1] - 1.0).abs() < 1e-5);
+        assert!((report.correlations[0][0] - 1.0).abs() < 1e-5);
+        assert_eq!(report.correlations[0][3], 0.0);
+
+        // Only the perfectly correlated pair exceeds the threshold
+        assert_eq!(report.correlated_pairs.len(), 1);
+        assert_eq!(report.correlated_pairs[0].first_label, "sentiment");
+        assert_eq!(report.correlated_pairs[0].second_label, "intensity");
+
+        // Lowering the threshold surfaces the negative correlation too
+        let report: CorrelationReport = correlation_report(&vectors, 0.8, 1e-6).unwrap();
+        let pairs: Vec<(usize, usize)> = report.correlated_pairs.iter().map(|pair| (pair.first, pair.second)).collect();
+        assert_eq!(pairs, vec![(0, 1), (0, 2), (1, 2)]);
+        assert!(report.correlated_pairs[1].correlation < 0.0);
+
+        // The constant dimension is flagged
+        assert_eq!(report.near_constant.len(), 1);
+        assert_eq!(report.near_constant[0].label, "length");
+        assert!(report.to_string().contains("sentiment ~ intensity"));
+    }
+
+    #[test]
+    fn test_quantization_round_trip() {
+        let mut rng = rand::rng();
+        let values: Vec<f32> = (0..64).map(|_| rng.random_range(1.0..9.0)).collect();
+        let vector: Vector<String> = text_vector(values.clone());
+        let quantized: QuantizedVector = vector.quantize(1.0, 9.0).unwrap();
+
+        // The error is at most half a step
+        let half_step: f32 = (9.0 - 1.0) / 255.0 / 2.0;
+        for (original, restored) in values.iter().zip(quantized.dequantize()) {
+            assert!((original - restored).abs() <= half_step + 1e-6);
+        }
+
+        // Values outside the bounds are clamped, bad bounds are rejected
+        let clamped: QuantizedVector = text_vector(vec![0.0, 10.0]).quantize(1.0, 9.0).unwrap();
+        assert_eq!(clamped.get_values(), &[0, 255]);
+        assert!(vector.quantize(9.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_quantized_similarity() {
+        let mut rng = rand::rng();
+        let a: Vector<String> = text_vector((0..32).map(|_| rng.random_range(0.0..1.0)).collect());
+        let b: Vector<String> = text_vector((0..32).map(|_| rng.random_range(-1.0..1.0)).collect());
+        let quantized_a: QuantizedVector = a.quantize(0.0, 1.0).unwrap();
+        let quantized_b: QuantizedVector = b.quantize(-1.0, 1.0).unwrap();
+
+        // Integer arithmetic matches the float metrics on the dequantized values
+        let restored_a: Vec<f32> = quantized_a.dequantize();
+        let restored_b: Vec<f32> = quantized_b.dequantize();
+        let dot: f32 = metrics::dot(&restored_a, &restored_b).unwrap();
+        assert!((quantized_a.dot(&quantized_b).unwrap() - dot).abs() < 1e-3);
+        let cosine: f32 = metrics::cosine_similarity(&restored_a, &restored_b).unwrap();
+        assert!((quantized_a.cosine_similarity(&quantized_b).unwrap() - cosine).abs() < 1e-3);
+        let distance: f32 = metrics::euclidean_distance(&restored_a, &restored_b).unwrap();
+        assert!((quantized_a.euclidean_distance(&quantized_b).unwrap() - distance).abs() < 1e-2);
+
+        // And stays close to the original vectors
+        assert!((quantized_a.cosine_similarity(&quantized_b).unwrap() - a.cosine_similarity(&b).unwrap()).abs() < 0.02);
+        assert!(quantized_a.dot(&text_vector(vec![1.0]).quantize(0.0, 1.0).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_quantized_footprint() {
+        let mut rng = rand::rng();
+        let vectors: Vec<Vector<String>> = (0..20)
+            .map(|_| {
+                let mut vector: Vector<String> = text_vector((0..32).map(|_| rng.random_range(1.0..9.0)).collect());
+                vector.normalize_min_max(1.0, 9.0).unwrap();
+                vector
+            })
+            .collect();
+        let quantized: Vec<QuantizedVector> = vectors.iter().map(|vector| vector.quantize(0.0, 1.0).unwrap()).collect();
+
+        let directory = tempfile::tempdir().unwrap();
+        let float_path = directory.path().join("float.jsonl");
+        let quantized_path = directory.path().join("quantized.jsonl");
+        let float_values: Vec<Vec<f32>> = vectors.iter().map(|vector| vector.get_vector()).collect();
+        save_jsonl(&float_path, &float_values).unwrap();
+        save_jsonl(&quantized_path, &quantized).unwrap();
+
+        // Base64 bytes take well under half the space of float literals
+        let float_size: u64 = std::fs::metadata(&float_path).unwrap().len();
+        let quantized_size: u64 = std::fs::metadata(&quantized_path).unwrap().len();
+        assert!(quantized_size * 2 < float_size);
+        assert_eq!(load_jsonl::<QuantizedVector>(&quantized_path).unwrap(), quantized);
+    }
+
+    #[test]
+    fn test_bit_packing() {
+        // 130 dimensions span three words, the last one partially
+        let values: Vec<f32> = (0..130).map(|index| if index % 3 == 0 { 1.0 } else { 0.0 }).collect();
+        let bits: BitVector = text_vector(values.clone()).binarize(&vec![0.5; 130]).unwrap();
+        assert_eq!(bits.len(), 130);
+        assert_eq!(bits.as_words().len(), 3);
+        for (index, value) in values.iter().enumerate() {
+            assert_eq!(bits.get(index), Some(*value > 0.5));
+        }
+        assert_eq!(bits.get(130), None);
+        assert_eq!(bits.count_ones(), 44);
+        assert_eq!(bits.as_words()[2] >> 2, 0);
+
+        // Hamming distance counts differing dimensions
+        let flipped: Vec<bool> = (0..130).map(|index| index % 3 == 0 || index == 128 || index == 1).collect();
+        let other: BitVector = BitVector::from_bits(&flipped);
+        assert_eq!(hamming_distance(&bits, &other).unwrap(), 2);
+        assert!(hamming_distance(&bits, &BitVector::from_bits(&[true])).is_err());
+        assert!(text_vector(values).binarize(&[0.5]).is_err());
+    }
+
+    #[test]
+    fn test_median_thresholds() {
+        let vectors: Vec<Vector<String>> = vec![
+            text_vector(vec![1.0, 4.0]),
+            text_vector(vec![9.0, 2.0]),
+            text_vector(vec![5.0, 3.0]),
+            text_vector(vec![2.0, 1.0]),
+        ];
+        let stats: DimensionStats = DimensionStats::compute(&vectors).unwrap();
+        assert_eq!(stats.median, vec![3.5, 2.5]);
+
+        let bits: BitVector = vectors[1].binarize(&stats.median).unwrap();
+        assert_eq!((bits.get(0), bits.get(1)), (Some(true), Some(false)));
+    }
+}
\ No newline at end of file
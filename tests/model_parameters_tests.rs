@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod tests {
+    use dim_rs::prelude::*;
+    use dim_rs::prompt::{compute_fingerprint, ModelOverride};
+    use dim_rs::vectorization::{ModelParameters, ModelParametersBuilder};
+    use serial_test::serial;
+
+    #[test]
+    fn test_builder() {
+        let parameters: ModelParameters = ModelParameters::builder()
+            .model("gpt-4o-mini")
+            .temperature(0.2)
+            .seed(42)
+            .max_tokens(64)
+            .top_p(0.9)
+            .build()
+            .unwrap();
+
+        assert_eq!(parameters.get_model(), "gpt-4o-mini");
+        assert_eq!(parameters.get_temperature(), 0.2);
+        assert_eq!(parameters.get_seed(), 42);
+        assert_eq!(parameters.get_max_tokens(), Some(64));
+        assert_eq!(parameters.get_top_p(), Some(0.9));
+        assert_eq!(parameters.get_frequency_penalty(), None);
+
+        // `new` keeps its defaults
+        let legacy: ModelParameters = ModelParameters::new("mistral".to_string(), None, Some(7));
+        assert_eq!(legacy.get_temperature(), 1.0);
+        assert_eq!(legacy.get_seed(), 7);
+        assert_eq!(legacy.get_max_tokens(), None);
+
+        // and, unlike the builder, does not validate
+        let unchecked: ModelParameters = ModelParameters::new("mistral".to_string(), Some(3.0), None);
+        assert_eq!(unchecked.get_temperature(), 3.0);
+    }
+
+    #[test]
+    fn test_builder_validation() {
+        assert!(ModelParameters::builder().build().is_err());
+        assert!(ModelParameters::builder().model("  ").build().is_err());
+        assert!(ModelParameters::builder().model("mistral").temperature(7.0).build().is_err());
+        assert!(ModelParameters::builder().model("mistral").temperature(-0.1).build().is_err());
+        assert!(ModelParameters::builder().model("mistral").top_p(0.0).build().is_err());
+        assert!(ModelParameters::builder().model("mistral").top_p(1.5).build().is_err());
+        assert!(ModelParameters::builder().model("mistral").presence_penalty(2.5).build().is_err());
+
+        // Boundaries are inclusive where the API allows it
+        assert!(ModelParameters::builder().model("mistral").temperature(2.0).top_p(1.0).build().is_ok());
+        let error: String = ModelParameters::builder().model("mistral").temperature(7.0).build().unwrap_err().to_string();
+        assert!(error.contains("temperature"));
+    }
+
+    #[test]
+    fn test_prompt_model_override() {
+        let defaults: ModelParameters = ModelParameters::builder()
+            .model("qwen2.5:3b")
+            .temperature(0.2)
+            .max_tokens(32)
+            .seed(1)
+            .build()
+            .unwrap();
+        let vision: Prompt = Prompt::from("Rate the composition from 1 to 9. {'composition_score': 5}")
+            .with_model_override(ModelOverride {
+                model: Some("gpt-4o".to_string()),
+                max_tokens: Some(128),
+                ..ModelOverride::default()
+            });
+
+        // Overridden fields replace the defaults, the rest falls back
+        let routed: ModelParameters = defaults.with_override(vision.get_model_override());
+        assert_eq!(routed.get_model(), "gpt-4o");
+        assert_eq!(routed.get_max_tokens(), Some(128));
+        assert_eq!(routed.get_temperature(), 0.2);
+        assert_eq!(routed.get_seed(), 1);
+        assert_eq!(defaults.with_override(None).get_model(), "qwen2.5:3b");
+
+        // Routing a prompt to another model changes the fingerprint
+        let plain: Prompt = Prompt::from("Rate the composition from 1 to 9. {'composition_score': 5}");
+        assert_ne!(compute_fingerprint(std::slice::from_ref(&vision), "qwen2.5:3b"), compute_fingerprint(&[plain], "qwen2.5:3b"));
+
+        // The override survives serialization
+        let restored: Prompt = serde_json::from_str(&serde_json::to_string(&vision).unwrap()).unwrap();
+        assert_eq!(restored, vision);
+    }
+
+    const ENV_VARS: [&str; 7] = [
+        "DIM_MODEL",
+        "DIM_TEMPERATURE",
+        "DIM_SEED",
+        "DIM_MAX_TOKENS",
+        "DIM_TOP_P",
+        "DIM_FREQUENCY_PENALTY",
+        "DIM_PRESENCE_PENALTY",
+    ];
+
+    fn clear_env() {
+        for name in ENV_VARS {
+            std::env::remove_var(name);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_from_env() {
+        clear_env();
+        std::env::set_var("DIM_MODEL", "llama3.2");
+        std::env::set_var("DIM_TEMPERATURE", "0.3");
+        std::env::set_var("DIM_SEED", "99");
+        std::env::set_var("DIM_MAX_TOKENS", "256");
+
+        let parameters: ModelParameters = ModelParameters::from_env().unwrap();
+        assert_eq!(parameters.get_model(), "llama3.2");
+        assert_eq!(parameters.get_temperature(), 0.3);
+        assert_eq!(parameters.get_seed(), 99);
+        assert_eq!(parameters.get_max_tokens(), Some(256));
+        assert_eq!(parameters.get_top_p(), None);
+
+        // Code can override what the environment provides
+        let parameters: ModelParameters = ModelParametersBuilder::from_env().unwrap().model("gpt-4o-mini").build().unwrap();
+        assert_eq!(parameters.get_model(), "gpt-4o-mini");
+        assert_eq!(parameters.get_seed(), 99);
+        clear_env();
+    }
+
+    #[test]
+    #[serial]
+    fn test_from_env_errors() {
+        clear_env();
+
+        // Without DIM_MODEL the model must come from code
+        assert!(ModelParameters::from_env().is_err());
+        assert_eq!(ModelParametersBuilder::from_env().unwrap().model("mistral").build().unwrap().get_temperature(), 1.0);
+
+        // Unparsable values name the variable
+        std::env::set_var("DIM_MODEL", "mistral");
+        std::env::set_var("DIM_MAX_TOKENS", "lots");
+        let error: String = ModelParameters::from_env().unwrap_err().to_string();
+        assert!(error.contains("DIM_MAX_TOKENS"));
+
+        // Parsable but invalid values fail validation
+        std::env::remove_var("DIM_MAX_TOKENS");
+        std::env::set_var("DIM_TEMPERATURE", "7");
+        assert!(ModelParameters::from_env().is_err());
+        clear_env();
+    }
+}
\ No newline at end of file
@@ -0,0 +1,61 @@
+#![cfg(feature = "ndarray")]
+
+#[cfg(test)]
+mod tests {
+    use dim_rs::prelude::*;
+    use dim_rs::vector::ndarray::{to_array2, update_from_array2};
+    use dim_rs::vector::METADATA_ID;
+    use ndarray::{array, Array2};
+
+    fn vectors() -> Vec<Vector<String>> {
+        [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]
+            .iter()
+            .enumerate()
+            .map(|(index, values)| {
+                let mut vector: Vector<String> = Vector::from_text(format!("item {}", index));
+                vector.overwrite_vector_with_labels(
+                    values.to_vec(),
+                    vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                ).unwrap();
+                vector.set_metadata(METADATA_ID, format!("item-{}", index));
+                vector
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_array2_round_trip() {
+        let mut vectors: Vec<Vector<String>> = vectors();
+        assert_eq!(vectors[1].as_array1(), array![4.0, 5.0, 6.0]);
+        assert_eq!(vectors[1].as_array1().as_ptr(), vectors[1].as_slice().as_ptr());
+
+        let matrix: Array2<f32> = to_array2(&vectors).unwrap();
+        assert_eq!(matrix, array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+
+        // Center each dimension and write the result back
+        let centered: Array2<f32> = &matrix - &matrix.mean_axis(ndarray::Axis(0)).unwrap();
+        update_from_array2(&mut vectors, &centered).unwrap();
+        assert_eq!(vectors[0].get_vector(), vec![-1.5, -1.5, -1.5]);
+        assert_eq!(vectors[1].get_labels(), &["a", "b", "c"]);
+        assert_eq!(vectors[1].get_id(), Some("item-1"));
+    }
+
+    #[test]
+    fn test_array2_shape_errors() {
+        let mut vectors: Vec<Vector<String>> = vectors();
+        let mut short: Vector<String> = Vector::from_text("short".to_string());
+        short.overwrite_vector(vec![1.0]);
+        let mut mixed: Vec<Vector<String>> = vectors.clone();
+        mixed.push(short);
+        assert!(to_array2(&mixed).unwrap_err().to_string().contains("vector 2 has 1 dimensions"));
+
+        // Wrong row or column counts leave every vector untouched
+        let error = update_from_array2(&mut vectors, &Array2::zeros((3, 3))).unwrap_err();
+        assert!(error.to_string().contains("3 rows for 2 vectors"));
+        let error = update_from_array2(&mut vectors, &Array2::zeros((2, 2))).unwrap_err();
+        assert!(error.to_string().contains("2 columns"));
+        assert_eq!(vectors[0].get_vector(), vec![1.0, 2.0, 3.0]);
+
+        assert_eq!(to_array2::<String>(&[]).unwrap().dim(), (0, 0));
+    }
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "npy")]
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use dim_rs::export::npy::{write_npy, write_npz};
+    use dim_rs::prelude::*;
+    use dim_rs::vector::METADATA_ID;
+
+    fn vectors() -> Vec<Vector<String>> {
+        [[1.0, 2.5, -3.0], [4.0, 0.0, 9.0]]
+            .iter()
+            .enumerate()
+            .map(|(index, values)| {
+                let mut vector: Vector<String> = Vector::from_text(format!("item {}", index));
+                vector.overwrite_vector_with_labels(
+                    values.to_vec(),
+                    vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                ).unwrap();
+                vector.set_metadata(METADATA_ID, format!("item-{}", index));
+                vector
+            })
+            .collect()
+    }
+
+    /// Splits a .npy file into its header dictionary and payload
+    fn parse_npy(bytes: &[u8]) -> (String, &[u8]) {
+        assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+        let header_length: usize = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!((10 + header_length) % 64, 0);
+        let header: String = String::from_utf8(bytes[10..10 + header_length].to_vec()).unwrap();
+        (header, &bytes[10 + header_length..])
+    }
+
+    #[test]
+    fn test_write_npy() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("vectors.npy");
+        write_npy(&path, &vectors()).unwrap();
+
+        let bytes: Vec<u8> = std::fs::read(&path).unwrap();
+        let (header, payload) = parse_npy(&bytes);
+        assert!(header.starts_with("{'descr': '<f4', 'fortran_order': False, 'shape': (2, 3), }"));
+        assert!(header.ends_with('\n'));
+
+        // Row-major little-endian f32 values
+        let expected: Vec<u8> = [1.0f32, 2.5, -3.0, 4.0, 0.0, 9.0]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        assert_eq!(payload, expected.as_slice());
+    }
+
+    #[test]
+    fn test_write_npz() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("vectors.npz");
+        write_npz(&path, &vectors()).unwrap();
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut read = |name: &str| {
+            let mut bytes: Vec<u8> = Vec::new();
+            archive.by_name(name).unwrap().read_to_end(&mut bytes).unwrap();
+            bytes
+        };
+
+        let matrix: Vec<u8> = read("vectors.npy");
+        assert!(parse_npy(&matrix).0.contains("'shape': (2, 3)"));
+
+        let ids: Vec<u8> = read("ids.npy");
+        let (header, payload) = parse_npy(&ids);
+        assert!(header.contains("'descr': '<U6'") && header.contains("'shape': (2,)"));
+        let first: String = payload[..24]
+            .chunks(4)
+            .map(|chunk| char::from_u32(u32::from_le_bytes(chunk.try_into().unwrap())).unwrap())
+            .collect();
+        assert_eq!(first, "item-0");
+
+        let labels: Vec<u8> = read("labels.npy");
+        assert!(parse_npy(&labels).0.contains("'descr': '<U1', 'fortran_order': False, 'shape': (3,)"));
+    }
+
+    #[test]
+    fn test_mismatched_dimensionality() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("vectors.npy");
+        let mut vectors: Vec<Vector<String>> = vectors();
+        vectors[1].overwrite_vector(vec![1.0]);
+
+        assert!(write_npy(&path, &vectors).is_err());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "parquet")]
+
+#[cfg(test)]
+mod tests {
+    use dim_rs::export::parquet::{read_parquet, write_parquet};
+    use dim_rs::prelude::*;
+    use image::DynamicImage;
+
+    fn vectors() -> Vec<Vector<String>> {
+        [vec![1.0, 2.5, -3.0], vec![4.0, 0.0, 9.0]]
+            .into_iter()
+            .enumerate()
+            .map(|(index, values)| {
+                let mut vector: Vector<String> = Vector::from_text(format!("item {}", index));
+                vector.overwrite_vector_with_labels(
+                    values,
+                    vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                ).unwrap();
+                vector.set_metadata(METADATA_ID, format!("item-{}", index));
+                vector.set_metadata(METADATA_MODEL, "mock-model");
+                vector.set_fingerprint("abc".to_string());
+                vector
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parquet_round_trip() {
+        let directory: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let path: std::path::PathBuf = directory.path().join("vectors.parquet");
+        let vectors: Vec<Vector<String>> = vectors();
+        write_parquet(&path, &vectors).unwrap();
+
+        let restored: Vec<Vector<String>> = read_parquet(&path).unwrap();
+        assert_eq!(restored.len(), 2);
+        for (original, restored) in vectors.iter().zip(&restored) {
+            assert_eq!(restored.get_data(), original.get_data());
+            assert_eq!(restored.get_vector(), original.get_vector());
+            assert_eq!(restored.get_labels(), original.get_labels());
+            assert_eq!(restored.get_metadata_map(), original.get_metadata_map());
+            assert_eq!(restored.get_fingerprint(), Some("abc"));
+        }
+    }
+
+    #[test]
+    fn test_parquet_images_and_errors() {
+        let directory: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let path: std::path::PathBuf = directory.path().join("images.parquet");
+
+        let mut image: Vector<DynamicImage> = Vector::from_image(DynamicImage::new_rgb8(2, 2));
+        image.overwrite_vector(vec![0.5, 0.25]);
+        image.set_metadata(METADATA_SOURCE, "photos/chair.png");
+        write_parquet(&path, std::slice::from_ref(&image)).unwrap();
+        let restored: Vec<Vector<String>> = read_parquet(&path).unwrap();
+        assert_eq!(restored[0].get_data(), "photos/chair.png");
+        assert!(restored[0].get_labels().is_empty());
+        assert_eq!(restored[0].get_vector(), vec![0.5, 0.25]);
+
+        let mut mismatched: Vec<Vector<String>> = vectors();
+        mismatched[1].overwrite_vector(vec![1.0]);
+        assert!(write_parquet(directory.path().join("bad.parquet"), &mismatched).is_err());
+        assert!(!directory.path().join("bad.parquet").exists());
+        assert!(write_parquet(&path, &Vec::<Vector<String>>::new()).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use dim_rs::export::pgvector::{save_sql, vector_literal, write_sql, PgvectorFormat, PgvectorOptions};
+    use dim_rs::prelude::*;
+    use dim_rs::vector::METADATA_ID;
+
+    fn vectors() -> Vec<Vector<String>> {
+        [vec![1.0, -0.5, 3.0], vec![0.25, 0.0, 1e-7], vec![2.0, 2.0, 2.0]]
+            .into_iter()
+            .enumerate()
+            .map(|(index, values)| {
+                let mut vector: Vector<String> = Vector::from_text(format!("it's item\t{}", index));
+                vector.overwrite_vector(values);
+                vector.set_metadata(METADATA_ID, format!("item-{}", index));
+                vector
+            })
+            .collect()
+    }
+
+    /// Parses a pgvector literal the way its text input function does
+    fn parse_vector(literal: &str) -> Vec<f32> {
+        let inner: &str = literal.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')).unwrap();
+        inner.split(',').map(|value| value.parse::<f32>().unwrap()).collect()
+    }
+
+    fn sql(vectors: &[Vector<String>], options: &PgvectorOptions) -> String {
+        let mut bytes: Vec<u8> = Vec::new();
+        write_sql(&mut bytes, vectors, "public.items", options).unwrap();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_write_inserts() {
+        let vectors: Vec<Vector<String>> = vectors();
+        let sql: String = sql(&vectors, &PgvectorOptions::new().with_batch_size(2));
+
+        assert!(sql.contains("CREATE TABLE IF NOT EXISTS \"public\".\"items\""));
+        assert!(sql.contains("embedding vector(3)"));
+        assert_eq!(sql.matches("INSERT INTO").count(), 2);
+        assert!(sql.contains("'it''s item\t0'"));
+
+        let literals: Vec<Vec<f32>> = sql
+            .split("'[")
+            .skip(1)
+            .map(|rest| parse_vector(&format!("[{}", rest.split('\'').next().unwrap())))
+            .collect();
+        let expected: Vec<Vec<f32>> = vectors.iter().map(|vector| vector.get_vector()).collect();
+        assert_eq!(literals, expected);
+        assert_eq!(vector_literal(&[1.0, -0.5]).unwrap(), "[1,-0.5]");
+        assert!(vector_literal(&[f32::NAN]).is_err());
+    }
+
+    #[test]
+    fn test_write_copy() {
+        let mut vectors: Vec<Vector<String>> = vectors();
+        vectors[1].set_metadata("note", "line\nbreak \\ here");
+
+        let copy: String = sql(&vectors, &PgvectorOptions::new().with_format(PgvectorFormat::Copy));
+        assert!(copy.contains("COPY \"public\".\"items\" (id, data_ref, embedding, metadata) FROM stdin;\n"));
+        assert!(copy.ends_with("\\.\n"));
+
+        let data: String = sql(&vectors, &PgvectorOptions::new().with_format(PgvectorFormat::CopyData));
+        let rows: Vec<Vec<&str>> = data.lines().map(|line| line.split('\t').collect()).collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0][..2], ["item-0", "it's item\\t0"]);
+        assert_eq!(parse_vector(rows[1][2]), vectors[1].get_vector());
+        assert!(rows[1][3].contains("line\\\\nbreak \\\\\\\\ here"));
+    }
+
+    #[test]
+    fn test_write_sql_errors() {
+        let mut vectors: Vec<Vector<String>> = vectors();
+        vectors[2].overwrite_vector(vec![1.0]);
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("items.sql");
+
+        let error: String = save_sql(&path, &vectors, "items", &PgvectorOptions::new()).unwrap_err().to_string();
+        assert!(error.contains("Dimensionality mismatch"));
+        assert!(!path.exists());
+
+        let empty: Vec<Vector<String>> = Vec::new();
+        assert!(save_sql(&path, &empty, "items", &PgvectorOptions::new()).is_err());
+        save_sql(&path, &empty, "items", &PgvectorOptions::new().with_create_table(false)).unwrap();
+        assert!(save_sql(&path, &vectors[..2], "bad..name", &PgvectorOptions::new()).is_err());
+    }
+}
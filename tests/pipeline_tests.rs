@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use async_openai::error::OpenAIError;
+    use async_openai::types::{CreateChatCompletionRequest, CreateChatCompletionResponse};
+    use dim_rs::llm::testing::{completion_response, MockBackend};
+    use dim_rs::pipeline::{process_jsonl, PipelineOptions, PipelineSummary};
+    use dim_rs::prelude::*;
+    use dim_rs::vector::METADATA_SOURCE;
+    use serde_json::Value;
+
+    /// Answers every prompt with a score of 5, taking longer for texts containing "slow"
+    #[derive(Debug, Default)]
+    struct DelayBackend {
+        in_flight: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    impl ChatBackend for DelayBackend {
+        async fn create_chat(&self, request: CreateChatCompletionRequest) -> Result<CreateChatCompletionResponse, OpenAIError> {
+            let current: usize = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(current, Ordering::SeqCst);
+            let slow: bool = serde_json::to_string(&request.messages).unwrap().contains("slow");
+            tokio::time::sleep(Duration::from_millis(if slow { 100 } else { 10 })).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            Ok(completion_response("{\"score\": 5}"))
+        }
+    }
+
+    fn vectorizer(backend: Arc<DelayBackend>) -> Vectorizer<DelayBackend> {
+        Vectorizer::builder()
+            .shared_client(backend)
+            .prompts(PromptSet::new(vec!["Rate it. {'score': 5}"]))
+            .model_parameters(ModelParameters::builder().model("mock-model").build().unwrap())
+            .build()
+            .unwrap()
+    }
+
+    fn sources(output: &[u8]) -> Vec<String> {
+        String::from_utf8(output.to_vec())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Vector<String>>(line).unwrap().get_metadata(METADATA_SOURCE).unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_process_jsonl() {
+        let input: &[u8] = b"{\"text\": \"slow one\", \"id\": 1}\n{\"text\": \"fast\"}\n\nnot json\n{\"body\": \"no text\"}\n{\"text\": \"fast again\"}\n";
+        let directory: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let errors_path: std::path::PathBuf = directory.path().join("errors.jsonl");
+        let errors: tokio::fs::File = tokio::fs::File::create(&errors_path).await.unwrap();
+
+        // Results are written as they complete, the slow first line last
+        let mut output: Vec<u8> = Vec::new();
+        let options: PipelineOptions = PipelineOptions::new().with_id_field("id").with_errors(errors);
+        let summary: PipelineSummary = process_jsonl(input, &mut output, &vectorizer(Arc::default()), options).await.unwrap();
+        assert_eq!(summary, PipelineSummary { succeeded: 3, failed: 2 });
+        assert_eq!(sources(&output), vec!["stdin:2", "stdin:6", "stdin:1"]);
+        let first: Vector<String> = serde_json::from_str(String::from_utf8(output).unwrap().lines().last().unwrap()).unwrap();
+        assert_eq!((first.get_vector(), first.get_id()), (vec![5.0], Some("1")));
+
+        // Malformed lines go to the errors stream with their original line
+        let errors: Vec<Value> = std::fs::read_to_string(&errors_path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(errors.len(), 2);
+        assert_eq!((errors[0]["line"].as_u64(), errors[0]["input"].as_str()), (Some(4), Some("not json")));
+        assert!(errors[1]["error"].as_str().unwrap().contains("field 'text' is missing"));
+
+        // The reorder buffer restores the input order
+        let mut output: Vec<u8> = Vec::new();
+        let options: PipelineOptions = PipelineOptions::new().with_preserve_order(true).with_source("texts.jsonl");
+        process_jsonl(input, &mut output, &vectorizer(Arc::default()), options).await.unwrap();
+        assert_eq!(sources(&output), vec!["texts.jsonl:1", "texts.jsonl:2", "texts.jsonl:6"]);
+    }
+
+    #[tokio::test]
+    async fn test_process_jsonl_bounds_in_flight() {
+        let input: String = (0..12).map(|index| format!("{{\"text\": \"slow {}\"}}\n", index)).collect();
+        let backend: Arc<DelayBackend> = Arc::default();
+
+        let mut output: Vec<u8> = Vec::new();
+        let options: PipelineOptions = PipelineOptions::new().with_max_in_flight(3).with_preserve_order(true);
+        let summary: PipelineSummary = process_jsonl(input.as_bytes(), &mut output, &vectorizer(backend.clone()), options).await.unwrap();
+        assert_eq!(summary.succeeded, 12);
+        assert_eq!(backend.peak.load(Ordering::SeqCst), 3);
+        assert_eq!(sources(&output), (1..=12).map(|line| format!("stdin:{}", line)).collect::<Vec<String>>());
+    }
+
+    #[tokio::test]
+    async fn test_process_jsonl_fails_items_with_failed_prompts() {
+        let backend: Arc<MockBackend> = Arc::new(MockBackend::new().with_response("Rate it", "{\"score\": 5}"));
+        let vectorizer: Vectorizer<MockBackend> = Vectorizer::builder()
+            .shared_client(backend)
+            .prompts(PromptSet::new(vec!["Rate it. {'score': 5}", "Rate the tone. {'tone': 5}"]))
+            .model_parameters(ModelParameters::builder().model("mock-model").build().unwrap())
+            .build()
+            .unwrap();
+        let directory: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let errors_path: std::path::PathBuf = directory.path().join("errors.jsonl");
+        let errors: tokio::fs::File = tokio::fs::File::create(&errors_path).await.unwrap();
+
+        // The partial vector is not written, and the errors stream names the failed prompt
+        let mut output: Vec<u8> = Vec::new();
+        let options: PipelineOptions = PipelineOptions::new().with_errors(errors);
+        let summary: PipelineSummary = process_jsonl(&b"{\"text\": \"alpha\"}\n"[..], &mut output, &vectorizer, options).await.unwrap();
+        assert_eq!(summary, PipelineSummary { succeeded: 0, failed: 1 });
+        assert!(output.is_empty());
+        let error: Value = serde_json::from_str(std::fs::read_to_string(&errors_path).unwrap().trim()).unwrap();
+        assert_eq!(error["failed_prompts"], serde_json::json!([1]));
+        assert!(error["error"].as_str().unwrap().contains("Prompts [1] failed"));
+    }
+}
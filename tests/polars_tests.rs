@@ -0,0 +1,65 @@
+#![cfg(feature = "polars")]
+
+#[cfg(test)]
+mod tests {
+    use dim_rs::export::polars::{from_dataframe, to_dataframe};
+    use dim_rs::prelude::*;
+    use dim_rs::vector::METADATA_ID;
+    use polars::prelude::DataFrame;
+
+    fn vectors() -> Vec<Vector<String>> {
+        [[1.0, 2.5, -3.0], [4.0, 0.0, 9.0]]
+            .iter()
+            .enumerate()
+            .map(|(index, values)| {
+                let mut vector: Vector<String> = Vector::from_text(format!("item {}", index));
+                vector.overwrite_vector_with_labels(
+                    values.to_vec(),
+                    vec!["tone".to_string(), "formality".to_string(), "urgency".to_string()],
+                ).unwrap();
+                vector.set_metadata(METADATA_ID, format!("item-{}", index));
+                vector
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_dataframe_round_trip() {
+        let df: DataFrame = to_dataframe(&vectors()).unwrap();
+        let names: Vec<String> = df.get_column_names().iter().map(|name| name.to_string()).collect();
+        assert_eq!(names, vec!["id", "text", "tone", "formality", "urgency"]);
+        assert_eq!(df.height(), 2);
+
+        let restored: Vec<Vector<String>> = from_dataframe(&df, "text", &["tone", "formality", "urgency"]).unwrap();
+        for (original, restored) in vectors().iter().zip(&restored) {
+            assert_eq!(restored.get_vector(), original.get_vector());
+            assert_eq!(restored.get_labels(), original.get_labels());
+            assert_eq!(restored.get_data(), original.get_data());
+            assert_eq!(restored.get_id(), original.get_id());
+        }
+
+        // Unlabeled vectors get dim_<index> columns
+        let mut unlabeled: Vector<String> = Vector::from_text("a".to_string());
+        unlabeled.overwrite_vector(vec![0.5, 1.5]);
+        let names: Vec<String> = to_dataframe(&[unlabeled]).unwrap().get_column_names().iter().map(|name| name.to_string()).collect();
+        assert_eq!(names, vec!["id", "text", "dim_0", "dim_1"]);
+    }
+
+    #[test]
+    fn test_dataframe_errors() {
+        let mut vectors: Vec<Vector<String>> = vectors();
+        let mut short: Vector<String> = Vector::from_text("short".to_string());
+        short.overwrite_vector(vec![1.0]);
+        vectors.push(short);
+        assert!(to_dataframe(&vectors).unwrap_err().to_string().contains("dimensionality"));
+
+        let mut duplicated: Vector<String> = Vector::from_text("a".to_string());
+        duplicated.overwrite_vector_with_labels(vec![1.0, 2.0], vec!["tone".to_string(), "tone".to_string()]).unwrap();
+        assert!(to_dataframe(&[duplicated]).unwrap_err().to_string().contains("Duplicate"));
+
+        let df: DataFrame = to_dataframe(&self::vectors()).unwrap();
+        assert!(from_dataframe(&df, "text", &["tone", "tone"]).unwrap_err().to_string().contains("Duplicate"));
+        assert!(from_dataframe(&df, "text", &["style"]).unwrap_err().to_string().contains("'style'"));
+        assert!(from_dataframe(&df, "text", &["id"]).unwrap_err().to_string().contains("not numeric"));
+    }
+}
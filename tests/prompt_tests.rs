@@ -0,0 +1,228 @@
+#[cfg(test)]
+mod tests {
+    use dim_rs::prelude::*;
+    use dim_rs::prompt::compute_fingerprint;
+    use dim_rs::prompt::lint::{LintOptions, LintRule, LintWarning};
+
+    #[test]
+    fn test_from_instruction() {
+        let instruction: String = "Rate the politeness from 1 to 9. {'politeness_score': 8}".to_string();
+        let prompt: Prompt = Prompt::from(instruction.clone());
+
+        // Verify correctness
+        assert_eq!(prompt.get_instruction(), instruction);
+        assert!(prompt.get_examples().is_empty());
+    }
+
+    #[test]
+    fn test_few_shot_examples() {
+        let prompt: Prompt = Prompt::from("Rate the politeness from 1 to 9. {'politeness_score': 8}")
+            .with_example("Get out of my office.", "{\"politeness_score\": 1}")
+            .with_example("Would you kindly pass the salt?", "{\"politeness_score\": 9}");
+
+        // Examples are kept in insertion order
+        let examples = prompt.get_examples();
+        assert_eq!(examples.len(), 2);
+        assert_eq!(examples[0].input, "Get out of my office.");
+        assert_eq!(examples[0].output, "{\"politeness_score\": 1}");
+        assert_eq!(examples[1].input, "Would you kindly pass the salt?");
+
+        // Examples are part of the prompt's identity
+        let zero_shot: Prompt = Prompt::from("Rate the politeness from 1 to 9. {'politeness_score': 8}");
+        assert_ne!(prompt, zero_shot);
+    }
+
+    #[test]
+    fn test_fingerprint_stability() {
+        let prompts: Vec<Prompt> = vec![
+            Prompt::from("Rate the formality from 1 to 9. {'formality_score': 4}"),
+            Prompt::from("Rate the urgency from 1 to 9. {'urgency_score': 2}"),
+        ];
+        let fingerprint: String = compute_fingerprint(&prompts, "mistral");
+
+        // Same prompts and model yield the same fingerprint
+        assert_eq!(fingerprint, compute_fingerprint(&prompts.clone(), "mistral"));
+
+        // Model, order and few-shot examples all change the fingerprint
+        assert_ne!(fingerprint, compute_fingerprint(&prompts, "minicpm-v"));
+        let reversed: Vec<Prompt> = prompts.iter().rev().cloned().collect();
+        assert_ne!(fingerprint, compute_fingerprint(&reversed, "mistral"));
+        let mut with_example: Vec<Prompt> = prompts.clone();
+        with_example[0].add_example("Yo, what's up?", "{\"formality_score\": 1}");
+        assert_ne!(fingerprint, compute_fingerprint(&with_example, "mistral"));
+    }
+
+    fn rules(warnings: &[LintWarning]) -> Vec<LintRule> {
+        warnings.iter().map(|warning| warning.rule).collect()
+    }
+
+    #[test]
+    fn test_lint_clean_prompt() {
+        let prompt: Prompt = Prompt::from(
+            "Score the sentiment intensity of the text from 1 (extremely negative) to 9 (extremely positive). Format your response exactly like this example: {'sentiment_score': 7}"
+        );
+        assert!(prompt.lint(&LintOptions::strict()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_lint_missing_json_example() {
+        let prompt: Prompt = Prompt::from("Rate the politeness of the text from 1 to 9.");
+        let warnings = prompt.lint(&LintOptions::default()).unwrap();
+        assert_eq!(rules(&warnings), vec![LintRule::MissingJsonExample]);
+    }
+
+    #[test]
+    fn test_lint_missing_numeric_range() {
+        let prompt: Prompt = Prompt::from("Rate the politeness of the text. {'politeness_score': 8}");
+        let warnings = prompt.lint(&LintOptions::default()).unwrap();
+        assert_eq!(rules(&warnings), vec![LintRule::MissingNumericRange]);
+    }
+
+    #[test]
+    fn test_lint_unparsable_json_example() {
+        let prompt: Prompt = Prompt::from("Rate the politeness from 1 to 9. {'politeness_score': your score}");
+        let warnings = prompt.lint(&LintOptions::default()).unwrap();
+        assert_eq!(rules(&warnings), vec![LintRule::UnparsableJsonExample]);
+    }
+
+    #[test]
+    fn test_lint_expected_dims_mismatch() {
+        let prompt: Prompt = Prompt::from("Rate warmth and energy from 1 to 9. {'warmth': 3, 'energy': 5}");
+        let warnings = prompt.lint(&LintOptions::default()).unwrap();
+        assert_eq!(rules(&warnings), vec![LintRule::ExpectedDimsMismatch]);
+
+        // Declaring both dimensions fixes it
+        let prompt: Prompt = prompt.with_expected_dims(2);
+        assert!(prompt.lint(&LintOptions::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_lint_character_budget() {
+        let prompt: Prompt = Prompt::from("Rate the politeness from 1 to 9. {'politeness_score': 8}")
+            .with_example("a".repeat(100), "{\"politeness_score\": 5}");
+        let options: LintOptions = LintOptions { max_characters: 64, strict: false };
+        let warnings = prompt.lint(&options).unwrap();
+        assert_eq!(rules(&warnings), vec![LintRule::ExceedsCharacterBudget]);
+    }
+
+    #[test]
+    fn test_lint_prompt_set_strict() {
+        let prompt_set: PromptSet = PromptSet::new(vec![
+            "Rate the formality from 1 to 9. {'formality_score': 4}",
+            "Rate the urgency.",
+        ]);
+
+        // Warnings point at the offending prompt
+        let warnings = prompt_set.lint(&LintOptions::default()).unwrap();
+        assert!(warnings.iter().all(|warning| warning.prompt_index == 1));
+        assert_eq!(rules(&warnings), vec![LintRule::MissingJsonExample, LintRule::MissingNumericRange]);
+
+        // Strict mode turns them into an error
+        assert!(prompt_set.lint(&LintOptions::strict()).is_err());
+    }
+
+    #[test]
+    fn test_from_attributes() {
+        let prompt_set: PromptSet = PromptSet::from_attributes(
+            &["sentiment", "Formality", "sarcasm level", "urgency_score"],
+            (1.0, 9.0),
+        ).unwrap();
+        assert_eq!(prompt_set.len(), 4);
+        assert_eq!(prompt_set.total_dims(), 4);
+
+        // Keys are snake_cased with a single _score suffix
+        let examples: Vec<&str> = prompt_set
+            .get_prompts()
+            .iter()
+            .map(|prompt| prompt.get_json_example().unwrap())
+            .collect();
+        assert_eq!(examples, vec![
+            "{'sentiment_score': 5}",
+            "{'formality_score': 5}",
+            "{'sarcasm_level_score': 5}",
+            "{'urgency_score': 5}",
+        ]);
+        assert!(prompt_set.get_prompts().iter().all(|prompt| prompt.get_scale() == Some((1.0, 9.0))));
+
+        // The generated set passes the linter
+        assert!(prompt_set.lint(&LintOptions::strict()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_from_image_attributes() {
+        let prompt_set: PromptSet = PromptSet::from_image_attributes(&["friendliness"], (0.0, 10.0)).unwrap();
+        let instruction: String = prompt_set.get_prompts()[0].get_instruction();
+        assert!(instruction.contains("of the image from 0"));
+        assert!(prompt_set.lint(&LintOptions::strict()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_from_attributes_rejects_bad_keys() {
+        // Attributes that collapse to the same key
+        assert!(PromptSet::from_attributes(&["Sentiment", "sentiment!"], (1.0, 9.0)).is_err());
+        // Attributes without any usable characters
+        assert!(PromptSet::from_attributes(&["???"], (1.0, 9.0)).is_err());
+        // Inverted scale
+        assert!(PromptSet::from_attributes(&["sentiment"], (9.0, 1.0)).is_err());
+    }
+
+    #[test]
+    fn test_prompt_labels() {
+        // Labels are derived from the JSON example
+        let prompt: Prompt = Prompt::from("Rate the urgency from 1 to 9. {'urgency_score': 2}");
+        assert_eq!(prompt.get_labels(), vec!["urgency_score".to_string()]);
+
+        // Explicit labels take precedence
+        let prompt: Prompt = prompt.with_labels(vec!["urgency".to_string()]);
+        assert_eq!(prompt.get_labels(), vec!["urgency".to_string()]);
+
+        // Unknown labels become placeholders in a set
+        let prompt_set: PromptSet = PromptSet::new(vec![
+            Prompt::from("Rate the urgency from 1 to 9."),
+            Prompt::from("Rate the formality from 1 to 9. {'formality_score': 4}"),
+        ]);
+        assert_eq!(prompt_set.get_labels(), vec!["dim_0".to_string(), "formality_score".to_string()]);
+    }
+
+    #[test]
+    fn test_json_schema() {
+        // Nested or unknown labels have no generated schema
+        assert!(Prompt::from("Rate it. {'scores': {'a': 1}}").get_json_schema().is_none());
+        assert!(Prompt::from("Rate it from 1 to 9.").get_json_schema().is_none());
+
+        let schema = Prompt::from("Rate it. {'a': 1, 'b': 2}").with_expected_dims(2).get_json_schema().unwrap();
+        assert_eq!(schema["required"], serde_json::json!(["a", "b"]));
+        assert_eq!(schema["properties"]["b"], serde_json::json!({ "type": "number" }));
+
+        // An explicit schema wins
+        let explicit = serde_json::json!({ "type": "object", "properties": { "x": { "type": "integer" } } });
+        let prompt: Prompt = Prompt::from("Rate it. {'a': 1}").with_json_schema(explicit.clone());
+        assert_eq!(prompt.get_json_schema(), Some(explicit));
+    }
+
+    #[test]
+    fn test_load_prompts() {
+        let path = std::env::temp_dir().join(format!("dim_prompts_{}.json", std::process::id()));
+
+        // Plain instructions and full prompts can be mixed
+        let prompt: Prompt = Prompt::from("Rate the formality from 1 to 9. {'formality_score': 4}").with_expected_dims(1);
+        let file = serde_json::json!(["Rate the urgency from 1 to 9.", prompt]);
+        std::fs::write(&path, file.to_string()).unwrap();
+        let prompt_set: PromptSet = load_prompts(&path).unwrap();
+        assert_eq!(prompt_set.len(), 2);
+        assert_eq!(prompt_set.get_prompts()[0], Prompt::from("Rate the urgency from 1 to 9."));
+        assert_eq!(prompt_set.get_prompts()[1], prompt);
+
+        // A serialized PromptSet loads the same way
+        std::fs::write(&path, serde_json::to_string(&prompt_set).unwrap()).unwrap();
+        assert_eq!(load_prompts(&path).unwrap(), prompt_set);
+
+        // Empty and malformed files are rejected
+        std::fs::write(&path, "[]").unwrap();
+        assert!(load_prompts(&path).unwrap_err().to_string().contains("no prompts"));
+        std::fs::write(&path, "{\"prompt\": 1}").unwrap();
+        assert!(load_prompts(&path).unwrap_err().to_string().contains("Failed to parse prompts"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,114 @@
+#![cfg(feature = "qdrant")]
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use dim_rs::export::qdrant::{upsert, QdrantConfig};
+    use dim_rs::prelude::*;
+    use dim_rs::vector::{METADATA_ID, METADATA_SOURCE};
+    use serde_json::Value;
+
+    use crate::common::{MockResponse, MockServer, RecordedRequest};
+
+    fn vectors() -> Vec<Vector<String>> {
+        (0..3)
+            .map(|index| {
+                let mut vector: Vector<String> = Vector::from_text(format!("item {}", index));
+                vector.overwrite_vector_with_labels(
+                    vec![index as f32, 1.0],
+                    vec!["a".to_string(), "b".to_string()],
+                ).unwrap();
+                vector.set_fingerprint("abc".to_string());
+                vector
+            })
+            .collect()
+    }
+
+    fn collection(size: usize) -> String {
+        format!(r#"{{"result":{{"config":{{"params":{{"vectors":{{"size":{},"distance":"Cosine"}}}}}}}}}}"#, size)
+    }
+
+    #[tokio::test]
+    async fn test_upsert_creates_collection() {
+        let server: MockServer = MockServer::start(vec![
+            MockResponse::status(404, r#"{"status":{"error":"Not found"}}"#),
+            MockResponse::ok(r#"{"result":true}"#),
+            MockResponse::ok(r#"{"result":{"status":"completed"}}"#),
+            MockResponse::ok(r#"{"result":{"status":"completed"}}"#),
+        ]).await;
+        let mut vectors: Vec<Vector<String>> = vectors();
+        vectors[0].set_metadata(METADATA_ID, "42");
+        vectors[1].set_metadata(METADATA_ID, "chair");
+        vectors[1].set_metadata(METADATA_SOURCE, "catalog.jsonl");
+
+        let config: QdrantConfig = QdrantConfig::new(&server.url).with_api_key("secret").with_batch_size(2);
+        assert_eq!(upsert("items", &vectors, &config).await.unwrap(), 3);
+
+        let requests: Vec<RecordedRequest> = server.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!((requests[0].method.as_str(), requests[0].path.as_str()), ("GET", "/collections/items"));
+        assert_eq!(requests[0].header("api-key"), Some("secret"));
+        assert_eq!(requests[1].method, "PUT");
+        assert_eq!(requests[1].json()["vectors"]["size"], 2);
+        assert_eq!(requests[1].json()["vectors"]["distance"], "Cosine");
+        assert_eq!(requests[2].path, "/collections/items/points?wait=true");
+
+        let points: Vec<Value> = requests[2].json()["points"].as_array().unwrap().clone();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0]["id"], 42);
+        assert_eq!(points[0]["payload"]["fingerprint"], "abc");
+        assert_eq!(points[1]["payload"]["source"], "catalog.jsonl");
+        assert_eq!(points[1]["vector"], serde_json::json!([1.0, 1.0]));
+
+        let derived: &str = points[1]["id"].as_str().unwrap();
+        assert_eq!(derived.len(), 36);
+        assert_eq!(&derived[14..15], "4");
+        assert_eq!(requests[3].json()["points"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_upsert_detects_mismatches() {
+        let server: MockServer = MockServer::start(vec![MockResponse::ok(collection(3))]).await;
+        let error: String = upsert("items", &vectors(), &QdrantConfig::new(&server.url)).await.unwrap_err().to_string();
+        assert!(error.contains("Dimensionality mismatch"));
+        assert_eq!(server.requests().len(), 1);
+
+        let server: MockServer = MockServer::start(vec![
+            MockResponse::ok(collection(2)),
+            MockResponse::ok(r#"{"result":{"points":[{"id":1,"payload":{"fingerprint":"xyz"}}]}}"#),
+        ]).await;
+        let error: String = upsert("items", &vectors(), &QdrantConfig::new(&server.url)).await.unwrap_err().to_string();
+        assert!(error.contains("Fingerprint mismatch"));
+        assert!(server.requests().iter().all(|request| !request.path.contains("wait=true")));
+
+        let mut mixed: Vec<Vector<String>> = vectors();
+        mixed[2].set_fingerprint("other".to_string());
+        assert!(upsert("items", &mixed, &QdrantConfig::new(&server.url)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_upsert_retries_transient_errors() {
+        let server: MockServer = MockServer::start(vec![
+            MockResponse::ok(collection(2)),
+            MockResponse::ok(r#"{"result":{"points":[]}}"#),
+            MockResponse::status(503, "unavailable"),
+            MockResponse::ok(r#"{"result":{"status":"completed"}}"#),
+        ]).await;
+        let config: QdrantConfig = QdrantConfig::new(&server.url).with_retry_delay(Duration::from_millis(1));
+        assert_eq!(upsert("items", &vectors(), &config).await.unwrap(), 3);
+        assert_eq!(server.requests().len(), 4);
+
+        let server: MockServer = MockServer::start(vec![
+            MockResponse::ok(collection(2)),
+            MockResponse::ok(r#"{"result":{"points":[]}}"#),
+            MockResponse::status(400, "bad request"),
+        ]).await;
+        let config: QdrantConfig = QdrantConfig::new(&server.url).with_retry_delay(Duration::from_millis(1));
+        let error: String = upsert("items", &vectors(), &config).await.unwrap_err().to_string();
+        assert!(error.contains("batch 0"));
+        assert_eq!(server.requests().len(), 3);
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use dim_rs::prelude::*;
+    use dim_rs::raw_data::{find_near_duplicates, image_sha256, load_image_directory, load_texts_csv, load_texts_jsonl, perceptual_hash, read_texts_jsonl, stream_image_directory, ImageDirectoryOptions, ImageHash, TextDatasetOptions};
+    use dim_rs::vector::{METADATA_FILE_SIZE, METADATA_ID, METADATA_MODIFIED_AT, METADATA_SOURCE};
+    use image::{DynamicImage, ImageBuffer, ImageFormat, Rgb};
+
+    /// A photo-like image with diagonal gradients and a bright square
+    fn photo() -> DynamicImage {
+        DynamicImage::ImageRgb8(ImageBuffer::from_fn(64, 48, |x, y| {
+            let square: bool = (20..40).contains(&x) && (10..30).contains(&y);
+            if square {
+                Rgb([230, 220, 40])
+            } else {
+                Rgb([(x * 3) as u8, (y * 4) as u8, ((x + y) * 2) as u8])
+            }
+        }))
+    }
+
+    #[test]
+    fn test_perceptual_hash() {
+        let original: DynamicImage = photo();
+        let hash: ImageHash = perceptual_hash(&original);
+        assert_eq!(hash, perceptual_hash(&original.clone()));
+        assert_eq!(hash.to_string().len(), 16);
+
+        // A brightened, re-saved copy stays close
+        let mut jpeg: Vec<u8> = Vec::new();
+        original.brighten(12).write_to(&mut Cursor::new(&mut jpeg), ImageFormat::Jpeg).unwrap();
+        let copy: DynamicImage = image::load_from_memory(&jpeg).unwrap();
+        assert!(hash.hamming_distance(&perceptual_hash(&copy)) <= 4);
+        assert_ne!(image_sha256(&original), image_sha256(&copy));
+
+        // A different image is far away
+        let flipped: DynamicImage = original.fliph();
+        assert!(hash.hamming_distance(&perceptual_hash(&flipped)) > 16);
+
+        let vectors: Vec<Vector<DynamicImage>> = vec![
+            Vector::from_image(original),
+            Vector::from_image(flipped),
+            Vector::from_image(copy),
+        ];
+        let pairs: Vec<(usize, usize, u32)> = find_near_duplicates(&vectors, 4);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!((pairs[0].0, pairs[0].1), (0, 2));
+    }
+
+    #[test]
+    fn test_load_image_directory() {
+        let directory: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let nested: std::path::PathBuf = directory.path().join("nested");
+        std::fs::create_dir(&nested).unwrap();
+        photo().save(directory.path().join("b.png")).unwrap();
+        photo().save(directory.path().join("a.JPG")).unwrap();
+        photo().save(nested.join("c.png")).unwrap();
+        std::fs::write(directory.path().join("broken.png"), b"not an image").unwrap();
+        std::fs::write(directory.path().join("notes.txt"), b"skipped").unwrap();
+
+        let vectors: Vec<Vector<DynamicImage>> = load_image_directory(directory.path(), &ImageDirectoryOptions::new()).unwrap();
+        let sources: Vec<String> = vectors
+            .iter()
+            .map(|vector| vector.get_metadata(METADATA_SOURCE).unwrap().rsplit('/').next().unwrap().to_string())
+            .collect();
+        assert_eq!(sources, ["a.JPG", "b.png"]);
+        let file_size: u64 = std::fs::metadata(directory.path().join("b.png")).unwrap().len();
+        assert_eq!(vectors[1].get_metadata(METADATA_FILE_SIZE), Some(file_size.to_string().as_str()));
+        assert!(vectors[1].get_metadata(METADATA_MODIFIED_AT).is_some());
+
+        let options: ImageDirectoryOptions = ImageDirectoryOptions::new().with_recursive(true).with_extensions(["png"]);
+        let vectors: Vec<Vector<DynamicImage>> = load_image_directory(directory.path(), &options).unwrap();
+        assert_eq!(vectors.len(), 2);
+        assert!(vectors[1].get_metadata(METADATA_SOURCE).unwrap().ends_with("c.png"));
+
+        // Failing fast stops at the undecodable file, and the limit counts loaded images
+        let error: String = load_image_directory(directory.path(), &ImageDirectoryOptions::new().with_skip_undecodable(false))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("broken.png"), "{}", error);
+        let streamed: Vec<Vector<DynamicImage>> = stream_image_directory(directory.path(), &options.with_limit(1))
+            .unwrap()
+            .collect::<Result<Vec<Vector<DynamicImage>>, _>>()
+            .unwrap();
+        assert_eq!(streamed.len(), 1);
+        assert!(load_image_directory(directory.path().join("missing"), &ImageDirectoryOptions::new()).is_err());
+    }
+
+    #[test]
+    fn test_load_texts_jsonl() {
+        let directory: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let path: std::path::PathBuf = directory.path().join("texts.jsonl");
+        std::fs::write(
+            &path,
+            "{\"id\": 7, \"body\": \"first\"}\n\n{\"id\": \"b\", \"body\": \"second\"}\nnot json\n{\"id\": 9}\n{\"body\": \"third\"}\n",
+        )
+        .unwrap();
+
+        let error: String = load_texts_jsonl(&path, "body", Some("id"), &TextDatasetOptions::new()).unwrap_err().to_string();
+        assert!(error.starts_with("line 4:"), "{}", error);
+
+        let options: TextDatasetOptions = TextDatasetOptions::new().with_skip_malformed(true);
+        let vectors: Vec<Vector<String>> = load_texts_jsonl(&path, "body", Some("id"), &options).unwrap();
+        let texts: Vec<&str> = vectors.iter().map(|vector| vector.get_data().as_str()).collect();
+        assert_eq!(texts, ["first", "second", "third"]);
+        assert_eq!(vectors[0].get_metadata(METADATA_ID), Some("7"));
+        assert_eq!(vectors[1].get_metadata(METADATA_ID), Some("b"));
+        assert_eq!(vectors[2].get_metadata(METADATA_ID), None);
+        assert_eq!(vectors[2].get_metadata(METADATA_SOURCE), Some(format!("{}:6", path.display()).as_str()));
+
+        // Streaming yields the error in place and stops
+        let streamed: Vec<Result<Vector<String>, anyhow::Error>> = read_texts_jsonl(&path, "body", None, &TextDatasetOptions::new()).unwrap().collect();
+        assert_eq!(streamed.len(), 3);
+        assert!(streamed[2].is_err());
+    }
+
+    #[test]
+    fn test_load_texts_csv() {
+        let directory: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let path: std::path::PathBuf = directory.path().join("texts.csv");
+        std::fs::write(&path, "id,text\na,\"hello, world\"\nb,\"two\nlines\"\nc,too,many\n,last\n").unwrap();
+
+        let error: String = load_texts_csv(&path, "text", Some("id"), &TextDatasetOptions::new()).unwrap_err().to_string();
+        assert!(error.starts_with("line 5:"), "{}", error);
+
+        let options: TextDatasetOptions = TextDatasetOptions::new().with_skip_malformed(true);
+        let vectors: Vec<Vector<String>> = load_texts_csv(&path, "text", Some("id"), &options).unwrap();
+        let texts: Vec<&str> = vectors.iter().map(|vector| vector.get_data().as_str()).collect();
+        assert_eq!(texts, ["hello, world", "two\nlines", "last"]);
+        assert_eq!(vectors[1].get_metadata(METADATA_ID), Some("b"));
+        assert_eq!(vectors[2].get_metadata(METADATA_ID), None);
+        assert_eq!(vectors[2].get_metadata(METADATA_SOURCE), Some(format!("{}:6", path.display()).as_str()));
+
+        assert!(load_texts_csv(&path, "body", None, &options).unwrap_err().to_string().contains("no column 'body'"));
+    }
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "redis")]
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use dim_rs::llm::testing::MockBackend;
+    use dim_rs::prelude::*;
+    use dim_rs::raw_data::utilities::text_sha256;
+    use dim_rs::vectorization::cache::redis::RedisCache;
+    use dim_rs::vectorization::cache::{CacheKey, CacheStats, CachedResult, VectorizationCache};
+
+    fn key(text: &str) -> CacheKey {
+        CacheKey {
+            data_hash: text_sha256(text),
+            fingerprint: "fingerprint".to_string(),
+            model: "mock-model".to_string(),
+            extraction_mode: "JsonObject/Parsed".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_redis_is_a_miss() {
+        // Nothing listens on port 1
+        let cache: RedisCache = RedisCache::new("redis://127.0.0.1:1/").unwrap().with_timeout(Duration::from_millis(200));
+        let result: CachedResult = CachedResult { values: vec![5.0], keys: vec!["score".to_string()], samples: Vec::new() };
+        cache.put(&key("Hello"), &result).unwrap();
+        assert_eq!(cache.get_many(&[key("Hello"), key("Other")]).unwrap(), vec![None, None]);
+        assert!(cache.stats().is_err());
+
+        let backend: Arc<MockBackend> = Arc::new(MockBackend::new().with_fallback("{\"score\": 5}"));
+        let parameters: ModelParameters = ModelParameters::builder()
+            .model("mock-model")
+            .cache(cache)
+            .build()
+            .unwrap();
+        let mut vector: Vector<String> = Vector::from_text("Hello".to_string());
+        let report: VectorizationReport = vectorize_string_concurrently(vec!["Rate it. {'score': 5}"], &mut vector, backend, parameters)
+            .await
+            .unwrap();
+        assert_eq!(vector.get_vector(), vec![5.0]);
+        assert_eq!(report.cache_hits(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_redis_cache() {
+        // Runs against a real server only, e.g. REDIS_URL=redis://127.0.0.1:6379/15
+        let Ok(url) = std::env::var("REDIS_URL") else {
+            eprintln!("REDIS_URL is not set, skipping");
+            return;
+        };
+        let prefix: String = format!("dim:test:{}:", std::process::id());
+        let cache: Arc<RedisCache> = Arc::new(RedisCache::new(&url).unwrap().with_prefix(prefix.clone()).with_ttl(Duration::from_secs(60)));
+        cache.clear().unwrap();
+
+        let result: CachedResult = CachedResult {
+            values: vec![5.0, 3.0],
+            keys: vec!["score".to_string(), "tone".to_string()],
+            samples: vec![vec![5.0, 3.0], vec![5.0, 4.0]],
+        };
+        cache.put(&key("Hello"), &result).unwrap();
+        assert_eq!(cache.get(&key("Hello")).unwrap(), Some(result.clone()));
+        assert_eq!(cache.get_many(&[key("Other"), key("Hello")]).unwrap(), vec![None, Some(result)]);
+        assert_eq!(cache.stats().unwrap().entries, 1);
+
+        // A second worker sharing the server skips the requests
+        let backend: Arc<MockBackend> = Arc::new(MockBackend::new().with_fallback("{\"score\": 5}"));
+        for _ in 0..2 {
+            let worker: RedisCache = RedisCache::new(&url).unwrap().with_prefix(prefix.clone());
+            let parameters: ModelParameters = ModelParameters::builder()
+                .model("mock-model")
+                .cache(worker)
+                .build()
+                .unwrap();
+            let mut vector: Vector<String> = Vector::from_text("Shared".to_string());
+            vectorize_string_concurrently(vec!["Rate it. {'score': 5}"], &mut vector, backend.clone(), parameters)
+                .await
+                .unwrap();
+            assert_eq!(vector.get_vector(), vec![5.0]);
+        }
+        assert_eq!(backend.get_requests().len(), 1);
+        assert_eq!(cache.stats().unwrap().entries, 2);
+
+        cache.clear().unwrap();
+        assert_eq!(cache.stats().unwrap(), CacheStats::default());
+    }
+}
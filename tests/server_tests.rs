@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    use axum::body::{to_bytes, Body};
+    use axum::http::{header, Request, StatusCode};
+    use axum::Router;
+    use base64::Engine;
+    use dim_rs::llm::testing::MockBackend;
+    use dim_rs::prelude::*;
+    use dim_rs::server::{router, router_with_options, status_for, ServerOptions, VectorResponse};
+    use image::{DynamicImage, ImageBuffer, ImageFormat, Rgba};
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    fn vectorizer(backend: Arc<MockBackend>) -> Vectorizer<Arc<MockBackend>> {
+        Vectorizer::builder()
+            .client(backend)
+            .prompts(PromptSet::new(vec!["Rate it. {'score': 5}"]))
+            .model_parameters(ModelParameters::builder().model("mock-model").build().unwrap())
+            .build()
+            .unwrap()
+    }
+
+    fn png_bytes() -> Vec<u8> {
+        let image: DynamicImage = DynamicImage::ImageRgba8(ImageBuffer::from_pixel(4, 4, Rgba([200, 30, 30, 255])));
+        let mut bytes: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        image.write_to(&mut bytes, ImageFormat::Png).unwrap();
+        bytes.into_inner()
+    }
+
+    async fn send(app: Router, request: Request<Body>) -> (StatusCode, Value) {
+        let response = app.oneshot(request).await.unwrap();
+        let status: StatusCode = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    fn post_json(uri: &str, body: Value) -> Request<Body> {
+        Request::post(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_vectorize_text_endpoint() {
+        let backend: Arc<MockBackend> = Arc::new(
+            MockBackend::new()
+                .with_response("Rate the tone", "{\"tone\": 2}")
+                .with_fallback("{\"score\": 6}"),
+        );
+        let options: ServerOptions = ServerOptions::new().with_prompt_set("tone", PromptSet::new(vec!["Rate the tone. {'tone': 5}"]));
+        let app: Router = router_with_options(vectorizer(backend.clone()), options).unwrap();
+
+        let (status, body) = send(app.clone(), post_json("/vectorize/text", json!({ "text": "alpha" }))).await;
+        assert_eq!(status, StatusCode::OK);
+        let response: VectorResponse = serde_json::from_value(body).unwrap();
+        assert_eq!(response.vector, vec![6.0]);
+        assert_eq!(response.labels, vec!["score"]);
+        assert!(response.fingerprint.is_some());
+        assert_eq!(response.usage.prompts.len(), 1);
+
+        // Named prompt sets are chosen per request, unknown ones are rejected
+        let (status, body) = send(app.clone(), post_json("/vectorize/text", json!({ "text": "alpha", "prompt_set": "tone" }))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["labels"], json!(["tone"]));
+        let (status, body) = send(app, post_json("/vectorize/text", json!({ "text": "alpha", "prompt_set": "style" }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("style"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_vectorize_image_endpoint() {
+        let backend: Arc<MockBackend> = Arc::new(MockBackend::new().with_fallback("{\"score\": 3}"));
+        let app: Router = router(vectorizer(backend));
+
+        // Base64 JSON, plain or as a data URL
+        let encoded: String = base64::engine::general_purpose::STANDARD.encode(png_bytes());
+        for image in [encoded.clone(), format!("data:image/png;base64,{}", encoded)] {
+            let (status, body) = send(app.clone(), post_json("/vectorize/image", json!({ "image": image }))).await;
+            assert_eq!(status, StatusCode::OK, "{}", body);
+            assert_eq!(body["vector"], json!([3.0]));
+        }
+
+        // Multipart form
+        let boundary: &str = "dim-boundary";
+        let mut form: Vec<u8> = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"image\"; filename=\"red.png\"\r\nContent-Type: image/png\r\n\r\n"
+        )
+        .into_bytes();
+        form.extend(png_bytes());
+        form.extend(format!("\r\n--{boundary}--\r\n").into_bytes());
+        let request: Request<Body> = Request::post("/vectorize/image")
+            .header(header::CONTENT_TYPE, format!("multipart/form-data; boundary={}", boundary))
+            .body(Body::from(form))
+            .unwrap();
+        let (status, body) = send(app.clone(), request).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(body["labels"], json!(["score"]));
+
+        // Undecodable images are rejected
+        let (status, _) = send(app, post_json("/vectorize/image", json!({ "image": "bm90IGFuIGltYWdl" }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_server_limits_and_health() {
+        let backend: Arc<MockBackend> = Arc::new(MockBackend::new().with_fallback("{\"score\": 6}"));
+        let options: ServerOptions = ServerOptions::new().with_max_body_bytes(64).with_max_concurrent_requests(1);
+        let app: Router = router_with_options(vectorizer(backend), options).unwrap();
+        let (status, _) = send(app.clone(), post_json("/vectorize/text", json!({ "text": "a".repeat(100) }))).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+        // The health check confirms the model with a test completion
+        let (status, body) = send(app, Request::get("/healthz").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["models"][0]["id"], "mock-model");
+
+        // A backend that cannot answer is an upstream failure
+        let backend: Arc<MockBackend> = Arc::new(MockBackend::new().with_response("Rate it", "{\"score\": 6}"));
+        let (status, body) = send(router(vectorizer(backend)), Request::get("/healthz").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+        assert!(body["error"].as_str().unwrap().contains("mock-model"));
+
+        assert_eq!(status_for(&DimError::ApiError { status: Some(429), message: String::new() }), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(status_for(&DimError::ApiError { status: Some(500), message: String::new() }), StatusCode::BAD_GATEWAY);
+        assert_eq!(
+            status_for(&DimError::ValidationFailed { prompt_index: 0, reason: "vector is empty".to_string() }),
+            StatusCode::BAD_GATEWAY
+        );
+        assert_eq!(status_for(&DimError::msg("cache unavailable")), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
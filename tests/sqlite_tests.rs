@@ -0,0 +1,60 @@
+#![cfg(feature = "sqlite")]
+
+#[cfg(test)]
+mod tests {
+    use dim_rs::prelude::*;
+    use dim_rs::vector::sqlite::SqliteVectorStore;
+    use image::DynamicImage;
+
+    fn text_vector(id: &str, text: &str, values: Vec<f32>) -> Vector<String> {
+        let mut vector: Vector<String> = Vector::from_text(text.to_string());
+        vector
+            .overwrite_vector_with_labels(values, vec!["tone".to_string(), "formality".to_string()])
+            .unwrap();
+        vector.set_fingerprint("fingerprint".to_string());
+        vector.set_metadata(METADATA_ID, id);
+        vector.set_metadata(METADATA_MODEL, "mock-model");
+        vector
+    }
+
+    #[test]
+    fn test_sqlite_round_trip() {
+        let directory: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let path: std::path::PathBuf = directory.path().join("vectors.db");
+        let store: SqliteVectorStore = SqliteVectorStore::open(&path).unwrap();
+        assert!(store.is_empty().unwrap());
+
+        store.insert(&text_vector("a", "first", vec![1.0, 2.5])).unwrap();
+        store.insert(&text_vector("b", "second", vec![3.0, 4.0])).unwrap();
+        let mut image: Vector<DynamicImage> = Vector::from_image(DynamicImage::new_rgb8(4, 4));
+        image.overwrite_vector_with_labels(vec![0.5], vec!["brightness".to_string()]).unwrap();
+        store.insert(&image).unwrap();
+
+        let first: Vector<String> = store.get("a").unwrap().unwrap();
+        assert_eq!(first.get_data(), "first");
+        assert_eq!(first.get_vector(), vec![1.0, 2.5]);
+        assert_eq!(first.get_labels(), ["tone", "formality"]);
+        assert_eq!(first.get_fingerprint(), Some("fingerprint"));
+        assert_eq!(first.get_metadata(METADATA_MODEL), Some("mock-model"));
+        assert!(store.get("missing").unwrap().is_none());
+
+        // Images keep their vector but not their pixels
+        let all: Vec<Vector<String>> = store.all().collect::<Result<_, _>>().unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[2].get_data_type(), DataType::Image);
+        assert_eq!(all[2].get_vector(), vec![0.5]);
+        assert_eq!(all[2].get_data(), "");
+
+        // Re-vectorized items replace their row, and the schema survives reopening
+        store.insert(&text_vector("a", "first, revised", vec![9.0, 9.0])).unwrap();
+        drop(store);
+        let store: SqliteVectorStore = SqliteVectorStore::open(&path).unwrap();
+        assert_eq!(store.len().unwrap(), 3);
+        assert_eq!(store.get("a").unwrap().unwrap().get_data(), "first, revised");
+
+        let texts: Vec<Vector<String>> = store.query(&Filter::new().data_type(DataType::Text)).unwrap();
+        assert_eq!(texts.len(), 2);
+        let second: Vec<Vector<String>> = store.query(&Filter::new().metadata_eq(METADATA_ID, "b")).unwrap();
+        assert_eq!(second[0].get_vector(), vec![3.0, 4.0]);
+    }
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "metrics")]
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use dim_rs::llm::testing::{ScriptedBackend, ScriptedReply};
+    use dim_rs::prelude::*;
+    use dim_rs::telemetry::{describe_metrics, METRIC_ATTEMPTS, METRIC_IN_FLIGHT, METRIC_REQUESTS, METRIC_REQUEST_DURATION, METRIC_RETRIES, METRIC_VALIDATION_FAILURES};
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+    use metrics_util::CompositeKey;
+
+    /// Returns the value of a metric with exactly these labels, if it was recorded.
+    fn find<'a>(snapshot: &'a [(CompositeKey, DebugValue)], name: &str, labels: &[(&str, &str)]) -> Option<&'a DebugValue> {
+        snapshot
+            .iter()
+            .find(|(key, _)| {
+                let mut recorded: Vec<(&str, &str)> = key.key().labels().map(|label| (label.key(), label.value())).collect();
+                let mut expected: Vec<(&str, &str)> = labels.to_vec();
+                recorded.sort();
+                expected.sort();
+                key.key().name() == name && recorded == expected
+            })
+            .map(|(_, value)| value)
+    }
+
+    #[tokio::test]
+    async fn test_request_metrics() {
+        let recorder: DebuggingRecorder = DebuggingRecorder::new();
+        let snapshotter: Snapshotter = recorder.snapshotter();
+        recorder.install().unwrap();
+        describe_metrics();
+
+        let backend: Arc<ScriptedBackend> = Arc::new(ScriptedBackend::new(vec![
+            ScriptedReply::ApiError("The server is overloaded".to_string()),
+            ScriptedReply::Answer("{\"score\": 42}".to_string()),
+            ScriptedReply::Answer("{\"score\": 5}".to_string()),
+        ]));
+        let parameters: ModelParameters = ModelParameters::builder().model("mock-model").build().unwrap();
+        let prompt: Prompt = Prompt::from("Rate it. {'score': 5}").with_scale((1.0, 9.0));
+        let mut vector: Vector<String> = Vector::from_text("A confidential support ticket".to_string());
+        vectorize_string_concurrently(vec![prompt], &mut vector, backend, parameters).await.unwrap();
+
+        let snapshot: Vec<(CompositeKey, DebugValue)> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| (key, value))
+            .collect();
+        let labels: [(&str, &str); 2] = [("model", "mock-model"), ("prompt", "score")];
+        let with = |extra: (&'static str, &'static str)| [labels[0], labels[1], extra];
+
+        assert_eq!(find(&snapshot, METRIC_REQUESTS, &with(("status", "error"))), Some(&DebugValue::Counter(1)));
+        assert_eq!(find(&snapshot, METRIC_REQUESTS, &with(("status", "ok"))), Some(&DebugValue::Counter(2)));
+        assert_eq!(find(&snapshot, METRIC_RETRIES, &with(("reason", "request_failed"))), Some(&DebugValue::Counter(1)));
+        assert_eq!(find(&snapshot, METRIC_RETRIES, &with(("reason", "validation_failed"))), Some(&DebugValue::Counter(1)));
+        assert_eq!(find(&snapshot, METRIC_VALIDATION_FAILURES, &labels), Some(&DebugValue::Counter(1)));
+        match find(&snapshot, METRIC_REQUEST_DURATION, &labels) {
+            Some(DebugValue::Histogram(latencies)) => assert_eq!(latencies.len(), 3),
+            other => panic!("Unexpected latencies: {:?}", other),
+        }
+        match find(&snapshot, METRIC_ATTEMPTS, &labels) {
+            Some(DebugValue::Histogram(attempts)) => assert_eq!(attempts.iter().map(|attempt| attempt.into_inner()).collect::<Vec<f64>>(), vec![2.0]),
+            other => panic!("Unexpected attempts: {:?}", other),
+        }
+        match find(&snapshot, METRIC_IN_FLIGHT, &[("model", "mock-model")]) {
+            Some(DebugValue::Gauge(in_flight)) => assert_eq!(in_flight.into_inner(), 0.0),
+            other => panic!("Unexpected in-flight requests: {:?}", other),
+        }
+
+        // The text being vectorized never becomes a label
+        assert!(snapshot
+            .iter()
+            .flat_map(|(key, _)| key.key().labels())
+            .all(|label| !label.value().contains("confidential")));
+    }
+}
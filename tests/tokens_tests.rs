@@ -0,0 +1,28 @@
+#![cfg(feature = "tokens")]
+
+#[cfg(test)]
+mod tests {
+    use dim_rs::raw_data::count_tokens;
+
+    #[test]
+    fn test_count_tokens() {
+        // Counts tiktoken gives for these texts
+        let sentence: &str = "The quick brown fox jumps over the lazy dog.";
+        assert_eq!(count_tokens(sentence, "gpt-4o-mini"), 10);
+        assert_eq!(count_tokens(sentence, "gpt-4"), 10);
+        assert_eq!(count_tokens(sentence, "openai/GPT-4o"), 10);
+        assert_eq!(count_tokens("1234567", "gpt-4o"), 3);
+        assert_eq!(count_tokens("", "gpt-4o"), 0);
+        assert_eq!(count_tokens("数据向量化", "gpt-4o"), 4);
+
+        // Each model family has its own encoding
+        assert_eq!(count_tokens("Привет, как дела?", "gpt-3.5-turbo"), 8);
+        assert_eq!(count_tokens("Привет, как дела?", "gpt-4o"), 6);
+
+        // OpenAI models newer than the tokenizer table use o200k
+        assert_eq!(count_tokens("Привет, как дела?", "gpt-5"), 6);
+
+        // Other models are counted at four characters per token
+        assert_eq!(count_tokens(sentence, "llama3"), 11);
+    }
+}
@@ -1,6 +1,11 @@
 #[cfg(test)]
 mod tests {
     use dim_rs::prelude::*;
+    use dim_rs::prompt::combine_fingerprints;
+    use dim_rs::raw_data::{AudioData, AudioFormat, VideoData, VideoFormat};
+    use dim_rs::vector::diff::VectorDiff;
+    use dim_rs::vector::serialization::SerializableImageVector;
+    use dim_rs::vector::{concat_all, METADATA_ID, METADATA_SOURCE, Precision, Vector64};
     use image::{DynamicImage, ImageBuffer, Rgba};
 
     #[test]
@@ -52,4 +57,299 @@ mod tests {
         assert_eq!(my_vector.get_vector(), new_values);
         assert_eq!(my_vector.get_dimensionality(), new_values.len());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_fingerprint_compatibility() {
+        let mut first: Vector<String> = Vector::from_text("first".to_string());
+        let mut second: Vector<String> = Vector::from_text("second".to_string());
+
+        // Unvectorized vectors carry no fingerprint
+        assert_eq!(first.get_fingerprint(), None);
+        assert!(first.compatible_with(&second));
+
+        first.set_fingerprint("abc".to_string());
+        assert_eq!(first.get_fingerprint(), Some("abc"));
+        assert!(!first.compatible_with(&second));
+
+        second.set_fingerprint("abc".to_string());
+        assert!(first.compatible_with(&second));
+
+        second.set_fingerprint("def".to_string());
+        assert!(!first.compatible_with(&second));
+    }
+
+    #[test]
+    fn test_labeled_vector() {
+        let mut my_vector: Vector<String> = Vector::from_text("Labeled".to_string());
+        my_vector.overwrite_vector_with_labels(
+            vec![7.0, 4.0],
+            vec!["sentiment_score".to_string(), "formality_score".to_string()],
+        ).unwrap();
+
+        // Lookup by label
+        assert_eq!(my_vector.get_by_label("formality_score"), Some(4.0));
+        assert_eq!(my_vector.get_by_label("urgency_score"), None);
+        assert_eq!(
+            my_vector.get_labeled_vector(),
+            vec![("sentiment_score".to_string(), 7.0), ("formality_score".to_string(), 4.0)]
+        );
+
+        // Labels must match the vector length
+        assert!(my_vector.overwrite_vector_with_labels(vec![1.0], vec![]).is_err());
+        assert_eq!(my_vector.get_labels().len(), 2);
+
+        // Plain overwrites clear the labels so they cannot desynchronize
+        my_vector.overwrite_vector(vec![1.0, 2.0, 3.0]);
+        assert!(my_vector.get_labels().is_empty());
+        assert_eq!(my_vector.get_labeled_vector()[2], ("dim_2".to_string(), 3.0));
+    }
+
+    #[test]
+    fn test_labels_are_serialized() {
+        let mut my_vector: Vector<String> = Vector::from_text("Serialized".to_string());
+        my_vector.overwrite_vector_with_labels(vec![2.0], vec!["urgency_score".to_string()]).unwrap();
+
+        let json: String = serde_json::to_string(&my_vector).unwrap();
+        let restored: Vector<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_labels(), my_vector.get_labels());
+        assert_eq!(restored.get_by_label("urgency_score"), Some(2.0));
+    }
+
+    #[test]
+    fn test_from_audio() {
+        let bytes: Vec<u8> = b"RIFF\x24\x00\x00\x00WAVEfmt ".to_vec();
+        let my_vector: Vector<AudioData> = Vector::from_audio(bytes.clone(), AudioFormat::Wav);
+
+        // Verify correctness
+        assert_eq!(my_vector.get_data().get_bytes(), bytes.as_slice());
+        assert_eq!(my_vector.get_data().get_format(), AudioFormat::Wav);
+        assert_eq!(my_vector.get_data_type(), DataType::Audio);
+        assert_eq!(my_vector.get_dimensionality(), 0);
+
+        // Round-trip through serde
+        let json: String = serde_json::to_string(&my_vector).unwrap();
+        let restored: Vector<AudioData> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_data(), my_vector.get_data());
+        assert_eq!(restored.get_data_type(), DataType::Audio);
+        assert_eq!(restored.get_dimensionality(), 0);
+    }
+
+    #[test]
+    fn test_from_video() {
+        let bytes: Vec<u8> = vec![0x00, 0x00, 0x00, 0x18, b'f', b't', b'y', b'p'];
+        let my_vector: Vector<VideoData> = Vector::from_video(bytes.clone(), VideoFormat::Mp4);
+
+        // Verify correctness
+        assert_eq!(my_vector.get_data().get_bytes(), bytes.as_slice());
+        assert_eq!(my_vector.get_data().get_format(), VideoFormat::Mp4);
+        assert_eq!(my_vector.get_data_type(), DataType::Video);
+        assert_eq!(my_vector.get_dimensionality(), 0);
+
+        // Round-trip through serde
+        let json: String = serde_json::to_string(&my_vector).unwrap();
+        let restored: Vector<VideoData> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_data(), my_vector.get_data());
+        assert_eq!(restored.get_data_type(), DataType::Video);
+    }
+
+    #[test]
+    fn test_metadata() {
+        let mut my_vector: Vector<String> = Vector::from_text("With provenance".to_string());
+        assert!(my_vector.get_metadata_map().is_empty());
+        assert_eq!(my_vector.get_id(), None);
+
+        my_vector.set_metadata(METADATA_ID, "item-42");
+        my_vector.set_metadata(METADATA_SOURCE, "./data/texts.jsonl");
+        my_vector.set_metadata("category", "outerwear");
+        assert_eq!(my_vector.get_id(), Some("item-42"));
+        assert_eq!(my_vector.get_metadata("category"), Some("outerwear"));
+
+        // Metadata is part of the serialized vector
+        let json: String = serde_json::to_string(&my_vector).unwrap();
+        let restored: Vector<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_metadata_map(), my_vector.get_metadata_map());
+
+        assert_eq!(my_vector.remove_metadata("category"), Some("outerwear".to_string()));
+        assert_eq!(my_vector.get_metadata("category"), None);
+    }
+
+    #[test]
+    fn test_deserialize_without_optional_fields() {
+        // Vectors saved before labels, fingerprints and metadata existed still load
+        let json: &str = r#"{"vector":[1.0,2.0],"data":"legacy","data_type":"Text"}"#;
+        let restored: Vector<String> = serde_json::from_str(json).unwrap();
+        assert_eq!(restored.get_vector(), vec![1.0, 2.0]);
+        assert!(restored.get_labels().is_empty());
+        assert!(restored.get_metadata_map().is_empty());
+        assert_eq!(restored.get_fingerprint(), None);
+    }
+
+    #[test]
+    fn test_image_vector_round_trip() {
+        let test_image: DynamicImage = DynamicImage::ImageRgba8(
+            ImageBuffer::from_fn(3, 2, |x, y| Rgba([x as u8 * 80, y as u8 * 120, 7, 255]))
+        );
+        let mut my_vector: Vector<DynamicImage> = Vector::from_image(test_image.clone());
+        my_vector.overwrite_vector_with_labels(vec![3.0, 8.0], vec!["a".to_string(), "b".to_string()]).unwrap();
+        my_vector.set_metadata(METADATA_SOURCE, "./images/item.png");
+
+        let serializable: SerializableImageVector = SerializableImageVector::try_from(&my_vector).unwrap();
+        let json: String = serde_json::to_string(&serializable).unwrap();
+        let restored: Vector<DynamicImage> = serde_json::from_str::<SerializableImageVector>(&json)
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        // Pixels, values and provenance all survive
+        assert_eq!(restored.get_data().to_rgba8(), test_image.to_rgba8());
+        assert_eq!(restored.get_vector(), my_vector.get_vector());
+        assert_eq!(restored.get_labels(), my_vector.get_labels());
+        assert_eq!(restored.get_data_type(), DataType::Image);
+        assert_eq!(restored.get_metadata(METADATA_SOURCE), Some("./images/item.png"));
+    }
+
+    #[test]
+    fn test_image_vector_only_serialization() {
+        let test_image: DynamicImage = DynamicImage::ImageRgba8(
+            ImageBuffer::from_fn(2, 2, |_, _| Rgba([255, 255, 255, 255]))
+        );
+        let mut my_vector: Vector<DynamicImage> = Vector::from_image(test_image);
+        my_vector.overwrite_vector(vec![1.0, 2.0]);
+
+        let serializable: SerializableImageVector = SerializableImageVector::vector_only(&my_vector);
+        let json: String = serde_json::to_string(&serializable).unwrap();
+        assert!(!json.contains("image\""));
+
+        // Numbers survive, but there is no image to restore
+        let restored: SerializableImageVector = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.vector, vec![1.0, 2.0]);
+        assert!(Vector::<DynamicImage>::try_from(restored).is_err());
+    }
+
+    fn labeled_text(text: &str, values: Vec<f32>, labels: &[&str], fingerprint: &str) -> Vector<String> {
+        let mut vector: Vector<String> = Vector::from_text(text.to_string());
+        vector.overwrite_vector_with_labels(values, labels.iter().map(|label| label.to_string()).collect()).unwrap();
+        vector.set_fingerprint(fingerprint.to_string());
+        vector
+    }
+
+    #[test]
+    fn test_concat() {
+        let mut style: Vector<String> = labeled_text("item", vec![1.0, 2.0], &["formality", "tone"], "style");
+        style.set_metadata(METADATA_ID, "item-1");
+        let mut content: Vector<String> = labeled_text("item", vec![3.0], &["topic"], "content");
+        content.set_metadata(METADATA_SOURCE, "feed.jsonl");
+
+        let mut combined: Vector<String> = style.clone();
+        combined.concat(&content).unwrap();
+        assert_eq!(combined.get_vector(), vec![1.0, 2.0, 3.0]);
+        assert_eq!(combined.get_labels(), &["formality", "tone", "topic"]);
+        assert_eq!(combined.get_id(), Some("item-1"));
+        assert_eq!(combined.get_metadata(METADATA_SOURCE), Some("feed.jsonl"));
+
+        // The fingerprint is a deterministic combination, distinct from either part
+        let fingerprint: &str = combined.get_fingerprint().unwrap();
+        assert_ne!(fingerprint, "style");
+        assert_eq!(fingerprint, combine_fingerprints("concat", &["style", "content"]));
+
+        // concat_all matches repeated concat
+        let all: Vector<String> = concat_all(&[style, content]).unwrap();
+        assert_eq!(all.get_vector(), combined.get_vector());
+        assert_eq!(all.get_fingerprint(), combined.get_fingerprint());
+    }
+
+    #[test]
+    fn test_concat_conflicts() {
+        let mut first: Vector<String> = labeled_text("item", vec![1.0], &["formality"], "a");
+        let different_data: Vector<String> = labeled_text("other item", vec![2.0], &["topic"], "b");
+        let same_label: Vector<String> = labeled_text("item", vec![2.0], &["formality"], "b");
+
+        assert!(first.concat(&different_data).is_err());
+        assert!(first.concat(&same_label).is_err());
+
+        // Failed concatenations leave the vector untouched
+        assert_eq!(first.get_vector(), vec![1.0]);
+        assert_eq!(first.get_fingerprint(), Some("a"));
+        assert!(concat_all::<String>(&[]).is_err());
+    }
+
+    #[test]
+    fn test_project() {
+        let vector: Vector<String> = labeled_text("item", vec![1.0, 2.0, 3.0], &["formality", "tone", "topic"], "full");
+
+        // Labels pick dimensions in the requested order
+        let style: Vector<String> = vector.project(&["tone", "formality"]).unwrap();
+        assert_eq!(style.get_vector(), vec![2.0, 1.0]);
+        assert_eq!(style.get_labels(), &["tone", "formality"]);
+        assert_eq!(style.get_data(), vector.get_data());
+
+        // Indices behave the same and projections are never compatible with the full vector
+        let by_index: Vector<String> = vector.project_dims(&[1, 0]).unwrap();
+        assert_eq!(by_index.get_vector(), style.get_vector());
+        assert!(by_index.compatible_with(&style));
+        assert!(!style.compatible_with(&vector));
+        assert!(!vector.project_dims(&[0, 1]).unwrap().compatible_with(&style));
+
+        // Unknown, repeated or out of range selections are rejected
+        assert!(vector.project(&["mood"]).is_err());
+        assert!(vector.project_dims(&[0, 0]).is_err());
+        assert!(vector.project_dims(&[3]).is_err());
+        let unlabeled: Vector<String> = Vector::from_text("item".to_string());
+        assert!(unlabeled.project(&["tone"]).is_err());
+    }
+
+    #[test]
+    fn test_approx_eq_and_compare() {
+        let before: Vector<String> = labeled_text("item", vec![1.0, 5.0, 100.0], &["formality", "tone", "topic"], "v1");
+        let after: Vector<String> = labeled_text("item", vec![1.001, 6.0, 101.0], &["formality", "tone", "topic"], "v2");
+
+        // Relative tolerance absorbs the shift on the large dimension, not on tone
+        assert!(before.approx_eq(&before.clone(), 0.0, 0.0));
+        assert!(!before.approx_eq(&after, 0.01, 0.02));
+        assert!(before.approx_eq(&after, 1.0, 0.0));
+
+        let diff: VectorDiff = before.compare(&after, 0.01, 0.02).unwrap();
+        assert!(!diff.is_within_tolerance());
+        assert_eq!(diff.differences.len(), 1);
+        assert_eq!(diff.differences[0].label, "tone");
+        assert_eq!(diff.differences[0].index, 1);
+        assert!((diff.max_abs_diff - 1.0).abs() < 1e-6);
+        assert!(before.compare(&after, 1.0, 0.0).unwrap().is_within_tolerance());
+
+        // Length mismatches are reported, not panicked on
+        let shorter: Vector<String> = labeled_text("item", vec![1.0], &["formality"], "v1");
+        assert!(!before.approx_eq(&shorter, 1.0, 1.0));
+        assert!(before.compare(&shorter, 1.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_vector64() {
+        let mut vector: Vector<String> = Vector::from_text("precise".to_string());
+        vector.overwrite_vector_with_labels(vec![3.0, 4.0], vec!["a".to_string(), "b".to_string()]).unwrap();
+        vector.set_metadata(METADATA_ID, "7");
+
+        // Widening keeps the labels and metadata
+        let mut precise: Vector64<String> = Vector64::from(vector.clone());
+        assert_eq!(precise.get_precision(), Precision::F64);
+        assert_eq!(precise.get_labeled_vector(), vec![("a".to_string(), 3.0), ("b".to_string(), 4.0)]);
+        assert_eq!(precise.get_id(), Some("7"));
+        precise.overwrite_vector(vec![0.1, 0.2]);
+        assert_eq!(precise.get_vector(), vec![0.1, 0.2]);
+        assert!((precise.cosine_similarity(&precise).unwrap() - 1.0).abs() < 1e-12);
+        precise.normalize_min_max(0.0, 0.4).unwrap();
+        assert_eq!(precise.get_vector(), vec![0.25, 0.5]);
+
+        // The precision is recorded, and f64 values are not loaded as f32
+        let json: String = serde_json::to_string(&precise).unwrap();
+        assert!(json.contains("\"precision\":\"f64\""), "{}", json);
+        let loaded: Vector64<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.get_vector(), precise.get_vector());
+        assert!(serde_json::from_str::<Vector<String>>(&json).is_err());
+
+        // f32 vectors load as either precision, including those written before it was recorded
+        let json: String = serde_json::to_string(&vector).unwrap();
+        assert!(json.contains("\"precision\":\"f32\""), "{}", json);
+        assert_eq!(serde_json::from_str::<Vector64<String>>(&json).unwrap().get_vector(), vec![3.0, 4.0]);
+        assert_eq!(precise.cast::<f32>().get_vector(), vec![0.25f32, 0.5]);
+    }
+}